@@ -0,0 +1,24 @@
+CREATE TYPE posting_side AS ENUM ('debit', 'credit');
+
+CREATE TABLE IF NOT EXISTS entries (
+    id UUID DEFAULT uuid_generate_v4() PRIMARY KEY,
+    ledger_id UUID NOT NULL REFERENCES ledgers (id) ON DELETE CASCADE,
+    date DATE NOT NULL,
+    description VARCHAR NOT NULL DEFAULT '',
+    created_at TIMESTAMPTZ NOT NULL,
+    updated_at TIMESTAMPTZ NOT NULL
+);
+
+CREATE INDEX IF NOT EXISTS entries_ledger_id_date_idx ON entries (ledger_id, date);
+
+-- amount 以最小货币单位存储, 方向由 side 决定
+CREATE TABLE IF NOT EXISTS postings (
+    id UUID DEFAULT uuid_generate_v4() PRIMARY KEY,
+    entry_id UUID NOT NULL REFERENCES entries (id) ON DELETE CASCADE,
+    account_id UUID NOT NULL,
+    amount BIGINT NOT NULL CHECK (amount > 0),
+    side posting_side NOT NULL
+);
+
+CREATE INDEX IF NOT EXISTS postings_entry_id_idx ON postings (entry_id);
+CREATE INDEX IF NOT EXISTS postings_account_id_idx ON postings (account_id);
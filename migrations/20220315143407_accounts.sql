@@ -0,0 +1,18 @@
+CREATE TYPE account_kind AS ENUM ('asset', 'liability', 'income', 'expense', 'equity');
+
+CREATE TABLE IF NOT EXISTS accounts (
+    id UUID DEFAULT uuid_generate_v4() PRIMARY KEY,
+    ledger_id UUID NOT NULL REFERENCES ledgers (id) ON DELETE CASCADE,
+    parent_id UUID REFERENCES accounts (id),
+    name VARCHAR NOT NULL,
+    kind account_kind NOT NULL,
+    closed_at TIMESTAMPTZ,
+    created_at TIMESTAMPTZ NOT NULL,
+    updated_at TIMESTAMPTZ NOT NULL
+);
+
+CREATE INDEX IF NOT EXISTS accounts_ledger_id_idx ON accounts (ledger_id);
+CREATE INDEX IF NOT EXISTS accounts_parent_id_idx ON accounts (parent_id);
+
+ALTER TABLE postings
+    ADD CONSTRAINT postings_account_id_fkey FOREIGN KEY (account_id) REFERENCES accounts (id);
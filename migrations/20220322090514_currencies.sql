@@ -0,0 +1,69 @@
+CREATE TABLE IF NOT EXISTS currencies (
+    code VARCHAR(3) PRIMARY KEY,
+    name VARCHAR NOT NULL,
+    minor_unit SMALLINT NOT NULL CHECK (minor_unit >= 0)
+);
+
+INSERT INTO currencies (code, name, minor_unit) VALUES
+    ('AUD', 'Australian Dollar', 2),
+    ('CAD', 'Canadian Dollar', 2),
+    ('CHF', 'Swiss Franc', 2),
+    ('CNY', 'Yuan Renminbi', 2),
+    ('EUR', 'Euro', 2),
+    ('GBP', 'Pound Sterling', 2),
+    ('HKD', 'Hong Kong Dollar', 2),
+    ('JPY', 'Yen', 0),
+    ('KRW', 'Won', 0),
+    ('SGD', 'Singapore Dollar', 2),
+    ('TWD', 'New Taiwan Dollar', 2),
+    ('USD', 'US Dollar', 2)
+ON CONFLICT DO NOTHING;
+
+-- 已有账本使用的未知币种, 按2位小数补录
+INSERT INTO currencies (code, name, minor_unit)
+    SELECT DISTINCT base_currency, base_currency, 2 FROM ledgers
+ON CONFLICT DO NOTHING;
+
+ALTER TABLE ledgers
+    ADD CONSTRAINT ledgers_base_currency_fkey FOREIGN KEY (base_currency) REFERENCES currencies (code);
+
+-- 1 单位 base 货币在 effective_date 起兑换 rate 单位 quote 货币
+CREATE TABLE IF NOT EXISTS exchange_rates (
+    id UUID DEFAULT uuid_generate_v4() PRIMARY KEY,
+    base VARCHAR(3) NOT NULL REFERENCES currencies (code),
+    quote VARCHAR(3) NOT NULL REFERENCES currencies (code),
+    rate NUMERIC NOT NULL CHECK (rate > 0),
+    effective_date DATE NOT NULL,
+    created_at TIMESTAMPTZ NOT NULL,
+    updated_at TIMESTAMPTZ NOT NULL,
+    UNIQUE (base, quote, effective_date)
+);
+
+-- 过账行的币种, 历史数据默认为账本本位币
+ALTER TABLE postings ADD COLUMN currency VARCHAR(3) REFERENCES currencies (code);
+UPDATE postings p SET currency = l.base_currency
+    FROM entries e JOIN ledgers l ON l.id = e.ledger_id
+    WHERE e.id = p.entry_id;
+ALTER TABLE postings ALTER COLUMN currency SET NOT NULL;
+
+-- 截至 as_of 日期 from -> to 的最新汇率, 优先直接汇率, 其次反向汇率, 均无时返回 NULL
+CREATE OR REPLACE FUNCTION exchange_rate(VARCHAR, VARCHAR, DATE) RETURNS NUMERIC AS $$
+    SELECT CASE WHEN $1 = $2 THEN 1::NUMERIC ELSE COALESCE(
+        (SELECT rate FROM exchange_rates
+            WHERE base = $1 AND quote = $2 AND effective_date <= $3
+            ORDER BY effective_date DESC LIMIT 1),
+        (SELECT 1 / rate FROM exchange_rates
+            WHERE base = $2 AND quote = $1 AND effective_date <= $3
+            ORDER BY effective_date DESC LIMIT 1)
+    ) END
+$$ LANGUAGE SQL STABLE;
+
+-- 将 from 货币的最小单位金额换算为 to 货币的最小单位金额, 四舍五入
+CREATE OR REPLACE FUNCTION convert_amount(NUMERIC, VARCHAR, VARCHAR, DATE) RETURNS BIGINT AS $$
+    SELECT ROUND(
+        $1 * exchange_rate($2, $3, $4)
+            * power(10::NUMERIC,
+                (SELECT minor_unit FROM currencies WHERE code = $3)
+                - (SELECT minor_unit FROM currencies WHERE code = $2))
+    )::BIGINT
+$$ LANGUAGE SQL STABLE;
@@ -0,0 +1,29 @@
+CREATE TABLE IF NOT EXISTS categories (
+    id UUID DEFAULT uuid_generate_v4() PRIMARY KEY,
+    ledger_id UUID NOT NULL REFERENCES ledgers (id) ON DELETE CASCADE,
+    name VARCHAR NOT NULL,
+    created_at TIMESTAMPTZ NOT NULL,
+    updated_at TIMESTAMPTZ NOT NULL,
+    UNIQUE (ledger_id, name)
+);
+
+-- 分录的分类与标签, 标签为自由文本
+ALTER TABLE entries ADD COLUMN category_id UUID REFERENCES categories (id) ON DELETE SET NULL;
+ALTER TABLE entries ADD COLUMN tags TEXT[] NOT NULL DEFAULT '{}';
+
+CREATE INDEX IF NOT EXISTS entries_tags_idx ON entries USING GIN (tags);
+
+-- 自动分类规则: 摘要包含 pattern(不区分大小写) 时设置 category_id 并添加 tag
+CREATE TABLE IF NOT EXISTS categorization_rules (
+    id UUID DEFAULT uuid_generate_v4() PRIMARY KEY,
+    ledger_id UUID NOT NULL REFERENCES ledgers (id) ON DELETE CASCADE,
+    pattern VARCHAR NOT NULL,
+    category_id UUID REFERENCES categories (id) ON DELETE CASCADE,
+    tag VARCHAR,
+    priority INTEGER NOT NULL DEFAULT 0,
+    created_at TIMESTAMPTZ NOT NULL,
+    updated_at TIMESTAMPTZ NOT NULL,
+    CHECK (category_id IS NOT NULL OR tag IS NOT NULL)
+);
+
+CREATE INDEX IF NOT EXISTS categorization_rules_ledger_id_idx ON categorization_rules (ledger_id);
@@ -0,0 +1,20 @@
+CREATE TYPE budget_period AS ENUM ('monthly', 'quarterly', 'yearly');
+
+-- 预算目标为分类或科目子树之一, 金额以账本本位币的最小货币单位存储
+CREATE TABLE IF NOT EXISTS budgets (
+    id UUID DEFAULT uuid_generate_v4() PRIMARY KEY,
+    ledger_id UUID NOT NULL REFERENCES ledgers (id) ON DELETE CASCADE,
+    name VARCHAR NOT NULL,
+    category_id UUID REFERENCES categories (id) ON DELETE CASCADE,
+    account_id UUID REFERENCES accounts (id) ON DELETE CASCADE,
+    period budget_period NOT NULL,
+    amount BIGINT NOT NULL CHECK (amount > 0),
+    rollover BOOLEAN NOT NULL DEFAULT FALSE,
+    start_date DATE NOT NULL,
+    created_at TIMESTAMPTZ NOT NULL,
+    updated_at TIMESTAMPTZ NOT NULL,
+    CHECK ((category_id IS NULL) <> (account_id IS NULL))
+);
+
+CREATE INDEX IF NOT EXISTS budgets_ledger_id_idx ON budgets (ledger_id);
+CREATE INDEX IF NOT EXISTS entries_category_id_idx ON entries (category_id);
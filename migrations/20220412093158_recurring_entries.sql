@@ -0,0 +1,34 @@
+CREATE TYPE recurrence_frequency AS ENUM ('daily', 'weekly', 'monthly', 'yearly');
+
+-- 周期分录模板, 每 every 个 frequency 生成一次, next_run 为空表示已结束
+CREATE TABLE IF NOT EXISTS recurring_entries (
+    id UUID DEFAULT uuid_generate_v4() PRIMARY KEY,
+    ledger_id UUID NOT NULL REFERENCES ledgers (id) ON DELETE CASCADE,
+    description VARCHAR NOT NULL,
+    category_id UUID REFERENCES categories (id) ON DELETE SET NULL,
+    tags TEXT[] NOT NULL DEFAULT '{}',
+    postings JSONB NOT NULL,
+    frequency recurrence_frequency NOT NULL,
+    every INTEGER NOT NULL CHECK (every > 0),
+    start_date DATE NOT NULL,
+    end_date DATE CHECK (end_date >= start_date),
+    next_run DATE,
+    -- 连续生成失败的次数与最近一次的错误, 达到上限后暂停调度, 成功或恢复后清零
+    failures INTEGER NOT NULL DEFAULT 0,
+    last_error VARCHAR,
+    created_at TIMESTAMPTZ NOT NULL,
+    updated_at TIMESTAMPTZ NOT NULL
+);
+
+CREATE INDEX IF NOT EXISTS recurring_entries_ledger_id_idx ON recurring_entries (ledger_id);
+CREATE INDEX IF NOT EXISTS recurring_entries_next_run_idx ON recurring_entries (next_run)
+    WHERE next_run IS NOT NULL;
+
+-- 已生成的周期, 主键保证同一周期只会记账一次
+CREATE TABLE IF NOT EXISTS recurring_occurrences (
+    recurring_id UUID NOT NULL REFERENCES recurring_entries (id) ON DELETE CASCADE,
+    occurrence_date DATE NOT NULL,
+    entry_id UUID REFERENCES entries (id) ON DELETE SET NULL,
+    created_at TIMESTAMPTZ NOT NULL,
+    PRIMARY KEY (recurring_id, occurrence_date)
+);
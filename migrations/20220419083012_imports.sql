@@ -0,0 +1,11 @@
+-- 已导入的对账单行指纹, 主键保证同一账本内同一笔交易只会导入一次,
+-- 分录删除后指纹随之删除, 可重新导入
+CREATE TABLE IF NOT EXISTS import_fingerprints (
+    ledger_id UUID NOT NULL REFERENCES ledgers (id) ON DELETE CASCADE,
+    fingerprint CHAR(64) NOT NULL,
+    entry_id UUID REFERENCES entries (id) ON DELETE CASCADE,
+    created_at TIMESTAMPTZ NOT NULL,
+    PRIMARY KEY (ledger_id, fingerprint)
+);
+
+CREATE INDEX IF NOT EXISTS import_fingerprints_entry_id_idx ON import_fingerprints (entry_id);
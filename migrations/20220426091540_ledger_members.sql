@@ -0,0 +1,24 @@
+CREATE TYPE ledger_role AS ENUM ('viewer', 'editor', 'owner');
+
+-- 账本成员及其角色, 每个账本至少保留一名 owner
+CREATE TABLE IF NOT EXISTS ledger_members (
+    ledger_id UUID NOT NULL REFERENCES ledgers (id) ON DELETE CASCADE,
+    user_id UUID NOT NULL REFERENCES users (id) ON DELETE CASCADE,
+    role ledger_role NOT NULL,
+    created_at TIMESTAMPTZ NOT NULL,
+    updated_at TIMESTAMPTZ NOT NULL,
+    PRIMARY KEY (ledger_id, user_id)
+);
+
+CREATE INDEX IF NOT EXISTS ledger_members_user_id_idx ON ledger_members (user_id);
+
+-- 已有账本的创建者成为 owner
+INSERT INTO ledger_members (ledger_id, user_id, role, created_at, updated_at)
+    SELECT id, owner_id, 'owner', created_at, updated_at FROM ledgers
+    ON CONFLICT DO NOTHING;
+
+-- 账本归属以成员表为准, owner_id 只记录创建者, 创建者注销后置空而不删除账本
+ALTER TABLE ledgers ALTER COLUMN owner_id DROP NOT NULL;
+ALTER TABLE ledgers DROP CONSTRAINT IF EXISTS ledgers_owner_id_fkey;
+ALTER TABLE ledgers ADD CONSTRAINT ledgers_owner_id_fkey
+    FOREIGN KEY (owner_id) REFERENCES users (id) ON DELETE SET NULL;
@@ -0,0 +1,5 @@
+-- 邮箱验证时间, 为空表示尚未验证; 修改邮箱后需重新验证
+ALTER TABLE users ADD COLUMN IF NOT EXISTS verified_at TIMESTAMPTZ;
+
+-- 迁移前注册的账号视为已验证, 避免开启验证后被拒绝登录
+UPDATE users SET verified_at = created_at WHERE verified_at IS NULL;
@@ -0,0 +1,20 @@
+-- TOTP 两步验证, 每个用户一个密钥; confirmed_at 为空表示尚在绑定中
+-- last_step 为最近一次通过校验的时间步, 同一验证码不能重复使用
+CREATE TABLE IF NOT EXISTS user_totp (
+    user_id UUID PRIMARY KEY REFERENCES users (id) ON DELETE CASCADE,
+    secret VARCHAR NOT NULL,
+    confirmed_at TIMESTAMPTZ,
+    last_step BIGINT,
+    created_at TIMESTAMPTZ NOT NULL
+);
+
+-- 恢复码, 仅存 SHA-256 摘要, 每个只能使用一次
+CREATE TABLE IF NOT EXISTS recovery_codes (
+    id UUID DEFAULT uuid_generate_v4() PRIMARY KEY,
+    user_id UUID NOT NULL REFERENCES users (id) ON DELETE CASCADE,
+    code_hash VARCHAR NOT NULL,
+    used_at TIMESTAMPTZ,
+    created_at TIMESTAMPTZ NOT NULL
+);
+
+CREATE INDEX IF NOT EXISTS recovery_codes_user_id_idx ON recovery_codes (user_id);
@@ -0,0 +1,22 @@
+-- WebAuthn 凭据, public_key 为 COSE_Key 原文; sign_count 用于发现被克隆的认证器
+CREATE TABLE IF NOT EXISTS passkey_credentials (
+    id UUID DEFAULT uuid_generate_v4() PRIMARY KEY,
+    user_id UUID NOT NULL REFERENCES users (id) ON DELETE CASCADE,
+    credential_id VARCHAR NOT NULL UNIQUE,
+    public_key BYTEA NOT NULL,
+    sign_count BIGINT NOT NULL DEFAULT 0,
+    name VARCHAR NOT NULL,
+    created_at TIMESTAMPTZ NOT NULL,
+    last_used_at TIMESTAMPTZ
+);
+
+CREATE INDEX IF NOT EXISTS passkey_credentials_user_id_idx ON passkey_credentials (user_id);
+
+-- 注册与登录仪式的挑战值, 使用后即删除; 登录挑战不关联用户
+CREATE TABLE IF NOT EXISTS passkey_challenges (
+    id UUID DEFAULT uuid_generate_v4() PRIMARY KEY,
+    user_id UUID REFERENCES users (id) ON DELETE CASCADE,
+    challenge VARCHAR NOT NULL,
+    expires_at TIMESTAMPTZ NOT NULL,
+    created_at TIMESTAMPTZ NOT NULL
+);
@@ -0,0 +1,23 @@
+-- 外部身份提供方账号与本地用户的关联, 同一提供方下 subject 唯一
+CREATE TABLE IF NOT EXISTS user_identities (
+    id UUID DEFAULT uuid_generate_v4() PRIMARY KEY,
+    user_id UUID NOT NULL REFERENCES users (id) ON DELETE CASCADE,
+    provider VARCHAR NOT NULL,
+    subject VARCHAR NOT NULL,
+    email VARCHAR,
+    created_at TIMESTAMPTZ NOT NULL,
+    last_login_at TIMESTAMPTZ,
+    UNIQUE (provider, subject)
+);
+
+CREATE INDEX IF NOT EXISTS user_identities_user_id_idx ON user_identities (user_id);
+
+-- 授权码流程中的 state, 回调时取出并删除; nonce 与 PKCE code_verifier 只保存在服务端
+CREATE TABLE IF NOT EXISTS oidc_login_states (
+    state VARCHAR PRIMARY KEY,
+    provider VARCHAR NOT NULL,
+    nonce VARCHAR NOT NULL,
+    code_verifier VARCHAR NOT NULL,
+    expires_at TIMESTAMPTZ NOT NULL,
+    created_at TIMESTAMPTZ NOT NULL
+);
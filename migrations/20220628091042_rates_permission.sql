@@ -0,0 +1,9 @@
+-- 汇率表为全部账本共用, 写入需要系统权限
+INSERT INTO permissions (name, description) VALUES
+    ('rates:write', 'create and update exchange rates')
+    ON CONFLICT DO NOTHING;
+
+INSERT INTO role_permissions (role_id, permission_id)
+    SELECT r.id, p.id FROM roles r CROSS JOIN permissions p
+    WHERE r.name = 'admin' AND p.name = 'rates:write'
+    ON CONFLICT DO NOTHING;
@@ -0,0 +1,3 @@
+-- 当前登录第二步令牌的 jti 与已尝试次数, 尝试次数达到上限后该令牌作废
+ALTER TABLE user_totp ADD COLUMN IF NOT EXISTS challenge_id UUID;
+ALTER TABLE user_totp ADD COLUMN IF NOT EXISTS challenge_attempts INT NOT NULL DEFAULT 0;
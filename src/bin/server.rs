@@ -2,7 +2,7 @@ use anyhow::Result;
 use cashbook::config;
 use clap::Parser;
 use dotenv::dotenv;
-use std::net::SocketAddr;
+use std::{net::SocketAddr, time::Duration};
 use tracing_subscriber::EnvFilter;
 
 #[tokio::main]
@@ -18,6 +18,12 @@ async fn main() -> Result<()> {
     let config = config::env::ServerConfig::parse();
 
     let pool = sqlx::PgPool::retrieve().await;
+    cashbook::rehash_legacy_passwords(pool.clone()).await?;
+    cashbook::bootstrap_admin(pool.clone()).await?;
+    tokio::spawn(cashbook::recurring_scheduler(
+        pool.clone(),
+        Duration::from_secs(config.recurring_interval_secs),
+    ));
 
     let addr = SocketAddr::from((config.serv_host, config.serv_port));
     tracing::debug!("listening on {}", addr);
@@ -1,3 +1,7 @@
+use super::{
+    env::{JwtClaimsConfig, JwtConfig, TokenConfig},
+    keys::KeyRing,
+};
 use std::env;
 
 lazy_static! {
@@ -5,4 +9,11 @@ lazy_static! {
     pub static ref BEARER: &'static str = "Bearer";
     /// token加密字符串
     pub static ref JWT_SECRET: String = env::var("JWT_SECRET").expect("JWT_SECRET must be set");
+    /// 令牌签名密钥
+    pub static ref JWT_KEYS: KeyRing = KeyRing::load(&JwtConfig::from_env(), || JWT_SECRET.clone())
+        .expect("invalid JWT signing keys");
+    /// 令牌声明与校验参数
+    pub static ref JWT_CLAIMS: JwtClaimsConfig = JwtClaimsConfig::from_env();
+    /// 令牌有效期
+    pub static ref TOKEN_TTL: TokenConfig = TokenConfig::from_env();
 }
@@ -9,6 +9,9 @@ pub struct ServerConfig {
     pub serv_host: IpAddr,
     #[clap(default_value = "8080", env)]
     pub serv_port: u16,
+    /// 周期分录调度间隔(秒)
+    #[clap(default_value = "300", env)]
+    pub recurring_interval_secs: u64,
 }
 
 /// PostgresSQL 数据库配置
@@ -25,3 +28,342 @@ pub struct PgConfig {
     #[clap(default_value = "", env)]
     pub pg_password: String,
 }
+
+/// Argon2id 密码哈希参数, 调整后已有密码在下次登录时重新哈希
+#[derive(Debug, Clone, Parser)]
+pub struct PasswordConfig {
+    /// 内存开销(KiB)
+    #[clap(default_value = "19456", env)]
+    pub argon2_memory_kib: u32,
+    /// 迭代次数
+    #[clap(default_value = "2", env)]
+    pub argon2_iterations: u32,
+    /// 并行度
+    #[clap(default_value = "1", env)]
+    pub argon2_parallelism: u32,
+}
+
+impl PasswordConfig {
+    /// 仅从环境变量读取, 不解析命令行参数
+    pub fn from_env() -> Self {
+        PasswordConfig::parse_from(["cashbook"])
+    }
+}
+
+/// 令牌有效期配置
+#[derive(Debug, Clone, Parser)]
+pub struct TokenConfig {
+    /// access token 有效期(秒)
+    #[clap(default_value = "900", env)]
+    pub access_token_ttl_secs: i64,
+    /// refresh token 有效期(天)
+    #[clap(default_value = "30", env)]
+    pub refresh_token_ttl_days: i64,
+}
+
+impl TokenConfig {
+    /// 仅从环境变量读取, 不解析命令行参数
+    pub fn from_env() -> Self {
+        TokenConfig::parse_from(["cashbook"])
+    }
+}
+
+/// JWT 签名密钥配置
+#[derive(Debug, Clone, Parser)]
+pub struct JwtConfig {
+    /// 逗号分隔的 `kid=PEM 私钥路径`, 支持 RS256 与 Ed25519; 为空时使用 JWT_SECRET 的 HS256
+    #[clap(env)]
+    pub jwt_keys: Option<String>,
+    /// 签发新令牌使用的 kid, 默认第一个; 轮换时保留旧密钥直到其签发的令牌全部过期
+    #[clap(env)]
+    pub jwt_active_kid: Option<String>,
+}
+
+impl JwtConfig {
+    /// 仅从环境变量读取, 不解析命令行参数
+    pub fn from_env() -> Self {
+        JwtConfig::parse_from(["cashbook"])
+    }
+}
+
+/// JWT 声明与校验配置
+#[derive(Debug, Clone, Parser)]
+pub struct JwtClaimsConfig {
+    /// 签发方(iss), 验签时必须一致
+    #[clap(default_value = "cashbook", env)]
+    pub jwt_issuer: String,
+    /// 受众(aud), 验签时必须一致
+    #[clap(default_value = "cashbook", env)]
+    pub jwt_audience: String,
+    /// 校验 exp/iat 时容忍的时钟偏差(秒)
+    #[clap(default_value = "60", env)]
+    pub jwt_leeway_secs: u64,
+}
+
+impl JwtClaimsConfig {
+    /// 仅从环境变量读取, 不解析命令行参数
+    pub fn from_env() -> Self {
+        JwtClaimsConfig::parse_from(["cashbook"])
+    }
+}
+
+/// 邮件发送配置, 未设置 SMTP_HOST 时写入 MAIL_DIR 目录
+#[derive(Debug, Clone, Parser)]
+pub struct MailConfig {
+    #[clap(env)]
+    pub smtp_host: Option<String>,
+    #[clap(default_value = "587", env)]
+    pub smtp_port: u16,
+    #[clap(env)]
+    pub smtp_username: Option<String>,
+    #[clap(env)]
+    pub smtp_password: Option<String>,
+    /// 连接后通过 STARTTLS 升级为加密连接
+    #[clap(default_value = "true", env, parse(try_from_str))]
+    pub smtp_starttls: bool,
+    /// EHLO 中声明的本机域名
+    #[clap(default_value = "localhost", env)]
+    pub smtp_helo: String,
+    /// 建立连接与等待每个应答的超时时间(秒)
+    #[clap(default_value = "30", env)]
+    pub smtp_timeout_secs: u64,
+    /// 发件人地址
+    #[clap(default_value = "cashbook@localhost", env)]
+    pub mail_from: String,
+    /// 开发环境下保存邮件的目录
+    #[clap(default_value = "mail", env)]
+    pub mail_dir: String,
+}
+
+impl MailConfig {
+    /// 仅从环境变量读取, 不解析命令行参数
+    pub fn from_env() -> Self {
+        MailConfig::parse_from(["cashbook"])
+    }
+}
+
+/// 注册邮箱验证配置
+#[derive(Debug, Clone, Parser)]
+pub struct VerificationConfig {
+    /// 未验证邮箱的账号禁止登录
+    #[clap(default_value = "false", env, parse(try_from_str))]
+    pub require_email_verification: bool,
+    /// 验证链接有效期(小时)
+    #[clap(default_value = "24", env)]
+    pub email_verification_ttl_hours: i64,
+    /// 验证页面地址, 令牌以 token 查询参数附加在后面
+    #[clap(default_value = "http://localhost:8080/verify-email", env)]
+    pub email_verification_url: String,
+}
+
+impl VerificationConfig {
+    /// 仅从环境变量读取, 不解析命令行参数
+    pub fn from_env() -> Self {
+        VerificationConfig::parse_from(["cashbook"])
+    }
+}
+
+/// 密码重置配置
+#[derive(Debug, Clone, Parser)]
+pub struct PasswordResetConfig {
+    /// 重置链接有效期(分钟)
+    #[clap(default_value = "60", env)]
+    pub password_reset_ttl_minutes: i64,
+    /// 重置页面地址, 令牌以 token 查询参数附加在后面
+    #[clap(default_value = "http://localhost:8080/reset-password", env)]
+    pub password_reset_url: String,
+}
+
+impl PasswordResetConfig {
+    /// 仅从环境变量读取, 不解析命令行参数
+    pub fn from_env() -> Self {
+        PasswordResetConfig::parse_from(["cashbook"])
+    }
+}
+
+/// TOTP 两步验证配置
+#[derive(Debug, Clone, Parser)]
+pub struct TotpConfig {
+    /// 验证器 App 中显示的发行方名称
+    #[clap(default_value = "cashbook", env)]
+    pub totp_issuer: String,
+    /// 允许的前后时间步数, 用于容忍客户端时钟偏差
+    #[clap(default_value = "1", env)]
+    pub totp_skew_steps: i64,
+    /// 启用时生成的恢复码数量
+    #[clap(default_value = "10", env)]
+    pub totp_recovery_codes: usize,
+    /// 登录第二步令牌有效期(秒)
+    #[clap(default_value = "300", env)]
+    pub mfa_token_ttl_secs: i64,
+    /// 每个登录第二步令牌可尝试的次数, 用完后需重新输入密码
+    #[clap(default_value = "5", env)]
+    pub mfa_max_attempts: i32,
+}
+
+impl TotpConfig {
+    /// 仅从环境变量读取, 不解析命令行参数
+    pub fn from_env() -> Self {
+        TotpConfig::parse_from(["cashbook"])
+    }
+}
+
+/// WebAuthn/passkey 配置
+#[derive(Debug, Clone, Parser)]
+pub struct WebAuthnConfig {
+    /// 依赖方 id, 即前端页面的域名
+    #[clap(default_value = "localhost", env)]
+    pub webauthn_rp_id: String,
+    /// 认证器中显示的依赖方名称
+    #[clap(default_value = "cashbook", env)]
+    pub webauthn_rp_name: String,
+    /// 前端页面的 origin, clientDataJSON 中的 origin 必须与之相同
+    #[clap(default_value = "http://localhost:8080", env)]
+    pub webauthn_origin: String,
+    /// 注册与登录仪式的有效期(秒)
+    #[clap(default_value = "300", env)]
+    pub webauthn_timeout_secs: i64,
+}
+
+impl WebAuthnConfig {
+    /// 仅从环境变量读取, 不解析命令行参数
+    pub fn from_env() -> Self {
+        WebAuthnConfig::parse_from(["cashbook"])
+    }
+}
+
+/// 系统管理员配置
+#[derive(Debug, Clone, Parser)]
+pub struct AdminConfig {
+    /// 启动时设为首个管理员的用户邮箱, 该用户须已注册并验证邮箱; 管理员曾经存在过则忽略
+    #[clap(env)]
+    pub bootstrap_admin_email: Option<String>,
+}
+
+impl AdminConfig {
+    /// 仅从环境变量读取, 不解析命令行参数
+    pub fn from_env() -> Self {
+        AdminConfig::parse_from(["cashbook"])
+    }
+}
+
+/// OIDC 单点登录配置, 各提供方的参数从 OIDC_<NAME>_* 环境变量读取
+#[derive(Debug, Clone, Parser)]
+pub struct OidcConfig {
+    /// 启用的提供方名称, 逗号分隔, 如 corp,google
+    #[clap(env)]
+    pub oidc_providers: Option<String>,
+    /// 回调地址前缀, 完整地址为 {前缀}/{provider}/callback, 需在提供方处登记
+    #[clap(default_value = "http://localhost:8080/auth/oidc", env)]
+    pub oidc_redirect_base_url: String,
+    /// 授权码登录流程的有效期(秒)
+    #[clap(default_value = "600", env)]
+    pub oidc_state_ttl_secs: i64,
+}
+
+/// 单个 OIDC 提供方
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct OidcProviderConfig {
+    pub name: String,
+    /// 签发方地址, 从 {issuer}/.well-known/openid-configuration 读取各端点
+    pub issuer: String,
+    pub client_id: String,
+    /// 公开客户端只使用 PKCE, 可不设置
+    pub client_secret: Option<String>,
+    pub scopes: String,
+}
+
+impl OidcConfig {
+    /// 仅从环境变量读取, 不解析命令行参数
+    pub fn from_env() -> Self {
+        OidcConfig::parse_from(["cashbook"])
+    }
+
+    /// 读取 OIDC_<NAME>_ISSUER、_CLIENT_ID、_CLIENT_SECRET 与 _SCOPES
+    pub fn providers(&self) -> anyhow::Result<Vec<OidcProviderConfig>> {
+        self.providers_from(|key| std::env::var(key).ok())
+    }
+
+    fn providers_from(
+        &self,
+        var: impl Fn(&str) -> Option<String>,
+    ) -> anyhow::Result<Vec<OidcProviderConfig>> {
+        let mut providers: Vec<OidcProviderConfig> = Vec::new();
+        for name in self
+            .oidc_providers
+            .iter()
+            .flat_map(|names| names.split(','))
+        {
+            let name = name.trim();
+            if name.is_empty() {
+                continue;
+            }
+            if providers.iter().any(|provider| provider.name == name) {
+                anyhow::bail!("duplicate OIDC provider {}", name);
+            }
+            let prefix = format!("OIDC_{}_", name.to_uppercase().replace('-', "_"));
+            let required = |key: &str| {
+                var(&format!("{}{}", prefix, key))
+                    .ok_or_else(|| anyhow::anyhow!("{}{} is not set", prefix, key))
+            };
+            providers.push(OidcProviderConfig {
+                name: name.to_owned(),
+                issuer: required("ISSUER")?.trim_end_matches('/').to_owned(),
+                client_id: required("CLIENT_ID")?,
+                client_secret: var(&format!("{}CLIENT_SECRET", prefix)),
+                scopes: var(&format!("{}SCOPES", prefix))
+                    .unwrap_or_else(|| "openid email profile".to_owned()),
+            });
+        }
+        Ok(providers)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashMap;
+
+    #[test]
+    fn test_oidc_providers_from_env() {
+        let vars: HashMap<&str, &str> = [
+            ("OIDC_CORP_ISSUER", "https://sso.example.com/"),
+            ("OIDC_CORP_CLIENT_ID", "cashbook"),
+            ("OIDC_CORP_CLIENT_SECRET", "secret"),
+            ("OIDC_MY_IDP_ISSUER", "http://localhost:9000"),
+            ("OIDC_MY_IDP_CLIENT_ID", "public"),
+            ("OIDC_MY_IDP_SCOPES", "openid email"),
+        ]
+        .into_iter()
+        .collect();
+        let var = |key: &str| vars.get(key).map(|value| value.to_string());
+        let config = |providers: &str| OidcConfig {
+            oidc_providers: Some(providers.to_owned()),
+            oidc_redirect_base_url: "http://localhost:8080/auth/oidc".to_owned(),
+            oidc_state_ttl_secs: 600,
+        };
+
+        let providers = config("corp, my-idp,").providers_from(var).unwrap();
+        assert_eq!(
+            vec![
+                OidcProviderConfig {
+                    name: "corp".to_owned(),
+                    issuer: "https://sso.example.com".to_owned(),
+                    client_id: "cashbook".to_owned(),
+                    client_secret: Some("secret".to_owned()),
+                    scopes: "openid email profile".to_owned(),
+                },
+                OidcProviderConfig {
+                    name: "my-idp".to_owned(),
+                    issuer: "http://localhost:9000".to_owned(),
+                    client_id: "public".to_owned(),
+                    client_secret: None,
+                    scopes: "openid email".to_owned(),
+                },
+            ],
+            providers
+        );
+        assert!(config("corp,corp").providers_from(var).is_err());
+        assert!(config("google").providers_from(var).is_err());
+    }
+}
@@ -0,0 +1,271 @@
+use anyhow::{anyhow, bail, Context};
+use jsonwebtoken::{errors::ErrorKind, Algorithm, DecodingKey, EncodingKey, Header, Validation};
+use ring::signature::{Ed25519KeyPair, KeyPair, RsaKeyPair};
+use serde::{de::DeserializeOwned, Serialize};
+use std::fs;
+
+use super::env::JwtConfig;
+
+/// 单个签名密钥, 非对称密钥同时携带对外公布的公钥
+pub struct SigningKey {
+    pub kid: Option<String>,
+    pub algorithm: Algorithm,
+    pub encoding: EncodingKey,
+    pub decoding: DecodingKey,
+    jwk: Option<Jwk>,
+}
+
+impl SigningKey {
+    /// HS256 共享密钥, 仅在未配置非对称密钥时使用, 不对外公布
+    pub fn hmac(secret: &[u8]) -> Self {
+        Self {
+            kid: None,
+            algorithm: Algorithm::HS256,
+            encoding: EncodingKey::from_secret(secret),
+            decoding: DecodingKey::from_secret(secret),
+            jwk: None,
+        }
+    }
+
+    /// 从 PEM 私钥加载, 支持 PKCS#8 Ed25519 与 PKCS#1/PKCS#8 RSA
+    pub fn from_pem(kid: &str, pem_bytes: &[u8]) -> anyhow::Result<Self> {
+        let block = pem::parse(pem_bytes)?;
+        let der = block.contents.as_slice();
+
+        if block.tag == "PRIVATE KEY" {
+            if let Ok(pair) = Ed25519KeyPair::from_pkcs8_maybe_unchecked(der) {
+                let public = pair.public_key().as_ref();
+                return Ok(Self {
+                    kid: Some(kid.to_owned()),
+                    algorithm: Algorithm::EdDSA,
+                    encoding: EncodingKey::from_ed_pem(pem_bytes)?,
+                    decoding: DecodingKey::from_ed_der(public),
+                    jwk: Some(Jwk::okp(kid, public)),
+                });
+            }
+        }
+
+        let pair = match block.tag.as_str() {
+            "PRIVATE KEY" => RsaKeyPair::from_pkcs8(der),
+            "RSA PRIVATE KEY" => RsaKeyPair::from_der(der),
+            tag => bail!("unsupported PEM block {}", tag),
+        }
+        .map_err(|err| anyhow!("invalid RSA key: {}", err))?;
+        let public = pair.public_key();
+        let n = public.modulus().big_endian_without_leading_zero();
+        let e = public.exponent().big_endian_without_leading_zero();
+        Ok(Self {
+            kid: Some(kid.to_owned()),
+            algorithm: Algorithm::RS256,
+            encoding: EncodingKey::from_rsa_pem(pem_bytes)?,
+            decoding: DecodingKey::from_rsa_raw_components(n, e),
+            jwk: Some(Jwk::rsa(kid, n, e)),
+        })
+    }
+}
+
+/// 签名密钥集合, 使用当前密钥签发, 任一已配置密钥都可用于验签
+pub struct KeyRing {
+    keys: Vec<SigningKey>,
+    active: usize,
+}
+
+impl KeyRing {
+    /// 按配置加载 PEM 密钥; 未配置时退回到 JWT_SECRET 的 HS256
+    pub fn load(config: &JwtConfig, secret: impl FnOnce() -> String) -> anyhow::Result<Self> {
+        let mut keys = Vec::new();
+        for entry in config.jwt_keys.iter().flat_map(|keys| keys.split(',')) {
+            let entry = entry.trim();
+            if entry.is_empty() {
+                continue;
+            }
+            let (kid, path) = entry
+                .split_once('=')
+                .with_context(|| format!("JWT_KEYS entry `{}` is not kid=path", entry))?;
+            let pem = fs::read(path.trim()).with_context(|| format!("read JWT key {}", path))?;
+            let key = SigningKey::from_pem(kid.trim(), &pem)
+                .with_context(|| format!("load JWT key {}", kid))?;
+            if keys.iter().any(|other: &SigningKey| other.kid == key.kid) {
+                bail!("duplicate JWT key id {}", kid);
+            }
+            keys.push(key);
+        }
+
+        if keys.is_empty() {
+            return Ok(Self::new(vec![SigningKey::hmac(secret().as_bytes())], 0));
+        }
+
+        let active = match &config.jwt_active_kid {
+            Some(kid) => keys
+                .iter()
+                .position(|key| key.kid.as_deref() == Some(kid.as_str()))
+                .with_context(|| format!("JWT_ACTIVE_KID {} is not configured", kid))?,
+            None => 0,
+        };
+        Ok(Self::new(keys, active))
+    }
+
+    pub fn new(keys: Vec<SigningKey>, active: usize) -> Self {
+        assert!(active < keys.len(), "active key out of range");
+        Self { keys, active }
+    }
+
+    /// 当前用于签发的密钥
+    pub fn active(&self) -> &SigningKey {
+        &self.keys[self.active]
+    }
+
+    /// 按令牌头部的 kid 查找验签密钥
+    pub fn find(&self, kid: Option<&str>) -> Option<&SigningKey> {
+        self.keys.iter().find(|key| key.kid.as_deref() == kid)
+    }
+
+    /// 使用当前密钥签名, 并在头部写入 kid 供验签方选择公钥
+    pub fn encode<T: Serialize>(&self, claims: &T) -> jsonwebtoken::errors::Result<String> {
+        let key = self.active();
+        let mut header = Header::new(key.algorithm);
+        header.kid = key.kid.clone();
+        jsonwebtoken::encode(&header, claims, &key.encoding)
+    }
+
+    /// 按 kid 选择验签密钥, 轮换后旧密钥签发的令牌在过期前仍然有效
+    pub fn decode<T: DeserializeOwned>(
+        &self,
+        token: &str,
+        mut validation: Validation,
+    ) -> jsonwebtoken::errors::Result<T> {
+        let header = jsonwebtoken::decode_header(token)?;
+        let key = self
+            .find(header.kid.as_deref())
+            .filter(|key| key.algorithm == header.alg)
+            .ok_or_else(|| jsonwebtoken::errors::Error::from(ErrorKind::InvalidToken))?;
+        validation.algorithms = vec![key.algorithm];
+        jsonwebtoken::decode(token, &key.decoding, &validation).map(|data| data.claims)
+    }
+
+    /// 对外公布的公钥集合
+    pub fn jwks(&self) -> JwkSet {
+        JwkSet {
+            keys: self.keys.iter().filter_map(|key| key.jwk.clone()).collect(),
+        }
+    }
+}
+
+/// RFC 7517 JSON Web Key, 仅包含公钥参数
+#[derive(Debug, Clone, Serialize)]
+pub struct Jwk {
+    pub kty: &'static str,
+    pub kid: String,
+    pub alg: &'static str,
+    #[serde(rename = "use")]
+    pub key_use: &'static str,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub n: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub e: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub crv: Option<&'static str>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub x: Option<String>,
+}
+
+impl Jwk {
+    fn rsa(kid: &str, n: &[u8], e: &[u8]) -> Self {
+        Self {
+            kty: "RSA",
+            kid: kid.to_owned(),
+            alg: "RS256",
+            key_use: "sig",
+            n: Some(base64url(n)),
+            e: Some(base64url(e)),
+            crv: None,
+            x: None,
+        }
+    }
+
+    fn okp(kid: &str, public: &[u8]) -> Self {
+        Self {
+            kty: "OKP",
+            kid: kid.to_owned(),
+            alg: "EdDSA",
+            key_use: "sig",
+            n: None,
+            e: None,
+            crv: Some("Ed25519"),
+            x: Some(base64url(public)),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct JwkSet {
+    pub keys: Vec<Jwk>,
+}
+
+fn base64url(bytes: &[u8]) -> String {
+    base64::encode_config(bytes, base64::URL_SAFE_NO_PAD)
+}
+
+#[cfg(test)]
+pub mod tests {
+    use super::*;
+
+    /// 测试用密钥文件路径
+    pub fn fixture(name: &str) -> String {
+        format!("{}/tests/fixtures/{}", env!("CARGO_MANIFEST_DIR"), name)
+    }
+
+    fn config(keys: &str, active: Option<&str>) -> JwtConfig {
+        JwtConfig {
+            jwt_keys: Some(keys.to_owned()),
+            jwt_active_kid: active.map(str::to_owned),
+        }
+    }
+
+    #[test]
+    fn test_key_ring_loads_rsa_and_ed25519_keys() {
+        let keys = format!(
+            "2022a={},2022b={}, ed={}",
+            fixture("jwt_rs256_2022a.pem"),
+            fixture("jwt_rs256_2022b.pem"),
+            fixture("jwt_ed25519.pem"),
+        );
+        let ring = KeyRing::load(&config(&keys, Some("2022b")), || unreachable!()).unwrap();
+
+        assert_eq!(Some("2022b"), ring.active().kid.as_deref());
+        assert_eq!(
+            Algorithm::RS256,
+            ring.find(Some("2022a")).unwrap().algorithm
+        );
+        assert_eq!(Algorithm::EdDSA, ring.find(Some("ed")).unwrap().algorithm);
+        assert!(ring.find(None).is_none());
+
+        let jwks = serde_json::to_value(ring.jwks()).unwrap();
+        let keys = jwks["keys"].as_array().unwrap();
+        assert_eq!(3, keys.len());
+        assert_eq!("AQAB", keys[0]["e"]);
+        assert_eq!("OKP", keys[2]["kty"]);
+        assert_eq!("Ed25519", keys[2]["crv"]);
+        assert!(keys.iter().all(|key| key.get("d").is_none()));
+    }
+
+    #[test]
+    fn test_key_ring_falls_back_to_hmac() {
+        let config = JwtConfig {
+            jwt_keys: None,
+            jwt_active_kid: None,
+        };
+        let ring = KeyRing::load(&config, || "secret".to_owned()).unwrap();
+
+        assert_eq!(Algorithm::HS256, ring.active().algorithm);
+        assert!(ring.find(None).is_some());
+        assert!(ring.jwks().keys.is_empty());
+    }
+
+    #[test]
+    fn test_key_ring_rejects_unknown_active_kid() {
+        let keys = format!("a={}", fixture("jwt_ed25519.pem"));
+        assert!(KeyRing::load(&config(&keys, Some("b")), || unreachable!()).is_err());
+        assert!(KeyRing::load(&config("a", None), || unreachable!()).is_err());
+    }
+}
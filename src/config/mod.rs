@@ -1,4 +1,4 @@
-/// 一些配置与基础设施链接管理相关模块
+//! 一些配置与基础设施链接管理相关模块
 
 /// 常量定义
 pub(crate) mod constants;
@@ -6,3 +6,5 @@ pub(crate) mod constants;
 pub mod db;
 /// 环境变量与配置解析
 pub mod env;
+/// JWT 签名密钥
+pub(crate) mod keys;
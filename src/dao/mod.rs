@@ -1,2 +1,40 @@
+/// 科目数据库数据访问实现
+pub(crate) mod account_repo;
+/// Beancount 导入数据库数据访问实现
+pub(crate) mod beancount_repo;
+/// 预算数据库数据访问实现
+pub(crate) mod budget_repo;
+/// 分类与自动分类规则数据库数据访问实现
+pub(crate) mod category_repo;
+/// 货币与汇率数据库数据访问实现
+pub(crate) mod currency_repo;
+/// 分录数据库数据访问实现
+pub(crate) mod entry_repo;
+/// 导出数据流式读取实现
+pub(crate) mod export_repo;
+/// 对账单导入数据库数据访问实现
+pub(crate) mod import_repo;
+/// ledger 数据库数据访问实现
+pub(crate) mod ledger_repo;
+/// 账本成员数据库数据访问实现
+pub(crate) mod member_repo;
+/// OIDC 外部身份与登录状态数据库数据访问实现
+pub(crate) mod oidc_repo;
+/// passkey 凭据与挑战值数据库数据访问实现
+pub(crate) mod passkey_repo;
+/// 密码重置令牌数据库数据访问实现
+pub(crate) mod password_reset_repo;
+/// 系统角色与权限数据库数据访问实现
+pub(crate) mod rbac_repo;
+/// 周期分录数据库数据访问实现
+pub(crate) mod recurring_repo;
+/// 报表聚合查询实现
+pub(crate) mod report_repo;
+/// access token 撤销列表数据访问实现
+pub(crate) mod revocation_repo;
+/// refresh token 数据库数据访问实现
+pub(crate) mod token_repo;
+/// TOTP 密钥与恢复码数据库数据访问实现
+pub(crate) mod totp_repo;
 /// user 数据库数据访问实现
 pub(crate) mod user_repo;
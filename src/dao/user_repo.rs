@@ -1,9 +1,7 @@
+use super::{member_repo::release_ledgers, rbac_repo::ensure_other_admin};
 use crate::{
     errors::Result,
-    models::{
-        auth::Credential,
-        user::{CreateUser, User, UserOption},
-    },
+    models::user::{CreateUser, User, UserOption},
 };
 use axum::async_trait;
 use chrono::Utc;
@@ -18,10 +16,17 @@ pub trait UserRepo {
     async fn create(&self, user: CreateUser) -> Result<User>;
     async fn get(&self, id: Uuid) -> Result<User>;
     async fn get_by_email(&self, email: &str) -> Result<User>;
+    /// 会删除最后一名管理员时返回 LastAdmin, 是共享账本的最后一名 owner 时返回 LastOwner;
+    /// 只有该用户一名成员的账本一并删除
     async fn delete(&self, id: Uuid) -> Result<User>;
     async fn update(&self, user: &User) -> Result<User>;
     async fn list(&self, fields: UserOption) -> Result<Vec<User>>;
-    async fn authenticate(&self, credential: Credential) -> Result<User>;
+    /// 仅当密码仍为 current 时替换为 hashed, 返回是否替换
+    async fn update_password(&self, id: Uuid, current: &str, hashed: &str) -> Result<bool>;
+    /// 仍以明文保存密码的用户
+    async fn legacy_passwords(&self) -> Result<Vec<User>>;
+    /// 邮箱仍为 email 且尚未验证时标记为已验证, 返回是否标记
+    async fn mark_verified(&self, id: Uuid, email: &str) -> Result<bool>;
 }
 
 #[derive(Clone)]
@@ -46,14 +51,15 @@ impl UserRepo for UserRepoImpl {
             ",
             User::TABLE,
         );
-        Ok(sqlx::query_as(&sql)
+        let user = sqlx::query_as(&sql)
             .bind(user.name)
             .bind(user.email)
             .bind(user.password)
             .bind(Utc::now())
             .bind(Utc::now())
             .fetch_one(&*self.pool)
-            .await?)
+            .await?;
+        Ok(user)
     }
 
     async fn get_by_email(&self, email: &str) -> Result<User> {
@@ -74,8 +80,13 @@ impl UserRepo for UserRepoImpl {
     }
 
     async fn delete(&self, id: Uuid) -> Result<User> {
+        let mut tx = self.pool.begin().await?;
+        // 角色与账本成员关联随用户级联删除, 需先检查不会删掉最后一名管理员或 owner
+        ensure_other_admin(&mut tx, id).await?;
+        release_ledgers(&mut tx, id).await?;
         let sql = format!(r#"DELETE FROM {} WHERE id = $1 RETURNING *"#, User::TABLE);
-        let user = sqlx::query_as(&sql).bind(id).fetch_one(&*self.pool).await?;
+        let user = sqlx::query_as(&sql).bind(id).fetch_one(&mut tx).await?;
+        tx.commit().await?;
         Ok(user)
     }
 
@@ -86,6 +97,7 @@ impl UserRepo for UserRepoImpl {
             name = $1,  
             email = $2,
             password = $3,
+            verified_at = CASE WHEN email = $2 THEN verified_at END,
             updated_at = $4
             WHERE id = $5
             RETURNING *
@@ -97,7 +109,7 @@ impl UserRepo for UserRepoImpl {
             .bind(&user.email)
             .bind(&user.password)
             .bind(Utc::now())
-            .bind(&user.id)
+            .bind(user.id)
             .fetch_one(&*self.pool)
             .await?;
         Ok(user)
@@ -109,17 +121,38 @@ impl UserRepo for UserRepoImpl {
         Ok(rows)
     }
 
-    async fn authenticate(&self, credential: Credential) -> Result<User> {
+    async fn update_password(&self, id: Uuid, current: &str, hashed: &str) -> Result<bool> {
         let sql = format!(
-            "SELECT true FROM {} WHERE email = $1 AND password = crypt($2, password) RETURNING *",
+            "UPDATE {} SET password = $1, updated_at = $2 WHERE id = $3 AND password = $4",
             User::TABLE
         );
-        let user = sqlx::query_as(&sql)
-            .bind(&credential.email)
-            .bind(&credential.password)
-            .fetch_one(&*self.pool)
+        let result = sqlx::query(&sql)
+            .bind(hashed)
+            .bind(Utc::now())
+            .bind(id)
+            .bind(current)
+            .execute(&*self.pool)
             .await?;
-        Ok(user)
+        Ok(result.rows_affected() == 1)
+    }
+
+    async fn legacy_passwords(&self) -> Result<Vec<User>> {
+        let sql = format!("SELECT * FROM {} WHERE password NOT LIKE '$%'", User::TABLE);
+        Ok(sqlx::query_as(&sql).fetch_all(&*self.pool).await?)
+    }
+
+    async fn mark_verified(&self, id: Uuid, email: &str) -> Result<bool> {
+        let sql = format!(
+            "UPDATE {} SET verified_at = $1 WHERE id = $2 AND email = $3 AND verified_at IS NULL",
+            User::TABLE
+        );
+        let result = sqlx::query(&sql)
+            .bind(Utc::now())
+            .bind(id)
+            .bind(email)
+            .execute(&*self.pool)
+            .await?;
+        Ok(result.rows_affected() == 1)
     }
 }
 
@@ -192,6 +225,28 @@ mod tests {
         assert_eq!("1111", &update_user.name);
         // info!("{}", serde_json::to_string(&update_user).unwrap());
 
+        info!("testing replace legacy password ");
+        assert_eq!(1, sut.legacy_passwords().await.unwrap().len());
+        assert!(!sut
+            .update_password(user.id, "stale", "$argon2id$x")
+            .await
+            .unwrap());
+        assert!(sut
+            .update_password(user.id, "", "$argon2id$x")
+            .await
+            .unwrap());
+        assert!(sut.legacy_passwords().await.unwrap().is_empty());
+
+        info!("testing verify email ");
+        assert!(user.verified_at.is_none());
+        assert!(!sut.mark_verified(user.id, "other").await.unwrap());
+        assert!(sut.mark_verified(user.id, "email1").await.unwrap());
+        assert!(!sut.mark_verified(user.id, "email1").await.unwrap());
+        let mut verified = sut.get(user.id).await.unwrap();
+        assert!(verified.verified_at.is_some());
+        verified.email = "email2".to_string();
+        assert!(sut.update(&verified).await.unwrap().verified_at.is_none());
+
         println!("testing list users ");
         let user_option = UserOption {
             name: Some(String::from("1111")),
@@ -202,7 +257,7 @@ mod tests {
         // info!("{}", serde_json::to_string(users).unwrap());
 
         info!("testing delete user ");
-        let old_user = users.get(0).unwrap();
+        let old_user = users.first().unwrap();
         let delete_user = sut.delete(old_user.id).await.unwrap();
 
         assert_eq!(
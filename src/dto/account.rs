@@ -0,0 +1,26 @@
+use crate::models::account::AccountKind;
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+use validator::Validate;
+
+/// 创建科目输入数据
+#[derive(Debug, Serialize, Deserialize, Validate)]
+pub struct CreateAccountInput {
+    #[validate(length(min = 1, max = 64))]
+    pub name: String,
+    pub kind: AccountKind,
+    pub parent_id: Option<Uuid>,
+}
+
+/// 科目重命名输入数据
+#[derive(Debug, Serialize, Deserialize, Validate)]
+pub struct RenameAccountInput {
+    #[validate(length(min = 1, max = 64))]
+    pub name: String,
+}
+
+/// 移动科目输入数据, parent_id 为空时移动到顶层
+#[derive(Debug, Serialize, Deserialize, Validate)]
+pub struct MoveAccountInput {
+    pub parent_id: Option<Uuid>,
+}
@@ -1,4 +1,3 @@
-use crate::models::user::User;
 use serde::{Deserialize, Serialize};
 use validator::Validate;
 
@@ -10,14 +9,106 @@ pub struct LoginInput {
     pub password: String,
 }
 
-#[derive(Debug)]
-pub struct AuthPayload {
-    pub token: String,
-    pub user: User,
+#[derive(Debug, Serialize, Deserialize)]
+pub struct TokenPayload {
+    pub access_token: String,
+    pub token_type: String,
+    /// access token 有效期(秒)
+    pub expires_in: i64,
+    pub refresh_token: String,
+}
+
+/// 申请限定授权范围的 access token, scope 以空格分隔, 如 "read"
+#[derive(Debug, Serialize, Deserialize, Validate)]
+pub struct ScopedTokenInput {
+    #[validate(length(min = 1))]
+    pub scope: String,
 }
 
+/// 限定授权范围的 access token, 不附带 refresh token
 #[derive(Debug, Serialize, Deserialize)]
-pub struct TokenPayload {
+pub struct ScopedTokenPayload {
     pub access_token: String,
     pub token_type: String,
+    /// access token 有效期(秒)
+    pub expires_in: i64,
+    pub scope: String,
+}
+
+/// 刷新令牌输入数据
+#[derive(Debug, Serialize, Deserialize, Validate)]
+pub struct RefreshInput {
+    #[validate(length(min = 1))]
+    pub refresh_token: String,
+}
+
+/// 邮箱验证输入数据
+#[derive(Debug, Serialize, Deserialize, Validate)]
+pub struct VerifyEmailInput {
+    #[validate(length(min = 1))]
+    pub token: String,
+}
+
+/// 重新发送验证邮件输入数据
+#[derive(Debug, Serialize, Deserialize, Validate)]
+pub struct ResendVerificationInput {
+    #[validate(email)]
+    pub email: String,
+}
+
+/// 申请重置密码输入数据
+#[derive(Debug, Serialize, Deserialize, Validate)]
+pub struct ForgotPasswordInput {
+    #[validate(email)]
+    pub email: String,
+}
+
+/// 重置密码输入数据, 密码规则与注册一致
+#[derive(Debug, Serialize, Deserialize, Validate)]
+pub struct ResetPasswordInput {
+    #[validate(length(min = 1))]
+    pub token: String,
+    #[validate(length(min = 6), must_match = "password2")]
+    pub password: String,
+    #[validate(length(min = 6))]
+    pub password2: String,
+}
+
+/// 已启用两步验证时 login 返回的第二步令牌
+#[derive(Debug, Serialize, Deserialize)]
+pub struct MfaChallenge {
+    pub mfa_required: bool,
+    pub mfa_token: String,
+    /// mfa_token 有效期(秒)
+    pub expires_in: i64,
+}
+
+/// login 响应, 已启用两步验证时只返回 MfaChallenge
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum LoginPayload {
+    Token(TokenPayload),
+    MfaRequired(MfaChallenge),
+}
+
+/// 登录第二步输入数据, code 为 TOTP 验证码或恢复码
+#[derive(Debug, Serialize, Deserialize, Validate)]
+pub struct MfaLoginInput {
+    #[validate(length(min = 1))]
+    pub mfa_token: String,
+    #[validate(length(min = 1))]
+    pub code: String,
+}
+
+/// 绑定 TOTP 时首次输入的验证码
+#[derive(Debug, Serialize, Deserialize, Validate)]
+pub struct ConfirmTotpInput {
+    #[validate(length(equal = 6))]
+    pub code: String,
+}
+
+/// 启用两步验证后返回的恢复码, 只返回这一次
+#[derive(Debug, Serialize, Deserialize)]
+pub struct RecoveryCodesPayload {
+    pub recovery_codes: Vec<String>,
 }
@@ -0,0 +1,45 @@
+use crate::models::budget::BudgetPeriod;
+use chrono::NaiveDate;
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+use validator::Validate;
+
+/// 创建预算输入数据, category_id 与 account_id 必须且只能指定一个, start_date 为空时取当天
+#[derive(Debug, Serialize, Deserialize, Validate)]
+pub struct CreateBudgetInput {
+    #[validate(length(min = 1, max = 64))]
+    pub name: String,
+    pub category_id: Option<Uuid>,
+    pub account_id: Option<Uuid>,
+    pub period: BudgetPeriod,
+    #[validate(range(min = 1))]
+    pub amount: i64,
+    #[serde(default)]
+    pub rollover: bool,
+    pub start_date: Option<NaiveDate>,
+}
+
+impl CreateBudgetInput {
+    pub fn check(&self) -> bool {
+        self.category_id.is_some() != self.account_id.is_some()
+    }
+}
+
+/// 更新预算输入数据
+#[derive(Debug, Serialize, Deserialize, Validate)]
+pub struct UpdateBudgetInput {
+    #[validate(length(min = 1, max = 64))]
+    pub name: Option<String>,
+    #[validate(range(min = 1))]
+    pub amount: Option<i64>,
+    pub rollover: Option<bool>,
+}
+
+impl UpdateBudgetInput {
+    pub fn check(&self) -> bool {
+        if self.name.is_none() && self.amount.is_none() && self.rollover.is_none() {
+            return false;
+        }
+        true
+    }
+}
@@ -0,0 +1,28 @@
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+use validator::Validate;
+
+/// 创建分类输入数据
+#[derive(Debug, Serialize, Deserialize, Validate)]
+pub struct CreateCategoryInput {
+    #[validate(length(min = 1, max = 64))]
+    pub name: String,
+}
+
+/// 创建自动分类规则输入数据, category_id 与 tag 至少指定一个
+#[derive(Debug, Serialize, Deserialize, Validate)]
+pub struct CreateRuleInput {
+    #[validate(length(min = 1, max = 128))]
+    pub pattern: String,
+    pub category_id: Option<Uuid>,
+    #[validate(length(min = 1, max = 32))]
+    pub tag: Option<String>,
+    #[serde(default)]
+    pub priority: i32,
+}
+
+impl CreateRuleInput {
+    pub fn check(&self) -> bool {
+        self.category_id.is_some() || self.tag.is_some()
+    }
+}
@@ -0,0 +1,45 @@
+use super::{ledger::validate_currency_code, user::PagationInput};
+use chrono::NaiveDate;
+use rust_decimal::Decimal;
+use serde::{de, Deserialize, Deserializer, Serialize};
+use std::str::FromStr;
+use validator::{Validate, ValidationError};
+
+/// 写入汇率输入数据, rate 只接受字符串, 数字字面量会经过 f64 丢失精度
+#[derive(Debug, Serialize, Deserialize, Validate)]
+pub struct UpsertExchangeRateInput {
+    #[validate(custom = "validate_currency_code")]
+    pub base: String,
+    #[validate(custom = "validate_currency_code")]
+    pub quote: String,
+    #[validate(custom = "validate_positive_rate")]
+    #[serde(deserialize_with = "deserialize_rate")]
+    pub rate: Decimal,
+    pub effective_date: NaiveDate,
+}
+
+#[derive(Debug, Default, Serialize, Deserialize, Validate)]
+pub struct ListExchangeRateInput {
+    #[validate(custom = "validate_currency_code")]
+    pub base: Option<String>,
+    #[validate(custom = "validate_currency_code")]
+    pub quote: Option<String>,
+    #[serde(flatten)]
+    pub limit_offset: PagationInput,
+}
+
+fn deserialize_rate<'de, D>(deserializer: D) -> Result<Decimal, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    let value = String::deserialize(deserializer)?;
+    Decimal::from_str(&value).map_err(de::Error::custom)
+}
+
+fn validate_positive_rate(rate: &Decimal) -> Result<(), ValidationError> {
+    if rate.is_sign_negative() || rate.is_zero() {
+        return Err(ValidationError::new("rate must be positive"));
+    }
+
+    Ok(())
+}
@@ -0,0 +1,52 @@
+use super::{ledger::validate_currency_code, user::PagationInput};
+use crate::models::entry::Side;
+use chrono::NaiveDate;
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+use validator::{Validate, ValidationError};
+
+/// 过账行输入数据, 金额为正的最小货币单位, 币种为空时使用账本本位币
+#[derive(Debug, Clone, Serialize, Deserialize, Validate)]
+pub struct PostingInput {
+    pub account_id: Uuid,
+    #[validate(range(min = 1))]
+    pub amount: i64,
+    #[validate(custom = "validate_currency_code")]
+    pub currency: Option<String>,
+    pub side: Side,
+}
+
+/// 创建分录输入数据, 未指定分类时按账本的自动分类规则补全
+#[derive(Debug, Serialize, Deserialize, Validate)]
+pub struct CreateEntryInput {
+    pub date: NaiveDate,
+    #[validate(length(max = 255))]
+    pub description: String,
+    pub category_id: Option<Uuid>,
+    #[serde(default)]
+    #[validate(custom = "validate_tags")]
+    pub tags: Vec<String>,
+    #[validate(length(min = 2))]
+    #[validate]
+    pub postings: Vec<PostingInput>,
+}
+
+#[derive(Debug, Default, Serialize, Deserialize, Validate)]
+pub struct ListEntryInput {
+    pub from: Option<NaiveDate>,
+    pub to: Option<NaiveDate>,
+    pub category_id: Option<Uuid>,
+    #[serde(flatten)]
+    pub limit_offset: PagationInput,
+}
+
+/// 标签非空且不超过 32 个字符
+pub fn validate_tags(tags: &[String]) -> Result<(), ValidationError> {
+    if tags
+        .iter()
+        .any(|tag| tag.trim().is_empty() || tag.chars().count() > 32)
+    {
+        return Err(ValidationError::new("invalid tag"));
+    }
+    Ok(())
+}
@@ -0,0 +1,8 @@
+use crate::models::export::ExportFormat;
+use serde::{Deserialize, Serialize};
+
+/// 导出查询参数
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ExportInput {
+    pub format: ExportFormat,
+}
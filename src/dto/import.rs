@@ -0,0 +1,65 @@
+use super::ledger::validate_currency_code;
+use crate::models::import::StatementFormat;
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+use validator::Validate;
+
+fn default_date_format() -> String {
+    "%Y-%m-%d".to_string()
+}
+
+fn default_delimiter() -> char {
+    ','
+}
+
+/// CSV 对账单列映射, 列以表头名称指定。
+/// 金额可由带符号的 amount 列(正数为转入)给出, 或由 debit(转出)/credit(转入)两列给出
+#[derive(Debug, Clone, Serialize, Deserialize, Validate)]
+pub struct ColumnMapping {
+    #[validate(length(min = 1))]
+    pub date: Option<String>,
+    #[validate(length(min = 1))]
+    pub description: Option<String>,
+    pub amount: Option<String>,
+    pub debit: Option<String>,
+    pub credit: Option<String>,
+    /// chrono 日期格式
+    #[serde(default = "default_date_format")]
+    #[validate(length(min = 1))]
+    pub date_format: String,
+    #[serde(default = "default_delimiter")]
+    pub delimiter: char,
+}
+
+impl ColumnMapping {
+    pub fn check(&self) -> bool {
+        self.date.is_some()
+            && self.description.is_some()
+            && self.amount.is_some() != (self.debit.is_some() || self.credit.is_some())
+            && self.delimiter.is_ascii()
+    }
+}
+
+/// 对账单导入参数, CSV 格式需要同时给出列映射
+#[derive(Debug, Clone, Serialize, Deserialize, Validate)]
+pub struct ImportOptions {
+    /// 对账单所属科目
+    pub account_id: Uuid,
+    /// 对方科目
+    pub contra_account_id: Uuid,
+    /// 对账单币种, 为空时使用账本本位币
+    #[validate(custom = "validate_currency_code")]
+    pub currency: Option<String>,
+    #[serde(default)]
+    pub format: StatementFormat,
+    #[serde(flatten)]
+    #[validate]
+    pub columns: ColumnMapping,
+}
+
+impl ImportOptions {
+    pub fn check(&self) -> bool {
+        self.account_id != self.contra_account_id
+            && (self.format != StatementFormat::Csv || self.columns.check())
+    }
+}
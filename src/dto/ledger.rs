@@ -0,0 +1,47 @@
+use super::user::PagationInput;
+use serde::{Deserialize, Serialize};
+use validator::{Validate, ValidationError};
+
+/// 创建账本输入数据
+#[derive(Debug, Serialize, Deserialize, Validate)]
+pub struct CreateLedgerInput {
+    #[validate(length(min = 1, max = 64))]
+    pub name: String,
+    #[validate(custom = "validate_currency_code")]
+    pub base_currency: String,
+}
+
+/// 更新账本输入数据
+#[derive(Debug, Serialize, Deserialize, Validate)]
+pub struct UpdateLedgerInput {
+    #[validate(length(min = 1, max = 64))]
+    pub name: Option<String>,
+    #[validate(custom = "validate_currency_code")]
+    pub base_currency: Option<String>,
+    pub archived: Option<bool>,
+}
+
+impl UpdateLedgerInput {
+    pub fn check(&self) -> bool {
+        if self.name.is_none() && self.base_currency.is_none() && self.archived.is_none() {
+            return false;
+        }
+        true
+    }
+}
+
+#[derive(Debug, Default, Serialize, Deserialize, Validate)]
+pub struct ListLedgerInput {
+    pub archived: Option<bool>,
+    #[serde(flatten)]
+    pub limit_offset: PagationInput,
+}
+
+/// 货币代码须为3位大写字母(ISO 4217)
+pub fn validate_currency_code(code: &str) -> Result<(), ValidationError> {
+    if code.len() != 3 || !code.chars().all(|c| c.is_ascii_uppercase()) {
+        return Err(ValidationError::new("currency must be an ISO 4217 code"));
+    }
+
+    Ok(())
+}
@@ -0,0 +1,17 @@
+use crate::models::member::LedgerRole;
+use serde::{Deserialize, Serialize};
+use validator::Validate;
+
+/// 邀请成员输入数据, 按邮箱查找已注册的用户
+#[derive(Debug, Serialize, Deserialize, Validate)]
+pub struct AddMemberInput {
+    #[validate(email)]
+    pub email: String,
+    pub role: LedgerRole,
+}
+
+/// 修改成员角色输入数据
+#[derive(Debug, Serialize, Deserialize, Validate)]
+pub struct UpdateMemberInput {
+    pub role: LedgerRole,
+}
@@ -1,4 +1,17 @@
+pub(crate) mod account;
 pub(crate) mod auth;
+pub(crate) mod budget;
+pub(crate) mod category;
+pub(crate) mod currency;
+pub(crate) mod entry;
+pub(crate) mod export;
+pub(crate) mod import;
+pub(crate) mod ledger;
+pub(crate) mod member;
+pub(crate) mod oidc;
+pub(crate) mod passkey;
+pub(crate) mod recurring;
+pub(crate) mod report;
 pub(crate) mod user;
 
 use validator::Validate;
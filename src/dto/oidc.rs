@@ -0,0 +1,11 @@
+use serde::{Deserialize, Serialize};
+
+/// 提供方跳转回回调地址时携带的查询参数
+#[derive(Debug, Serialize, Deserialize)]
+pub struct OidcCallbackQuery {
+    pub code: Option<String>,
+    pub state: Option<String>,
+    /// 用户拒绝授权等情况下提供方返回的错误码
+    pub error: Option<String>,
+    pub error_description: Option<String>,
+}
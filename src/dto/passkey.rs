@@ -0,0 +1,128 @@
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+use validator::Validate;
+
+/// 完成 passkey 注册的输入数据
+#[derive(Debug, Serialize, Deserialize, Validate)]
+pub struct PasskeyRegistrationInput {
+    pub challenge_id: Uuid,
+    /// 便于用户区分设备的名称
+    #[validate(length(min = 1, max = 64))]
+    pub name: String,
+    pub credential: RegistrationCredential,
+}
+
+/// navigator.credentials.create() 的结果, 二进制字段均为 base64url 编码
+#[derive(Debug, Serialize, Deserialize)]
+pub struct RegistrationCredential {
+    pub id: String,
+    pub response: AttestationResponse,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AttestationResponse {
+    #[serde(rename = "clientDataJSON")]
+    pub client_data_json: String,
+    pub attestation_object: String,
+}
+
+/// 使用 passkey 登录的输入数据
+#[derive(Debug, Serialize, Deserialize, Validate)]
+pub struct PasskeyLoginInput {
+    pub challenge_id: Uuid,
+    pub credential: AssertionCredential,
+}
+
+/// navigator.credentials.get() 的结果, 二进制字段均为 base64url 编码
+#[derive(Debug, Serialize, Deserialize)]
+pub struct AssertionCredential {
+    pub id: String,
+    pub response: AssertionResponse,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AssertionResponse {
+    #[serde(rename = "clientDataJSON")]
+    pub client_data_json: String,
+    pub authenticator_data: String,
+    pub signature: String,
+    #[serde(default)]
+    pub user_handle: Option<String>,
+}
+
+/// 开始注册时返回, public_key 直接传给 navigator.credentials.create()
+#[derive(Debug, Serialize, Deserialize)]
+pub struct PasskeyCreationOptions {
+    pub challenge_id: Uuid,
+    pub public_key: PublicKeyCredentialCreationOptions,
+}
+
+/// 开始登录时返回, public_key 直接传给 navigator.credentials.get()
+#[derive(Debug, Serialize, Deserialize)]
+pub struct PasskeyRequestOptions {
+    pub challenge_id: Uuid,
+    pub public_key: PublicKeyCredentialRequestOptions,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PublicKeyCredentialCreationOptions {
+    pub challenge: String,
+    pub rp: RelyingParty,
+    pub user: UserEntity,
+    pub pub_key_cred_params: Vec<CredentialParameter>,
+    /// 毫秒
+    pub timeout: i64,
+    pub attestation: String,
+    pub exclude_credentials: Vec<CredentialDescriptor>,
+    pub authenticator_selection: AuthenticatorSelection,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PublicKeyCredentialRequestOptions {
+    pub challenge: String,
+    pub rp_id: String,
+    /// 毫秒
+    pub timeout: i64,
+    pub user_verification: String,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct RelyingParty {
+    pub id: String,
+    pub name: String,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct UserEntity {
+    /// 用户 id 的 16 字节, base64url 编码
+    pub id: String,
+    pub name: String,
+    pub display_name: String,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct CredentialParameter {
+    #[serde(rename = "type")]
+    pub kind: String,
+    /// COSE 算法标识
+    pub alg: i64,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct CredentialDescriptor {
+    #[serde(rename = "type")]
+    pub kind: String,
+    pub id: String,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AuthenticatorSelection {
+    pub resident_key: String,
+    pub user_verification: String,
+}
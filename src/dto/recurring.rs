@@ -0,0 +1,30 @@
+use super::entry::{validate_tags, PostingInput};
+use crate::models::recurring::Frequency;
+use chrono::NaiveDate;
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+use validator::Validate;
+
+fn default_every() -> i32 {
+    1
+}
+
+/// 创建周期分录输入数据, 每 every 个 frequency 记账一次
+#[derive(Debug, Serialize, Deserialize, Validate)]
+pub struct CreateRecurringInput {
+    #[validate(length(max = 255))]
+    pub description: String,
+    pub category_id: Option<Uuid>,
+    #[serde(default)]
+    #[validate(custom = "validate_tags")]
+    pub tags: Vec<String>,
+    #[validate(length(min = 2))]
+    #[validate]
+    pub postings: Vec<PostingInput>,
+    pub frequency: Frequency,
+    #[serde(default = "default_every")]
+    #[validate(range(min = 1, max = 366))]
+    pub every: i32,
+    pub start_date: NaiveDate,
+    pub end_date: Option<NaiveDate>,
+}
@@ -0,0 +1,26 @@
+use crate::models::report::ReportGrouping;
+use chrono::{NaiveDate, Utc};
+use serde::{Deserialize, Serialize};
+use validator::Validate;
+
+/// 截止日期查询参数, 为空时取当天
+#[derive(Debug, Default, Serialize, Deserialize, Validate)]
+pub struct AsOfInput {
+    pub as_of: Option<NaiveDate>,
+}
+
+/// 期间报表查询参数, to 为空时取当天, from 为空时取 to 所在年度的第一天
+#[derive(Debug, Default, Serialize, Deserialize, Validate)]
+pub struct PeriodInput {
+    pub from: Option<NaiveDate>,
+    pub to: Option<NaiveDate>,
+    #[serde(default)]
+    pub group_by: ReportGrouping,
+}
+
+impl PeriodInput {
+    pub fn check(&self) -> bool {
+        let to = self.to.unwrap_or_else(|| Utc::today().naive_utc());
+        self.from.is_none_or(|from| from <= to)
+    }
+}
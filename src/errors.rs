@@ -20,6 +20,72 @@ pub enum Error {
     AxumExtension(#[from] axum::extract::rejection::ExtensionRejection),
     #[error("email: {0} is already taken")]
     DuplicateUserEmail(String),
+    #[error("permission denied")]
+    Forbidden,
+    #[error("unbalanced entry: {0}")]
+    UnbalancedEntry(String),
+    #[error("invalid account: {0}")]
+    InvalidAccount(String),
+    #[error("account still carries a balance of {0} {1}")]
+    AccountHasBalance(i64, String),
+    #[error("invalid category: {0}")]
+    InvalidCategory(String),
+    #[error("invalid schedule: {0}")]
+    InvalidSchedule(String),
+    #[error("invalid currency: {0}")]
+    InvalidCurrency(String),
+    #[error("missing exchange rate: {0}")]
+    MissingExchangeRate(String),
+    #[error("invalid statement: {0}")]
+    InvalidStatement(String),
+    #[error(transparent)]
+    AxumPath(#[from] axum::extract::rejection::PathRejection),
+    #[error("invalid member: {0}")]
+    InvalidMember(String),
+    #[error("user is already a member of the ledger")]
+    DuplicateMember,
+    #[error("ledger must keep at least one owner")]
+    LastOwner,
+    #[error("invalid role: {0}")]
+    InvalidRole(String),
+    #[error("system must keep at least one admin")]
+    LastAdmin,
+    #[error("password hashing failed: {0}")]
+    PasswordHash(String),
+    #[error("invalid refresh token")]
+    InvalidRefreshToken,
+    #[error("refresh token reused, session revoked")]
+    RefreshTokenReused,
+    #[error("token has been revoked")]
+    TokenRevoked,
+    #[error("invalid or expired verification token")]
+    InvalidVerificationToken,
+    #[error("email address has not been verified")]
+    EmailNotVerified,
+    #[error("sending mail failed: {0}")]
+    Mail(String),
+    #[error("invalid or expired password reset token")]
+    InvalidResetToken,
+    #[error("invalid two-factor code")]
+    InvalidMfaCode,
+    #[error("invalid or expired mfa token")]
+    InvalidMfaToken,
+    #[error("two-factor authentication is already enabled")]
+    MfaAlreadyEnabled,
+    #[error("invalid passkey: {0}")]
+    InvalidPasskey(String),
+    #[error("passkey is already registered")]
+    DuplicatePasskey,
+    #[error("unknown identity provider: {0}")]
+    UnknownOidcProvider(String),
+    #[error("single sign-on failed: {0}")]
+    OidcLogin(String),
+    #[error("identity provider unavailable: {0}")]
+    OidcProvider(String),
+    #[error("an account with this email already exists and cannot be linked automatically")]
+    OidcAccountConflict,
+    #[error("external identity is already linked")]
+    DuplicateIdentity,
 }
 
 impl Error {
@@ -36,10 +102,41 @@ pub type ApiResult<T> = std::result::Result<T, ApiError>;
 impl From<Error> for ApiError {
     fn from(err: Error) -> Self {
         let status = match err {
-            Error::WrongCredentials => StatusCode::UNAUTHORIZED,
-            Error::Validation(_) | Error::EmptyFields(_) | Error::DuplicateUserEmail(_) => {
-                StatusCode::BAD_REQUEST
-            }
+            Error::WrongCredentials
+            | Error::AxumTypedHeader(_)
+            | Error::InvalidRefreshToken
+            | Error::RefreshTokenReused
+            | Error::TokenRevoked
+            | Error::InvalidMfaCode
+            | Error::InvalidMfaToken
+            | Error::OidcLogin(_) => StatusCode::UNAUTHORIZED,
+            Error::Forbidden | Error::EmailNotVerified => StatusCode::FORBIDDEN,
+            Error::Validation(_)
+            | Error::EmptyFields(_)
+            | Error::DuplicateUserEmail(_)
+            | Error::UnbalancedEntry(_)
+            | Error::InvalidAccount(_)
+            | Error::InvalidCategory(_)
+            | Error::InvalidSchedule(_)
+            | Error::InvalidCurrency(_)
+            | Error::MissingExchangeRate(_)
+            | Error::InvalidStatement(_)
+            | Error::InvalidMember(_)
+            | Error::InvalidRole(_)
+            | Error::InvalidVerificationToken
+            | Error::InvalidResetToken
+            | Error::InvalidPasskey(_)
+            | Error::AxumPath(_) => StatusCode::BAD_REQUEST,
+            Error::AccountHasBalance(..)
+            | Error::DuplicateMember
+            | Error::LastOwner
+            | Error::LastAdmin
+            | Error::MfaAlreadyEnabled
+            | Error::DuplicatePasskey
+            | Error::OidcAccountConflict
+            | Error::DuplicateIdentity => StatusCode::CONFLICT,
+            Error::UnknownOidcProvider(_) => StatusCode::NOT_FOUND,
+            Error::OidcProvider(_) => StatusCode::BAD_GATEWAY,
             _ => StatusCode::INTERNAL_SERVER_ERROR,
         };
         let payload = json!({"ok": false, "error": err.to_string()});
@@ -17,12 +17,21 @@ mod routers;
 mod services;
 
 use axum::Router;
-use std::sync::Arc;
+use chrono::Utc;
+use services::{
+    rbac::{RbacService, RbacServiceImpl},
+    recurring::{RecurringService, RecurringServiceImpl},
+    user::{UserService, UserServiceImpl},
+};
+use std::{sync::Arc, time::Duration};
 use tower::ServiceBuilder;
 use tower_http::{cors::CorsLayer, trace::TraceLayer};
 
 /// 路由入口
 pub fn app(pg_pool: sqlx::PgPool) -> Router {
+    // 启动时加载签名密钥, 配置错误时立即退出而不是在首个请求时失败
+    lazy_static::initialize(&config::constants::JWT_KEYS);
+
     let middleware_stack = ServiceBuilder::new()
         .layer(TraceLayer::new_for_http())
         .layer(CorsLayer::permissive())
@@ -31,5 +40,45 @@ pub fn app(pg_pool: sqlx::PgPool) -> Router {
     let pool = Arc::new(pg_pool);
     Router::new()
         .nest("/api/v1", routers::routers(pool))
+        .merge(routers::well_known())
         .layer(middleware_stack)
 }
+
+/// 启动时将迁移前以明文保存的密码替换为哈希
+pub async fn rehash_legacy_passwords(pg_pool: sqlx::PgPool) -> anyhow::Result<()> {
+    let svc = UserServiceImpl::new(Arc::new(pg_pool));
+    let rehashed = svc.rehash_legacy_passwords().await?;
+    if rehashed > 0 {
+        tracing::info!("rehashed {} legacy plaintext passwords", rehashed);
+    }
+    Ok(())
+}
+
+/// 启动时将 BOOTSTRAP_ADMIN_EMAIL 指定的用户设为首个管理员, 管理员曾经存在过则不做变更
+pub async fn bootstrap_admin(pg_pool: sqlx::PgPool) -> anyhow::Result<()> {
+    let email = match config::env::AdminConfig::from_env().bootstrap_admin_email {
+        Some(email) => email,
+        None => return Ok(()),
+    };
+    let svc = RbacServiceImpl::new(Arc::new(pg_pool));
+    if svc.bootstrap_admin(&email).await? {
+        tracing::info!("granted admin role to {}", email);
+    } else {
+        tracing::debug!("admin bootstrap skipped for {}", email);
+    }
+    Ok(())
+}
+
+/// 周期分录后台调度, 每隔 every 生成一次到期分录, 多实例并发运行时不会重复记账
+pub async fn recurring_scheduler(pg_pool: sqlx::PgPool, every: Duration) {
+    let svc = RecurringServiceImpl::new(Arc::new(pg_pool));
+    let mut interval = tokio::time::interval(every);
+    loop {
+        interval.tick().await;
+        match svc.run_due(Utc::today().naive_utc()).await {
+            Ok(0) => {}
+            Ok(created) => tracing::info!("created {} recurring entries", created),
+            Err(err) => tracing::error!("run recurring entries failed: {}", err),
+        }
+    }
+}
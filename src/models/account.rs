@@ -0,0 +1,143 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use uuid::Uuid;
+
+/// 科目类别
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, Hash, sqlx::Type)]
+#[sqlx(type_name = "account_kind", rename_all = "lowercase")]
+#[serde(rename_all = "lowercase")]
+pub enum AccountKind {
+    Asset,
+    Liability,
+    Income,
+    Expense,
+    Equity,
+}
+
+// Account创建参数
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CreateAccount {
+    pub ledger_id: Uuid,
+    pub parent_id: Option<Uuid>,
+    pub name: String,
+    pub kind: AccountKind,
+}
+
+/// 会计科目, 每个账本下按 parent_id 组织为树
+#[derive(Serialize, Deserialize, Debug, Clone, sqlx::FromRow)]
+pub struct Account {
+    pub id: Uuid,
+    pub ledger_id: Uuid,
+    pub parent_id: Option<Uuid>,
+    pub name: String,
+    pub kind: AccountKind,
+    pub closed_at: Option<DateTime<Utc>>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+impl Account {
+    pub const TABLE: &'static str = "accounts";
+
+    pub fn is_closed(&self) -> bool {
+        self.closed_at.is_some()
+    }
+}
+
+impl Default for Account {
+    fn default() -> Self {
+        Account {
+            id: Uuid::new_v4(),
+            ledger_id: Uuid::nil(),
+            parent_id: None,
+            name: Default::default(),
+            kind: AccountKind::Asset,
+            closed_at: None,
+            created_at: Utc::now(),
+            updated_at: Utc::now(),
+        }
+    }
+}
+
+/// 科目树节点
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct AccountNode {
+    #[serde(flatten)]
+    pub account: Account,
+    pub children: Vec<AccountNode>,
+}
+
+impl AccountNode {
+    /// 由账本下的全部科目构造科目树, 同级按名称排序
+    pub fn build_tree(accounts: Vec<Account>) -> Vec<AccountNode> {
+        let mut children: HashMap<Option<Uuid>, Vec<Account>> = HashMap::new();
+        for account in accounts {
+            children.entry(account.parent_id).or_default().push(account);
+        }
+        Self::attach(None, &mut children)
+    }
+
+    fn attach(
+        parent_id: Option<Uuid>,
+        children: &mut HashMap<Option<Uuid>, Vec<Account>>,
+    ) -> Vec<AccountNode> {
+        let mut accounts = children.remove(&parent_id).unwrap_or_default();
+        accounts.sort_by(|a, b| a.name.cmp(&b.name));
+        accounts
+            .into_iter()
+            .map(|account| {
+                let children = Self::attach(Some(account.id), children);
+                AccountNode { account, children }
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_build_account_tree() {
+        let assets = Account {
+            name: "Assets".to_string(),
+            ..Default::default()
+        };
+        let bank = Account {
+            name: "Bank".to_string(),
+            parent_id: Some(assets.id),
+            ..Default::default()
+        };
+        let cash = Account {
+            name: "Cash".to_string(),
+            parent_id: Some(assets.id),
+            ..Default::default()
+        };
+        let checking = Account {
+            name: "Checking".to_string(),
+            parent_id: Some(bank.id),
+            ..Default::default()
+        };
+        let expenses = Account {
+            name: "Expenses".to_string(),
+            kind: AccountKind::Expense,
+            ..Default::default()
+        };
+
+        let tree = AccountNode::build_tree(vec![
+            checking.clone(),
+            expenses.clone(),
+            cash.clone(),
+            bank.clone(),
+            assets.clone(),
+        ]);
+        assert_eq!(2, tree.len());
+        assert_eq!(assets.id, tree[0].account.id);
+        assert_eq!(expenses.id, tree[1].account.id);
+        assert_eq!(2, tree[0].children.len());
+        assert_eq!(bank.id, tree[0].children[0].account.id);
+        assert_eq!(checking.id, tree[0].children[0].children[0].account.id);
+        assert!(tree[0].children[1].children.is_empty());
+    }
+}
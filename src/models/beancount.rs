@@ -0,0 +1,66 @@
+use super::{account::AccountKind, currency::UpsertExchangeRate, entry::Side};
+use chrono::NaiveDate;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use uuid::Uuid;
+
+/// 导入文件中某一行的错误
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct LineError {
+    pub line: usize,
+    pub message: String,
+}
+
+/// 待创建的科目, name 与 parent 为 Assets:Bank 形式的科目全名
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct NewAccount {
+    pub name: String,
+    pub parent: Option<String>,
+    /// 本级科目名称
+    pub component: String,
+    pub kind: AccountKind,
+}
+
+/// 待写入的过账行, account 为科目全名
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PlannedPosting {
+    pub account: String,
+    pub amount: i64,
+    pub currency: String,
+    pub side: Side,
+}
+
+/// 待写入的分录
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PlannedEntry {
+    pub date: NaiveDate,
+    pub description: String,
+    pub tags: Vec<String>,
+    pub postings: Vec<PlannedPosting>,
+}
+
+/// 校验通过后在同一事务中写入的导入计划
+#[derive(Debug, Clone, Default)]
+pub struct BeancountPlan {
+    pub ledger_id: Uuid,
+    /// 账本中已有科目的全名
+    pub existing: HashMap<String, Uuid>,
+    /// 按父科目在前的顺序排列
+    pub accounts: Vec<NewAccount>,
+    pub entries: Vec<PlannedEntry>,
+    pub rates: Vec<UpsertExchangeRate>,
+    pub closes: Vec<(String, NaiveDate)>,
+}
+
+/// 导入结果, 存在错误时不写入任何数据
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+pub struct BeancountReport {
+    pub committed: bool,
+    pub accounts: usize,
+    pub entries: usize,
+    pub prices: usize,
+    /// 没有 rates:write 权限时跳过的 price 指令数
+    pub skipped_prices: usize,
+    pub balances: usize,
+    pub errors: Vec<LineError>,
+}
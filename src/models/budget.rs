@@ -0,0 +1,202 @@
+use chrono::{DateTime, Datelike, Duration, NaiveDate, Utc};
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+/// 预算周期, 均按自然月/季/年划分
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, sqlx::Type)]
+#[sqlx(type_name = "budget_period", rename_all = "lowercase")]
+#[serde(rename_all = "lowercase")]
+pub enum BudgetPeriod {
+    Monthly,
+    Quarterly,
+    Yearly,
+}
+
+impl BudgetPeriod {
+    fn months(&self) -> i32 {
+        match self {
+            BudgetPeriod::Monthly => 1,
+            BudgetPeriod::Quarterly => 3,
+            BudgetPeriod::Yearly => 12,
+        }
+    }
+
+    /// 对应 postgres date_trunc 的精度
+    pub fn trunc_unit(&self) -> &'static str {
+        match self {
+            BudgetPeriod::Monthly => "month",
+            BudgetPeriod::Quarterly => "quarter",
+            BudgetPeriod::Yearly => "year",
+        }
+    }
+
+    /// date 所在周期的第一天
+    pub fn start_of(&self, date: NaiveDate) -> NaiveDate {
+        let month0 = date.month0() as i32 / self.months() * self.months();
+        NaiveDate::from_ymd(date.year(), month0 as u32 + 1, 1)
+    }
+
+    /// 周期起始日前后移动 n 个周期
+    pub fn shift(&self, start: NaiveDate, n: i32) -> NaiveDate {
+        let months = start.year() * 12 + start.month0() as i32 + n * self.months();
+        NaiveDate::from_ymd(months.div_euclid(12), months.rem_euclid(12) as u32 + 1, 1)
+    }
+
+    /// 周期的最后一天
+    pub fn end_of(&self, start: NaiveDate) -> NaiveDate {
+        self.shift(start, 1) - Duration::days(1)
+    }
+}
+
+// Budget创建参数
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CreateBudget {
+    pub ledger_id: Uuid,
+    pub name: String,
+    pub category_id: Option<Uuid>,
+    pub account_id: Option<Uuid>,
+    pub period: BudgetPeriod,
+    pub amount: i64,
+    pub rollover: bool,
+    pub start_date: NaiveDate,
+}
+
+/// 预算, 目标为分类或科目子树之一, amount 为每个周期的本位币额度
+#[derive(Serialize, Deserialize, Debug, Clone, sqlx::FromRow)]
+pub struct Budget {
+    pub id: Uuid,
+    pub ledger_id: Uuid,
+    pub name: String,
+    pub category_id: Option<Uuid>,
+    pub account_id: Option<Uuid>,
+    pub period: BudgetPeriod,
+    pub amount: i64,
+    pub rollover: bool,
+    pub start_date: NaiveDate,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+impl Budget {
+    pub const TABLE: &'static str = "budgets";
+}
+
+impl Default for Budget {
+    fn default() -> Self {
+        Budget {
+            id: Uuid::new_v4(),
+            ledger_id: Uuid::nil(),
+            name: Default::default(),
+            category_id: None,
+            account_id: None,
+            period: BudgetPeriod::Monthly,
+            amount: 0,
+            rollover: false,
+            start_date: Utc::today().naive_utc(),
+            created_at: Utc::now(),
+            updated_at: Utc::now(),
+        }
+    }
+}
+
+/// 单个周期内的本位币支出, missing_rates 为缺少汇率无法换算的过账行数
+#[derive(Serialize, Deserialize, Debug, Clone, sqlx::FromRow)]
+pub struct PeriodSpent {
+    pub start: NaiveDate,
+    pub spent: i64,
+    pub missing_rates: i64,
+}
+
+/// 单个周期的预算执行情况, budgeted 含结转额度, percentage 保留两位小数
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct PeriodStatus {
+    pub start: NaiveDate,
+    pub end: NaiveDate,
+    pub budgeted: i64,
+    pub spent: i64,
+    pub remaining: i64,
+    pub percentage: Decimal,
+    pub over_spent: bool,
+}
+
+impl PeriodStatus {
+    pub fn new(start: NaiveDate, end: NaiveDate, budgeted: i64, spent: i64) -> Self {
+        let percentage = if budgeted > 0 {
+            (Decimal::from(spent) * Decimal::from(100) / Decimal::from(budgeted)).round_dp(2)
+        } else {
+            Decimal::ZERO
+        };
+        PeriodStatus {
+            start,
+            end,
+            budgeted,
+            spent,
+            remaining: budgeted - spent,
+            percentage,
+            over_spent: spent > budgeted,
+        }
+    }
+}
+
+/// 预算当前及上一周期的执行情况, 上一周期早于预算开始时为空
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct BudgetStatus {
+    pub budget_id: Uuid,
+    pub current: PeriodStatus,
+    pub previous: Option<PeriodStatus>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_budget_period_bounds() {
+        let date = NaiveDate::from_ymd(2022, 5, 17);
+        assert_eq!(
+            NaiveDate::from_ymd(2022, 5, 1),
+            BudgetPeriod::Monthly.start_of(date)
+        );
+        assert_eq!(
+            NaiveDate::from_ymd(2022, 4, 1),
+            BudgetPeriod::Quarterly.start_of(date)
+        );
+        assert_eq!(
+            NaiveDate::from_ymd(2022, 1, 1),
+            BudgetPeriod::Yearly.start_of(date)
+        );
+
+        let january = NaiveDate::from_ymd(2022, 1, 1);
+        assert_eq!(
+            NaiveDate::from_ymd(2021, 12, 1),
+            BudgetPeriod::Monthly.shift(january, -1)
+        );
+        assert_eq!(
+            NaiveDate::from_ymd(2021, 10, 1),
+            BudgetPeriod::Quarterly.shift(january, -1)
+        );
+        assert_eq!(
+            NaiveDate::from_ymd(2022, 2, 28),
+            BudgetPeriod::Monthly.end_of(NaiveDate::from_ymd(2022, 2, 1))
+        );
+        assert_eq!(
+            NaiveDate::from_ymd(2022, 12, 31),
+            BudgetPeriod::Yearly.end_of(january)
+        );
+    }
+
+    #[test]
+    fn test_period_status() {
+        let start = NaiveDate::from_ymd(2022, 3, 1);
+        let status = PeriodStatus::new(start, BudgetPeriod::Monthly.end_of(start), 3000, 1000);
+        assert_eq!(2000, status.remaining);
+        assert_eq!(Decimal::new(3333, 2), status.percentage);
+        assert!(!status.over_spent);
+
+        let status = PeriodStatus::new(start, BudgetPeriod::Monthly.end_of(start), 3000, 4500);
+        assert_eq!(-1500, status.remaining);
+        assert_eq!(Decimal::new(150, 0), status.percentage);
+        assert!(status.over_spent);
+    }
+}
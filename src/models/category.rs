@@ -0,0 +1,84 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+// Category创建参数
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CreateCategory {
+    pub ledger_id: Uuid,
+    pub name: String,
+}
+
+/// 分录分类, 每个账本独立维护
+#[derive(Serialize, Deserialize, Debug, Clone, sqlx::FromRow)]
+pub struct Category {
+    pub id: Uuid,
+    pub ledger_id: Uuid,
+    pub name: String,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+impl Category {
+    pub const TABLE: &'static str = "categories";
+}
+
+impl Default for Category {
+    fn default() -> Self {
+        Category {
+            id: Uuid::new_v4(),
+            ledger_id: Uuid::nil(),
+            name: Default::default(),
+            created_at: Utc::now(),
+            updated_at: Utc::now(),
+        }
+    }
+}
+
+// CategorizationRule创建参数
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CreateRule {
+    pub ledger_id: Uuid,
+    pub pattern: String,
+    pub category_id: Option<Uuid>,
+    pub tag: Option<String>,
+    pub priority: i32,
+}
+
+/// 自动分类规则, 摘要包含 pattern(不区分大小写) 时设置分类并添加标签
+#[derive(Serialize, Deserialize, Debug, Clone, sqlx::FromRow)]
+pub struct CategorizationRule {
+    pub id: Uuid,
+    pub ledger_id: Uuid,
+    pub pattern: String,
+    pub category_id: Option<Uuid>,
+    pub tag: Option<String>,
+    pub priority: i32,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+impl CategorizationRule {
+    pub const TABLE: &'static str = "categorization_rules";
+
+    pub fn matches(&self, description: &str) -> bool {
+        description
+            .to_lowercase()
+            .contains(&self.pattern.to_lowercase())
+    }
+}
+
+impl Default for CategorizationRule {
+    fn default() -> Self {
+        CategorizationRule {
+            id: Uuid::new_v4(),
+            ledger_id: Uuid::nil(),
+            pattern: Default::default(),
+            category_id: None,
+            tag: None,
+            priority: 0,
+            created_at: Utc::now(),
+            updated_at: Utc::now(),
+        }
+    }
+}
@@ -0,0 +1,101 @@
+use chrono::{DateTime, NaiveDate, Utc};
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+use std::fmt::Display;
+use uuid::Uuid;
+
+/// 货币, minor_unit 为最小货币单位的小数位数(如 CNY 为 2, JPY 为 0)
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq, sqlx::FromRow)]
+pub struct Currency {
+    pub code: String,
+    pub name: String,
+    pub minor_unit: i16,
+}
+
+impl Currency {
+    pub const TABLE: &'static str = "currencies";
+}
+
+/// 金额, amount 为 currency 的最小货币单位
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq, sqlx::FromRow)]
+pub struct Money {
+    pub amount: i64,
+    pub currency: String,
+}
+
+// ExchangeRate写入参数
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct UpsertExchangeRate {
+    pub base: String,
+    pub quote: String,
+    pub rate: Decimal,
+    pub effective_date: NaiveDate,
+}
+
+/// 汇率, 自 effective_date 起 1 单位 base 货币兑换 rate 单位 quote 货币
+#[derive(Serialize, Deserialize, Debug, Clone, sqlx::FromRow)]
+pub struct ExchangeRate {
+    pub id: Uuid,
+    pub base: String,
+    pub quote: String,
+    pub rate: Decimal,
+    pub effective_date: NaiveDate,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+impl ExchangeRate {
+    pub const TABLE: &'static str = "exchange_rates";
+}
+
+// list 查询条件
+#[derive(Serialize, Deserialize, Debug, Default, Clone)]
+pub struct ExchangeRateOption {
+    pub base: Option<String>,
+    pub quote: Option<String>,
+    pub limit: Option<u32>,
+    pub offset: Option<u32>,
+}
+
+// 实现std::fmt::Display trait，方便在format!中组装sql的查询条件
+// base/quote 在 dto 层已校验为3位大写字母
+impl Display for ExchangeRateOption {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let mut conditions = Vec::new();
+        if let Some(ref base) = self.base {
+            conditions.push(format!("base='{base}'"));
+        }
+
+        if let Some(ref quote) = self.quote {
+            conditions.push(format!("quote='{quote}'"));
+        }
+
+        let mut where_condition = String::from("");
+        if !conditions.is_empty() {
+            where_condition = format!("WHERE {} ", conditions.join(" AND "));
+        }
+
+        let offset = self.offset.unwrap_or(0);
+        let limit = self.limit.unwrap_or(20);
+        write!(
+            f,
+            "{where_condition}ORDER BY effective_date DESC, base, quote OFFSET {offset} LIMIT {limit}"
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    #[test]
+    fn test_exchange_rate_option_as_sql_condition() {
+        use super::ExchangeRateOption;
+
+        let option = ExchangeRateOption {
+            base: Some("USD".to_string()),
+            quote: Some("CNY".to_string()),
+            ..Default::default()
+        };
+        let expect_condition = "WHERE base='USD' AND quote='CNY' ORDER BY effective_date DESC, base, quote OFFSET 0 LIMIT 20";
+        assert_eq!(expect_condition, format!("{option}"));
+    }
+}
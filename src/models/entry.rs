@@ -0,0 +1,160 @@
+use chrono::{DateTime, NaiveDate, Utc};
+use serde::{Deserialize, Serialize};
+use std::fmt::Display;
+use uuid::Uuid;
+
+/// 记账方向
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, sqlx::Type)]
+#[sqlx(type_name = "posting_side", rename_all = "lowercase")]
+#[serde(rename_all = "lowercase")]
+pub enum Side {
+    Debit,
+    Credit,
+}
+
+// Posting创建参数
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CreatePosting {
+    pub account_id: Uuid,
+    pub amount: i64,
+    pub currency: String,
+    pub side: Side,
+}
+
+// Entry创建参数, 分录及其所有过账行在同一事务中写入
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CreateEntry {
+    pub ledger_id: Uuid,
+    pub date: NaiveDate,
+    pub description: String,
+    pub category_id: Option<Uuid>,
+    pub tags: Vec<String>,
+    pub postings: Vec<CreatePosting>,
+}
+
+/// 记账分录
+#[derive(Serialize, Deserialize, Debug, Clone, sqlx::FromRow)]
+pub struct Entry {
+    pub id: Uuid,
+    pub ledger_id: Uuid,
+    pub date: NaiveDate,
+    pub description: String,
+    pub category_id: Option<Uuid>,
+    pub tags: Vec<String>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+impl Entry {
+    pub const TABLE: &'static str = "entries";
+}
+
+impl Default for Entry {
+    fn default() -> Self {
+        Entry {
+            id: Uuid::new_v4(),
+            ledger_id: Uuid::nil(),
+            date: Utc::today().naive_utc(),
+            description: Default::default(),
+            category_id: None,
+            tags: Vec::new(),
+            created_at: Utc::now(),
+            updated_at: Utc::now(),
+        }
+    }
+}
+
+/// 过账行, 金额以 currency 的最小货币单位(如分)存储
+#[derive(Serialize, Deserialize, Debug, Clone, sqlx::FromRow)]
+pub struct Posting {
+    pub id: Uuid,
+    pub entry_id: Uuid,
+    pub account_id: Uuid,
+    pub amount: i64,
+    pub currency: String,
+    pub side: Side,
+}
+
+impl Posting {
+    pub const TABLE: &'static str = "postings";
+}
+
+/// 分录及其过账行
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+pub struct EntryDetail {
+    #[serde(flatten)]
+    pub entry: Entry,
+    pub postings: Vec<Posting>,
+}
+
+// list 查询条件
+#[derive(Serialize, Deserialize, Debug, Default, Clone)]
+pub struct EntryOption {
+    pub ledger_id: Option<Uuid>,
+    pub from: Option<NaiveDate>,
+    pub to: Option<NaiveDate>,
+    pub category_id: Option<Uuid>,
+    pub limit: Option<u32>,
+    pub offset: Option<u32>,
+}
+
+// 实现std::fmt::Display trait，方便在format!中组装sql的查询条件
+impl Display for EntryOption {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let mut conditions = Vec::new();
+        if let Some(ledger_id) = self.ledger_id {
+            conditions.push(format!("ledger_id='{ledger_id}'"));
+        }
+
+        if let Some(from) = self.from {
+            conditions.push(format!("date>='{from}'"));
+        }
+
+        if let Some(to) = self.to {
+            conditions.push(format!("date<='{to}'"));
+        }
+
+        if let Some(category_id) = self.category_id {
+            conditions.push(format!("category_id='{category_id}'"));
+        }
+
+        let mut where_condition = String::from("");
+        if !conditions.is_empty() {
+            where_condition = format!("WHERE {} ", conditions.join(" AND "));
+        }
+
+        let offset = self.offset.unwrap_or(0);
+        let limit = self.limit.unwrap_or(20);
+        write!(
+            f,
+            "{where_condition}ORDER BY date, created_at OFFSET {offset} LIMIT {limit}"
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    #[test]
+    fn test_entry_option_as_sql_condition() {
+        use super::EntryOption;
+        use chrono::NaiveDate;
+        use uuid::Uuid;
+
+        let default_option = EntryOption::default();
+        let expect_condition = "ORDER BY date, created_at OFFSET 0 LIMIT 20";
+        assert_eq!(expect_condition, format!("{default_option}"));
+
+        let ledger_id = Uuid::new_v4();
+        let where_option = EntryOption {
+            ledger_id: Some(ledger_id),
+            from: NaiveDate::from_ymd_opt(2022, 1, 1),
+            to: NaiveDate::from_ymd_opt(2022, 1, 31),
+            limit: Some(50),
+            ..Default::default()
+        };
+        let expect_condition = format!(
+            "WHERE ledger_id='{ledger_id}' AND date>='2022-01-01' AND date<='2022-01-31' ORDER BY date, created_at OFFSET 0 LIMIT 50"
+        );
+        assert_eq!(expect_condition, format!("{where_option}"));
+    }
+}
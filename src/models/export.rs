@@ -0,0 +1,154 @@
+use super::{account::AccountKind, entry::Side};
+use chrono::NaiveDate;
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+/// 导出格式
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum ExportFormat {
+    Csv,
+    Jsonl,
+    Beancount,
+    Ledger,
+}
+
+impl ExportFormat {
+    pub fn content_type(&self) -> &'static str {
+        match self {
+            ExportFormat::Csv => "text/csv; charset=utf-8",
+            ExportFormat::Jsonl => "application/x-ndjson",
+            ExportFormat::Beancount | ExportFormat::Ledger => "text/plain; charset=utf-8",
+        }
+    }
+
+    pub fn extension(&self) -> &'static str {
+        match self {
+            ExportFormat::Csv => "csv",
+            ExportFormat::Jsonl => "jsonl",
+            ExportFormat::Beancount => "beancount",
+            ExportFormat::Ledger => "ledger",
+        }
+    }
+}
+
+/// 导出的科目, path 为从顶级科目到该科目的名称路径
+#[derive(Serialize, Deserialize, Debug, Clone, sqlx::FromRow)]
+pub struct ExportAccount {
+    pub id: Uuid,
+    pub kind: AccountKind,
+    pub path: Vec<String>,
+    /// 科目创建日期与首笔过账日期中较早的一个
+    pub opened_on: NaiveDate,
+    pub closed_on: Option<NaiveDate>,
+}
+
+impl ExportAccount {
+    pub fn name(&self) -> String {
+        account_name(self.kind, &self.path)
+    }
+}
+
+/// 导出的过账行, 按分录排序后连续出现
+#[derive(Serialize, Deserialize, Debug, Clone, sqlx::FromRow)]
+pub struct ExportPosting {
+    pub entry_id: Uuid,
+    pub date: NaiveDate,
+    pub description: String,
+    pub category: Option<String>,
+    pub tags: Vec<String>,
+    pub kind: AccountKind,
+    pub account: Vec<String>,
+    pub amount: i64,
+    pub currency: String,
+    pub minor_unit: i16,
+    pub side: Side,
+}
+
+impl ExportPosting {
+    pub fn account_name(&self) -> String {
+        account_name(self.kind, &self.account)
+    }
+
+    /// 带小数位的金额, 借方为正、贷方为负
+    pub fn signed_amount(&self) -> Decimal {
+        let amount = match self.side {
+            Side::Debit => self.amount,
+            Side::Credit => -self.amount,
+        };
+        Decimal::new(amount, self.minor_unit as u32)
+    }
+}
+
+/// 导出的分录
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct ExportEntry {
+    pub id: Uuid,
+    pub date: NaiveDate,
+    pub description: String,
+    pub category: Option<String>,
+    pub tags: Vec<String>,
+    pub postings: Vec<ExportPosting>,
+}
+
+impl From<ExportPosting> for ExportEntry {
+    fn from(posting: ExportPosting) -> Self {
+        ExportEntry {
+            id: posting.entry_id,
+            date: posting.date,
+            description: posting.description.clone(),
+            category: posting.category.clone(),
+            tags: posting.tags.clone(),
+            postings: vec![posting],
+        }
+    }
+}
+
+/// 纯文本记账工具使用的科目全名, 如 Assets:Bank:Checking。
+/// 各级名称中字母数字以外的字符替换为 -, 首字母转为大写
+pub fn account_name(kind: AccountKind, path: &[String]) -> String {
+    let root = match kind {
+        AccountKind::Asset => "Assets",
+        AccountKind::Liability => "Liabilities",
+        AccountKind::Equity => "Equity",
+        AccountKind::Income => "Income",
+        AccountKind::Expense => "Expenses",
+    };
+    let mut name = root.to_string();
+    for component in path {
+        let component: String = component
+            .chars()
+            .map(|c| if c.is_alphanumeric() { c } else { '-' })
+            .collect();
+        let mut component = component.trim_matches('-').to_string();
+        match component.chars().next() {
+            Some(first) if first.is_ascii_lowercase() => {
+                component.replace_range(..1, &first.to_ascii_uppercase().to_string())
+            }
+            Some(_) => {}
+            None => component.push('X'),
+        }
+        name.push(':');
+        name.push_str(&component);
+    }
+    name
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_account_name() {
+        let path = |names: &[&str]| names.iter().map(|n| n.to_string()).collect::<Vec<_>>();
+        assert_eq!(
+            "Assets:Bank:Checking-account",
+            account_name(AccountKind::Asset, &path(&["Bank", "checking account"]))
+        );
+        assert_eq!(
+            "Expenses:餐饮:Misc:X",
+            account_name(AccountKind::Expense, &path(&["餐饮", "(misc)", "--"]))
+        );
+    }
+}
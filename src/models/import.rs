@@ -0,0 +1,145 @@
+use super::entry::{CreateEntry, CreatePosting, EntryDetail, Side};
+use chrono::NaiveDate;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use uuid::Uuid;
+
+/// 对账单文件格式, QFX 与 OFX 格式相同
+#[derive(Serialize, Deserialize, Debug, Default, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum StatementFormat {
+    #[default]
+    Csv,
+    #[serde(alias = "qfx")]
+    Ofx,
+    Camt053,
+}
+
+/// 各格式解析器输出的原始交易, amount 为带符号的最小货币单位金额, 正数表示转入
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct StatementRecord {
+    /// 行号或交易序号, 便于定位错误
+    pub line: usize,
+    pub date: NaiveDate,
+    pub description: String,
+    pub amount: i64,
+    /// 对账单声明的币种(OFX CURDEF, CAMT Amt 的 Ccy 属性), 未声明时为空
+    pub currency: Option<String>,
+    /// 银行提供的交易唯一标识(OFX FITID, CAMT AcctSvcrRef), 存在时作为去重依据
+    pub transaction_id: Option<String>,
+}
+
+/// 对账单解析出的一行交易, amount 为带符号的最小货币单位金额, 正数表示转入对账单科目
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct StatementLine {
+    /// 在对账单文件中的行号, 便于定位错误
+    pub line: usize,
+    pub date: NaiveDate,
+    pub description: String,
+    pub amount: i64,
+    pub fingerprint: String,
+}
+
+impl StatementLine {
+    /// 已导入指纹表
+    pub const FINGERPRINTS_TABLE: &'static str = "import_fingerprints";
+
+    /// 生成分录: 转入时借记对账单科目、贷记对方科目, 转出时相反
+    pub fn to_entry(
+        &self,
+        ledger_id: Uuid,
+        account_id: Uuid,
+        contra_account_id: Uuid,
+        currency: &str,
+    ) -> CreateEntry {
+        let (account_side, contra_side) = if self.amount > 0 {
+            (Side::Debit, Side::Credit)
+        } else {
+            (Side::Credit, Side::Debit)
+        };
+        let posting = |account_id, side| CreatePosting {
+            account_id,
+            amount: self.amount.abs(),
+            currency: currency.to_string(),
+            side,
+        };
+        CreateEntry {
+            ledger_id,
+            date: self.date,
+            description: self.description.clone(),
+            category_id: None,
+            tags: Vec::new(),
+            postings: vec![
+                posting(account_id, account_side),
+                posting(contra_account_id, contra_side),
+            ],
+        }
+    }
+}
+
+/// 对账单行指纹: 由科目、日期、金额、规范化后的摘要以及同一文件内相同交易的序号计算,
+/// 重复导入有重叠的对账单时同一笔交易得到相同指纹
+pub fn fingerprint(
+    account_id: Uuid,
+    date: NaiveDate,
+    amount: i64,
+    description: &str,
+    occurrence: usize,
+) -> String {
+    let description = description
+        .split_whitespace()
+        .collect::<Vec<_>>()
+        .join(" ")
+        .to_lowercase();
+    let mut hasher = Sha256::new();
+    hasher.update(format!(
+        "{account_id}|{date}|{amount}|{description}|{occurrence}"
+    ));
+    hex::encode(hasher.finalize())
+}
+
+/// 带银行交易标识的对账单行指纹: 由科目与交易标识计算, 不受摘要或金额格式变化影响
+pub fn transaction_fingerprint(account_id: Uuid, transaction_id: &str) -> String {
+    let mut hasher = Sha256::new();
+    hasher.update(format!("{account_id}|id|{transaction_id}"));
+    hex::encode(hasher.finalize())
+}
+
+/// 预览行, duplicate 表示该行此前已导入过
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct PreviewLine {
+    #[serde(flatten)]
+    pub line: StatementLine,
+    pub duplicate: bool,
+}
+
+/// 导入结果
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+pub struct ImportSummary {
+    pub imported: usize,
+    pub duplicates: usize,
+    pub entries: Vec<EntryDetail>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_fingerprint_is_stable() {
+        let account_id = Uuid::new_v4();
+        let date = NaiveDate::from_ymd(2022, 4, 1);
+        let fp = fingerprint(account_id, date, -1250, "Coffee  Shop ", 0);
+        assert_eq!(64, fp.len());
+        assert_eq!(fp, fingerprint(account_id, date, -1250, "coffee shop", 0));
+        assert_ne!(fp, fingerprint(account_id, date, -1250, "coffee shop", 1));
+        assert_ne!(
+            fp,
+            fingerprint(Uuid::new_v4(), date, -1250, "coffee shop", 0)
+        );
+
+        let fp = transaction_fingerprint(account_id, "202204010001");
+        assert_eq!(fp, transaction_fingerprint(account_id, "202204010001"));
+        assert_ne!(fp, transaction_fingerprint(Uuid::new_v4(), "202204010001"));
+    }
+}
@@ -0,0 +1,108 @@
+use super::member::LedgerMember;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::fmt::Display;
+use uuid::Uuid;
+
+// Ledger创建参数
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CreateLedger {
+    pub name: String,
+    pub owner_id: Uuid,
+    pub base_currency: String,
+}
+
+/// 账本
+#[derive(Serialize, Deserialize, Debug, Clone, sqlx::FromRow)]
+pub struct Ledger {
+    pub id: Uuid,
+    pub name: String,
+    /// 创建者, 账本归属以成员角色为准; 创建者注销后为空
+    pub owner_id: Option<Uuid>,
+    /// 本位币, ISO 4217 货币代码
+    pub base_currency: String,
+    pub archived: bool,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+impl Ledger {
+    pub const TABLE: &'static str = "ledgers";
+}
+
+impl Default for Ledger {
+    fn default() -> Self {
+        Ledger {
+            id: Uuid::new_v4(),
+            name: Default::default(),
+            owner_id: None,
+            base_currency: String::from("CNY"),
+            archived: false,
+            created_at: Utc::now(),
+            updated_at: Utc::now(),
+        }
+    }
+}
+
+// list 查询条件, member_id 为成员用户 id
+#[derive(Serialize, Deserialize, Debug, Default, Clone)]
+pub struct LedgerOption {
+    pub member_id: Option<Uuid>,
+    pub archived: Option<bool>,
+    pub limit: Option<u32>,
+    pub offset: Option<u32>,
+}
+
+// 实现std::fmt::Display trait，方便在format!中组装sql的查询条件
+impl Display for LedgerOption {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let mut conditions = Vec::new();
+        if let Some(member_id) = self.member_id {
+            conditions.push(format!(
+                "id IN (SELECT ledger_id FROM {} WHERE user_id='{member_id}')",
+                LedgerMember::TABLE
+            ));
+        }
+
+        if let Some(archived) = self.archived {
+            conditions.push(format!("archived={archived}"));
+        }
+
+        let mut where_condition = String::from("");
+        if !conditions.is_empty() {
+            where_condition = format!("WHERE {} ", conditions.join(" AND "));
+        }
+
+        let offset = self.offset.unwrap_or(0);
+        let limit = self.limit.unwrap_or(20);
+        write!(
+            f,
+            "{where_condition}ORDER BY created_at OFFSET {offset} LIMIT {limit}"
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    #[test]
+    fn test_ledger_option_as_sql_condition() {
+        use super::LedgerOption;
+        use uuid::Uuid;
+
+        let default_option = LedgerOption::default();
+        let expect_condition = "ORDER BY created_at OFFSET 0 LIMIT 20";
+        assert_eq!(expect_condition, format!("{default_option}"));
+
+        let member_id = Uuid::new_v4();
+        let where_option = LedgerOption {
+            member_id: Some(member_id),
+            archived: Some(false),
+            offset: Some(2),
+            ..Default::default()
+        };
+        let expect_condition = format!(
+            "WHERE id IN (SELECT ledger_id FROM ledger_members WHERE user_id='{member_id}') AND archived=false ORDER BY created_at OFFSET 2 LIMIT 20"
+        );
+        assert_eq!(expect_condition, format!("{where_option}"));
+    }
+}
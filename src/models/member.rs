@@ -0,0 +1,53 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+/// 账本成员角色, 权限依次递增: viewer 只读, editor 可记账, owner 可管理账本与成员
+#[derive(
+    Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, sqlx::Type,
+)]
+#[sqlx(type_name = "ledger_role", rename_all = "lowercase")]
+#[serde(rename_all = "lowercase")]
+pub enum LedgerRole {
+    Viewer,
+    Editor,
+    Owner,
+}
+
+// 成员添加参数
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CreateLedgerMember {
+    pub ledger_id: Uuid,
+    pub user_id: Uuid,
+    pub role: LedgerRole,
+}
+
+/// 账本成员, name 与 email 来自用户信息
+#[derive(Serialize, Deserialize, Debug, Clone, sqlx::FromRow)]
+pub struct LedgerMember {
+    pub ledger_id: Uuid,
+    pub user_id: Uuid,
+    pub name: String,
+    pub email: String,
+    pub role: LedgerRole,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+impl LedgerMember {
+    pub const TABLE: &'static str = "ledger_members";
+}
+
+impl Default for LedgerMember {
+    fn default() -> Self {
+        LedgerMember {
+            ledger_id: Uuid::nil(),
+            user_id: Uuid::new_v4(),
+            name: Default::default(),
+            email: Default::default(),
+            role: LedgerRole::Viewer,
+            created_at: Utc::now(),
+            updated_at: Utc::now(),
+        }
+    }
+}
@@ -1,3 +1,36 @@
-pub(crate) mod auth;
+/// 会计科目数据模型
+pub(crate) mod account;
+/// Beancount 导入数据模型
+pub(crate) mod beancount;
+/// 预算数据模型
+pub(crate) mod budget;
+/// 分类与自动分类规则数据模型
+pub(crate) mod category;
+/// 货币与汇率数据模型
+pub(crate) mod currency;
+/// 记账分录数据模型
+pub(crate) mod entry;
+/// 导出数据模型
+pub(crate) mod export;
+/// 对账单导入数据模型
+pub(crate) mod import;
+/// 账本模块数据模型
+pub(crate) mod ledger;
+/// 账本成员数据模型
+pub(crate) mod member;
+/// OIDC 外部身份数据模型
+pub(crate) mod oidc;
+/// passkey 凭据数据模型
+pub(crate) mod passkey;
+/// 系统角色与权限数据模型
+pub(crate) mod rbac;
+/// 周期分录数据模型
+pub(crate) mod recurring;
+/// 报表数据模型
+pub(crate) mod report;
+/// 令牌数据模型
+pub(crate) mod token;
+/// TOTP 两步验证数据模型
+pub(crate) mod totp;
 /// 用户模块数据操作实现
 pub(crate) mod user;
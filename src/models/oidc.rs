@@ -0,0 +1,46 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+// 外部身份关联写入参数
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CreateUserIdentity {
+    pub user_id: Uuid,
+    pub provider: String,
+    pub subject: String,
+    pub email: Option<String>,
+}
+
+/// 用户在外部身份提供方的账号
+#[derive(Serialize, Deserialize, Debug, Clone, sqlx::FromRow)]
+pub struct UserIdentity {
+    pub id: Uuid,
+    pub user_id: Uuid,
+    /// 配置中的提供方名称
+    pub provider: String,
+    /// id_token 中的 sub
+    pub subject: String,
+    pub email: Option<String>,
+    pub created_at: DateTime<Utc>,
+    pub last_login_at: Option<DateTime<Utc>>,
+}
+
+impl UserIdentity {
+    pub const TABLE: &'static str = "user_identities";
+}
+
+/// 一次授权码登录的服务端状态
+#[derive(Serialize, Deserialize, Debug, Clone, sqlx::FromRow)]
+pub struct OidcLoginState {
+    pub state: String,
+    pub provider: String,
+    pub nonce: String,
+    /// PKCE code_verifier
+    pub code_verifier: String,
+    pub expires_at: DateTime<Utc>,
+    pub created_at: DateTime<Utc>,
+}
+
+impl OidcLoginState {
+    pub const TABLE: &'static str = "oidc_login_states";
+}
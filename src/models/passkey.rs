@@ -0,0 +1,49 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+// passkey 凭据写入参数
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CreatePasskeyCredential {
+    pub user_id: Uuid,
+    pub credential_id: String,
+    pub public_key: Vec<u8>,
+    pub sign_count: i64,
+    pub name: String,
+}
+
+/// 用户注册的 passkey, 公钥不在响应中返回
+#[derive(Serialize, Deserialize, Debug, Clone, sqlx::FromRow)]
+pub struct PasskeyCredential {
+    pub id: Uuid,
+    pub user_id: Uuid,
+    /// 认证器生成的凭据 id, base64url 编码
+    pub credential_id: String,
+    /// COSE_Key 编码的公钥
+    #[serde(skip_serializing, default)]
+    pub public_key: Vec<u8>,
+    pub sign_count: i64,
+    pub name: String,
+    pub created_at: DateTime<Utc>,
+    pub last_used_at: Option<DateTime<Utc>>,
+}
+
+impl PasskeyCredential {
+    pub const TABLE: &'static str = "passkey_credentials";
+}
+
+/// 一次注册或登录仪式的挑战值
+#[derive(Serialize, Deserialize, Debug, Clone, sqlx::FromRow)]
+pub struct PasskeyChallenge {
+    pub id: Uuid,
+    /// 注册时为当前用户, 登录时为空
+    pub user_id: Option<Uuid>,
+    /// base64url 编码
+    pub challenge: String,
+    pub expires_at: DateTime<Utc>,
+    pub created_at: DateTime<Utc>,
+}
+
+impl PasskeyChallenge {
+    pub const TABLE: &'static str = "passkey_challenges";
+}
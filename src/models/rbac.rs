@@ -0,0 +1,92 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+/// 内置管理员角色, 持有全部系统权限
+pub const ADMIN_ROLE: &str = "admin";
+
+/// 系统级权限, 名称与 permissions 表对应
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Permission {
+    /// 查看任意用户
+    #[serde(rename = "users:read")]
+    UsersRead,
+    /// 修改或删除任意用户
+    #[serde(rename = "users:write")]
+    UsersWrite,
+    /// 授予与撤销角色
+    #[serde(rename = "roles:write")]
+    RolesWrite,
+    /// 写入所有账本共用的汇率
+    #[serde(rename = "rates:write")]
+    RatesWrite,
+}
+
+impl Permission {
+    pub const ALL: [Permission; 4] = [
+        Permission::UsersRead,
+        Permission::UsersWrite,
+        Permission::RolesWrite,
+        Permission::RatesWrite,
+    ];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Permission::UsersRead => "users:read",
+            Permission::UsersWrite => "users:write",
+            Permission::RolesWrite => "roles:write",
+            Permission::RatesWrite => "rates:write",
+        }
+    }
+
+    /// 按名称查找权限, 数据库中存在但代码未识别的权限忽略
+    pub fn from_name(name: &str) -> Option<Permission> {
+        Permission::ALL.into_iter().find(|p| p.name() == name)
+    }
+}
+
+/// 系统角色
+#[derive(Serialize, Deserialize, Debug, Clone, sqlx::FromRow)]
+pub struct Role {
+    pub id: Uuid,
+    pub name: String,
+    pub description: String,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+impl Role {
+    pub const TABLE: &'static str = "roles";
+    pub const PERMISSIONS_TABLE: &'static str = "permissions";
+    pub const ROLE_PERMISSIONS_TABLE: &'static str = "role_permissions";
+    pub const USER_ROLES_TABLE: &'static str = "user_roles";
+}
+
+impl Default for Role {
+    fn default() -> Self {
+        Role {
+            id: Uuid::new_v4(),
+            name: Default::default(),
+            description: Default::default(),
+            created_at: Utc::now(),
+            updated_at: Utc::now(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_permission_name_round_trip() {
+        for permission in Permission::ALL {
+            assert_eq!(Some(permission), Permission::from_name(permission.name()));
+            assert_eq!(
+                format!("\"{}\"", permission.name()),
+                serde_json::to_string(&permission).unwrap()
+            );
+        }
+        assert_eq!(None, Permission::from_name("ledgers:read"));
+    }
+}
@@ -0,0 +1,220 @@
+use super::entry::{CreateEntry, CreatePosting};
+use chrono::{DateTime, Datelike, Duration, NaiveDate, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::types::Json;
+use uuid::Uuid;
+
+/// 重复频率
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, sqlx::Type)]
+#[sqlx(type_name = "recurrence_frequency", rename_all = "lowercase")]
+#[serde(rename_all = "lowercase")]
+pub enum Frequency {
+    Daily,
+    Weekly,
+    Monthly,
+    Yearly,
+}
+
+/// 重复规则, 第 n 次发生在 start_date 之后 n * every 个 frequency,
+/// 按月/年重复时日期超出当月天数则取月末
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Schedule {
+    pub frequency: Frequency,
+    pub every: i32,
+    pub start_date: NaiveDate,
+}
+
+impl Schedule {
+    /// 第 n 次发生的日期, 第 0 次为 start_date
+    pub fn nth(&self, n: i32) -> NaiveDate {
+        let steps = n * self.every;
+        match self.frequency {
+            Frequency::Daily => self.start_date + Duration::days(steps as i64),
+            Frequency::Weekly => self.start_date + Duration::weeks(steps as i64),
+            Frequency::Monthly => add_months(self.start_date, steps),
+            Frequency::Yearly => add_months(self.start_date, steps * 12),
+        }
+    }
+
+    /// date 之后的第一次发生日期
+    pub fn next_after(&self, date: NaiveDate) -> NaiveDate {
+        if date < self.start_date {
+            return self.start_date;
+        }
+        let elapsed = match self.frequency {
+            Frequency::Daily => (date - self.start_date).num_days() as i32,
+            Frequency::Weekly => (date - self.start_date).num_weeks() as i32,
+            Frequency::Monthly => months_between(self.start_date, date),
+            Frequency::Yearly => months_between(self.start_date, date) / 12,
+        };
+        // 月末取整可能让估算偏差一次, 从估算值前一次开始向后找
+        let mut n = (elapsed / self.every - 1).max(0);
+        while self.nth(n) <= date {
+            n += 1;
+        }
+        self.nth(n)
+    }
+}
+
+fn months_between(from: NaiveDate, to: NaiveDate) -> i32 {
+    (to.year() - from.year()) * 12 + to.month0() as i32 - from.month0() as i32
+}
+
+fn add_months(date: NaiveDate, months: i32) -> NaiveDate {
+    let months = date.year() * 12 + date.month0() as i32 + months;
+    let (year, month) = (months.div_euclid(12), months.rem_euclid(12) as u32 + 1);
+    let last_day =
+        NaiveDate::from_ymd(year + month as i32 / 12, month % 12 + 1, 1) - Duration::days(1);
+    NaiveDate::from_ymd(year, month, date.day().min(last_day.day()))
+}
+
+// RecurringEntry创建参数
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CreateRecurringEntry {
+    pub ledger_id: Uuid,
+    pub description: String,
+    pub category_id: Option<Uuid>,
+    pub tags: Vec<String>,
+    pub postings: Vec<CreatePosting>,
+    pub frequency: Frequency,
+    pub every: i32,
+    pub start_date: NaiveDate,
+    pub end_date: Option<NaiveDate>,
+}
+
+/// 周期分录模板, 由后台任务在 next_run 到期时生成分录
+#[derive(Serialize, Deserialize, Debug, Clone, sqlx::FromRow)]
+pub struct RecurringEntry {
+    pub id: Uuid,
+    pub ledger_id: Uuid,
+    pub description: String,
+    pub category_id: Option<Uuid>,
+    pub tags: Vec<String>,
+    pub postings: Json<Vec<CreatePosting>>,
+    pub frequency: Frequency,
+    pub every: i32,
+    pub start_date: NaiveDate,
+    pub end_date: Option<NaiveDate>,
+    pub next_run: Option<NaiveDate>,
+    /// 连续生成失败的次数, 达到 MAX_FAILURES 后暂停调度
+    pub failures: i32,
+    pub last_error: Option<String>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+impl RecurringEntry {
+    pub const TABLE: &'static str = "recurring_entries";
+    pub const OCCURRENCES_TABLE: &'static str = "recurring_occurrences";
+    /// 每次调度最多补记的周期数, 起始日期很早的模板分多次调度补齐
+    pub const MAX_CATCH_UP: usize = 31;
+    /// 连续失败达到该次数后暂停调度, 需手动恢复
+    pub const MAX_FAILURES: i32 = 5;
+
+    pub fn schedule(&self) -> Schedule {
+        Schedule {
+            frequency: self.frequency,
+            every: self.every,
+            start_date: self.start_date,
+        }
+    }
+
+    /// date 是否仍在模板的有效期内
+    pub fn covers(&self, date: NaiveDate) -> bool {
+        self.end_date.is_none_or(|end_date| date <= end_date)
+    }
+
+    /// 生成 date 这一次的分录
+    pub fn occurrence(&self, date: NaiveDate) -> CreateEntry {
+        CreateEntry {
+            ledger_id: self.ledger_id,
+            date,
+            description: self.description.clone(),
+            category_id: self.category_id,
+            tags: self.tags.clone(),
+            postings: self.postings.0.clone(),
+        }
+    }
+}
+
+impl Default for RecurringEntry {
+    fn default() -> Self {
+        let today = Utc::today().naive_utc();
+        RecurringEntry {
+            id: Uuid::new_v4(),
+            ledger_id: Uuid::nil(),
+            description: Default::default(),
+            category_id: None,
+            tags: Vec::new(),
+            postings: Json(Vec::new()),
+            frequency: Frequency::Monthly,
+            every: 1,
+            start_date: today,
+            end_date: None,
+            next_run: Some(today),
+            failures: 0,
+            last_error: None,
+            created_at: Utc::now(),
+            updated_at: Utc::now(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_schedule_nth() {
+        let monthly = Schedule {
+            frequency: Frequency::Monthly,
+            every: 1,
+            start_date: NaiveDate::from_ymd(2022, 1, 31),
+        };
+        assert_eq!(NaiveDate::from_ymd(2022, 2, 28), monthly.nth(1));
+        assert_eq!(NaiveDate::from_ymd(2022, 3, 31), monthly.nth(2));
+        assert_eq!(NaiveDate::from_ymd(2023, 1, 31), monthly.nth(12));
+
+        let fortnightly = Schedule {
+            frequency: Frequency::Weekly,
+            every: 2,
+            start_date: NaiveDate::from_ymd(2022, 3, 4),
+        };
+        assert_eq!(NaiveDate::from_ymd(2022, 3, 18), fortnightly.nth(1));
+
+        let leap_day = Schedule {
+            frequency: Frequency::Yearly,
+            every: 1,
+            start_date: NaiveDate::from_ymd(2020, 2, 29),
+        };
+        assert_eq!(NaiveDate::from_ymd(2021, 2, 28), leap_day.nth(1));
+        assert_eq!(NaiveDate::from_ymd(2024, 2, 29), leap_day.nth(4));
+    }
+
+    #[test]
+    fn test_schedule_next_after() {
+        let monthly = Schedule {
+            frequency: Frequency::Monthly,
+            every: 1,
+            start_date: NaiveDate::from_ymd(2022, 1, 31),
+        };
+        let next = monthly.next_after(NaiveDate::from_ymd(2022, 1, 31));
+        assert_eq!(NaiveDate::from_ymd(2022, 2, 28), next);
+        let next = monthly.next_after(next);
+        assert_eq!(NaiveDate::from_ymd(2022, 3, 31), next);
+        assert_eq!(
+            monthly.start_date,
+            monthly.next_after(NaiveDate::from_ymd(2021, 12, 1))
+        );
+
+        let every_ten_days = Schedule {
+            frequency: Frequency::Daily,
+            every: 10,
+            start_date: NaiveDate::from_ymd(2022, 3, 1),
+        };
+        assert_eq!(
+            NaiveDate::from_ymd(2022, 4, 10),
+            every_ten_days.next_after(NaiveDate::from_ymd(2022, 3, 31))
+        );
+    }
+}
@@ -0,0 +1,270 @@
+use super::{account::AccountKind, budget::BudgetPeriod};
+use chrono::NaiveDate;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use uuid::Uuid;
+
+/// 科目余额, balance 为科目自身余额, total 为包含所有子科目的汇总余额(借方为正)
+#[derive(Serialize, Deserialize, Debug, Clone, sqlx::FromRow)]
+pub struct AccountBalance {
+    pub account_id: Uuid,
+    pub parent_id: Option<Uuid>,
+    pub name: String,
+    pub kind: AccountKind,
+    pub balance: i64,
+    pub total: i64,
+}
+
+/// 试算平衡表行, 科目净额按方向列示于借方或贷方
+#[derive(Serialize, Deserialize, Debug, Clone, sqlx::FromRow)]
+pub struct TrialBalanceLine {
+    pub account_id: Uuid,
+    pub name: String,
+    pub kind: AccountKind,
+    pub debit: i64,
+    pub credit: i64,
+}
+
+/// 试算平衡表
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct TrialBalance {
+    pub as_of: NaiveDate,
+    pub lines: Vec<TrialBalanceLine>,
+    pub total_debit: i64,
+    pub total_credit: i64,
+    pub balanced: bool,
+}
+
+impl TrialBalance {
+    pub fn new(as_of: NaiveDate, lines: Vec<TrialBalanceLine>) -> Self {
+        let total_debit = lines.iter().map(|line| line.debit).sum();
+        let total_credit = lines.iter().map(|line| line.credit).sum();
+        TrialBalance {
+            as_of,
+            lines,
+            total_debit,
+            total_credit,
+            balanced: total_debit == total_credit,
+        }
+    }
+}
+
+/// 期间报表的分组粒度
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum ReportGrouping {
+    #[default]
+    Month,
+    Quarter,
+    Year,
+}
+
+impl ReportGrouping {
+    /// 与预算周期相同的自然月/季/年划分
+    pub fn period(&self) -> BudgetPeriod {
+        match self {
+            ReportGrouping::Month => BudgetPeriod::Monthly,
+            ReportGrouping::Quarter => BudgetPeriod::Quarterly,
+            ReportGrouping::Year => BudgetPeriod::Yearly,
+        }
+    }
+
+    /// from 至 to 之间各期间的起止日期, 首尾期间截取到查询范围内
+    pub fn periods(&self, from: NaiveDate, to: NaiveDate) -> Vec<(NaiveDate, NaiveDate)> {
+        let period = self.period();
+        let mut periods = Vec::new();
+        let mut start = period.start_of(from);
+        while start <= to {
+            periods.push((start.max(from), period.end_of(start).min(to)));
+            start = period.shift(start, 1);
+        }
+        periods
+    }
+}
+
+/// 损益表聚合行, amount 为期间内该分类下科目的发生额(借方为正)
+#[derive(Serialize, Deserialize, Debug, Clone, sqlx::FromRow)]
+pub struct IncomeStatementRow {
+    pub period_start: NaiveDate,
+    pub category_id: Option<Uuid>,
+    pub category: Option<String>,
+    pub kind: AccountKind,
+    pub amount: i64,
+}
+
+/// 分类合计, category_id 为空表示未分类
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct CategoryTotal {
+    pub category_id: Option<Uuid>,
+    pub category: Option<String>,
+    pub amount: i64,
+}
+
+/// 损益表单个期间, 收入与费用均以正数列示
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct IncomeStatementPeriod {
+    pub start: NaiveDate,
+    pub end: NaiveDate,
+    pub income: Vec<CategoryTotal>,
+    pub expenses: Vec<CategoryTotal>,
+    pub total_income: i64,
+    pub total_expenses: i64,
+    pub net_income: i64,
+}
+
+/// 损益表, 按期间列示各分类的收入与费用
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct IncomeStatement {
+    pub from: NaiveDate,
+    pub to: NaiveDate,
+    pub group_by: ReportGrouping,
+    pub periods: Vec<IncomeStatementPeriod>,
+}
+
+impl IncomeStatement {
+    pub fn new(
+        from: NaiveDate,
+        to: NaiveDate,
+        group_by: ReportGrouping,
+        rows: Vec<IncomeStatementRow>,
+    ) -> Self {
+        let mut by_period: BTreeMap<NaiveDate, Vec<IncomeStatementRow>> = BTreeMap::new();
+        for row in rows {
+            let start = group_by.period().start_of(row.period_start);
+            by_period.entry(start).or_default().push(row);
+        }
+
+        let periods = group_by
+            .periods(from, to)
+            .into_iter()
+            .map(|(start, end)| {
+                let (mut income, mut expenses) = (Vec::new(), Vec::new());
+                let rows = by_period
+                    .remove(&group_by.period().start_of(start))
+                    .unwrap_or_default();
+                for row in rows {
+                    // 收入类科目贷方为正
+                    let (totals, amount) = match row.kind {
+                        AccountKind::Income => (&mut income, -row.amount),
+                        AccountKind::Expense => (&mut expenses, row.amount),
+                        _ => continue,
+                    };
+                    totals.push(CategoryTotal {
+                        category_id: row.category_id,
+                        category: row.category,
+                        amount,
+                    });
+                }
+                let total_income = income.iter().map(|t: &CategoryTotal| t.amount).sum();
+                let total_expenses = expenses.iter().map(|t: &CategoryTotal| t.amount).sum();
+                IncomeStatementPeriod {
+                    start,
+                    end,
+                    income,
+                    expenses,
+                    total_income,
+                    total_expenses,
+                    net_income: total_income - total_expenses,
+                }
+            })
+            .collect();
+
+        IncomeStatement {
+            from,
+            to,
+            group_by,
+            periods,
+        }
+    }
+}
+
+/// 现金流量聚合行, 按分录计算资产类科目的净变动后分别汇总流入与流出
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq, sqlx::FromRow)]
+pub struct CashFlowRow {
+    pub period_start: NaiveDate,
+    pub category_id: Option<Uuid>,
+    pub category: Option<String>,
+    pub inflow: i64,
+    pub outflow: i64,
+}
+
+/// 分类现金流量合计
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct CashFlowTotal {
+    pub category_id: Option<Uuid>,
+    pub category: Option<String>,
+    pub inflow: i64,
+    pub outflow: i64,
+    pub net: i64,
+}
+
+/// 现金流量表单个期间
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct CashFlowPeriod {
+    pub start: NaiveDate,
+    pub end: NaiveDate,
+    pub categories: Vec<CashFlowTotal>,
+    pub inflow: i64,
+    pub outflow: i64,
+    pub net: i64,
+}
+
+/// 现金流量表, 资产类科目之间的转账不计入
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct CashFlow {
+    pub from: NaiveDate,
+    pub to: NaiveDate,
+    pub group_by: ReportGrouping,
+    pub periods: Vec<CashFlowPeriod>,
+}
+
+impl CashFlow {
+    pub fn new(
+        from: NaiveDate,
+        to: NaiveDate,
+        group_by: ReportGrouping,
+        rows: Vec<CashFlowRow>,
+    ) -> Self {
+        let mut by_period: BTreeMap<NaiveDate, Vec<CashFlowRow>> = BTreeMap::new();
+        for row in rows {
+            let start = group_by.period().start_of(row.period_start);
+            by_period.entry(start).or_default().push(row);
+        }
+
+        let periods = group_by
+            .periods(from, to)
+            .into_iter()
+            .map(|(start, end)| {
+                let categories: Vec<CashFlowTotal> = by_period
+                    .remove(&group_by.period().start_of(start))
+                    .unwrap_or_default()
+                    .into_iter()
+                    .map(|row| CashFlowTotal {
+                        category_id: row.category_id,
+                        category: row.category,
+                        inflow: row.inflow,
+                        outflow: row.outflow,
+                        net: row.inflow - row.outflow,
+                    })
+                    .collect();
+                let inflow = categories.iter().map(|t| t.inflow).sum();
+                let outflow = categories.iter().map(|t| t.outflow).sum();
+                CashFlowPeriod {
+                    start,
+                    end,
+                    categories,
+                    inflow,
+                    outflow,
+                    net: inflow - outflow,
+                }
+            })
+            .collect();
+
+        CashFlow {
+            from,
+            to,
+            group_by,
+            periods,
+        }
+    }
+}
@@ -0,0 +1,83 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use uuid::Uuid;
+
+// refresh token 写入参数
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CreateRefreshToken {
+    pub user_id: Uuid,
+    pub family_id: Uuid,
+    pub token_hash: String,
+    pub expires_at: DateTime<Utc>,
+}
+
+/// 服务端保存的 refresh token, 令牌原文只返回给客户端一次
+#[derive(Serialize, Deserialize, Debug, Clone, sqlx::FromRow)]
+pub struct RefreshToken {
+    pub id: Uuid,
+    pub user_id: Uuid,
+    pub family_id: Uuid,
+    pub token_hash: String,
+    pub expires_at: DateTime<Utc>,
+    pub used_at: Option<DateTime<Utc>>,
+    pub revoked_at: Option<DateTime<Utc>>,
+    pub created_at: DateTime<Utc>,
+}
+
+impl RefreshToken {
+    pub const TABLE: &'static str = "refresh_tokens";
+}
+
+impl Default for RefreshToken {
+    fn default() -> Self {
+        RefreshToken {
+            id: Uuid::new_v4(),
+            user_id: Uuid::new_v4(),
+            family_id: Uuid::new_v4(),
+            token_hash: Default::default(),
+            expires_at: Utc::now(),
+            used_at: None,
+            revoked_at: None,
+            created_at: Utc::now(),
+        }
+    }
+}
+
+/// 签发或轮换得到的 refresh token 原文
+#[derive(Debug, Clone)]
+pub struct RefreshGrant {
+    pub user_id: Uuid,
+    pub family_id: Uuid,
+    pub token: String,
+}
+
+/// 密码重置令牌, 令牌原文只通过邮件发送一次
+#[derive(Serialize, Deserialize, Debug, Clone, sqlx::FromRow)]
+pub struct PasswordReset {
+    pub id: Uuid,
+    pub user_id: Uuid,
+    pub token_hash: String,
+    pub expires_at: DateTime<Utc>,
+    pub used_at: Option<DateTime<Utc>>,
+    pub created_at: DateTime<Utc>,
+}
+
+impl PasswordReset {
+    pub const TABLE: &'static str = "password_resets";
+}
+
+/// access token 撤销记录的表名
+pub struct Revocation;
+
+impl Revocation {
+    /// 按 jti 撤销的单个令牌
+    pub const TOKENS_TABLE: &'static str = "revoked_tokens";
+    /// 按签发时间撤销的用户全部令牌
+    pub const USERS_TABLE: &'static str = "token_revocations";
+}
+
+/// 令牌原文的 SHA-256 摘要, 数据库泄露时无法直接使用
+pub fn token_hash(token: &str) -> String {
+    hex::encode(Sha256::digest(token.as_bytes()))
+}
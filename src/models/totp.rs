@@ -0,0 +1,44 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+/// 用户的 TOTP 密钥, 密钥不在响应中返回
+#[derive(Serialize, Deserialize, Debug, Clone, sqlx::FromRow)]
+pub struct UserTotp {
+    pub user_id: Uuid,
+    #[serde(skip_serializing)]
+    pub secret: String,
+    /// 首次验证码校验通过的时间, 为空表示尚未启用
+    pub confirmed_at: Option<DateTime<Utc>>,
+    /// 最近一次使用的时间步
+    pub last_step: Option<i64>,
+    /// 当前登录第二步令牌已尝试的次数
+    pub challenge_attempts: i32,
+    pub created_at: DateTime<Utc>,
+}
+
+impl UserTotp {
+    pub const TABLE: &'static str = "user_totp";
+}
+
+/// 恢复码, 原文只在启用时返回一次
+#[derive(Serialize, Deserialize, Debug, Clone, sqlx::FromRow)]
+pub struct RecoveryCode {
+    pub id: Uuid,
+    pub user_id: Uuid,
+    pub code_hash: String,
+    pub used_at: Option<DateTime<Utc>>,
+    pub created_at: DateTime<Utc>,
+}
+
+impl RecoveryCode {
+    pub const TABLE: &'static str = "recovery_codes";
+}
+
+/// 开始绑定时返回给用户的密钥, 可手动输入或以 otpauth URI 生成二维码
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct TotpEnrollment {
+    /// base32 编码的密钥
+    pub secret: String,
+    pub otpauth_uri: String,
+}
@@ -16,7 +16,11 @@ pub struct User {
     pub id: Uuid,
     pub name: String,
     pub email: String,
+    /// Argon2id 哈希, 不在响应中返回
+    #[serde(default, skip_serializing)]
     pub password: String,
+    /// 邮箱验证时间, 为空表示尚未验证
+    pub verified_at: Option<DateTime<Utc>>,
     pub created_at: DateTime<Utc>,
     pub updated_at: DateTime<Utc>,
 }
@@ -32,6 +36,7 @@ impl Default for User {
             name: Default::default(),
             email: Default::default(),
             password: Default::default(),
+            verified_at: None,
             created_at: Utc::now(),
             updated_at: Utc::now(),
         }
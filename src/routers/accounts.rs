@@ -0,0 +1,211 @@
+use super::{ApiResponse, Editor, LedgerAccess, Viewer};
+use crate::{
+    dto::validate_payload,
+    errors::ApiResult,
+    services::{
+        account::{
+            Account, AccountNode, AccountServiceImpl, CreateAccountInput, DynAccountService,
+            MoveAccountInput, RenameAccountInput,
+        },
+        ledger::{DynLedgerService, LedgerServiceImpl},
+    },
+};
+use axum::{
+    extract::{Extension, Path},
+    routing::{post, put},
+    AddExtensionLayer, Json, Router,
+};
+use sqlx::postgres::PgPool;
+use std::sync::Arc;
+use uuid::Uuid;
+
+pub(crate) fn router(pool: Arc<PgPool>) -> Router {
+    let account_svc: DynAccountService = Arc::new(AccountServiceImpl::new(pool.clone()));
+    let ledger_svc: DynLedgerService = Arc::new(LedgerServiceImpl::new(pool));
+    configure(account_svc, ledger_svc)
+}
+
+fn configure(account_svc: DynAccountService, ledger_svc: DynLedgerService) -> Router {
+    Router::new()
+        .route(
+            "/:ledger_id/accounts",
+            post(create_account).get(account_tree),
+        )
+        .route("/:ledger_id/accounts/:account_id", put(rename_account))
+        .route("/:ledger_id/accounts/:account_id/move", post(move_account))
+        .route(
+            "/:ledger_id/accounts/:account_id/close",
+            post(close_account),
+        )
+        .layer(&AddExtensionLayer::new(account_svc))
+        .layer(&AddExtensionLayer::new(ledger_svc))
+}
+
+async fn create_account(
+    _: LedgerAccess<Editor>,
+    Extension(svc): Extension<DynAccountService>,
+    Path(ledger_id): Path<Uuid>,
+    Json(input): Json<CreateAccountInput>,
+) -> ApiResult<ApiResponse<Account>> {
+    validate_payload(&input)?;
+    Ok(ApiResponse::success(svc.create(ledger_id, input).await?))
+}
+
+async fn account_tree(
+    _: LedgerAccess<Viewer>,
+    Extension(svc): Extension<DynAccountService>,
+    Path(ledger_id): Path<Uuid>,
+) -> ApiResult<ApiResponse<Vec<AccountNode>>> {
+    Ok(ApiResponse::success(svc.tree(ledger_id).await?))
+}
+
+async fn rename_account(
+    _: LedgerAccess<Editor>,
+    Extension(svc): Extension<DynAccountService>,
+    Path((ledger_id, id)): Path<(Uuid, Uuid)>,
+    Json(input): Json<RenameAccountInput>,
+) -> ApiResult<ApiResponse<Account>> {
+    validate_payload(&input)?;
+    Ok(ApiResponse::success(
+        svc.rename(ledger_id, id, input).await?,
+    ))
+}
+
+async fn move_account(
+    _: LedgerAccess<Editor>,
+    Extension(svc): Extension<DynAccountService>,
+    Path((ledger_id, id)): Path<(Uuid, Uuid)>,
+    Json(input): Json<MoveAccountInput>,
+) -> ApiResult<ApiResponse<Account>> {
+    Ok(ApiResponse::success(
+        svc.move_to(ledger_id, id, input).await?,
+    ))
+}
+
+async fn close_account(
+    _: LedgerAccess<Editor>,
+    Extension(svc): Extension<DynAccountService>,
+    Path((ledger_id, id)): Path<(Uuid, Uuid)>,
+) -> ApiResult<ApiResponse<Account>> {
+    Ok(ApiResponse::success(svc.close(ledger_id, id).await?))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        errors::Error,
+        models::{ledger::Ledger, member::LedgerRole, user::User},
+        routers::jwt,
+        services::{
+            account::MockAccountService,
+            auth::{DynAuthService, MockAuthService},
+            ledger::MockLedgerService,
+        },
+    };
+    use axum::{
+        body::Body,
+        http::{self, request::Request, StatusCode},
+    };
+    use std::sync::Arc;
+    use tower::ServiceExt;
+
+    fn configure_with_user(account_svc: MockAccountService, user_id: Uuid) -> Router {
+        let mut auth_svc = MockAuthService::new();
+        auth_svc.expect_authenticate().returning(move |_, _, _| {
+            Ok(User {
+                id: user_id,
+                ..Default::default()
+            })
+        });
+        let mut ledger_svc = MockLedgerService::new();
+        ledger_svc.expect_access().returning(|user_id, id| {
+            let ledger = Ledger {
+                id,
+                owner_id: Some(user_id),
+                ..Default::default()
+            };
+            Ok((ledger, LedgerRole::Owner))
+        });
+        let auth_svc: DynAuthService = Arc::new(auth_svc);
+        configure(Arc::new(account_svc), Arc::new(ledger_svc))
+            .layer(&AddExtensionLayer::new(auth_svc))
+    }
+
+    #[tokio::test]
+    async fn test_account_controller_tree() {
+        let mut svc = MockAccountService::new();
+        svc.expect_tree().returning(|ledger_id| {
+            let root = Account {
+                ledger_id,
+                name: "Assets".to_string(),
+                ..Default::default()
+            };
+            let child = Account {
+                ledger_id,
+                parent_id: Some(root.id),
+                name: "Cash".to_string(),
+                ..Default::default()
+            };
+            Ok(AccountNode::build_tree(vec![root, child]))
+        });
+
+        std::env::set_var("JWT_SECRET", "example_secret_key");
+        let user_id = Uuid::new_v4();
+        let app = configure_with_user(svc, user_id);
+
+        let response = app
+            .oneshot(
+                Request::builder()
+                    .method(http::Method::GET)
+                    .uri(format!("/{}/accounts", Uuid::new_v4()))
+                    .header(
+                        http::header::AUTHORIZATION,
+                        format!("Bearer {}", jwt::sign(user_id).unwrap()),
+                    )
+                    .body(Body::empty())
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+
+        assert_eq!(response.status(), StatusCode::OK);
+        let body = hyper::body::to_bytes(response.into_body()).await.unwrap();
+        let tree: ApiResponse<Vec<AccountNode>> = serde_json::from_slice(&body).unwrap();
+        let tree = tree.data.unwrap();
+        assert_eq!(1, tree.len());
+        assert_eq!("Cash", tree[0].children[0].account.name);
+    }
+
+    #[tokio::test]
+    async fn test_account_controller_close_with_balance() {
+        let mut svc = MockAccountService::new();
+        svc.expect_close()
+            .returning(|_, _| Err(Error::AccountHasBalance(100, "CNY".to_string())));
+
+        std::env::set_var("JWT_SECRET", "example_secret_key");
+        let user_id = Uuid::new_v4();
+        let app = configure_with_user(svc, user_id);
+
+        let response = app
+            .oneshot(
+                Request::builder()
+                    .method(http::Method::POST)
+                    .uri(format!(
+                        "/{}/accounts/{}/close",
+                        Uuid::new_v4(),
+                        Uuid::new_v4()
+                    ))
+                    .header(
+                        http::header::AUTHORIZATION,
+                        format!("Bearer {}", jwt::sign(user_id).unwrap()),
+                    )
+                    .body(Body::empty())
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+
+        assert_eq!(response.status(), StatusCode::CONFLICT);
+    }
+}
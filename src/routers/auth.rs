@@ -1,55 +1,376 @@
 use super::jwt;
-use super::ApiResponse;
+use super::{ApiResponse, Session};
 use crate::{
-    config::constants::BEARER,
+    config::constants::{BEARER, TOKEN_TTL},
     dto::{
-        auth::{LoginInput, TokenPayload},
+        auth::{
+            ConfirmTotpInput, ForgotPasswordInput, LoginInput, LoginPayload, MfaLoginInput,
+            RecoveryCodesPayload, RefreshInput, ResendVerificationInput, ResetPasswordInput,
+            ScopedTokenInput, ScopedTokenPayload, TokenPayload, VerifyEmailInput,
+        },
+        oidc::OidcCallbackQuery,
+        passkey::{
+            PasskeyCreationOptions, PasskeyLoginInput, PasskeyRegistrationInput,
+            PasskeyRequestOptions,
+        },
         validate_payload,
     },
     errors::{ApiResult, Error},
-    services::auth::{DynAuthService, User},
+    models::{passkey::PasskeyCredential, totp::TotpEnrollment},
+    services::{
+        auth::{DynAuthService, User},
+        oidc::{DynOidcService, OidcServiceImpl},
+        passkey::{DynPasskeyService, PasskeyServiceImpl},
+        password_reset::{DynPasswordResetService, PasswordResetServiceImpl},
+        token::{DynTokenService, RefreshGrant, TokenServiceImpl},
+        totp::{DynTotpService, TotpServiceImpl},
+        verification::{DynVerificationService, VerificationServiceImpl},
+    },
 };
 use axum::{
-    extract::Extension,
-    routing::{get, post},
-    Json, Router,
+    extract::{Extension, Path, Query},
+    response::Redirect,
+    routing::{delete, get, post},
+    AddExtensionLayer, Json, Router,
 };
+use sqlx::postgres::PgPool;
+use std::sync::Arc;
+use uuid::Uuid;
 
-pub(crate) fn router() -> Router {
-    configure()
+pub(crate) fn router(pool: Arc<PgPool>) -> Router {
+    let token_svc: DynTokenService = Arc::new(TokenServiceImpl::new(pool.clone()));
+    let verification_svc: DynVerificationService =
+        Arc::new(VerificationServiceImpl::new(pool.clone()));
+    let reset_svc: DynPasswordResetService = Arc::new(PasswordResetServiceImpl::new(pool.clone()));
+    let totp_svc: DynTotpService = Arc::new(TotpServiceImpl::new(pool.clone()));
+    let passkey_svc: DynPasskeyService = Arc::new(PasskeyServiceImpl::new(pool.clone()));
+    let oidc_svc: DynOidcService = Arc::new(OidcServiceImpl::new(pool));
+    configure(
+        token_svc,
+        verification_svc,
+        reset_svc,
+        totp_svc,
+        passkey_svc,
+        oidc_svc,
+    )
 }
 
-fn configure() -> Router {
+fn configure(
+    token_svc: DynTokenService,
+    verification_svc: DynVerificationService,
+    reset_svc: DynPasswordResetService,
+    totp_svc: DynTotpService,
+    passkey_svc: DynPasskeyService,
+    oidc_svc: DynOidcService,
+) -> Router {
     Router::new()
         .route("/login", post(login))
+        .route("/login/mfa", post(login_mfa))
+        .route("/refresh", post(refresh))
+        .route("/logout", post(logout))
+        .route("/logout-all", post(logout_all))
+        .route("/tokens/scoped", post(issue_scoped_token))
         .route("/authorize", get(authorize))
+        .route("/verify-email", post(verify_email))
+        .route("/verify-email/resend", post(resend_verification))
+        .route("/password/forgot", post(forgot_password))
+        .route("/password/reset", post(reset_password))
+        .route("/mfa/totp/enroll", post(enroll_totp))
+        .route("/mfa/totp/confirm", post(confirm_totp))
+        .route("/passkeys", get(list_passkeys))
+        .route("/passkeys/:id", delete(delete_passkey))
+        .route("/passkeys/register/start", post(start_passkey_registration))
+        .route(
+            "/passkeys/register/finish",
+            post(finish_passkey_registration),
+        )
+        .route("/passkeys/login/start", post(start_passkey_login))
+        .route("/passkeys/login/finish", post(finish_passkey_login))
+        .route("/oidc/:provider/start", get(start_oidc_login))
+        .route("/oidc/:provider/callback", get(finish_oidc_login))
+        .layer(&AddExtensionLayer::new(token_svc))
+        .layer(&AddExtensionLayer::new(verification_svc))
+        .layer(&AddExtensionLayer::new(reset_svc))
+        .layer(&AddExtensionLayer::new(totp_svc))
+        .layer(&AddExtensionLayer::new(passkey_svc))
+        .layer(&AddExtensionLayer::new(oidc_svc))
 }
 
 pub async fn authorize(user: User) -> ApiResponse<User> {
     ApiResponse::success(user)
 }
 
+// 签发与 refresh token family 关联的 access token, 并与 refresh token 一起返回
+fn token_payload(grant: RefreshGrant) -> ApiResult<TokenPayload> {
+    Ok(TokenPayload {
+        access_token: jwt::sign_session(grant.user_id, grant.family_id)?,
+        token_type: BEARER.to_string(),
+        expires_in: TOKEN_TTL.access_token_ttl_secs,
+        refresh_token: grant.token,
+    })
+}
+
+/// 密码登录, 已启用两步验证时只返回第二步令牌
 async fn login(
     Extension(svc): Extension<DynAuthService>,
+    Extension(tokens): Extension<DynTokenService>,
+    Extension(totp): Extension<DynTotpService>,
     Json(input): Json<LoginInput>,
+) -> ApiResult<ApiResponse<LoginPayload>> {
+    validate_payload(&input)?;
+    let user = svc.sign_in(input).await?;
+    if let Some(challenge) = totp.challenge(user.id).await? {
+        return Ok(ApiResponse::success(LoginPayload::MfaRequired(challenge)));
+    }
+    let payload = token_payload(tokens.issue(user.id).await?)?;
+    Ok(ApiResponse::success(LoginPayload::Token(payload)))
+}
+
+/// 登录第二步, 校验 TOTP 验证码或恢复码后签发令牌
+async fn login_mfa(
+    Extension(tokens): Extension<DynTokenService>,
+    Extension(totp): Extension<DynTotpService>,
+    Json(input): Json<MfaLoginInput>,
 ) -> ApiResult<ApiResponse<TokenPayload>> {
     validate_payload(&input)?;
-    let user = svc
-        .sign_in(input)
-        .await
-        .map_err(|_| Error::WrongCredentials)?;
-    let token = jwt::sign(user.id)?;
-    Ok(ApiResponse::success(TokenPayload {
-        access_token: token,
+    let user_id = totp.verify(&input.mfa_token, &input.code).await?;
+    Ok(ApiResponse::success(token_payload(
+        tokens.issue(user_id).await?,
+    )?))
+}
+
+/// 使用 refresh token 换取新的令牌对, 旧 refresh token 随即失效
+async fn refresh(
+    Extension(tokens): Extension<DynTokenService>,
+    Json(input): Json<RefreshInput>,
+) -> ApiResult<ApiResponse<TokenPayload>> {
+    validate_payload(&input)?;
+    Ok(ApiResponse::success(token_payload(
+        tokens.refresh(input.refresh_token).await?,
+    )?))
+}
+
+/// 注销当前令牌, 同一次登录签发的 refresh token 一并失效
+async fn logout(
+    session: Session,
+    Extension(svc): Extension<DynAuthService>,
+) -> ApiResult<ApiResponse<bool>> {
+    let claims = session.claims;
+    svc.logout(session.user.id, claims.jti, claims.exp, claims.sid)
+        .await?;
+    Ok(ApiResponse::success(true))
+}
+
+/// 为当前用户签发授权范围不超过当前令牌的 access token, 如供只读集成使用
+async fn issue_scoped_token(
+    session: Session,
+    Json(input): Json<ScopedTokenInput>,
+) -> ApiResult<ApiResponse<ScopedTokenPayload>> {
+    validate_payload(&input)?;
+    let scopes = input
+        .scope
+        .split_whitespace()
+        .map(|name| {
+            jwt::Scope::from_name(name)
+                .ok_or_else(|| Error::new_empty_fields_error(format!("unknown scope {name}")))
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+    if scopes.is_empty() {
+        return Err(Error::new_empty_fields_error("scope is required".to_string()).into());
+    }
+    let access_token = jwt::sign_narrowed(&session.claims, &scopes)?;
+    Ok(ApiResponse::success(ScopedTokenPayload {
+        access_token,
         token_type: BEARER.to_string(),
+        expires_in: TOKEN_TTL.access_token_ttl_secs,
+        scope: scopes
+            .iter()
+            .map(|scope| scope.name())
+            .collect::<Vec<_>>()
+            .join(" "),
     }))
 }
 
+/// 注销用户在所有设备上的令牌
+async fn logout_all(
+    user: User,
+    Extension(svc): Extension<DynAuthService>,
+) -> ApiResult<ApiResponse<bool>> {
+    svc.logout_all(user.id).await?;
+    Ok(ApiResponse::success(true))
+}
+
+/// 使用邮件中的令牌验证邮箱
+async fn verify_email(
+    Extension(svc): Extension<DynVerificationService>,
+    Json(input): Json<VerifyEmailInput>,
+) -> ApiResult<ApiResponse<bool>> {
+    validate_payload(&input)?;
+    svc.verify(&input.token).await?;
+    Ok(ApiResponse::success(true))
+}
+
+/// 重新发送验证邮件, 无论邮箱是否注册都返回成功
+async fn resend_verification(
+    Extension(svc): Extension<DynVerificationService>,
+    Json(input): Json<ResendVerificationInput>,
+) -> ApiResult<ApiResponse<bool>> {
+    validate_payload(&input)?;
+    svc.resend(&input.email).await?;
+    Ok(ApiResponse::success(true))
+}
+
+/// 申请重置密码, 无论邮箱是否注册都返回成功
+async fn forgot_password(
+    Extension(svc): Extension<DynPasswordResetService>,
+    Json(input): Json<ForgotPasswordInput>,
+) -> ApiResult<ApiResponse<bool>> {
+    validate_payload(&input)?;
+    svc.forgot(&input.email).await?;
+    Ok(ApiResponse::success(true))
+}
+
+/// 使用邮件中的令牌设置新密码, 已登录的会话全部失效
+async fn reset_password(
+    Extension(svc): Extension<DynPasswordResetService>,
+    Json(input): Json<ResetPasswordInput>,
+) -> ApiResult<ApiResponse<bool>> {
+    validate_payload(&input)?;
+    svc.reset(&input.token, input.password).await?;
+    Ok(ApiResponse::success(true))
+}
+
+/// 开始绑定 TOTP, 返回密钥与 otpauth URI
+async fn enroll_totp(
+    user: User,
+    Extension(svc): Extension<DynTotpService>,
+) -> ApiResult<ApiResponse<TotpEnrollment>> {
+    Ok(ApiResponse::success(svc.enroll(&user).await?))
+}
+
+/// 输入首个验证码启用两步验证, 恢复码只返回这一次
+async fn confirm_totp(
+    user: User,
+    Extension(svc): Extension<DynTotpService>,
+    Json(input): Json<ConfirmTotpInput>,
+) -> ApiResult<ApiResponse<RecoveryCodesPayload>> {
+    validate_payload(&input)?;
+    let recovery_codes = svc.confirm(user.id, &input.code).await?;
+    Ok(ApiResponse::success(RecoveryCodesPayload {
+        recovery_codes,
+    }))
+}
+
+/// 开始注册 passkey, 返回传给 navigator.credentials.create() 的选项
+async fn start_passkey_registration(
+    user: User,
+    Extension(svc): Extension<DynPasskeyService>,
+) -> ApiResult<ApiResponse<PasskeyCreationOptions>> {
+    Ok(ApiResponse::success(svc.start_registration(&user).await?))
+}
+
+/// 保存认证器返回的 passkey
+async fn finish_passkey_registration(
+    user: User,
+    Extension(svc): Extension<DynPasskeyService>,
+    Json(input): Json<PasskeyRegistrationInput>,
+) -> ApiResult<ApiResponse<PasskeyCredential>> {
+    validate_payload(&input)?;
+    Ok(ApiResponse::success(
+        svc.finish_registration(user.id, input).await?,
+    ))
+}
+
+/// 开始 passkey 登录, 返回传给 navigator.credentials.get() 的选项
+async fn start_passkey_login(
+    Extension(svc): Extension<DynPasskeyService>,
+) -> ApiResult<ApiResponse<PasskeyRequestOptions>> {
+    Ok(ApiResponse::success(svc.start_authentication().await?))
+}
+
+/// 校验 passkey 断言, 与密码登录签发相同的令牌
+async fn finish_passkey_login(
+    Extension(svc): Extension<DynPasskeyService>,
+    Extension(tokens): Extension<DynTokenService>,
+    Json(input): Json<PasskeyLoginInput>,
+) -> ApiResult<ApiResponse<TokenPayload>> {
+    validate_payload(&input)?;
+    let user = svc.finish_authentication(input).await.map_err(|err| {
+        tracing::info!("passkey login failed: {err}");
+        Error::WrongCredentials
+    })?;
+    Ok(ApiResponse::success(token_payload(
+        tokens.issue(user.id).await?,
+    )?))
+}
+
+/// 当前用户注册的 passkey
+async fn list_passkeys(
+    user: User,
+    Extension(svc): Extension<DynPasskeyService>,
+) -> ApiResult<ApiResponse<Vec<PasskeyCredential>>> {
+    Ok(ApiResponse::success(svc.list(user.id).await?))
+}
+
+async fn delete_passkey(
+    user: User,
+    Extension(svc): Extension<DynPasskeyService>,
+    Path(id): Path<Uuid>,
+) -> ApiResult<ApiResponse<bool>> {
+    Ok(ApiResponse::success(svc.delete(user.id, id).await?))
+}
+
+/// 跳转到身份提供方的授权页面
+async fn start_oidc_login(
+    Extension(svc): Extension<DynOidcService>,
+    Path(provider): Path<String>,
+) -> ApiResult<Redirect> {
+    let url = svc.start(&provider).await?;
+    let uri = url
+        .parse()
+        .map_err(|_| Error::OidcProvider("invalid authorization endpoint".to_string()))?;
+    Ok(Redirect::to(uri))
+}
+
+/// 提供方授权后的回调, 与密码登录一样在启用两步验证时只返回第二步令牌
+async fn finish_oidc_login(
+    Extension(svc): Extension<DynOidcService>,
+    Extension(tokens): Extension<DynTokenService>,
+    Extension(totp): Extension<DynTotpService>,
+    Path(provider): Path<String>,
+    Query(query): Query<OidcCallbackQuery>,
+) -> ApiResult<ApiResponse<LoginPayload>> {
+    if let Some(error) = query.error {
+        let reason = query.error_description.unwrap_or(error);
+        return Err(Error::OidcLogin(reason).into());
+    }
+    let (code, state) = query
+        .code
+        .zip(query.state)
+        .ok_or_else(|| Error::OidcLogin("missing code or state".to_string()))?;
+    let user = svc
+        .callback(&provider, &code, &state)
+        .await
+        .map_err(|err| {
+            tracing::info!("oidc login with {provider} failed: {err}");
+            err
+        })?;
+    if let Some(challenge) = totp.challenge(user.id).await? {
+        return Ok(ApiResponse::success(LoginPayload::MfaRequired(challenge)));
+    }
+    let payload = token_payload(tokens.issue(user.id).await?)?;
+    Ok(ApiResponse::success(LoginPayload::Token(payload)))
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::services::auth::MockAuthService;
-    use axum::AddExtensionLayer;
+    use crate::dto::auth::MfaChallenge;
+    use crate::services::{
+        auth::MockAuthService, oidc::MockOidcService, passkey::MockPasskeyService,
+        password_reset::MockPasswordResetService, token::MockTokenService, totp::MockTotpService,
+        verification::MockVerificationService,
+    };
     use axum::{
         body::Body,
         http::{self, request::Request, StatusCode},
@@ -59,7 +380,51 @@ mod tests {
     use tower::ServiceExt;
 
     fn configure_with_auth_service(auth_svc: DynAuthService) -> Router {
-        configure().layer(&AddExtensionLayer::new(auth_svc))
+        let mut totp_svc = MockTotpService::new();
+        totp_svc.expect_challenge().returning(|_| Ok(None));
+        configure_with_totp_service(auth_svc, totp_svc)
+    }
+
+    fn configure_with_totp_service(auth_svc: DynAuthService, totp_svc: MockTotpService) -> Router {
+        let mut token_svc = MockTokenService::new();
+        token_svc.expect_issue().returning(|user_id| {
+            Ok(RefreshGrant {
+                user_id,
+                family_id: Uuid::new_v4(),
+                token: "issued-refresh-token".to_string(),
+            })
+        });
+        configure_with_services(auth_svc, token_svc, totp_svc)
+    }
+
+    fn configure_with_services(
+        auth_svc: DynAuthService,
+        token_svc: MockTokenService,
+        totp_svc: MockTotpService,
+    ) -> Router {
+        configure(
+            Arc::new(token_svc),
+            Arc::new(MockVerificationService::new()),
+            Arc::new(MockPasswordResetService::new()),
+            Arc::new(totp_svc),
+            Arc::new(MockPasskeyService::new()),
+            Arc::new(MockOidcService::new()),
+        )
+        .layer(&AddExtensionLayer::new(auth_svc))
+    }
+
+    fn refresh_request(refresh_token: &str) -> Request<Body> {
+        Request::builder()
+            .method(http::Method::POST)
+            .uri("/refresh")
+            .header(http::header::CONTENT_TYPE, mime::APPLICATION_JSON.as_ref())
+            .body(Body::from(
+                serde_json::to_string(&RefreshInput {
+                    refresh_token: refresh_token.to_string(),
+                })
+                .unwrap(),
+            ))
+            .unwrap()
     }
 
     #[tokio::test]
@@ -96,13 +461,15 @@ mod tests {
         let actual: ApiResponse<TokenPayload> = serde_json::from_slice(&body).unwrap();
         // println!("{:?}", actual);
         assert!(actual.ok);
-        assert!(actual.data.is_some());
+        let payload = actual.data.unwrap();
+        assert_eq!("issued-refresh-token", payload.refresh_token);
+        assert!(jwt::verify(&payload.access_token).is_ok());
     }
 
     #[tokio::test]
     async fn test_authenticate_success() {
         let mut svc = MockAuthService::new();
-        svc.expect_get().returning(|id| {
+        svc.expect_authenticate().returning(|id, _, _| {
             Ok(User {
                 id,
                 email: "test@example.com".to_string(),
@@ -113,7 +480,7 @@ mod tests {
         std::env::set_var("JWT_SECRET", "example_secret_key");
         let app = configure_with_auth_service(Arc::new(svc));
 
-        let token = format!("Bearer {}", jwt::sign(uuid::Uuid::new_v4()).unwrap());
+        let token = format!("Bearer {}", jwt::sign(Uuid::new_v4()).unwrap());
         println!("---->> token: {}", &token);
         let response = app
             .oneshot(
@@ -133,4 +500,547 @@ mod tests {
         assert!(actual.ok);
         assert_eq!(actual.data.unwrap().email, "test@example.com");
     }
+
+    #[tokio::test]
+    async fn test_issue_scoped_token_only_narrows() {
+        let mut svc = MockAuthService::new();
+        svc.expect_authenticate().returning(|id, _, _| {
+            Ok(User {
+                id,
+                ..Default::default()
+            })
+        });
+
+        std::env::set_var("JWT_SECRET", "example_secret_key");
+        let app = configure_with_auth_service(Arc::new(svc));
+        let user_id = Uuid::new_v4();
+        let sid = Uuid::new_v4();
+        let scoped_request = |token: &str, scope: &str| {
+            Request::builder()
+                .method(http::Method::POST)
+                .uri("/tokens/scoped")
+                .header(http::header::CONTENT_TYPE, mime::APPLICATION_JSON.as_ref())
+                .header(http::header::AUTHORIZATION, format!("Bearer {token}"))
+                .body(Body::from(format!(r#"{{"scope":"{scope}"}}"#)))
+                .unwrap()
+        };
+
+        let session = jwt::sign_session(user_id, sid).unwrap();
+        let response = app
+            .clone()
+            .oneshot(scoped_request(&session, "read"))
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        let body = hyper::body::to_bytes(response.into_body()).await.unwrap();
+        let actual: ApiResponse<ScopedTokenPayload> = serde_json::from_slice(&body).unwrap();
+        let payload = actual.data.unwrap();
+        assert_eq!("read", payload.scope);
+        let claims = jwt::verify(&payload.access_token).unwrap();
+        assert_eq!(user_id, claims.sub);
+        assert_eq!(Some(sid), claims.sid);
+        assert!(!claims.has_scope(jwt::Scope::Write));
+
+        // 只读令牌不能用于签发新令牌
+        let response = app
+            .clone()
+            .oneshot(scoped_request(&payload.access_token, "read write"))
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::FORBIDDEN);
+
+        let response = app
+            .oneshot(scoped_request(&session, "admin"))
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+    }
+
+    #[tokio::test]
+    async fn test_refresh_rotates_tokens() {
+        let user_id = Uuid::new_v4();
+        let mut token_svc = MockTokenService::new();
+        token_svc
+            .expect_refresh()
+            .with(eq("old-refresh-token".to_string()))
+            .returning(move |_| {
+                Ok(RefreshGrant {
+                    user_id,
+                    family_id: Uuid::new_v4(),
+                    token: "new-refresh-token".to_string(),
+                })
+            });
+
+        std::env::set_var("JWT_SECRET", "example_secret_key");
+        let app = configure_with_services(
+            Arc::new(MockAuthService::new()),
+            token_svc,
+            MockTotpService::new(),
+        );
+
+        let response = app
+            .oneshot(refresh_request("old-refresh-token"))
+            .await
+            .unwrap();
+
+        assert_eq!(response.status(), StatusCode::OK);
+        let body = hyper::body::to_bytes(response.into_body()).await.unwrap();
+        let payload = serde_json::from_slice::<ApiResponse<TokenPayload>>(&body)
+            .unwrap()
+            .data
+            .unwrap();
+        assert_eq!("new-refresh-token", payload.refresh_token);
+        assert_eq!(user_id, jwt::verify(&payload.access_token).unwrap().sub);
+    }
+
+    #[tokio::test]
+    async fn test_refresh_reused_token_is_rejected() {
+        let mut token_svc = MockTokenService::new();
+        token_svc
+            .expect_refresh()
+            .returning(|_| Err(Error::RefreshTokenReused));
+
+        std::env::set_var("JWT_SECRET", "example_secret_key");
+        let app = configure_with_services(
+            Arc::new(MockAuthService::new()),
+            token_svc,
+            MockTotpService::new(),
+        );
+
+        let response = app.oneshot(refresh_request("replayed")).await.unwrap();
+        assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+    }
+
+    #[tokio::test]
+    async fn test_logout_revokes_current_token() {
+        let user_id = Uuid::new_v4();
+        let family_id = Uuid::new_v4();
+        let mut svc = MockAuthService::new();
+        svc.expect_authenticate().returning(|id, _, _| {
+            Ok(User {
+                id,
+                ..Default::default()
+            })
+        });
+        svc.expect_logout()
+            .withf(move |id, _, _, sid| *id == user_id && *sid == Some(family_id))
+            .times(1)
+            .returning(|_, _, _, _| Ok(()));
+
+        std::env::set_var("JWT_SECRET", "example_secret_key");
+        let app = configure_with_auth_service(Arc::new(svc));
+
+        let response = app
+            .oneshot(
+                Request::builder()
+                    .method(http::Method::POST)
+                    .uri("/logout")
+                    .header(
+                        http::header::AUTHORIZATION,
+                        format!("Bearer {}", jwt::sign_session(user_id, family_id).unwrap()),
+                    )
+                    .body(Body::empty())
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+
+        assert_eq!(response.status(), StatusCode::OK);
+    }
+
+    #[tokio::test]
+    async fn test_revoked_token_is_rejected() {
+        let mut svc = MockAuthService::new();
+        svc.expect_authenticate()
+            .returning(|_, _, _| Err(Error::TokenRevoked));
+
+        std::env::set_var("JWT_SECRET", "example_secret_key");
+        let app = configure_with_auth_service(Arc::new(svc));
+
+        let response = app
+            .oneshot(
+                Request::builder()
+                    .method(http::Method::GET)
+                    .uri("/authorize")
+                    .header(
+                        http::header::AUTHORIZATION,
+                        format!("Bearer {}", jwt::sign(Uuid::new_v4()).unwrap()),
+                    )
+                    .body(Body::empty())
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+
+        assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+    }
+
+    #[tokio::test]
+    async fn test_verify_email() {
+        let mut verification_svc = MockVerificationService::new();
+        verification_svc
+            .expect_verify()
+            .with(eq("valid-token"))
+            .returning(|_| Ok(()));
+        verification_svc
+            .expect_verify()
+            .with(eq("used-token"))
+            .returning(|_| Err(Error::InvalidVerificationToken));
+        let app = configure(
+            Arc::new(MockTokenService::new()),
+            Arc::new(verification_svc),
+            Arc::new(MockPasswordResetService::new()),
+            Arc::new(MockTotpService::new()),
+            Arc::new(MockPasskeyService::new()),
+            Arc::new(MockOidcService::new()),
+        );
+
+        for (token, status) in [
+            ("valid-token", StatusCode::OK),
+            ("used-token", StatusCode::BAD_REQUEST),
+        ] {
+            let response = app
+                .clone()
+                .oneshot(
+                    Request::builder()
+                        .method(http::Method::POST)
+                        .uri("/verify-email")
+                        .header(http::header::CONTENT_TYPE, mime::APPLICATION_JSON.as_ref())
+                        .body(Body::from(
+                            serde_json::to_string(&VerifyEmailInput {
+                                token: token.to_string(),
+                            })
+                            .unwrap(),
+                        ))
+                        .unwrap(),
+                )
+                .await
+                .unwrap();
+
+            assert_eq!(response.status(), status);
+        }
+    }
+
+    #[tokio::test]
+    async fn test_login_unverified_email() {
+        let mut svc = MockAuthService::new();
+        svc.expect_sign_in()
+            .returning(|_| Err(Error::EmailNotVerified));
+        let app = configure_with_auth_service(Arc::new(svc));
+
+        let response = app
+            .oneshot(
+                Request::builder()
+                    .method(http::Method::POST)
+                    .uri("/login")
+                    .header(http::header::CONTENT_TYPE, mime::APPLICATION_JSON.as_ref())
+                    .body(Body::from(
+                        serde_json::to_string(&LoginInput {
+                            email: "shenshouer@163.com".to_string(),
+                            password: "password".to_string(),
+                        })
+                        .unwrap(),
+                    ))
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+
+        assert_eq!(response.status(), StatusCode::FORBIDDEN);
+    }
+
+    #[tokio::test]
+    async fn test_login_propagates_internal_error() {
+        let mut svc = MockAuthService::new();
+        svc.expect_sign_in()
+            .returning(|_| Err(Error::DataStore(sqlx::Error::PoolTimedOut)));
+        let app = configure_with_auth_service(Arc::new(svc));
+
+        let response = app
+            .oneshot(
+                Request::builder()
+                    .method(http::Method::POST)
+                    .uri("/login")
+                    .header(http::header::CONTENT_TYPE, mime::APPLICATION_JSON.as_ref())
+                    .body(Body::from(
+                        serde_json::to_string(&LoginInput {
+                            email: "shenshouer@163.com".to_string(),
+                            password: "password".to_string(),
+                        })
+                        .unwrap(),
+                    ))
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+
+        assert_eq!(response.status(), StatusCode::INTERNAL_SERVER_ERROR);
+    }
+
+    #[tokio::test]
+    async fn test_reset_password_validates_input() {
+        let mut reset_svc = MockPasswordResetService::new();
+        reset_svc
+            .expect_reset()
+            .with(eq("reset-token"), eq("new-password".to_string()))
+            .times(1)
+            .returning(|_, _| Ok(()));
+        let app = configure(
+            Arc::new(MockTokenService::new()),
+            Arc::new(MockVerificationService::new()),
+            Arc::new(reset_svc),
+            Arc::new(MockTotpService::new()),
+            Arc::new(MockPasskeyService::new()),
+            Arc::new(MockOidcService::new()),
+        );
+
+        for (password2, status) in [
+            ("other-password", StatusCode::BAD_REQUEST),
+            ("new-password", StatusCode::OK),
+        ] {
+            let response = app
+                .clone()
+                .oneshot(
+                    Request::builder()
+                        .method(http::Method::POST)
+                        .uri("/password/reset")
+                        .header(http::header::CONTENT_TYPE, mime::APPLICATION_JSON.as_ref())
+                        .body(Body::from(
+                            serde_json::to_string(&ResetPasswordInput {
+                                token: "reset-token".to_string(),
+                                password: "new-password".to_string(),
+                                password2: password2.to_string(),
+                            })
+                            .unwrap(),
+                        ))
+                        .unwrap(),
+                )
+                .await
+                .unwrap();
+
+            assert_eq!(response.status(), status);
+        }
+    }
+
+    #[tokio::test]
+    async fn test_login_with_totp_requires_second_step() {
+        let user_id = Uuid::new_v4();
+        let mut svc = MockAuthService::new();
+        svc.expect_sign_in().returning(move |_| {
+            Ok(User {
+                id: user_id,
+                ..Default::default()
+            })
+        });
+        let mut totp_svc = MockTotpService::new();
+        totp_svc
+            .expect_challenge()
+            .with(eq(user_id))
+            .returning(|_| {
+                Ok(Some(MfaChallenge {
+                    mfa_required: true,
+                    mfa_token: "mfa-token".to_string(),
+                    expires_in: 300,
+                }))
+            });
+        totp_svc
+            .expect_verify()
+            .returning(move |token, code| match (token, code) {
+                ("mfa-token", "123456") => Ok(user_id),
+                _ => Err(Error::InvalidMfaCode),
+            });
+
+        std::env::set_var("JWT_SECRET", "example_secret_key");
+        let app = configure_with_totp_service(Arc::new(svc), totp_svc);
+
+        let response = app
+            .clone()
+            .oneshot(
+                Request::builder()
+                    .method(http::Method::POST)
+                    .uri("/login")
+                    .header(http::header::CONTENT_TYPE, mime::APPLICATION_JSON.as_ref())
+                    .body(Body::from(
+                        serde_json::to_string(&LoginInput {
+                            email: "shenshouer@163.com".to_string(),
+                            password: "password".to_string(),
+                        })
+                        .unwrap(),
+                    ))
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        let body = hyper::body::to_bytes(response.into_body()).await.unwrap();
+        let actual: ApiResponse<LoginPayload> = serde_json::from_slice(&body).unwrap();
+        assert!(matches!(
+            actual.data,
+            Some(LoginPayload::MfaRequired(MfaChallenge {
+                mfa_required: true,
+                ..
+            }))
+        ));
+
+        let mfa_request = |code: &str| {
+            Request::builder()
+                .method(http::Method::POST)
+                .uri("/login/mfa")
+                .header(http::header::CONTENT_TYPE, mime::APPLICATION_JSON.as_ref())
+                .body(Body::from(
+                    serde_json::to_string(&MfaLoginInput {
+                        mfa_token: "mfa-token".to_string(),
+                        code: code.to_string(),
+                    })
+                    .unwrap(),
+                ))
+                .unwrap()
+        };
+        let response = app.clone().oneshot(mfa_request("654321")).await.unwrap();
+        assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+
+        let response = app.oneshot(mfa_request("123456")).await.unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        let body = hyper::body::to_bytes(response.into_body()).await.unwrap();
+        let actual: ApiResponse<TokenPayload> = serde_json::from_slice(&body).unwrap();
+        let payload = actual.data.unwrap();
+        assert_eq!("issued-refresh-token", payload.refresh_token);
+        assert_eq!(user_id, jwt::verify(&payload.access_token).unwrap().sub);
+    }
+
+    #[tokio::test]
+    async fn test_passkey_login_issues_tokens() {
+        use crate::dto::passkey::{AssertionCredential, AssertionResponse};
+
+        let user_id = Uuid::new_v4();
+        let mut passkey_svc = MockPasskeyService::new();
+        passkey_svc
+            .expect_finish_authentication()
+            .returning(move |input| match input.credential.id.as_str() {
+                "known" => Ok(User {
+                    id: user_id,
+                    ..Default::default()
+                }),
+                _ => Err(Error::InvalidPasskey("unknown credential".to_string())),
+            });
+        let mut token_svc = MockTokenService::new();
+        token_svc
+            .expect_issue()
+            .with(eq(user_id))
+            .times(1)
+            .returning(|user_id| {
+                Ok(RefreshGrant {
+                    user_id,
+                    family_id: Uuid::new_v4(),
+                    token: "issued-refresh-token".to_string(),
+                })
+            });
+
+        std::env::set_var("JWT_SECRET", "example_secret_key");
+        let app = configure(
+            Arc::new(token_svc),
+            Arc::new(MockVerificationService::new()),
+            Arc::new(MockPasswordResetService::new()),
+            Arc::new(MockTotpService::new()),
+            Arc::new(passkey_svc),
+            Arc::new(MockOidcService::new()),
+        );
+        let request = |credential_id: &str| {
+            Request::builder()
+                .method(http::Method::POST)
+                .uri("/passkeys/login/finish")
+                .header(http::header::CONTENT_TYPE, mime::APPLICATION_JSON.as_ref())
+                .body(Body::from(
+                    serde_json::to_string(&PasskeyLoginInput {
+                        challenge_id: Uuid::new_v4(),
+                        credential: AssertionCredential {
+                            id: credential_id.to_string(),
+                            response: AssertionResponse {
+                                client_data_json: String::new(),
+                                authenticator_data: String::new(),
+                                signature: String::new(),
+                                user_handle: None,
+                            },
+                        },
+                    })
+                    .unwrap(),
+                ))
+                .unwrap()
+        };
+
+        let response = app.clone().oneshot(request("unknown")).await.unwrap();
+        assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+
+        let response = app.oneshot(request("known")).await.unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        let body = hyper::body::to_bytes(response.into_body()).await.unwrap();
+        let actual: ApiResponse<TokenPayload> = serde_json::from_slice(&body).unwrap();
+        let payload = actual.data.unwrap();
+        assert_eq!(user_id, jwt::verify(&payload.access_token).unwrap().sub);
+    }
+
+    #[tokio::test]
+    async fn test_oidc_login_redirects_and_issues_tokens() {
+        let user_id = Uuid::new_v4();
+        let mut oidc_svc = MockOidcService::new();
+        oidc_svc
+            .expect_start()
+            .withf(|provider| provider == "corp")
+            .returning(|_| Ok("https://sso.example.com/authorize?state=abc".to_string()));
+        oidc_svc
+            .expect_callback()
+            .withf(|provider, code, state| provider == "corp" && code == "code" && state == "abc")
+            .times(1)
+            .returning(move |_, _, _| {
+                Ok(User {
+                    id: user_id,
+                    ..Default::default()
+                })
+            });
+        let mut totp_svc = MockTotpService::new();
+        totp_svc.expect_challenge().returning(|_| Ok(None));
+        let mut token_svc = MockTokenService::new();
+        token_svc.expect_issue().returning(|user_id| {
+            Ok(RefreshGrant {
+                user_id,
+                family_id: Uuid::new_v4(),
+                token: "issued-refresh-token".to_string(),
+            })
+        });
+
+        std::env::set_var("JWT_SECRET", "example_secret_key");
+        let app = configure(
+            Arc::new(token_svc),
+            Arc::new(MockVerificationService::new()),
+            Arc::new(MockPasswordResetService::new()),
+            Arc::new(totp_svc),
+            Arc::new(MockPasskeyService::new()),
+            Arc::new(oidc_svc),
+        );
+        let get = |uri: &str| Request::builder().uri(uri).body(Body::empty()).unwrap();
+
+        let response = app.clone().oneshot(get("/oidc/corp/start")).await.unwrap();
+        assert_eq!(response.status(), StatusCode::SEE_OTHER);
+        assert_eq!(
+            "https://sso.example.com/authorize?state=abc",
+            response.headers()[http::header::LOCATION]
+        );
+
+        let response = app
+            .clone()
+            .oneshot(get("/oidc/corp/callback?error=access_denied&state=abc"))
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+
+        let response = app
+            .oneshot(get("/oidc/corp/callback?code=code&state=abc"))
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        let body = hyper::body::to_bytes(response.into_body()).await.unwrap();
+        let actual: ApiResponse<TokenPayload> = serde_json::from_slice(&body).unwrap();
+        let payload = actual.data.unwrap();
+        assert_eq!(user_id, jwt::verify(&payload.access_token).unwrap().sub);
+    }
 }
@@ -0,0 +1,224 @@
+use super::{ApiResponse, Editor, LedgerAccess, Viewer};
+use crate::{
+    dto::validate_payload,
+    errors::{ApiResult, Error},
+    services::{
+        budget::{
+            AsOfInput, Budget, BudgetServiceImpl, BudgetStatus, CreateBudgetInput,
+            DynBudgetService, UpdateBudgetInput,
+        },
+        ledger::{DynLedgerService, LedgerServiceImpl},
+    },
+};
+use axum::{
+    extract::{Extension, Path, Query},
+    routing::{get, post},
+    AddExtensionLayer, Json, Router,
+};
+use sqlx::postgres::PgPool;
+use std::sync::Arc;
+use uuid::Uuid;
+
+pub(crate) fn router(pool: Arc<PgPool>) -> Router {
+    let budget_svc: DynBudgetService = Arc::new(BudgetServiceImpl::new(pool.clone()));
+    let ledger_svc: DynLedgerService = Arc::new(LedgerServiceImpl::new(pool));
+    configure(budget_svc, ledger_svc)
+}
+
+fn configure(budget_svc: DynBudgetService, ledger_svc: DynLedgerService) -> Router {
+    Router::new()
+        .route("/:ledger_id/budgets", post(create_budget).get(list_budget))
+        .route(
+            "/:ledger_id/budgets/:budget_id",
+            get(get_budget).put(update_budget).delete(delete_budget),
+        )
+        .route("/:ledger_id/budgets/:budget_id/status", get(budget_status))
+        .layer(&AddExtensionLayer::new(budget_svc))
+        .layer(&AddExtensionLayer::new(ledger_svc))
+}
+
+async fn create_budget(
+    _: LedgerAccess<Editor>,
+    Extension(svc): Extension<DynBudgetService>,
+    Path(ledger_id): Path<Uuid>,
+    Json(input): Json<CreateBudgetInput>,
+) -> ApiResult<ApiResponse<Budget>> {
+    if !input.check() {
+        return Err(Error::new_empty_fields_error(
+            "budget needs exactly one of category_id and account_id".to_string(),
+        )
+        .into());
+    }
+    validate_payload(&input)?;
+    Ok(ApiResponse::success(svc.create(ledger_id, input).await?))
+}
+
+async fn get_budget(
+    _: LedgerAccess<Viewer>,
+    Extension(svc): Extension<DynBudgetService>,
+    Path((ledger_id, id)): Path<(Uuid, Uuid)>,
+) -> ApiResult<ApiResponse<Budget>> {
+    Ok(ApiResponse::success(svc.get(ledger_id, id).await?))
+}
+
+async fn update_budget(
+    _: LedgerAccess<Editor>,
+    Extension(svc): Extension<DynBudgetService>,
+    Path((ledger_id, id)): Path<(Uuid, Uuid)>,
+    Json(input): Json<UpdateBudgetInput>,
+) -> ApiResult<ApiResponse<Budget>> {
+    if !input.check() {
+        return Err(Error::new_empty_fields_error("update budget failed".to_string()).into());
+    }
+    validate_payload(&input)?;
+    Ok(ApiResponse::success(
+        svc.update(ledger_id, id, input).await?,
+    ))
+}
+
+async fn delete_budget(
+    _: LedgerAccess<Editor>,
+    Extension(svc): Extension<DynBudgetService>,
+    Path((ledger_id, id)): Path<(Uuid, Uuid)>,
+) -> ApiResult<ApiResponse<Budget>> {
+    Ok(ApiResponse::success(svc.delete(ledger_id, id).await?))
+}
+
+async fn list_budget(
+    _: LedgerAccess<Viewer>,
+    Extension(svc): Extension<DynBudgetService>,
+    Path(ledger_id): Path<Uuid>,
+) -> ApiResult<ApiResponse<Vec<Budget>>> {
+    Ok(ApiResponse::success(svc.list(ledger_id).await?))
+}
+
+async fn budget_status(
+    _: LedgerAccess<Viewer>,
+    Extension(svc): Extension<DynBudgetService>,
+    Path((ledger_id, id)): Path<(Uuid, Uuid)>,
+    Query(input): Query<AsOfInput>,
+) -> ApiResult<ApiResponse<BudgetStatus>> {
+    validate_payload(&input)?;
+    Ok(ApiResponse::success(
+        svc.status(ledger_id, id, input).await?,
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        models::{budget::PeriodStatus, ledger::Ledger, member::LedgerRole, user::User},
+        routers::jwt,
+        services::{
+            auth::{DynAuthService, MockAuthService},
+            budget::MockBudgetService,
+            ledger::MockLedgerService,
+        },
+    };
+    use axum::{
+        body::Body,
+        http::{self, request::Request, StatusCode},
+    };
+    use chrono::NaiveDate;
+    use std::sync::Arc;
+    use tower::ServiceExt;
+
+    fn configure_with_user(budget_svc: MockBudgetService, user_id: Uuid) -> Router {
+        let mut auth_svc = MockAuthService::new();
+        auth_svc.expect_authenticate().returning(move |_, _, _| {
+            Ok(User {
+                id: user_id,
+                ..Default::default()
+            })
+        });
+        let mut ledger_svc = MockLedgerService::new();
+        ledger_svc.expect_access().returning(|user_id, id| {
+            let ledger = Ledger {
+                id,
+                owner_id: Some(user_id),
+                ..Default::default()
+            };
+            Ok((ledger, LedgerRole::Owner))
+        });
+        let auth_svc: DynAuthService = Arc::new(auth_svc);
+        configure(Arc::new(budget_svc), Arc::new(ledger_svc))
+            .layer(&AddExtensionLayer::new(auth_svc))
+    }
+
+    #[tokio::test]
+    async fn test_budget_controller_status() {
+        let mut svc = MockBudgetService::new();
+        svc.expect_status().returning(|_, id, input| {
+            let start = NaiveDate::from_ymd(2022, 3, 1);
+            assert_eq!(Some(NaiveDate::from_ymd(2022, 3, 20)), input.as_of);
+            Ok(BudgetStatus {
+                budget_id: id,
+                current: PeriodStatus::new(start, NaiveDate::from_ymd(2022, 3, 31), 3000, 1000),
+                previous: None,
+            })
+        });
+
+        std::env::set_var("JWT_SECRET", "example_secret_key");
+        let user_id = Uuid::new_v4();
+        let app = configure_with_user(svc, user_id);
+
+        let response = app
+            .oneshot(
+                Request::builder()
+                    .method(http::Method::GET)
+                    .uri(format!(
+                        "/{}/budgets/{}/status?as_of=2022-03-20",
+                        Uuid::new_v4(),
+                        Uuid::new_v4()
+                    ))
+                    .header(
+                        http::header::AUTHORIZATION,
+                        format!("Bearer {}", jwt::sign(user_id).unwrap()),
+                    )
+                    .body(Body::empty())
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+
+        assert_eq!(response.status(), StatusCode::OK);
+        let body = hyper::body::to_bytes(response.into_body()).await.unwrap();
+        let value: serde_json::Value = serde_json::from_slice(&body).unwrap();
+        assert_eq!("33.33", value["data"]["current"]["percentage"]);
+        assert_eq!(2000, value["data"]["current"]["remaining"]);
+    }
+
+    #[tokio::test]
+    async fn test_budget_controller_create_with_two_targets() {
+        let mut svc = MockBudgetService::new();
+        svc.expect_create().never();
+
+        std::env::set_var("JWT_SECRET", "example_secret_key");
+        let user_id = Uuid::new_v4();
+        let app = configure_with_user(svc, user_id);
+
+        let body = format!(
+            r#"{{"name":"food","category_id":"{}","account_id":"{}","period":"monthly","amount":1000}}"#,
+            Uuid::new_v4(),
+            Uuid::new_v4()
+        );
+        let response = app
+            .oneshot(
+                Request::builder()
+                    .method(http::Method::POST)
+                    .uri(format!("/{}/budgets", Uuid::new_v4()))
+                    .header(http::header::CONTENT_TYPE, mime::APPLICATION_JSON.as_ref())
+                    .header(
+                        http::header::AUTHORIZATION,
+                        format!("Bearer {}", jwt::sign(user_id).unwrap()),
+                    )
+                    .body(Body::from(body))
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+    }
+}
@@ -0,0 +1,208 @@
+use super::{ApiResponse, Editor, LedgerAccess, Viewer};
+use crate::{
+    dto::validate_payload,
+    errors::{ApiResult, Error},
+    services::{
+        categorization::{
+            CategorizationRule, CategorizationServiceImpl, Category, CreateCategoryInput,
+            CreateRuleInput, DynCategorizationService,
+        },
+        ledger::{DynLedgerService, LedgerServiceImpl},
+    },
+};
+use axum::{
+    extract::{Extension, Path},
+    routing::{delete, post},
+    AddExtensionLayer, Json, Router,
+};
+use sqlx::postgres::PgPool;
+use std::sync::Arc;
+use uuid::Uuid;
+
+pub(crate) fn router(pool: Arc<PgPool>) -> Router {
+    let categorization_svc: DynCategorizationService =
+        Arc::new(CategorizationServiceImpl::new(pool.clone()));
+    let ledger_svc: DynLedgerService = Arc::new(LedgerServiceImpl::new(pool));
+    configure(categorization_svc, ledger_svc)
+}
+
+fn configure(categorization_svc: DynCategorizationService, ledger_svc: DynLedgerService) -> Router {
+    Router::new()
+        .route(
+            "/:ledger_id/categories",
+            post(create_category).get(list_category),
+        )
+        .route(
+            "/:ledger_id/categories/:category_id",
+            delete(delete_category),
+        )
+        .route("/:ledger_id/rules", post(create_rule).get(list_rule))
+        .route("/:ledger_id/rules/:rule_id", delete(delete_rule))
+        .layer(&AddExtensionLayer::new(categorization_svc))
+        .layer(&AddExtensionLayer::new(ledger_svc))
+}
+
+async fn create_category(
+    _: LedgerAccess<Editor>,
+    Extension(svc): Extension<DynCategorizationService>,
+    Path(ledger_id): Path<Uuid>,
+    Json(input): Json<CreateCategoryInput>,
+) -> ApiResult<ApiResponse<Category>> {
+    validate_payload(&input)?;
+    Ok(ApiResponse::success(
+        svc.create_category(ledger_id, input).await?,
+    ))
+}
+
+async fn list_category(
+    _: LedgerAccess<Viewer>,
+    Extension(svc): Extension<DynCategorizationService>,
+    Path(ledger_id): Path<Uuid>,
+) -> ApiResult<ApiResponse<Vec<Category>>> {
+    Ok(ApiResponse::success(svc.list_categories(ledger_id).await?))
+}
+
+async fn delete_category(
+    _: LedgerAccess<Editor>,
+    Extension(svc): Extension<DynCategorizationService>,
+    Path((ledger_id, id)): Path<(Uuid, Uuid)>,
+) -> ApiResult<ApiResponse<Category>> {
+    Ok(ApiResponse::success(
+        svc.delete_category(ledger_id, id).await?,
+    ))
+}
+
+async fn create_rule(
+    _: LedgerAccess<Editor>,
+    Extension(svc): Extension<DynCategorizationService>,
+    Path(ledger_id): Path<Uuid>,
+    Json(input): Json<CreateRuleInput>,
+) -> ApiResult<ApiResponse<CategorizationRule>> {
+    if !input.check() {
+        return Err(Error::new_empty_fields_error("create rule failed".to_string()).into());
+    }
+    validate_payload(&input)?;
+    Ok(ApiResponse::success(
+        svc.create_rule(ledger_id, input).await?,
+    ))
+}
+
+async fn list_rule(
+    _: LedgerAccess<Viewer>,
+    Extension(svc): Extension<DynCategorizationService>,
+    Path(ledger_id): Path<Uuid>,
+) -> ApiResult<ApiResponse<Vec<CategorizationRule>>> {
+    Ok(ApiResponse::success(svc.list_rules(ledger_id).await?))
+}
+
+async fn delete_rule(
+    _: LedgerAccess<Editor>,
+    Extension(svc): Extension<DynCategorizationService>,
+    Path((ledger_id, id)): Path<(Uuid, Uuid)>,
+) -> ApiResult<ApiResponse<CategorizationRule>> {
+    Ok(ApiResponse::success(svc.delete_rule(ledger_id, id).await?))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        models::{ledger::Ledger, member::LedgerRole, user::User},
+        routers::jwt,
+        services::{
+            auth::{DynAuthService, MockAuthService},
+            categorization::MockCategorizationService,
+            ledger::MockLedgerService,
+        },
+    };
+    use axum::{
+        body::Body,
+        http::{self, request::Request, StatusCode},
+    };
+    use std::sync::Arc;
+    use tower::ServiceExt;
+
+    fn configure_with_user(categorization_svc: MockCategorizationService, user_id: Uuid) -> Router {
+        let mut auth_svc = MockAuthService::new();
+        auth_svc.expect_authenticate().returning(move |_, _, _| {
+            Ok(User {
+                id: user_id,
+                ..Default::default()
+            })
+        });
+        let mut ledger_svc = MockLedgerService::new();
+        ledger_svc.expect_access().returning(|user_id, id| {
+            let ledger = Ledger {
+                id,
+                owner_id: Some(user_id),
+                ..Default::default()
+            };
+            Ok((ledger, LedgerRole::Owner))
+        });
+        let auth_svc: DynAuthService = Arc::new(auth_svc);
+        configure(Arc::new(categorization_svc), Arc::new(ledger_svc))
+            .layer(&AddExtensionLayer::new(auth_svc))
+    }
+
+    fn create_rule_request(user_id: Uuid, body: &'static str) -> Request<Body> {
+        Request::builder()
+            .method(http::Method::POST)
+            .uri(format!("/{}/rules", Uuid::new_v4()))
+            .header(http::header::CONTENT_TYPE, mime::APPLICATION_JSON.as_ref())
+            .header(
+                http::header::AUTHORIZATION,
+                format!("Bearer {}", jwt::sign(user_id).unwrap()),
+            )
+            .body(Body::from(body))
+            .unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_categorization_controller_create_rule() {
+        let mut svc = MockCategorizationService::new();
+        svc.expect_create_rule().returning(|ledger_id, input| {
+            Ok(CategorizationRule {
+                ledger_id,
+                pattern: input.pattern,
+                tag: input.tag,
+                ..Default::default()
+            })
+        });
+
+        std::env::set_var("JWT_SECRET", "example_secret_key");
+        let user_id = Uuid::new_v4();
+        let app = configure_with_user(svc, user_id);
+
+        let response = app
+            .oneshot(create_rule_request(
+                user_id,
+                r#"{"pattern":"coffee","tag":"daily"}"#,
+            ))
+            .await
+            .unwrap();
+
+        assert_eq!(response.status(), StatusCode::OK);
+        let body = hyper::body::to_bytes(response.into_body()).await.unwrap();
+        let rule: ApiResponse<CategorizationRule> = serde_json::from_slice(&body).unwrap();
+        let rule = rule.data.unwrap();
+        assert_eq!("coffee", rule.pattern);
+        assert_eq!(0, rule.priority);
+    }
+
+    #[tokio::test]
+    async fn test_categorization_controller_create_rule_without_action() {
+        let mut svc = MockCategorizationService::new();
+        svc.expect_create_rule().never();
+
+        std::env::set_var("JWT_SECRET", "example_secret_key");
+        let user_id = Uuid::new_v4();
+        let app = configure_with_user(svc, user_id);
+
+        let response = app
+            .oneshot(create_rule_request(user_id, r#"{"pattern":"coffee"}"#))
+            .await
+            .unwrap();
+
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+    }
+}
@@ -0,0 +1,239 @@
+use super::{ApiResponse, Permitted, WriteRates};
+use crate::{
+    dto::validate_payload,
+    errors::ApiResult,
+    models::user::User,
+    services::{
+        currency::{
+            Currency, CurrencyServiceImpl, DynCurrencyService, ExchangeRate, ListExchangeRateInput,
+            UpsertExchangeRateInput,
+        },
+        rbac::{DynRbacService, RbacServiceImpl},
+    },
+};
+use axum::{
+    extract::{Extension, Query},
+    routing::get,
+    AddExtensionLayer, Json, Router,
+};
+use sqlx::postgres::PgPool;
+use std::sync::Arc;
+
+pub(crate) fn router(pool: Arc<PgPool>) -> Router {
+    let currency_svc: DynCurrencyService = Arc::new(CurrencyServiceImpl::new(pool.clone()));
+    let rbac_svc: DynRbacService = Arc::new(RbacServiceImpl::new(pool));
+    configure(currency_svc, rbac_svc)
+}
+
+fn configure(currency_svc: DynCurrencyService, rbac_svc: DynRbacService) -> Router {
+    Router::new()
+        .route("/", get(list_currency))
+        .route("/rates", get(list_rate).put(upsert_rate))
+        .layer(&AddExtensionLayer::new(currency_svc))
+        .layer(&AddExtensionLayer::new(rbac_svc))
+}
+
+async fn list_currency(
+    _user: User,
+    Extension(svc): Extension<DynCurrencyService>,
+) -> ApiResult<ApiResponse<Vec<Currency>>> {
+    Ok(ApiResponse::success(svc.list().await?))
+}
+
+/// 汇率为所有账本共用, 写入需要 rates:write 权限
+async fn upsert_rate(
+    _permitted: Permitted<WriteRates>,
+    Extension(svc): Extension<DynCurrencyService>,
+    Json(input): Json<UpsertExchangeRateInput>,
+) -> ApiResult<ApiResponse<ExchangeRate>> {
+    validate_payload(&input)?;
+    Ok(ApiResponse::success(svc.upsert_rate(input).await?))
+}
+
+async fn list_rate(
+    _user: User,
+    Extension(svc): Extension<DynCurrencyService>,
+    Query(mut input): Query<ListExchangeRateInput>,
+) -> ApiResult<ApiResponse<Vec<ExchangeRate>>> {
+    input.limit_offset.check();
+    validate_payload(&input)?;
+    Ok(ApiResponse::success(svc.list_rates(input).await?))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        models::rbac::Permission,
+        routers::jwt,
+        services::{
+            auth::{DynAuthService, MockAuthService},
+            currency::MockCurrencyService,
+            rbac::MockRbacService,
+        },
+    };
+    use axum::{
+        body::Body,
+        http::{self, request::Request, StatusCode},
+    };
+    use chrono::Utc;
+    use rust_decimal::Decimal;
+    use std::{str::FromStr, sync::Arc};
+    use tower::ServiceExt;
+    use uuid::Uuid;
+
+    fn configure_with_user(currency_svc: MockCurrencyService) -> Router {
+        configure_with_permissions(currency_svc, &[Permission::RatesWrite])
+    }
+
+    fn configure_with_permissions(
+        currency_svc: MockCurrencyService,
+        permissions: &'static [Permission],
+    ) -> Router {
+        let mut auth_svc = MockAuthService::new();
+        auth_svc.expect_authenticate().returning(|id, _, _| {
+            Ok(User {
+                id,
+                ..Default::default()
+            })
+        });
+        let mut rbac_svc = MockRbacService::new();
+        rbac_svc
+            .expect_permissions()
+            .returning(move |_| Ok(permissions.iter().copied().collect()));
+        let auth_svc: DynAuthService = Arc::new(auth_svc);
+        configure(Arc::new(currency_svc), Arc::new(rbac_svc))
+            .layer(&AddExtensionLayer::new(auth_svc))
+    }
+
+    #[tokio::test]
+    async fn test_currency_controller_upsert_rate_keeps_precision() {
+        let mut svc = MockCurrencyService::new();
+        svc.expect_upsert_rate().returning(|input| {
+            Ok(ExchangeRate {
+                id: Uuid::new_v4(),
+                base: input.base,
+                quote: input.quote,
+                rate: input.rate,
+                effective_date: input.effective_date,
+                created_at: Utc::now(),
+                updated_at: Utc::now(),
+            })
+        });
+
+        std::env::set_var("JWT_SECRET", "example_secret_key");
+        let app = configure_with_user(svc);
+
+        let response = app
+            .oneshot(
+                Request::builder()
+                    .method(http::Method::PUT)
+                    .uri("/rates")
+                    .header(http::header::CONTENT_TYPE, mime::APPLICATION_JSON.as_ref())
+                    .header(
+                        http::header::AUTHORIZATION,
+                        format!("Bearer {}", jwt::sign(Uuid::new_v4()).unwrap()),
+                    )
+                    .body(Body::from(
+                        r#"{"base":"USD","quote":"CNY","rate":"6.31720000000000000001","effective_date":"2022-03-01"}"#,
+                    ))
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+
+        assert_eq!(response.status(), StatusCode::OK);
+        let body = hyper::body::to_bytes(response.into_body()).await.unwrap();
+        let value: serde_json::Value = serde_json::from_slice(&body).unwrap();
+        assert_eq!("6.31720000000000000001", value["data"]["rate"]);
+
+        let rate: ApiResponse<ExchangeRate> = serde_json::from_slice(&body).unwrap();
+        assert_eq!(
+            Decimal::from_str("6.31720000000000000001").unwrap(),
+            rate.data.unwrap().rate
+        );
+
+        // 数字字面量会经过 f64 解析, 直接拒绝
+        let mut svc = MockCurrencyService::new();
+        svc.expect_upsert_rate().never();
+        let app = configure_with_user(svc);
+
+        let response = app
+            .oneshot(
+                Request::builder()
+                    .method(http::Method::PUT)
+                    .uri("/rates")
+                    .header(http::header::CONTENT_TYPE, mime::APPLICATION_JSON.as_ref())
+                    .header(
+                        http::header::AUTHORIZATION,
+                        format!("Bearer {}", jwt::sign(Uuid::new_v4()).unwrap()),
+                    )
+                    .body(Body::from(
+                        r#"{"base":"USD","quote":"CNY","rate":6.31720000000000000001,"effective_date":"2022-03-01"}"#,
+                    ))
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+    }
+
+    #[tokio::test]
+    async fn test_currency_controller_upsert_rate_negative() {
+        let mut svc = MockCurrencyService::new();
+        svc.expect_upsert_rate().never();
+
+        std::env::set_var("JWT_SECRET", "example_secret_key");
+        let app = configure_with_user(svc);
+
+        let response = app
+            .oneshot(
+                Request::builder()
+                    .method(http::Method::PUT)
+                    .uri("/rates")
+                    .header(http::header::CONTENT_TYPE, mime::APPLICATION_JSON.as_ref())
+                    .header(
+                        http::header::AUTHORIZATION,
+                        format!("Bearer {}", jwt::sign(Uuid::new_v4()).unwrap()),
+                    )
+                    .body(Body::from(
+                        r#"{"base":"USD","quote":"CNY","rate":"-1","effective_date":"2022-03-01"}"#,
+                    ))
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+    }
+
+    #[tokio::test]
+    async fn test_currency_controller_upsert_rate_requires_permission() {
+        let mut svc = MockCurrencyService::new();
+        svc.expect_upsert_rate().never();
+
+        std::env::set_var("JWT_SECRET", "example_secret_key");
+        let app = configure_with_permissions(svc, &[]);
+
+        let response = app
+            .oneshot(
+                Request::builder()
+                    .method(http::Method::PUT)
+                    .uri("/rates")
+                    .header(http::header::CONTENT_TYPE, mime::APPLICATION_JSON.as_ref())
+                    .header(
+                        http::header::AUTHORIZATION,
+                        format!("Bearer {}", jwt::sign(Uuid::new_v4()).unwrap()),
+                    )
+                    .body(Body::from(
+                        r#"{"base":"USD","quote":"CNY","rate":"6.3172","effective_date":"2022-03-01"}"#,
+                    ))
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+
+        assert_eq!(response.status(), StatusCode::FORBIDDEN);
+    }
+}
@@ -0,0 +1,221 @@
+use super::{ApiResponse, Editor, LedgerAccess, Viewer};
+use crate::{
+    dto::validate_payload,
+    errors::ApiResult,
+    services::{
+        entry::{
+            CreateEntryInput, DynEntryService, Entry, EntryDetail, EntryServiceImpl, ListEntryInput,
+        },
+        ledger::{DynLedgerService, LedgerServiceImpl},
+    },
+};
+use axum::{
+    extract::{Extension, Path, Query},
+    routing::{get, post},
+    AddExtensionLayer, Json, Router,
+};
+use sqlx::postgres::PgPool;
+use std::sync::Arc;
+use uuid::Uuid;
+
+pub(crate) fn router(pool: Arc<PgPool>) -> Router {
+    let entry_svc: DynEntryService = Arc::new(EntryServiceImpl::new(pool.clone()));
+    let ledger_svc: DynLedgerService = Arc::new(LedgerServiceImpl::new(pool));
+    configure(entry_svc, ledger_svc)
+}
+
+fn configure(entry_svc: DynEntryService, ledger_svc: DynLedgerService) -> Router {
+    Router::new()
+        .route("/:ledger_id/entries", post(create_entry).get(list_entry))
+        .route(
+            "/:ledger_id/entries/:entry_id",
+            get(get_entry).delete(delete_entry),
+        )
+        .layer(&AddExtensionLayer::new(entry_svc))
+        .layer(&AddExtensionLayer::new(ledger_svc))
+}
+
+async fn create_entry(
+    access: LedgerAccess<Editor>,
+    Extension(svc): Extension<DynEntryService>,
+    Json(input): Json<CreateEntryInput>,
+) -> ApiResult<ApiResponse<EntryDetail>> {
+    let ledger = access.ledger;
+    validate_payload(&input)?;
+    Ok(ApiResponse::success(svc.create(&ledger, input).await?))
+}
+
+async fn get_entry(
+    _: LedgerAccess<Viewer>,
+    Extension(svc): Extension<DynEntryService>,
+    Path((ledger_id, id)): Path<(Uuid, Uuid)>,
+) -> ApiResult<ApiResponse<EntryDetail>> {
+    Ok(ApiResponse::success(svc.get(ledger_id, id).await?))
+}
+
+async fn delete_entry(
+    _: LedgerAccess<Editor>,
+    Extension(svc): Extension<DynEntryService>,
+    Path((ledger_id, id)): Path<(Uuid, Uuid)>,
+) -> ApiResult<ApiResponse<Entry>> {
+    Ok(ApiResponse::success(svc.delete(ledger_id, id).await?))
+}
+
+async fn list_entry(
+    _: LedgerAccess<Viewer>,
+    Extension(svc): Extension<DynEntryService>,
+    Path(ledger_id): Path<Uuid>,
+    Query(mut input): Query<ListEntryInput>,
+) -> ApiResult<ApiResponse<Vec<EntryDetail>>> {
+    input.limit_offset.check();
+    validate_payload(&input)?;
+    Ok(ApiResponse::success(svc.list(ledger_id, input).await?))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        dto::entry::PostingInput,
+        errors::Error,
+        models::{entry::Side, ledger::Ledger, member::LedgerRole, user::User},
+        routers::jwt,
+        services::{
+            auth::{DynAuthService, MockAuthService},
+            entry::MockEntryService,
+            ledger::MockLedgerService,
+        },
+    };
+    use axum::{
+        body::Body,
+        http::{self, request::Request, StatusCode},
+    };
+    use chrono::NaiveDate;
+    use std::sync::Arc;
+    use tower::ServiceExt;
+
+    fn configure_with_user(entry_svc: MockEntryService, user_id: Uuid) -> Router {
+        let mut auth_svc = MockAuthService::new();
+        auth_svc.expect_authenticate().returning(move |_, _, _| {
+            Ok(User {
+                id: user_id,
+                ..Default::default()
+            })
+        });
+        let mut ledger_svc = MockLedgerService::new();
+        ledger_svc.expect_access().returning(|user_id, id| {
+            let ledger = Ledger {
+                id,
+                owner_id: Some(user_id),
+                ..Default::default()
+            };
+            Ok((ledger, LedgerRole::Owner))
+        });
+        let auth_svc: DynAuthService = Arc::new(auth_svc);
+        configure(Arc::new(entry_svc), Arc::new(ledger_svc))
+            .layer(&AddExtensionLayer::new(auth_svc))
+    }
+
+    fn create_entry_request(user_id: Uuid, input: &CreateEntryInput) -> Request<Body> {
+        Request::builder()
+            .method(http::Method::POST)
+            .uri(format!("/{}/entries", Uuid::new_v4()))
+            .header(http::header::CONTENT_TYPE, mime::APPLICATION_JSON.as_ref())
+            .header(
+                http::header::AUTHORIZATION,
+                format!("Bearer {}", jwt::sign(user_id).unwrap()),
+            )
+            .body(Body::from(serde_json::to_string(input).unwrap()))
+            .unwrap()
+    }
+
+    fn posting(amount: i64, side: Side) -> PostingInput {
+        PostingInput {
+            account_id: Uuid::new_v4(),
+            amount,
+            currency: None,
+            side,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_entry_controller_create() {
+        let mut svc = MockEntryService::new();
+        svc.expect_create().returning(|ledger, _| {
+            Ok(EntryDetail {
+                entry: Entry {
+                    ledger_id: ledger.id,
+                    ..Default::default()
+                },
+                ..Default::default()
+            })
+        });
+
+        std::env::set_var("JWT_SECRET", "example_secret_key");
+        let user_id = Uuid::new_v4();
+        let app = configure_with_user(svc, user_id);
+
+        let input = CreateEntryInput {
+            date: NaiveDate::from_ymd(2022, 3, 1),
+            description: "salary".to_string(),
+            category_id: None,
+            tags: Vec::new(),
+            postings: vec![posting(100, Side::Debit), posting(100, Side::Credit)],
+        };
+        let response = app
+            .oneshot(create_entry_request(user_id, &input))
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+    }
+
+    #[tokio::test]
+    async fn test_entry_controller_create_unbalanced() {
+        let mut svc = MockEntryService::new();
+        svc.expect_create().returning(|_, _| {
+            Err(Error::UnbalancedEntry(
+                "debits 100 != credits 90".to_string(),
+            ))
+        });
+
+        std::env::set_var("JWT_SECRET", "example_secret_key");
+        let user_id = Uuid::new_v4();
+        let app = configure_with_user(svc, user_id);
+
+        let input = CreateEntryInput {
+            date: NaiveDate::from_ymd(2022, 3, 1),
+            description: "salary".to_string(),
+            category_id: None,
+            tags: Vec::new(),
+            postings: vec![posting(100, Side::Debit), posting(90, Side::Credit)],
+        };
+        let response = app
+            .oneshot(create_entry_request(user_id, &input))
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+    }
+
+    #[tokio::test]
+    async fn test_entry_controller_create_single_posting() {
+        let mut svc = MockEntryService::new();
+        svc.expect_create().never();
+
+        std::env::set_var("JWT_SECRET", "example_secret_key");
+        let user_id = Uuid::new_v4();
+        let app = configure_with_user(svc, user_id);
+
+        let input = CreateEntryInput {
+            date: NaiveDate::from_ymd(2022, 3, 1),
+            description: "salary".to_string(),
+            category_id: None,
+            tags: Vec::new(),
+            postings: vec![posting(100, Side::Debit)],
+        };
+        let response = app
+            .oneshot(create_entry_request(user_id, &input))
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+    }
+}
@@ -0,0 +1,172 @@
+use super::{LedgerAccess, Viewer};
+use crate::{
+    errors::ApiResult,
+    services::{
+        export::{DynExportService, ExportInput, ExportServiceImpl},
+        ledger::{DynLedgerService, LedgerServiceImpl},
+    },
+};
+use axum::{
+    body::StreamBody,
+    extract::{Extension, Query},
+    http::{header, HeaderMap, HeaderValue},
+    response::{IntoResponse, Response},
+    routing::get,
+    AddExtensionLayer, Router,
+};
+use sqlx::postgres::PgPool;
+use std::sync::Arc;
+
+pub(crate) fn router(pool: Arc<PgPool>) -> Router {
+    let export_svc: DynExportService = Arc::new(ExportServiceImpl::new(pool.clone()));
+    let ledger_svc: DynLedgerService = Arc::new(LedgerServiceImpl::new(pool));
+    configure(export_svc, ledger_svc)
+}
+
+fn configure(export_svc: DynExportService, ledger_svc: DynLedgerService) -> Router {
+    Router::new()
+        .route("/:ledger_id/export", get(export_ledger))
+        .layer(&AddExtensionLayer::new(export_svc))
+        .layer(&AddExtensionLayer::new(ledger_svc))
+}
+
+// 响应体为流, 导出过程中出错时只能中断连接
+async fn export_ledger(
+    access: LedgerAccess<Viewer>,
+    Extension(svc): Extension<DynExportService>,
+    Query(input): Query<ExportInput>,
+) -> ApiResult<Response> {
+    let ledger = access.ledger;
+
+    let mut headers = HeaderMap::new();
+    headers.insert(
+        header::CONTENT_TYPE,
+        HeaderValue::from_static(input.format.content_type()),
+    );
+    let disposition = format!(
+        "attachment; filename=\"{}.{}\"",
+        ledger.id,
+        input.format.extension()
+    );
+    if let Ok(value) = HeaderValue::from_str(&disposition) {
+        headers.insert(header::CONTENT_DISPOSITION, value);
+    }
+
+    let body = StreamBody::new(svc.export(&ledger, input.format));
+    Ok((headers, body).into_response())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        models::{export::ExportFormat, ledger::Ledger, member::LedgerRole, user::User},
+        routers::jwt,
+        services::{
+            auth::{DynAuthService, MockAuthService},
+            export::MockExportService,
+            ledger::MockLedgerService,
+        },
+    };
+    use axum::{
+        body::Body,
+        http::{self, request::Request, StatusCode},
+    };
+    use futures::{stream, StreamExt};
+    use std::sync::Arc;
+    use tower::ServiceExt;
+    use uuid::Uuid;
+
+    fn configure_with_user(export_svc: MockExportService, user_id: Uuid) -> Router {
+        let mut auth_svc = MockAuthService::new();
+        auth_svc.expect_authenticate().returning(move |_, _, _| {
+            Ok(User {
+                id: user_id,
+                ..Default::default()
+            })
+        });
+        let mut ledger_svc = MockLedgerService::new();
+        ledger_svc.expect_access().returning(|user_id, id| {
+            let ledger = Ledger {
+                id,
+                owner_id: Some(user_id),
+                ..Default::default()
+            };
+            Ok((ledger, LedgerRole::Owner))
+        });
+        let auth_svc: DynAuthService = Arc::new(auth_svc);
+        configure(Arc::new(export_svc), Arc::new(ledger_svc))
+            .layer(&AddExtensionLayer::new(auth_svc))
+    }
+
+    #[tokio::test]
+    async fn test_export_controller_streams_body() {
+        let mut svc = MockExportService::new();
+        svc.expect_export().returning(|_, format| {
+            assert_eq!(ExportFormat::Beancount, format);
+            stream::iter(vec![
+                Ok("option \"operating_currency\" \"CNY\"\n".to_string()),
+                Ok("2022-01-01 open Assets:Bank\n".to_string()),
+            ])
+            .boxed()
+        });
+
+        std::env::set_var("JWT_SECRET", "example_secret_key");
+        let user_id = Uuid::new_v4();
+        let app = configure_with_user(svc, user_id);
+
+        let ledger_id = Uuid::new_v4();
+        let response = app
+            .oneshot(
+                Request::builder()
+                    .method(http::Method::GET)
+                    .uri(format!("/{}/export?format=beancount", ledger_id))
+                    .header(
+                        http::header::AUTHORIZATION,
+                        format!("Bearer {}", jwt::sign(user_id).unwrap()),
+                    )
+                    .body(Body::empty())
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+
+        assert_eq!(response.status(), StatusCode::OK);
+        assert_eq!(
+            format!("attachment; filename=\"{ledger_id}.beancount\""),
+            response.headers()[header::CONTENT_DISPOSITION]
+        );
+        let body = hyper::body::to_bytes(response.into_body()).await.unwrap();
+        assert_eq!(
+            "option \"operating_currency\" \"CNY\"\n2022-01-01 open Assets:Bank\n",
+            body
+        );
+    }
+
+    #[tokio::test]
+    async fn test_export_controller_unknown_format() {
+        let mut svc = MockExportService::new();
+        svc.expect_export().never();
+
+        std::env::set_var("JWT_SECRET", "example_secret_key");
+        let user_id = Uuid::new_v4();
+        let app = configure_with_user(svc, user_id);
+
+        let response = app
+            .oneshot(
+                Request::builder()
+                    .method(http::Method::GET)
+                    .uri(format!("/{}/export?format=xlsx", Uuid::new_v4()))
+                    .header(
+                        http::header::AUTHORIZATION,
+                        format!("Bearer {}", jwt::sign(user_id).unwrap()),
+                    )
+                    .body(Body::empty())
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+    }
+}
@@ -0,0 +1,342 @@
+use super::{ApiResponse, Editor, LedgerAccess};
+use crate::{
+    dto::validate_payload,
+    errors::{ApiResult, Error},
+    models::rbac::Permission,
+    services::{
+        beancount::{BeancountReport, BeancountServiceImpl, DynBeancountService},
+        import::{DynImportService, ImportOptions, ImportServiceImpl, ImportSummary, PreviewLine},
+        ledger::{DynLedgerService, LedgerServiceImpl},
+        rbac::{DynRbacService, RbacServiceImpl},
+    },
+};
+use axum::{
+    extract::{ContentLengthLimit, Extension, Multipart},
+    routing::post,
+    AddExtensionLayer, Router,
+};
+use sqlx::postgres::PgPool;
+use std::sync::Arc;
+
+/// 对账单文件大小上限
+const MAX_STATEMENT_SIZE: u64 = 10 * 1024 * 1024;
+
+pub(crate) fn router(pool: Arc<PgPool>) -> Router {
+    let import_svc: DynImportService = Arc::new(ImportServiceImpl::new(pool.clone()));
+    let beancount_svc: DynBeancountService = Arc::new(BeancountServiceImpl::new(pool.clone()));
+    let ledger_svc: DynLedgerService = Arc::new(LedgerServiceImpl::new(pool.clone()));
+    let rbac_svc: DynRbacService = Arc::new(RbacServiceImpl::new(pool));
+    configure(import_svc, beancount_svc, ledger_svc, rbac_svc)
+}
+
+fn configure(
+    import_svc: DynImportService,
+    beancount_svc: DynBeancountService,
+    ledger_svc: DynLedgerService,
+    rbac_svc: DynRbacService,
+) -> Router {
+    Router::new()
+        .route("/:ledger_id/imports", post(import_statement))
+        .route("/:ledger_id/imports/preview", post(preview_statement))
+        .route("/:ledger_id/imports/beancount", post(import_beancount))
+        .layer(&AddExtensionLayer::new(import_svc))
+        .layer(&AddExtensionLayer::new(beancount_svc))
+        .layer(&AddExtensionLayer::new(ledger_svc))
+        .layer(&AddExtensionLayer::new(rbac_svc))
+}
+
+// 读取 multipart 表单中的 options(JSON 导入参数) 与 file(对账单文件) 两个字段
+async fn read_statement(mut multipart: Multipart) -> ApiResult<(ImportOptions, Vec<u8>)> {
+    let invalid =
+        |err: axum::extract::multipart::MultipartError| Error::InvalidStatement(err.to_string());
+    let (mut options, mut file) = (None, None);
+    while let Some(field) = multipart.next_field().await.map_err(invalid)? {
+        match field.name() {
+            Some("options") => {
+                let text = field.text().await.map_err(invalid)?;
+                let value: ImportOptions = serde_json::from_str(&text)
+                    .map_err(|err| Error::InvalidStatement(format!("options: {err}")))?;
+                options = Some(value);
+            }
+            Some("file") => file = Some(field.bytes().await.map_err(invalid)?.to_vec()),
+            _ => {}
+        }
+    }
+
+    match (options, file) {
+        (Some(options), Some(file)) => {
+            if !options.check() {
+                return Err(Error::new_empty_fields_error(
+                    "import needs two distinct accounts, csv also needs date, description and either amount or debit/credit columns"
+                        .to_string(),
+                )
+                .into());
+            }
+            validate_payload(&options)?;
+            Ok((options, file))
+        }
+        _ => Err(Error::new_empty_fields_error("options and file are required".to_string()).into()),
+    }
+}
+
+async fn preview_statement(
+    access: LedgerAccess<Editor>,
+    Extension(svc): Extension<DynImportService>,
+    ContentLengthLimit(multipart): ContentLengthLimit<Multipart, MAX_STATEMENT_SIZE>,
+) -> ApiResult<ApiResponse<Vec<PreviewLine>>> {
+    let ledger = access.ledger;
+    let (options, file) = read_statement(multipart).await?;
+    Ok(ApiResponse::success(
+        svc.preview(&ledger, options, file).await?,
+    ))
+}
+
+async fn import_statement(
+    access: LedgerAccess<Editor>,
+    Extension(svc): Extension<DynImportService>,
+    ContentLengthLimit(multipart): ContentLengthLimit<Multipart, MAX_STATEMENT_SIZE>,
+) -> ApiResult<ApiResponse<ImportSummary>> {
+    let ledger = access.ledger;
+    let (options, file) = read_statement(multipart).await?;
+    Ok(ApiResponse::success(
+        svc.import(&ledger, options, file).await?,
+    ))
+}
+
+// 读取 multipart 表单中的 file 字段作为 Beancount 文本
+async fn read_beancount(mut multipart: Multipart) -> ApiResult<String> {
+    let invalid =
+        |err: axum::extract::multipart::MultipartError| Error::InvalidStatement(err.to_string());
+    while let Some(field) = multipart.next_field().await.map_err(invalid)? {
+        if field.name() == Some("file") {
+            return Ok(field.text().await.map_err(invalid)?);
+        }
+    }
+    Err(Error::new_empty_fields_error("file is required".to_string()).into())
+}
+
+/// 导入 Beancount 文件, 存在错误时不写入任何数据, 结果中 committed 为 false 并列出各行错误;
+/// 没有 rates:write 权限时跳过 price 指令, 不改动共用汇率
+async fn import_beancount(
+    access: LedgerAccess<Editor>,
+    Extension(svc): Extension<DynBeancountService>,
+    Extension(rbac): Extension<DynRbacService>,
+    ContentLengthLimit(multipart): ContentLengthLimit<Multipart, MAX_STATEMENT_SIZE>,
+) -> ApiResult<ApiResponse<BeancountReport>> {
+    let write_rates = rbac
+        .permissions(access.user.id)
+        .await?
+        .contains(&Permission::RatesWrite);
+    let ledger = access.ledger;
+    let text = read_beancount(multipart).await?;
+    Ok(ApiResponse::success(
+        svc.import(&ledger, text, write_rates).await?,
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        models::{import::StatementFormat, ledger::Ledger, member::LedgerRole, user::User},
+        routers::jwt,
+        services::{
+            auth::{DynAuthService, MockAuthService},
+            beancount::MockBeancountService,
+            import::MockImportService,
+            ledger::MockLedgerService,
+            rbac::MockRbacService,
+        },
+    };
+    use axum::{
+        body::Body,
+        http::{self, request::Request, StatusCode},
+    };
+    use std::sync::Arc;
+    use tower::ServiceExt;
+    use uuid::Uuid;
+
+    const BOUNDARY: &str = "statement-boundary";
+
+    fn configure_with_user(import_svc: MockImportService, user_id: Uuid) -> Router {
+        configure_with_services(import_svc, MockBeancountService::new(), user_id)
+    }
+
+    fn configure_with_services(
+        import_svc: MockImportService,
+        beancount_svc: MockBeancountService,
+        user_id: Uuid,
+    ) -> Router {
+        let mut auth_svc = MockAuthService::new();
+        auth_svc.expect_authenticate().returning(move |_, _, _| {
+            Ok(User {
+                id: user_id,
+                ..Default::default()
+            })
+        });
+        let mut ledger_svc = MockLedgerService::new();
+        ledger_svc.expect_access().returning(|user_id, id| {
+            let ledger = Ledger {
+                id,
+                owner_id: Some(user_id),
+                ..Default::default()
+            };
+            Ok((ledger, LedgerRole::Owner))
+        });
+        let mut rbac_svc = MockRbacService::new();
+        rbac_svc
+            .expect_permissions()
+            .returning(|_| Ok(Default::default()));
+        let auth_svc: DynAuthService = Arc::new(auth_svc);
+        configure(
+            Arc::new(import_svc),
+            Arc::new(beancount_svc),
+            Arc::new(ledger_svc),
+            Arc::new(rbac_svc),
+        )
+        .layer(&AddExtensionLayer::new(auth_svc))
+    }
+
+    fn multipart_request(uri: String, user_id: Uuid, fields: &[(&str, &str)]) -> Request<Body> {
+        let mut body = String::new();
+        for (name, value) in fields {
+            body.push_str(&format!(
+                "--{BOUNDARY}\r\nContent-Disposition: form-data; name=\"{name}\"\r\n\r\n{value}\r\n"
+            ));
+        }
+        body.push_str(&format!("--{BOUNDARY}--\r\n"));
+        Request::builder()
+            .method(http::Method::POST)
+            .uri(uri)
+            .header(
+                http::header::CONTENT_TYPE,
+                format!("multipart/form-data; boundary={BOUNDARY}"),
+            )
+            .header(http::header::CONTENT_LENGTH, body.len())
+            .header(
+                http::header::AUTHORIZATION,
+                format!("Bearer {}", jwt::sign(user_id).unwrap()),
+            )
+            .body(Body::from(body))
+            .unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_import_controller_import() {
+        let mut svc = MockImportService::new();
+        svc.expect_import().returning(|_, options, file| {
+            assert_eq!(Some("Amount".to_string()), options.columns.amount);
+            assert!(file.starts_with(b"Date,Memo,Amount"));
+            Ok(ImportSummary {
+                imported: 1,
+                duplicates: 1,
+                entries: Vec::new(),
+            })
+        });
+
+        std::env::set_var("JWT_SECRET", "example_secret_key");
+        let user_id = Uuid::new_v4();
+        let app = configure_with_user(svc, user_id);
+
+        let mapping = format!(
+            r#"{{"account_id":"{}","contra_account_id":"{}","date":"Date","description":"Memo","amount":"Amount"}}"#,
+            Uuid::new_v4(),
+            Uuid::new_v4()
+        );
+        let request = multipart_request(
+            format!("/{}/imports", Uuid::new_v4()),
+            user_id,
+            &[
+                ("options", &mapping),
+                ("file", "Date,Memo,Amount\n2022-04-01,Coffee,-12.50\n"),
+            ],
+        );
+        let response = app.oneshot(request).await.unwrap();
+
+        assert_eq!(response.status(), StatusCode::OK);
+        let body = hyper::body::to_bytes(response.into_body()).await.unwrap();
+        let value: serde_json::Value = serde_json::from_slice(&body).unwrap();
+        assert_eq!(1, value["data"]["duplicates"]);
+    }
+
+    #[tokio::test]
+    async fn test_import_controller_preview_with_ambiguous_mapping() {
+        let mut svc = MockImportService::new();
+        svc.expect_preview().never();
+
+        std::env::set_var("JWT_SECRET", "example_secret_key");
+        let user_id = Uuid::new_v4();
+        let app = configure_with_user(svc, user_id);
+
+        let mapping = format!(
+            r#"{{"account_id":"{}","contra_account_id":"{}","date":"Date","description":"Memo","amount":"Amount","debit":"Out"}}"#,
+            Uuid::new_v4(),
+            Uuid::new_v4()
+        );
+        let request = multipart_request(
+            format!("/{}/imports/preview", Uuid::new_v4()),
+            user_id,
+            &[("options", &mapping), ("file", "Date,Memo,Amount\n")],
+        );
+        let response = app.oneshot(request).await.unwrap();
+
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+    }
+
+    #[tokio::test]
+    async fn test_import_controller_preview_ofx_without_columns() {
+        let mut svc = MockImportService::new();
+        svc.expect_preview().returning(|_, options, _| {
+            assert_eq!(StatementFormat::Ofx, options.format);
+            Ok(Vec::new())
+        });
+
+        std::env::set_var("JWT_SECRET", "example_secret_key");
+        let user_id = Uuid::new_v4();
+        let app = configure_with_user(svc, user_id);
+
+        let options = format!(
+            r#"{{"account_id":"{}","contra_account_id":"{}","format":"qfx"}}"#,
+            Uuid::new_v4(),
+            Uuid::new_v4()
+        );
+        let request = multipart_request(
+            format!("/{}/imports/preview", Uuid::new_v4()),
+            user_id,
+            &[("options", &options), ("file", "<OFX></OFX>")],
+        );
+        let response = app.oneshot(request).await.unwrap();
+
+        assert_eq!(response.status(), StatusCode::OK);
+    }
+
+    #[tokio::test]
+    async fn test_import_controller_import_beancount() {
+        let mut svc = MockBeancountService::new();
+        svc.expect_import().returning(|_, text, write_rates| {
+            assert!(text.starts_with("2022-01-01 open Assets:Bank"));
+            assert!(!write_rates);
+            Ok(BeancountReport {
+                committed: true,
+                accounts: 1,
+                ..Default::default()
+            })
+        });
+
+        std::env::set_var("JWT_SECRET", "example_secret_key");
+        let user_id = Uuid::new_v4();
+        let app = configure_with_services(MockImportService::new(), svc, user_id);
+
+        let request = multipart_request(
+            format!("/{}/imports/beancount", Uuid::new_v4()),
+            user_id,
+            &[("file", "2022-01-01 open Assets:Bank\n")],
+        );
+        let response = app.oneshot(request).await.unwrap();
+
+        assert_eq!(response.status(), StatusCode::OK);
+        let body = hyper::body::to_bytes(response.into_body()).await.unwrap();
+        let value: serde_json::Value = serde_json::from_slice(&body).unwrap();
+        assert_eq!(true, value["data"]["committed"]);
+    }
+}
@@ -0,0 +1,80 @@
+use crate::config::{
+    constants::JWT_KEYS,
+    keys::{JwkSet, KeyRing},
+};
+use axum::{
+    extract::Extension,
+    http::{header, HeaderMap, HeaderValue},
+    response::IntoResponse,
+    routing::get,
+    AddExtensionLayer, Json, Router,
+};
+
+pub(crate) fn router() -> Router {
+    configure(&JWT_KEYS)
+}
+
+fn configure(keys: &'static KeyRing) -> Router {
+    Router::new()
+        .route("/jwks.json", get(jwks))
+        .layer(&AddExtensionLayer::new(keys))
+}
+
+// 按 RFC 7517 原样返回, 不包装为 ApiResponse, 允许验签方短暂缓存
+async fn jwks(Extension(keys): Extension<&'static KeyRing>) -> impl IntoResponse {
+    let mut headers = HeaderMap::new();
+    headers.insert(
+        header::CACHE_CONTROL,
+        HeaderValue::from_static("public, max-age=300"),
+    );
+    (headers, Json::<JwkSet>(keys.jwks()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::{env::JwtConfig, keys::tests::fixture};
+    use axum::{
+        body::Body,
+        http::{self, Request, StatusCode},
+    };
+    use tower::ServiceExt;
+
+    #[tokio::test]
+    async fn test_jwks_controller_publishes_public_keys() {
+        let config = JwtConfig {
+            jwt_keys: Some(format!(
+                "2022a={},ed={}",
+                fixture("jwt_rs256_2022a.pem"),
+                fixture("jwt_ed25519.pem"),
+            )),
+            jwt_active_kid: Some("ed".to_owned()),
+        };
+        let keys = KeyRing::load(&config, || unreachable!()).unwrap();
+        let app = configure(Box::leak(Box::new(keys)));
+
+        let response = app
+            .oneshot(
+                Request::builder()
+                    .method(http::Method::GET)
+                    .uri("/jwks.json")
+                    .body(Body::empty())
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+
+        assert_eq!(response.status(), StatusCode::OK);
+        let body = hyper::body::to_bytes(response.into_body()).await.unwrap();
+        let jwks: serde_json::Value = serde_json::from_slice(&body).unwrap();
+        let kids: Vec<_> = jwks["keys"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|key| key["kid"].as_str().unwrap())
+            .collect();
+        assert_eq!(vec!["2022a", "ed"], kids);
+        assert_eq!("RS256", jwks["keys"][0]["alg"]);
+        assert_eq!("sig", jwks["keys"][1]["use"]);
+    }
+}
@@ -1,43 +1,240 @@
 use chrono::{Duration, Utc};
-use jsonwebtoken::{DecodingKey, EncodingKey, Header, Validation};
+use jsonwebtoken::Validation;
 use serde::{Deserialize, Serialize};
 use uuid::Uuid;
 
-use crate::{config::constants::JWT_SECRET, errors::Result};
+use crate::{
+    config::{
+        constants::{JWT_CLAIMS, JWT_KEYS, TOKEN_TTL},
+        env::JwtClaimsConfig,
+        keys::KeyRing,
+    },
+    errors::{Error, Result},
+};
+
+/// 令牌授权范围, 在 scope 声明中以空格分隔
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Scope {
+    /// 只读请求(GET/HEAD/OPTIONS)
+    Read,
+    /// 修改数据的请求
+    Write,
+}
+
+impl Scope {
+    pub const ALL: [Scope; 2] = [Scope::Read, Scope::Write];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Scope::Read => "read",
+            Scope::Write => "write",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<Scope> {
+        Scope::ALL.into_iter().find(|scope| scope.name() == name)
+    }
+}
 
 #[derive(Debug, Deserialize, Serialize)]
 pub struct Claims {
+    pub iss: String,
+    pub aud: String,
     pub sub: Uuid,
     pub exp: i64,
     pub iat: i64,
+    /// 令牌唯一标识, 注销时加入撤销列表
+    pub jti: Uuid,
+    /// 签发时的 refresh token family, 注销时一并撤销
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub sid: Option<Uuid>,
+    /// 授权范围
+    #[serde(default)]
+    pub scope: String,
 }
 
 impl Claims {
-    pub fn new(id: Uuid) -> Self {
+    pub fn new(id: Uuid, sid: Option<Uuid>, scopes: &[Scope]) -> Self {
         let iat = Utc::now();
-        let exp = iat + Duration::hours(24);
+        let exp = iat + Duration::seconds(TOKEN_TTL.access_token_ttl_secs);
+        let scope = scopes
+            .iter()
+            .map(|scope| scope.name())
+            .collect::<Vec<_>>()
+            .join(" ");
 
         Self {
+            iss: JWT_CLAIMS.jwt_issuer.clone(),
+            aud: JWT_CLAIMS.jwt_audience.clone(),
             sub: id,
             iat: iat.timestamp(),
             exp: exp.timestamp(),
+            jti: Uuid::new_v4(),
+            sid,
+            scope,
         }
     }
+
+    pub fn has_scope(&self, scope: Scope) -> bool {
+        self.scope
+            .split_whitespace()
+            .any(|name| name == scope.name())
+    }
 }
 
+fn encode(claims: &Claims) -> Result<String> {
+    Ok(JWT_KEYS.encode(claims)?)
+}
+
+/// 签发不关联 refresh token 的 access token
+#[cfg(test)]
 pub fn sign(id: Uuid) -> Result<String> {
-    Ok(jsonwebtoken::encode(
-        &Header::default(),
-        &Claims::new(id),
-        &EncodingKey::from_secret(JWT_SECRET.as_bytes()),
-    )?)
+    sign_scoped(id, &Scope::ALL)
+}
+
+/// 签发限定授权范围的 access token
+#[cfg(test)]
+pub fn sign_scoped(id: Uuid, scopes: &[Scope]) -> Result<String> {
+    encode(&Claims::new(id, None, scopes))
+}
+
+/// 签发与 refresh token family 关联的 access token, 拥有全部授权范围
+pub fn sign_session(id: Uuid, sid: Uuid) -> Result<String> {
+    encode(&Claims::new(id, Some(sid), &Scope::ALL))
+}
+
+/// 以当前令牌为依据签发授权范围更小的 access token, 沿用其 sid 以便随会话一同注销;
+/// 请求当前令牌不具备的范围时返回 Forbidden
+pub fn sign_narrowed(current: &Claims, scopes: &[Scope]) -> Result<String> {
+    if !scopes.iter().all(|&scope| current.has_scope(scope)) {
+        return Err(Error::Forbidden);
+    }
+    encode(&Claims::new(current.sub, current.sid, scopes))
 }
 
 pub fn verify(token: &str) -> Result<Claims> {
-    Ok(jsonwebtoken::decode(
-        token,
-        &DecodingKey::from_secret(JWT_SECRET.as_bytes()),
-        &Validation::default(),
-    )
-    .map(|data| data.claims)?)
+    verify_with(&JWT_KEYS, &JWT_CLAIMS, token)
+}
+
+// iss/aud 与配置不符的令牌一律拒绝
+fn verify_with(keys: &KeyRing, config: &JwtClaimsConfig, token: &str) -> Result<Claims> {
+    let mut validation = Validation::default();
+    validation.leeway = config.jwt_leeway_secs;
+    validation.set_issuer(&[&config.jwt_issuer]);
+    validation.set_audience(&[&config.jwt_audience]);
+    validation.set_required_spec_claims(&["exp", "iss", "aud"]);
+    Ok(keys.decode(token, validation)?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::{env::JwtConfig, keys::tests::fixture};
+
+    fn key_ring(active: &str) -> KeyRing {
+        let config = JwtConfig {
+            jwt_keys: Some(format!(
+                "2022a={},ed={}",
+                fixture("jwt_rs256_2022a.pem"),
+                fixture("jwt_ed25519.pem"),
+            )),
+            jwt_active_kid: Some(active.to_owned()),
+        };
+        KeyRing::load(&config, || unreachable!()).unwrap()
+    }
+
+    #[test]
+    fn test_verify_token_signed_by_previous_key() {
+        let user_id = Uuid::new_v4();
+        let token = key_ring("2022a")
+            .encode(&Claims::new(user_id, None, &Scope::ALL))
+            .unwrap();
+        let header = jsonwebtoken::decode_header(&token).unwrap();
+        assert_eq!(Some("2022a".to_owned()), header.kid);
+
+        // 轮换到 Ed25519 后旧令牌仍可验签, 新令牌使用新密钥
+        let rotated = key_ring("ed");
+        assert_eq!(
+            user_id,
+            verify_with(&rotated, &JWT_CLAIMS, &token).unwrap().sub
+        );
+        let token = rotated
+            .encode(&Claims::new(user_id, None, &Scope::ALL))
+            .unwrap();
+        let header = jsonwebtoken::decode_header(&token).unwrap();
+        assert_eq!(jsonwebtoken::Algorithm::EdDSA, header.alg);
+        assert_eq!(
+            user_id,
+            verify_with(&rotated, &JWT_CLAIMS, &token).unwrap().sub
+        );
+    }
+
+    #[test]
+    fn test_verify_rejects_unknown_kid() {
+        let token = key_ring("ed")
+            .encode(&Claims::new(Uuid::new_v4(), None, &Scope::ALL))
+            .unwrap();
+
+        let config = JwtConfig {
+            jwt_keys: Some(format!("2022a={}", fixture("jwt_rs256_2022a.pem"))),
+            jwt_active_kid: None,
+        };
+        let retired = KeyRing::load(&config, || unreachable!()).unwrap();
+        assert!(verify_with(&retired, &JWT_CLAIMS, &token).is_err());
+
+        let hmac = KeyRing::load(
+            &JwtConfig {
+                jwt_keys: None,
+                jwt_active_kid: None,
+            },
+            || "secret".to_owned(),
+        )
+        .unwrap();
+        assert!(verify_with(&hmac, &JWT_CLAIMS, &token).is_err());
+    }
+
+    #[test]
+    fn test_verify_rejects_foreign_issuer_and_audience() {
+        let keys = key_ring("ed");
+        let config = JwtClaimsConfig {
+            jwt_issuer: "cashbook".to_owned(),
+            jwt_audience: "cashbook".to_owned(),
+            jwt_leeway_secs: 0,
+        };
+
+        let mut claims = Claims::new(Uuid::new_v4(), None, &[Scope::Read]);
+        claims.iss = "cashbook".to_owned();
+        claims.aud = "cashbook".to_owned();
+        let token = keys.encode(&claims).unwrap();
+        let verified = verify_with(&keys, &config, &token).unwrap();
+        assert!(verified.has_scope(Scope::Read));
+        assert!(!verified.has_scope(Scope::Write));
+
+        claims.aud = "billing".to_owned();
+        let token = keys.encode(&claims).unwrap();
+        assert!(verify_with(&keys, &config, &token).is_err());
+
+        claims.aud = "cashbook".to_owned();
+        claims.iss = "someone-else".to_owned();
+        let token = keys.encode(&claims).unwrap();
+        assert!(verify_with(&keys, &config, &token).is_err());
+    }
+
+    #[test]
+    fn test_sign_narrowed_keeps_sid_and_only_narrows() {
+        std::env::set_var("JWT_SECRET", "example_secret_key");
+        let sid = Uuid::new_v4();
+        let session = verify(&sign_session(Uuid::new_v4(), sid).unwrap()).unwrap();
+
+        let narrowed = verify(&sign_narrowed(&session, &[Scope::Read]).unwrap()).unwrap();
+        assert_eq!(session.sub, narrowed.sub);
+        assert_eq!(Some(sid), narrowed.sid);
+        assert_eq!("read", narrowed.scope);
+
+        // 只能收窄, 不能扩大授权范围
+        assert!(matches!(
+            sign_narrowed(&narrowed, &Scope::ALL),
+            Err(Error::Forbidden)
+        ));
+    }
 }
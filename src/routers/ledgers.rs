@@ -0,0 +1,292 @@
+use super::{ApiResponse, LedgerAccess, Owner, Viewer};
+use crate::{
+    dto::validate_payload,
+    errors::{ApiResult, Error},
+    models::user::User,
+    services::ledger::{
+        CreateLedgerInput, DynLedgerService, Ledger, LedgerServiceImpl, ListLedgerInput,
+        UpdateLedgerInput,
+    },
+};
+use axum::{
+    extract::{Extension, Path, Query},
+    routing::{get, post},
+    AddExtensionLayer, Json, Router,
+};
+use sqlx::postgres::PgPool;
+use std::sync::Arc;
+use uuid::Uuid;
+
+pub(crate) fn router(pool: Arc<PgPool>) -> Router {
+    let ledger_svc: DynLedgerService = Arc::new(LedgerServiceImpl::new(pool));
+    configure(ledger_svc)
+}
+
+fn configure(ledger_svc: DynLedgerService) -> Router {
+    Router::new()
+        .route("/", post(create_ledger).get(list_ledger))
+        .route(
+            "/:ledger_id",
+            get(get_ledger).delete(delete_ledger).put(update_ledger),
+        )
+        .layer(&AddExtensionLayer::new(ledger_svc))
+}
+
+async fn create_ledger(
+    user: User,
+    Extension(svc): Extension<DynLedgerService>,
+    Json(input): Json<CreateLedgerInput>,
+) -> ApiResult<ApiResponse<Ledger>> {
+    validate_payload(&input)?;
+    Ok(ApiResponse::success(svc.create(user.id, input).await?))
+}
+
+async fn get_ledger(access: LedgerAccess<Viewer>) -> ApiResult<ApiResponse<Ledger>> {
+    Ok(ApiResponse::success(access.ledger))
+}
+
+async fn delete_ledger(
+    access: LedgerAccess<Owner>,
+    Extension(svc): Extension<DynLedgerService>,
+    Path(id): Path<Uuid>,
+) -> ApiResult<ApiResponse<Ledger>> {
+    Ok(ApiResponse::success(svc.delete(access.user.id, id).await?))
+}
+
+async fn update_ledger(
+    access: LedgerAccess<Owner>,
+    Extension(svc): Extension<DynLedgerService>,
+    Path(id): Path<Uuid>,
+    Json(input): Json<UpdateLedgerInput>,
+) -> ApiResult<ApiResponse<Ledger>> {
+    if !input.check() {
+        return Err(Error::new_empty_fields_error("update ledger failed".to_string()).into());
+    }
+    validate_payload(&input)?;
+    Ok(ApiResponse::success(
+        svc.update(access.user.id, id, input).await?,
+    ))
+}
+
+async fn list_ledger(
+    user: User,
+    Extension(svc): Extension<DynLedgerService>,
+    Query(mut input): Query<ListLedgerInput>,
+) -> ApiResult<ApiResponse<Vec<Ledger>>> {
+    input.limit_offset.check();
+    validate_payload(&input)?;
+    Ok(ApiResponse::success(svc.list(user.id, input).await?))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        models::member::LedgerRole,
+        routers::jwt,
+        services::{
+            auth::{DynAuthService, MockAuthService},
+            ledger::MockLedgerService,
+        },
+    };
+    use axum::{
+        body::Body,
+        http::{self, request::Request, StatusCode},
+    };
+    use std::sync::Arc;
+    use tower::ServiceExt;
+
+    fn configure_with_user(ledger_svc: DynLedgerService, user_id: Uuid) -> Router {
+        let mut auth_svc = MockAuthService::new();
+        auth_svc.expect_authenticate().returning(move |_, _, _| {
+            Ok(User {
+                id: user_id,
+                ..Default::default()
+            })
+        });
+        let auth_svc: DynAuthService = Arc::new(auth_svc);
+        configure(ledger_svc).layer(&AddExtensionLayer::new(auth_svc))
+    }
+
+    #[tokio::test]
+    async fn test_ledger_controller_create() {
+        let mut svc = MockLedgerService::new();
+        svc.expect_create().returning(|owner_id, input| {
+            Ok(Ledger {
+                name: input.name,
+                owner_id: Some(owner_id),
+                base_currency: input.base_currency,
+                ..Default::default()
+            })
+        });
+
+        std::env::set_var("JWT_SECRET", "example_secret_key");
+        let user_id = Uuid::new_v4();
+        let app = configure_with_user(Arc::new(svc), user_id);
+
+        let response = app
+            .oneshot(
+                Request::builder()
+                    .method(http::Method::POST)
+                    .uri("/")
+                    .header(http::header::CONTENT_TYPE, mime::APPLICATION_JSON.as_ref())
+                    .header(
+                        http::header::AUTHORIZATION,
+                        format!("Bearer {}", jwt::sign(user_id).unwrap()),
+                    )
+                    .body(Body::from(
+                        serde_json::to_string(&CreateLedgerInput {
+                            name: "household".to_string(),
+                            base_currency: "EUR".to_string(),
+                        })
+                        .unwrap(),
+                    ))
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+
+        assert_eq!(response.status(), StatusCode::OK);
+
+        let body = hyper::body::to_bytes(response.into_body()).await.unwrap();
+        let ledger: ApiResponse<Ledger> = serde_json::from_slice(&body).unwrap();
+        let ledger = ledger.data.unwrap();
+        assert_eq!(Some(user_id), ledger.owner_id);
+        assert_eq!("EUR", ledger.base_currency);
+    }
+
+    #[tokio::test]
+    async fn test_ledger_controller_create_invalid_currency() {
+        let svc = MockLedgerService::new();
+
+        std::env::set_var("JWT_SECRET", "example_secret_key");
+        let user_id = Uuid::new_v4();
+        let app = configure_with_user(Arc::new(svc), user_id);
+
+        let response = app
+            .oneshot(
+                Request::builder()
+                    .method(http::Method::POST)
+                    .uri("/")
+                    .header(http::header::CONTENT_TYPE, mime::APPLICATION_JSON.as_ref())
+                    .header(
+                        http::header::AUTHORIZATION,
+                        format!("Bearer {}", jwt::sign(user_id).unwrap()),
+                    )
+                    .body(Body::from(
+                        serde_json::to_string(&CreateLedgerInput {
+                            name: "household".to_string(),
+                            base_currency: "euro".to_string(),
+                        })
+                        .unwrap(),
+                    ))
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+    }
+
+    #[tokio::test]
+    async fn test_ledger_controller_get_forbidden() {
+        let mut svc = MockLedgerService::new();
+        svc.expect_access().returning(|_, _| Err(Error::Forbidden));
+
+        std::env::set_var("JWT_SECRET", "example_secret_key");
+        let user_id = Uuid::new_v4();
+        let app = configure_with_user(Arc::new(svc), user_id);
+
+        let response = app
+            .oneshot(
+                Request::builder()
+                    .method(http::Method::GET)
+                    .uri(format!("/{}", Uuid::new_v4()))
+                    .header(
+                        http::header::AUTHORIZATION,
+                        format!("Bearer {}", jwt::sign(user_id).unwrap()),
+                    )
+                    .body(Body::empty())
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+
+        assert_eq!(response.status(), StatusCode::FORBIDDEN);
+    }
+
+    #[tokio::test]
+    async fn test_ledger_controller_delete_requires_owner() {
+        let mut svc = MockLedgerService::new();
+        svc.expect_access().returning(|user_id, id| {
+            let ledger = Ledger {
+                id,
+                owner_id: Some(user_id),
+                ..Default::default()
+            };
+            Ok((ledger, LedgerRole::Editor))
+        });
+        svc.expect_delete().never();
+
+        std::env::set_var("JWT_SECRET", "example_secret_key");
+        let user_id = Uuid::new_v4();
+        let app = configure_with_user(Arc::new(svc), user_id);
+
+        let response = app
+            .oneshot(
+                Request::builder()
+                    .method(http::Method::DELETE)
+                    .uri(format!("/{}", Uuid::new_v4()))
+                    .header(
+                        http::header::AUTHORIZATION,
+                        format!("Bearer {}", jwt::sign(user_id).unwrap()),
+                    )
+                    .body(Body::empty())
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+
+        assert_eq!(response.status(), StatusCode::FORBIDDEN);
+    }
+
+    #[tokio::test]
+    async fn test_ledger_controller_read_only_token() {
+        let mut svc = MockLedgerService::new();
+        svc.expect_access().returning(|user_id, id| {
+            let ledger = Ledger {
+                id,
+                owner_id: Some(user_id),
+                ..Default::default()
+            };
+            Ok((ledger, LedgerRole::Owner))
+        });
+        svc.expect_delete().never();
+
+        std::env::set_var("JWT_SECRET", "example_secret_key");
+        let user_id = Uuid::new_v4();
+        let app = configure_with_user(Arc::new(svc), user_id);
+        let token = jwt::sign_scoped(user_id, &[jwt::Scope::Read]).unwrap();
+
+        let ledger_id = Uuid::new_v4();
+        for (method, status) in [
+            (http::Method::GET, StatusCode::OK),
+            (http::Method::DELETE, StatusCode::FORBIDDEN),
+        ] {
+            let response = app
+                .clone()
+                .oneshot(
+                    Request::builder()
+                        .method(method)
+                        .uri(format!("/{}", ledger_id))
+                        .header(http::header::AUTHORIZATION, format!("Bearer {}", token))
+                        .body(Body::empty())
+                        .unwrap(),
+                )
+                .await
+                .unwrap();
+
+            assert_eq!(response.status(), status);
+        }
+    }
+}
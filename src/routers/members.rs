@@ -0,0 +1,233 @@
+use super::{ApiResponse, LedgerAccess, Owner, Viewer};
+use crate::{
+    dto::validate_payload,
+    errors::{ApiResult, Error},
+    services::{
+        ledger::{DynLedgerService, LedgerServiceImpl},
+        member::{
+            AddMemberInput, DynMemberService, LedgerMember, LedgerRole, MemberServiceImpl,
+            UpdateMemberInput,
+        },
+    },
+};
+use axum::{
+    extract::{Extension, Path},
+    routing::{get, put},
+    AddExtensionLayer, Json, Router,
+};
+use sqlx::postgres::PgPool;
+use std::sync::Arc;
+use uuid::Uuid;
+
+pub(crate) fn router(pool: Arc<PgPool>) -> Router {
+    let member_svc: DynMemberService = Arc::new(MemberServiceImpl::new(pool.clone()));
+    let ledger_svc: DynLedgerService = Arc::new(LedgerServiceImpl::new(pool));
+    configure(member_svc, ledger_svc)
+}
+
+fn configure(member_svc: DynMemberService, ledger_svc: DynLedgerService) -> Router {
+    Router::new()
+        .route("/:ledger_id/members", get(list_member).post(add_member))
+        .route(
+            "/:ledger_id/members/:user_id",
+            put(update_member).delete(remove_member),
+        )
+        .layer(&AddExtensionLayer::new(member_svc))
+        .layer(&AddExtensionLayer::new(ledger_svc))
+}
+
+async fn list_member(
+    _: LedgerAccess<Viewer>,
+    Extension(svc): Extension<DynMemberService>,
+    Path(ledger_id): Path<Uuid>,
+) -> ApiResult<ApiResponse<Vec<LedgerMember>>> {
+    Ok(ApiResponse::success(svc.list(ledger_id).await?))
+}
+
+async fn add_member(
+    _: LedgerAccess<Owner>,
+    Extension(svc): Extension<DynMemberService>,
+    Path(ledger_id): Path<Uuid>,
+    Json(input): Json<AddMemberInput>,
+) -> ApiResult<ApiResponse<LedgerMember>> {
+    validate_payload(&input)?;
+    Ok(ApiResponse::success(svc.add(ledger_id, input).await?))
+}
+
+async fn update_member(
+    _: LedgerAccess<Owner>,
+    Extension(svc): Extension<DynMemberService>,
+    Path((ledger_id, user_id)): Path<(Uuid, Uuid)>,
+    Json(input): Json<UpdateMemberInput>,
+) -> ApiResult<ApiResponse<LedgerMember>> {
+    validate_payload(&input)?;
+    Ok(ApiResponse::success(
+        svc.update(ledger_id, user_id, input).await?,
+    ))
+}
+
+// owner 可移除任意成员, 其他成员只能退出账本
+async fn remove_member(
+    access: LedgerAccess<Viewer>,
+    Extension(svc): Extension<DynMemberService>,
+    Path((ledger_id, user_id)): Path<(Uuid, Uuid)>,
+) -> ApiResult<ApiResponse<LedgerMember>> {
+    if access.role != LedgerRole::Owner && access.user.id != user_id {
+        return Err(Error::Forbidden.into());
+    }
+    Ok(ApiResponse::success(svc.remove(ledger_id, user_id).await?))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        models::{ledger::Ledger, user::User},
+        routers::jwt,
+        services::{
+            auth::{DynAuthService, MockAuthService},
+            ledger::MockLedgerService,
+            member::MockMemberService,
+        },
+    };
+    use axum::{
+        body::Body,
+        http::{self, request::Request, StatusCode},
+    };
+    use std::sync::Arc;
+    use tower::ServiceExt;
+
+    fn configure_with_role(
+        member_svc: MockMemberService,
+        user_id: Uuid,
+        role: LedgerRole,
+    ) -> Router {
+        let mut auth_svc = MockAuthService::new();
+        auth_svc.expect_authenticate().returning(move |_, _, _| {
+            Ok(User {
+                id: user_id,
+                ..Default::default()
+            })
+        });
+        let mut ledger_svc = MockLedgerService::new();
+        ledger_svc.expect_access().returning(move |_, id| {
+            let ledger = Ledger {
+                id,
+                ..Default::default()
+            };
+            Ok((ledger, role))
+        });
+        let auth_svc: DynAuthService = Arc::new(auth_svc);
+        configure(Arc::new(member_svc), Arc::new(ledger_svc))
+            .layer(&AddExtensionLayer::new(auth_svc))
+    }
+
+    fn request(method: http::Method, uri: String, user_id: Uuid, body: &str) -> Request<Body> {
+        Request::builder()
+            .method(method)
+            .uri(uri)
+            .header(http::header::CONTENT_TYPE, mime::APPLICATION_JSON.as_ref())
+            .header(
+                http::header::AUTHORIZATION,
+                format!("Bearer {}", jwt::sign(user_id).unwrap()),
+            )
+            .body(Body::from(body.to_string()))
+            .unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_member_controller_add() {
+        let mut svc = MockMemberService::new();
+        svc.expect_add().returning(|ledger_id, input| {
+            Ok(LedgerMember {
+                ledger_id,
+                email: input.email,
+                role: input.role,
+                ..Default::default()
+            })
+        });
+
+        std::env::set_var("JWT_SECRET", "example_secret_key");
+        let user_id = Uuid::new_v4();
+        let app = configure_with_role(svc, user_id, LedgerRole::Owner);
+
+        let response = app
+            .oneshot(request(
+                http::Method::POST,
+                format!("/{}/members", Uuid::new_v4()),
+                user_id,
+                r#"{"email":"editor@example.com","role":"editor"}"#,
+            ))
+            .await
+            .unwrap();
+
+        assert_eq!(response.status(), StatusCode::OK);
+        let body = hyper::body::to_bytes(response.into_body()).await.unwrap();
+        let member: ApiResponse<LedgerMember> = serde_json::from_slice(&body).unwrap();
+        assert_eq!(LedgerRole::Editor, member.data.unwrap().role);
+    }
+
+    #[tokio::test]
+    async fn test_member_controller_editor_cannot_invite() {
+        let mut svc = MockMemberService::new();
+        svc.expect_add().never();
+
+        std::env::set_var("JWT_SECRET", "example_secret_key");
+        let user_id = Uuid::new_v4();
+        let app = configure_with_role(svc, user_id, LedgerRole::Editor);
+
+        let response = app
+            .oneshot(request(
+                http::Method::POST,
+                format!("/{}/members", Uuid::new_v4()),
+                user_id,
+                r#"{"email":"viewer@example.com","role":"viewer"}"#,
+            ))
+            .await
+            .unwrap();
+
+        assert_eq!(response.status(), StatusCode::FORBIDDEN);
+    }
+
+    #[tokio::test]
+    async fn test_member_controller_viewer_can_leave() {
+        let mut svc = MockMemberService::new();
+        svc.expect_remove()
+            .times(1)
+            .returning(|ledger_id, user_id| {
+                Ok(LedgerMember {
+                    ledger_id,
+                    user_id,
+                    ..Default::default()
+                })
+            });
+
+        std::env::set_var("JWT_SECRET", "example_secret_key");
+        let user_id = Uuid::new_v4();
+        let app = configure_with_role(svc, user_id, LedgerRole::Viewer);
+        let ledger_id = Uuid::new_v4();
+
+        let response = app
+            .clone()
+            .oneshot(request(
+                http::Method::DELETE,
+                format!("/{ledger_id}/members/{}", Uuid::new_v4()),
+                user_id,
+                "",
+            ))
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::FORBIDDEN);
+
+        let response = app
+            .oneshot(request(
+                http::Method::DELETE,
+                format!("/{ledger_id}/members/{user_id}"),
+                user_id,
+                "",
+            ))
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+    }
+}
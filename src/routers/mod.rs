@@ -1,39 +1,91 @@
+/// 会计科目逻辑层
+mod accounts;
 /// 认证实现
 mod auth;
+/// 预算逻辑层
+mod budgets;
+/// 分类与自动分类规则逻辑层
+mod categories;
+/// 货币与汇率逻辑层
+mod currencies;
+/// 记账分录逻辑层
+mod entries;
+/// 账本导出逻辑层
+mod exports;
 /// 主页
 mod home;
+/// 对账单导入逻辑层
+mod imports;
+/// 对外公布的验签公钥
+mod jwks;
 /// token相关功能
 mod jwt;
+/// 账本模块逻辑层
+mod ledgers;
+/// 账本成员逻辑层
+mod members;
+/// 周期分录逻辑层
+mod recurring;
+/// 报表逻辑层
+mod reports;
 /// 用户模块逻辑层
 mod users;
 
 use super::{
     errors::{ApiError, Error},
-    models::user::User,
-    services::auth::{AuthServiceImpl, DynAuthService},
+    models::{ledger::Ledger, member::LedgerRole, rbac::Permission, user::User},
+    services::{
+        auth::{AuthServiceImpl, DynAuthService},
+        ledger::DynLedgerService,
+        rbac::DynRbacService,
+    },
 };
 use axum::{
     async_trait,
-    extract::{Extension, FromRequest, RequestParts, TypedHeader},
-    http::StatusCode,
+    extract::{Extension, FromRequest, Path, RequestParts, TypedHeader},
+    http::{Method, StatusCode},
     response::{IntoResponse, Response},
     AddExtensionLayer, Json, Router,
 };
 use headers::{authorization::Bearer, Authorization};
 use serde::{Deserialize, Serialize};
 use sqlx::postgres::PgPool;
-use std::sync::Arc;
+use std::{
+    collections::{HashMap, HashSet},
+    marker::PhantomData,
+    sync::Arc,
+};
+use uuid::Uuid;
 
 // 路由配置
 pub fn routers(pool: Arc<PgPool>) -> Router {
     let auth_svc: DynAuthService = Arc::new(AuthServiceImpl::new(pool.clone()));
     Router::new()
-        .nest("/users", users::router(pool))
-        .nest("/auth", auth::router())
+        .nest("/users", users::router(pool.clone()))
+        .nest("/currencies", currencies::router(pool.clone()))
+        .nest(
+            "/ledgers",
+            ledgers::router(pool.clone())
+                .merge(accounts::router(pool.clone()))
+                .merge(budgets::router(pool.clone()))
+                .merge(categories::router(pool.clone()))
+                .merge(entries::router(pool.clone()))
+                .merge(exports::router(pool.clone()))
+                .merge(imports::router(pool.clone()))
+                .merge(members::router(pool.clone()))
+                .merge(recurring::router(pool.clone()))
+                .merge(reports::router(pool.clone())),
+        )
+        .nest("/auth", auth::router(pool))
         .nest("", home::router())
         .layer(&AddExtensionLayer::new(auth_svc))
 }
 
+// 标准发现路径, 挂载在 API 版本前缀之外
+pub fn well_known() -> Router {
+    Router::new().nest("/.well-known", jwks::router())
+}
+
 // 统一APi成功响应格式
 #[derive(Debug, Serialize, Deserialize)]
 pub struct ApiResponse<T: Serialize> {
@@ -67,9 +119,15 @@ where
     }
 }
 
-// 从请求中直接获取到用户信息
+/// 当前请求携带的有效令牌及其所属用户
+pub(crate) struct Session {
+    pub user: User,
+    pub claims: jwt::Claims,
+}
+
+// 校验令牌签名、有效期与授权范围, 并确认令牌未被注销
 #[async_trait]
-impl<B> FromRequest<B> for User
+impl<B> FromRequest<B> for Session
 where
     B: Send,
 {
@@ -85,7 +143,183 @@ where
             .map_err(Error::from)?;
 
         let claims = jwt::verify(bearer.token())?;
-        let user = svc.get(claims.sub).await?;
-        Ok(user)
+        let required = match *req.method() {
+            Method::GET | Method::HEAD | Method::OPTIONS => jwt::Scope::Read,
+            _ => jwt::Scope::Write,
+        };
+        if !claims.has_scope(required) {
+            return Err(Error::Forbidden.into());
+        }
+        let user = svc.authenticate(claims.sub, claims.jti, claims.iat).await?;
+        Ok(Session { user, claims })
+    }
+}
+
+// 从请求中直接获取到用户信息
+#[async_trait]
+impl<B> FromRequest<B> for User
+where
+    B: Send,
+{
+    type Rejection = ApiError;
+
+    async fn from_request(req: &mut RequestParts<B>) -> Result<Self, Self::Rejection> {
+        Ok(Session::from_request(req).await?.user)
+    }
+}
+
+/// 当前用户及其系统权限
+pub(crate) struct Principal {
+    pub user: User,
+    pub permissions: HashSet<Permission>,
+}
+
+impl Principal {
+    pub fn can(&self, permission: Permission) -> bool {
+        self.permissions.contains(&permission)
+    }
+
+    /// 操作对象为本人或持有 permission 时通过, 否则返回 403
+    pub fn require_self_or(&self, user_id: Uuid, permission: Permission) -> Result<(), Error> {
+        if self.user.id == user_id || self.can(permission) {
+            Ok(())
+        } else {
+            Err(Error::Forbidden)
+        }
+    }
+}
+
+// 从请求中获取用户并加载其系统权限
+#[async_trait]
+impl<B> FromRequest<B> for Principal
+where
+    B: Send,
+{
+    type Rejection = ApiError;
+
+    async fn from_request(req: &mut RequestParts<B>) -> Result<Self, Self::Rejection> {
+        let user = User::from_request(req).await?;
+        let Extension(rbac) = Extension::<DynRbacService>::from_request(req)
+            .await
+            .map_err(Error::from)?;
+        let permissions = rbac.permissions(user.id).await?;
+        Ok(Principal { user, permissions })
+    }
+}
+
+/// 路由所需的系统权限
+pub(crate) trait RequiredPermission {
+    const PERMISSION: Permission;
+}
+
+/// 查看任意用户
+pub(crate) struct ReadUsers;
+/// 授予与撤销角色
+pub(crate) struct WriteRoles;
+/// 写入汇率
+pub(crate) struct WriteRates;
+
+impl RequiredPermission for ReadUsers {
+    const PERMISSION: Permission = Permission::UsersRead;
+}
+
+impl RequiredPermission for WriteRoles {
+    const PERMISSION: Permission = Permission::RolesWrite;
+}
+
+impl RequiredPermission for WriteRates {
+    const PERMISSION: Permission = Permission::RatesWrite;
+}
+
+/// 持有权限 P 的当前用户, 否则返回 403
+pub(crate) struct Permitted<P> {
+    pub principal: Principal,
+    required: PhantomData<P>,
+}
+
+#[async_trait]
+impl<B, P> FromRequest<B> for Permitted<P>
+where
+    B: Send,
+    P: RequiredPermission + Send,
+{
+    type Rejection = ApiError;
+
+    async fn from_request(req: &mut RequestParts<B>) -> Result<Self, Self::Rejection> {
+        let principal = Principal::from_request(req).await?;
+        if !principal.can(P::PERMISSION) {
+            return Err(Error::Forbidden.into());
+        }
+        Ok(Permitted {
+            principal,
+            required: PhantomData,
+        })
+    }
+}
+
+/// 访问账本所需的最低角色
+pub(crate) trait RequiredRole {
+    const ROLE: LedgerRole;
+}
+
+/// 只读访问
+pub(crate) struct Viewer;
+/// 记账等修改账本数据的访问
+pub(crate) struct Editor;
+/// 管理账本设置与成员的访问
+pub(crate) struct Owner;
+
+impl RequiredRole for Viewer {
+    const ROLE: LedgerRole = LedgerRole::Viewer;
+}
+
+impl RequiredRole for Editor {
+    const ROLE: LedgerRole = LedgerRole::Editor;
+}
+
+impl RequiredRole for Owner {
+    const ROLE: LedgerRole = LedgerRole::Owner;
+}
+
+/// 当前用户对路径参数 ledger_id 所指账本的访问权限, 不是成员或角色低于 R 时返回 403
+pub(crate) struct LedgerAccess<R> {
+    pub user: User,
+    pub ledger: Ledger,
+    pub role: LedgerRole,
+    required: PhantomData<R>,
+}
+
+// 从请求中获取用户及其账本角色并校验
+#[async_trait]
+impl<B, R> FromRequest<B> for LedgerAccess<R>
+where
+    B: Send,
+    R: RequiredRole + Send,
+{
+    type Rejection = ApiError;
+
+    async fn from_request(req: &mut RequestParts<B>) -> Result<Self, Self::Rejection> {
+        let user = User::from_request(req).await?;
+        let Path(params) = Path::<HashMap<String, String>>::from_request(req)
+            .await
+            .map_err(Error::from)?;
+        let ledger_id = params
+            .get("ledger_id")
+            .and_then(|id| Uuid::parse_str(id).ok())
+            .ok_or_else(|| Error::new_empty_fields_error("invalid ledger_id".to_string()))?;
+        let Extension(ledgers) = Extension::<DynLedgerService>::from_request(req)
+            .await
+            .map_err(Error::from)?;
+
+        let (ledger, role) = ledgers.access(user.id, ledger_id).await?;
+        if role < R::ROLE {
+            return Err(Error::Forbidden.into());
+        }
+        Ok(LedgerAccess {
+            user,
+            ledger,
+            role,
+            required: PhantomData,
+        })
     }
 }
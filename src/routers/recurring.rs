@@ -0,0 +1,206 @@
+use super::{ApiResponse, Editor, LedgerAccess, Viewer};
+use crate::{
+    dto::validate_payload,
+    errors::ApiResult,
+    services::{
+        ledger::{DynLedgerService, LedgerServiceImpl},
+        recurring::{
+            CreateRecurringInput, DynRecurringService, RecurringEntry, RecurringServiceImpl,
+        },
+    },
+};
+use axum::{
+    extract::{Extension, Path},
+    routing::{get, post},
+    AddExtensionLayer, Json, Router,
+};
+use sqlx::postgres::PgPool;
+use std::sync::Arc;
+use uuid::Uuid;
+
+pub(crate) fn router(pool: Arc<PgPool>) -> Router {
+    let recurring_svc: DynRecurringService = Arc::new(RecurringServiceImpl::new(pool.clone()));
+    let ledger_svc: DynLedgerService = Arc::new(LedgerServiceImpl::new(pool));
+    configure(recurring_svc, ledger_svc)
+}
+
+fn configure(recurring_svc: DynRecurringService, ledger_svc: DynLedgerService) -> Router {
+    Router::new()
+        .route(
+            "/:ledger_id/recurring",
+            post(create_recurring).get(list_recurring),
+        )
+        .route(
+            "/:ledger_id/recurring/:recurring_id",
+            get(get_recurring).delete(delete_recurring),
+        )
+        .route(
+            "/:ledger_id/recurring/:recurring_id/resume",
+            post(resume_recurring),
+        )
+        .layer(&AddExtensionLayer::new(recurring_svc))
+        .layer(&AddExtensionLayer::new(ledger_svc))
+}
+
+async fn create_recurring(
+    access: LedgerAccess<Editor>,
+    Extension(svc): Extension<DynRecurringService>,
+    Json(input): Json<CreateRecurringInput>,
+) -> ApiResult<ApiResponse<RecurringEntry>> {
+    let ledger = access.ledger;
+    validate_payload(&input)?;
+    Ok(ApiResponse::success(svc.create(&ledger, input).await?))
+}
+
+async fn get_recurring(
+    _: LedgerAccess<Viewer>,
+    Extension(svc): Extension<DynRecurringService>,
+    Path((ledger_id, id)): Path<(Uuid, Uuid)>,
+) -> ApiResult<ApiResponse<RecurringEntry>> {
+    Ok(ApiResponse::success(svc.get(ledger_id, id).await?))
+}
+
+async fn delete_recurring(
+    _: LedgerAccess<Editor>,
+    Extension(svc): Extension<DynRecurringService>,
+    Path((ledger_id, id)): Path<(Uuid, Uuid)>,
+) -> ApiResult<ApiResponse<RecurringEntry>> {
+    Ok(ApiResponse::success(svc.delete(ledger_id, id).await?))
+}
+
+async fn resume_recurring(
+    _: LedgerAccess<Editor>,
+    Extension(svc): Extension<DynRecurringService>,
+    Path((ledger_id, id)): Path<(Uuid, Uuid)>,
+) -> ApiResult<ApiResponse<RecurringEntry>> {
+    Ok(ApiResponse::success(svc.resume(ledger_id, id).await?))
+}
+
+async fn list_recurring(
+    _: LedgerAccess<Viewer>,
+    Extension(svc): Extension<DynRecurringService>,
+    Path(ledger_id): Path<Uuid>,
+) -> ApiResult<ApiResponse<Vec<RecurringEntry>>> {
+    Ok(ApiResponse::success(svc.list(ledger_id).await?))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        models::{ledger::Ledger, member::LedgerRole, user::User},
+        routers::jwt,
+        services::{
+            auth::{DynAuthService, MockAuthService},
+            ledger::MockLedgerService,
+            recurring::MockRecurringService,
+        },
+    };
+    use axum::{
+        body::Body,
+        http::{self, request::Request, StatusCode},
+    };
+    use chrono::NaiveDate;
+    use std::sync::Arc;
+    use tower::ServiceExt;
+
+    fn configure_with_user(recurring_svc: MockRecurringService, user_id: Uuid) -> Router {
+        let mut auth_svc = MockAuthService::new();
+        auth_svc.expect_authenticate().returning(move |_, _, _| {
+            Ok(User {
+                id: user_id,
+                ..Default::default()
+            })
+        });
+        let mut ledger_svc = MockLedgerService::new();
+        ledger_svc.expect_access().returning(|user_id, id| {
+            let ledger = Ledger {
+                id,
+                owner_id: Some(user_id),
+                ..Default::default()
+            };
+            Ok((ledger, LedgerRole::Owner))
+        });
+        let auth_svc: DynAuthService = Arc::new(auth_svc);
+        configure(Arc::new(recurring_svc), Arc::new(ledger_svc))
+            .layer(&AddExtensionLayer::new(auth_svc))
+    }
+
+    #[tokio::test]
+    async fn test_recurring_controller_create() {
+        let mut svc = MockRecurringService::new();
+        svc.expect_create().returning(|ledger, input| {
+            assert_eq!(3, input.every);
+            Ok(RecurringEntry {
+                ledger_id: ledger.id,
+                every: input.every,
+                start_date: input.start_date,
+                next_run: Some(input.start_date),
+                ..Default::default()
+            })
+        });
+
+        std::env::set_var("JWT_SECRET", "example_secret_key");
+        let user_id = Uuid::new_v4();
+        let app = configure_with_user(svc, user_id);
+
+        let body = format!(
+            r#"{{"description":"insurance","frequency":"monthly","every":3,"start_date":"2022-01-15",
+            "postings":[{{"account_id":"{}","amount":900,"side":"debit"}},{{"account_id":"{}","amount":900,"side":"credit"}}]}}"#,
+            Uuid::new_v4(),
+            Uuid::new_v4()
+        );
+        let response = app
+            .oneshot(
+                Request::builder()
+                    .method(http::Method::POST)
+                    .uri(format!("/{}/recurring", Uuid::new_v4()))
+                    .header(http::header::CONTENT_TYPE, mime::APPLICATION_JSON.as_ref())
+                    .header(
+                        http::header::AUTHORIZATION,
+                        format!("Bearer {}", jwt::sign(user_id).unwrap()),
+                    )
+                    .body(Body::from(body))
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+
+        assert_eq!(response.status(), StatusCode::OK);
+        let body = hyper::body::to_bytes(response.into_body()).await.unwrap();
+        let value: serde_json::Value = serde_json::from_slice(&body).unwrap();
+        assert_eq!(
+            NaiveDate::from_ymd(2022, 1, 15).to_string(),
+            value["data"]["next_run"]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_recurring_controller_create_without_postings() {
+        let mut svc = MockRecurringService::new();
+        svc.expect_create().never();
+
+        std::env::set_var("JWT_SECRET", "example_secret_key");
+        let user_id = Uuid::new_v4();
+        let app = configure_with_user(svc, user_id);
+
+        let body = r#"{"description":"empty","frequency":"weekly","start_date":"2022-01-15","postings":[]}"#;
+        let response = app
+            .oneshot(
+                Request::builder()
+                    .method(http::Method::POST)
+                    .uri(format!("/{}/recurring", Uuid::new_v4()))
+                    .header(http::header::CONTENT_TYPE, mime::APPLICATION_JSON.as_ref())
+                    .header(
+                        http::header::AUTHORIZATION,
+                        format!("Bearer {}", jwt::sign(user_id).unwrap()),
+                    )
+                    .body(Body::from(body))
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+    }
+}
@@ -0,0 +1,246 @@
+use super::{ApiResponse, LedgerAccess, Viewer};
+use crate::{
+    dto::validate_payload,
+    errors::{ApiResult, Error},
+    services::{
+        ledger::{DynLedgerService, LedgerServiceImpl},
+        report::{
+            AccountBalance, AsOfInput, CashFlow, DynReportService, IncomeStatement, PeriodInput,
+            ReportServiceImpl, TrialBalance,
+        },
+    },
+};
+use axum::{
+    extract::{Extension, Path, Query},
+    routing::get,
+    AddExtensionLayer, Router,
+};
+use sqlx::postgres::PgPool;
+use std::sync::Arc;
+use uuid::Uuid;
+
+pub(crate) fn router(pool: Arc<PgPool>) -> Router {
+    let report_svc: DynReportService = Arc::new(ReportServiceImpl::new(pool.clone()));
+    let ledger_svc: DynLedgerService = Arc::new(LedgerServiceImpl::new(pool));
+    configure(report_svc, ledger_svc)
+}
+
+fn configure(report_svc: DynReportService, ledger_svc: DynLedgerService) -> Router {
+    Router::new()
+        .route("/:ledger_id/balances", get(balances))
+        .route("/:ledger_id/trial-balance", get(trial_balance))
+        .route(
+            "/:ledger_id/reports/income-statement",
+            get(income_statement),
+        )
+        .route("/:ledger_id/reports/cash-flow", get(cash_flow))
+        .layer(&AddExtensionLayer::new(report_svc))
+        .layer(&AddExtensionLayer::new(ledger_svc))
+}
+
+async fn balances(
+    _: LedgerAccess<Viewer>,
+    Extension(svc): Extension<DynReportService>,
+    Path(ledger_id): Path<Uuid>,
+    Query(input): Query<AsOfInput>,
+) -> ApiResult<ApiResponse<Vec<AccountBalance>>> {
+    validate_payload(&input)?;
+    Ok(ApiResponse::success(svc.balances(ledger_id, input).await?))
+}
+
+async fn trial_balance(
+    _: LedgerAccess<Viewer>,
+    Extension(svc): Extension<DynReportService>,
+    Path(ledger_id): Path<Uuid>,
+    Query(input): Query<AsOfInput>,
+) -> ApiResult<ApiResponse<TrialBalance>> {
+    validate_payload(&input)?;
+    Ok(ApiResponse::success(
+        svc.trial_balance(ledger_id, input).await?,
+    ))
+}
+
+fn check_period(input: &PeriodInput) -> ApiResult<()> {
+    if !input.check() {
+        return Err(
+            Error::new_empty_fields_error("from must not be later than to".to_string()).into(),
+        );
+    }
+    validate_payload(input)?;
+    Ok(())
+}
+
+async fn income_statement(
+    _: LedgerAccess<Viewer>,
+    Extension(svc): Extension<DynReportService>,
+    Path(ledger_id): Path<Uuid>,
+    Query(input): Query<PeriodInput>,
+) -> ApiResult<ApiResponse<IncomeStatement>> {
+    check_period(&input)?;
+    Ok(ApiResponse::success(
+        svc.income_statement(ledger_id, input).await?,
+    ))
+}
+
+async fn cash_flow(
+    _: LedgerAccess<Viewer>,
+    Extension(svc): Extension<DynReportService>,
+    Path(ledger_id): Path<Uuid>,
+    Query(input): Query<PeriodInput>,
+) -> ApiResult<ApiResponse<CashFlow>> {
+    check_period(&input)?;
+    Ok(ApiResponse::success(svc.cash_flow(ledger_id, input).await?))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        models::{ledger::Ledger, member::LedgerRole, report::ReportGrouping, user::User},
+        routers::jwt,
+        services::{
+            auth::{DynAuthService, MockAuthService},
+            ledger::MockLedgerService,
+            report::MockReportService,
+        },
+    };
+    use axum::{
+        body::Body,
+        http::{self, request::Request, StatusCode},
+    };
+    use chrono::NaiveDate;
+    use std::sync::Arc;
+    use tower::ServiceExt;
+
+    fn configure_with_user(report_svc: MockReportService, user_id: Uuid) -> Router {
+        let mut auth_svc = MockAuthService::new();
+        auth_svc.expect_authenticate().returning(move |_, _, _| {
+            Ok(User {
+                id: user_id,
+                ..Default::default()
+            })
+        });
+        let mut ledger_svc = MockLedgerService::new();
+        ledger_svc.expect_access().returning(|user_id, id| {
+            let ledger = Ledger {
+                id,
+                owner_id: Some(user_id),
+                ..Default::default()
+            };
+            Ok((ledger, LedgerRole::Owner))
+        });
+        let auth_svc: DynAuthService = Arc::new(auth_svc);
+        configure(Arc::new(report_svc), Arc::new(ledger_svc))
+            .layer(&AddExtensionLayer::new(auth_svc))
+    }
+
+    #[tokio::test]
+    async fn test_report_controller_trial_balance_as_of() {
+        let mut svc = MockReportService::new();
+        svc.expect_trial_balance()
+            .withf(|_, input| input.as_of == Some(NaiveDate::from_ymd(2022, 3, 31)))
+            .returning(|_, input| Ok(TrialBalance::new(input.as_of.unwrap(), Vec::new())));
+
+        std::env::set_var("JWT_SECRET", "example_secret_key");
+        let user_id = Uuid::new_v4();
+        let app = configure_with_user(svc, user_id);
+
+        let response = app
+            .oneshot(
+                Request::builder()
+                    .method(http::Method::GET)
+                    .uri(format!(
+                        "/{}/trial-balance?as_of=2022-03-31",
+                        Uuid::new_v4()
+                    ))
+                    .header(
+                        http::header::AUTHORIZATION,
+                        format!("Bearer {}", jwt::sign(user_id).unwrap()),
+                    )
+                    .body(Body::empty())
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+
+        assert_eq!(response.status(), StatusCode::OK);
+        let body = hyper::body::to_bytes(response.into_body()).await.unwrap();
+        let trial_balance: ApiResponse<TrialBalance> = serde_json::from_slice(&body).unwrap();
+        assert!(trial_balance.data.unwrap().balanced);
+    }
+
+    #[tokio::test]
+    async fn test_report_controller_income_statement() {
+        let mut svc = MockReportService::new();
+        svc.expect_income_statement()
+            .withf(|_, input| {
+                input.from == Some(NaiveDate::from_ymd(2022, 1, 1))
+                    && input.group_by == ReportGrouping::Quarter
+            })
+            .returning(|_, input| {
+                Ok(IncomeStatement::new(
+                    input.from.unwrap(),
+                    input.to.unwrap(),
+                    input.group_by,
+                    Vec::new(),
+                ))
+            });
+
+        std::env::set_var("JWT_SECRET", "example_secret_key");
+        let user_id = Uuid::new_v4();
+        let app = configure_with_user(svc, user_id);
+
+        let response = app
+            .oneshot(
+                Request::builder()
+                    .method(http::Method::GET)
+                    .uri(format!(
+                        "/{}/reports/income-statement?from=2022-01-01&to=2022-06-30&group_by=quarter",
+                        Uuid::new_v4()
+                    ))
+                    .header(
+                        http::header::AUTHORIZATION,
+                        format!("Bearer {}", jwt::sign(user_id).unwrap()),
+                    )
+                    .body(Body::empty())
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+
+        assert_eq!(response.status(), StatusCode::OK);
+        let body = hyper::body::to_bytes(response.into_body()).await.unwrap();
+        let statement: ApiResponse<IncomeStatement> = serde_json::from_slice(&body).unwrap();
+        assert_eq!(2, statement.data.unwrap().periods.len());
+    }
+
+    #[tokio::test]
+    async fn test_report_controller_cash_flow_reversed_range() {
+        let mut svc = MockReportService::new();
+        svc.expect_cash_flow().never();
+
+        std::env::set_var("JWT_SECRET", "example_secret_key");
+        let user_id = Uuid::new_v4();
+        let app = configure_with_user(svc, user_id);
+
+        let response = app
+            .oneshot(
+                Request::builder()
+                    .method(http::Method::GET)
+                    .uri(format!(
+                        "/{}/reports/cash-flow?from=2022-06-30&to=2022-01-01",
+                        Uuid::new_v4()
+                    ))
+                    .header(
+                        http::header::AUTHORIZATION,
+                        format!("Bearer {}", jwt::sign(user_id).unwrap()),
+                    )
+                    .body(Body::empty())
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+    }
+}
@@ -1,14 +1,19 @@
-use super::ApiResponse;
+use super::{ApiResponse, Permitted, Principal, ReadUsers, WriteRoles};
 use crate::{
     dto::validate_payload,
     errors::{ApiResult, Error},
-    services::user::{
-        DynUserService, ListUserInput, RegisterInput, UpdateUserInput, User, UserServiceImpl,
+    models::rbac::{Permission, Role},
+    services::{
+        rbac::{DynRbacService, RbacServiceImpl},
+        user::{
+            DynUserService, ListUserInput, RegisterInput, UpdateUserInput, User, UserServiceImpl,
+        },
+        verification::{DynVerificationService, VerificationServiceImpl},
     },
 };
 use axum::{
     extract::{Extension, Path, Query},
-    routing::{get, post},
+    routing::{get, post, put},
     AddExtensionLayer, Json, Router,
 };
 use sqlx::postgres::PgPool;
@@ -16,56 +21,89 @@ use std::sync::Arc;
 use uuid::Uuid;
 
 pub(crate) fn router(pool: Arc<PgPool>) -> Router {
-    let user_svc: DynUserService = Arc::new(UserServiceImpl::new(pool));
-    configure(user_svc)
+    let user_svc: DynUserService = Arc::new(UserServiceImpl::new(pool.clone()));
+    let rbac_svc: DynRbacService = Arc::new(RbacServiceImpl::new(pool.clone()));
+    let verification_svc: DynVerificationService = Arc::new(VerificationServiceImpl::new(pool));
+    configure(user_svc, rbac_svc, verification_svc)
 }
 
-fn configure(user_svc: DynUserService) -> Router {
+fn configure(
+    user_svc: DynUserService,
+    rbac_svc: DynRbacService,
+    verification_svc: DynVerificationService,
+) -> Router {
     Router::new()
         .route("/", post(create_user).get(list_user))
         .route(
             "/:user_id",
             get(get_user).delete(delete_user).put(update_user),
         )
+        .route("/:user_id/roles", get(list_role))
+        .route("/:user_id/roles/:role", put(grant_role).delete(revoke_role))
         .layer(&AddExtensionLayer::new(user_svc))
+        .layer(&AddExtensionLayer::new(rbac_svc))
+        .layer(&AddExtensionLayer::new(verification_svc))
+}
+
+// 验证邮件在后台发送, 生成邮件失败也不影响注册或修改, 用户可稍后重新发送
+async fn send_verification(svc: &DynVerificationService, user: &User) {
+    if let Err(err) = svc.send(user).await {
+        tracing::error!("send verification email to user {} failed: {err}", user.id);
+    }
 }
 
 async fn create_user(
     Extension(svc): Extension<DynUserService>,
+    Extension(verification): Extension<DynVerificationService>,
     Json(input): Json<RegisterInput>,
 ) -> ApiResult<ApiResponse<User>> {
     validate_payload(&input)?;
-    Ok(ApiResponse::success(svc.create(input).await?))
+    let user = svc.create(input).await?;
+    send_verification(&verification, &user).await;
+    Ok(ApiResponse::success(user))
 }
 
 async fn get_user(
+    principal: Principal,
     Extension(svc): Extension<DynUserService>,
     Path(id): Path<Uuid>,
 ) -> ApiResult<ApiResponse<User>> {
-    // Ok(svc.get(id).await?.into())
+    principal.require_self_or(id, Permission::UsersRead)?;
     Ok(ApiResponse::success(svc.get(id).await?))
 }
 
 async fn delete_user(
+    principal: Principal,
     Extension(svc): Extension<DynUserService>,
     Path(id): Path<Uuid>,
 ) -> ApiResult<ApiResponse<User>> {
+    principal.require_self_or(id, Permission::UsersWrite)?;
     Ok(ApiResponse::success(svc.delete(id).await?))
 }
 
 async fn update_user(
+    principal: Principal,
     Extension(svc): Extension<DynUserService>,
+    Extension(verification): Extension<DynVerificationService>,
     Path(id): Path<Uuid>,
     Json(input): Json<UpdateUserInput>,
 ) -> ApiResult<ApiResponse<User>> {
+    principal.require_self_or(id, Permission::UsersWrite)?;
     if !input.check() {
         return Err(Error::new_empty_fields_error("update user failed".to_string()).into());
     }
     validate_payload(&input)?;
-    Ok(ApiResponse::success(svc.update(id, input).await?))
+    let email_changed = input.email.is_some();
+    let user = svc.update(id, input).await?;
+    // 修改邮箱后需重新验证
+    if email_changed {
+        send_verification(&verification, &user).await;
+    }
+    Ok(ApiResponse::success(user))
 }
 
 async fn list_user(
+    _: Permitted<ReadUsers>,
     Extension(svc): Extension<DynUserService>,
     Query(mut input): Query<ListUserInput>,
 ) -> ApiResult<ApiResponse<Vec<User>>> {
@@ -74,18 +112,100 @@ async fn list_user(
     Ok(ApiResponse::success(svc.list(input).await?))
 }
 
+async fn list_role(
+    principal: Principal,
+    Extension(svc): Extension<DynRbacService>,
+    Path(id): Path<Uuid>,
+) -> ApiResult<ApiResponse<Vec<Role>>> {
+    principal.require_self_or(id, Permission::UsersRead)?;
+    Ok(ApiResponse::success(svc.roles(id).await?))
+}
+
+async fn grant_role(
+    admin: Permitted<WriteRoles>,
+    Extension(svc): Extension<DynRbacService>,
+    Path((id, role)): Path<(Uuid, String)>,
+) -> ApiResult<ApiResponse<Role>> {
+    let role = svc.grant(id, role).await?;
+    tracing::info!(
+        "user {} granted role {} for user {id}",
+        admin.principal.user.id,
+        role.name
+    );
+    Ok(ApiResponse::success(role))
+}
+
+async fn revoke_role(
+    admin: Permitted<WriteRoles>,
+    Extension(svc): Extension<DynRbacService>,
+    Path((id, role)): Path<(Uuid, String)>,
+) -> ApiResult<ApiResponse<Role>> {
+    let role = svc.revoke(id, role).await?;
+    tracing::info!(
+        "user {} revoked role {} for user {id}",
+        admin.principal.user.id,
+        role.name
+    );
+    Ok(ApiResponse::success(role))
+}
+
 #[cfg(test)]
 mod tests {
 
     use super::*;
-    use crate::{routers::jwt, services::user::MockUserService};
+    use crate::{
+        routers::jwt,
+        services::{
+            auth::{DynAuthService, MockAuthService},
+            rbac::MockRbacService,
+            user::MockUserService,
+            verification::MockVerificationService,
+        },
+    };
     use axum::{
         body::Body,
         http::{self, request::Request, StatusCode},
     };
-    use std::sync::Arc;
+    use std::{collections::HashSet, sync::Arc};
     use tower::ServiceExt;
 
+    fn configure_with_user(
+        user_svc: MockUserService,
+        user_id: Uuid,
+        permissions: &'static [Permission],
+    ) -> Router {
+        let mut auth_svc = MockAuthService::new();
+        auth_svc.expect_authenticate().returning(move |_, _, _| {
+            Ok(User {
+                id: user_id,
+                ..Default::default()
+            })
+        });
+        let mut rbac_svc = MockRbacService::new();
+        rbac_svc
+            .expect_permissions()
+            .returning(move |_| Ok(permissions.iter().copied().collect::<HashSet<_>>()));
+        let auth_svc: DynAuthService = Arc::new(auth_svc);
+        configure(
+            Arc::new(user_svc),
+            Arc::new(rbac_svc),
+            Arc::new(MockVerificationService::new()),
+        )
+        .layer(&AddExtensionLayer::new(auth_svc))
+    }
+
+    fn request(method: http::Method, uri: String, user_id: Uuid) -> Request<Body> {
+        Request::builder()
+            .method(method)
+            .uri(uri)
+            .header(
+                http::header::AUTHORIZATION,
+                format!("Bearer {}", jwt::sign(user_id).unwrap()),
+            )
+            .body(Body::empty())
+            .unwrap()
+    }
+
     #[tokio::test]
     async fn test_user_controller_create() {
         let mut svc = MockUserService::new();
@@ -95,12 +215,24 @@ mod tests {
                 name: input.name,
                 email: input.email,
                 password: input.password,
+                verified_at: None,
                 created_at: chrono::Utc::now(),
                 updated_at: chrono::Utc::now(),
             })
         });
 
-        let app = configure(Arc::new(svc));
+        let mut verification_svc = MockVerificationService::new();
+        verification_svc
+            .expect_send()
+            .withf(|user| user.email == "shenshouer51@gmail.com")
+            .times(1)
+            .returning(|_| Err(Error::Mail("connection refused".to_string())));
+
+        let app = configure(
+            Arc::new(svc),
+            Arc::new(MockRbacService::new()),
+            Arc::new(verification_svc),
+        );
 
         let create_user_param = &RegisterInput {
             name: "testname".to_string(),
@@ -143,21 +275,13 @@ mod tests {
                 ..Default::default()
             })
         });
-        let app = configure(Arc::new(svc));
 
+        std::env::set_var("JWT_SECRET", "example_secret_key");
         let uid = Uuid::new_v4();
+        let app = configure_with_user(svc, uid, &[]);
+
         let response = app
-            .oneshot(
-                Request::builder()
-                    .method(http::Method::GET)
-                    .uri(format!("/{}", uid))
-                    .header(
-                        http::header::AUTHORIZATION,
-                        format!("Bearer {}", jwt::sign(uuid::Uuid::new_v4()).unwrap()),
-                    )
-                    .body(Body::empty())
-                    .unwrap(),
-            )
+            .oneshot(request(http::Method::GET, format!("/{}", uid), uid))
             .await
             .unwrap();
 
@@ -167,4 +291,63 @@ mod tests {
         let user: ApiResponse<User> = serde_json::from_slice(&body).unwrap();
         assert_eq!(uid, user.data.unwrap().id);
     }
+
+    #[tokio::test]
+    async fn test_user_controller_requires_permission_for_other_users() {
+        let mut svc = MockUserService::new();
+        svc.expect_get().never();
+        svc.expect_delete().never();
+        svc.expect_list().never();
+
+        std::env::set_var("JWT_SECRET", "example_secret_key");
+        let uid = Uuid::new_v4();
+        let app = configure_with_user(svc, uid, &[]);
+        let other = Uuid::new_v4();
+
+        for (method, uri) in [
+            (http::Method::GET, format!("/{}", other)),
+            (http::Method::DELETE, format!("/{}", other)),
+            (http::Method::GET, "/".to_string()),
+            (http::Method::PUT, format!("/{}/roles/admin", uid)),
+        ] {
+            let response = app
+                .clone()
+                .oneshot(request(method, uri, uid))
+                .await
+                .unwrap();
+            assert_eq!(response.status(), StatusCode::FORBIDDEN);
+        }
+
+        let response = app
+            .oneshot(Request::get("/").body(Body::empty()).unwrap())
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+    }
+
+    #[tokio::test]
+    async fn test_user_controller_admin_deletes_other_user() {
+        let mut svc = MockUserService::new();
+        svc.expect_delete().times(1).returning(|id| {
+            Ok(User {
+                id,
+                ..Default::default()
+            })
+        });
+
+        std::env::set_var("JWT_SECRET", "example_secret_key");
+        let uid = Uuid::new_v4();
+        let app = configure_with_user(svc, uid, &[Permission::UsersWrite]);
+
+        let other = Uuid::new_v4();
+        let response = app
+            .oneshot(request(http::Method::DELETE, format!("/{}", other), uid))
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+
+        let body = hyper::body::to_bytes(response.into_body()).await.unwrap();
+        let user: ApiResponse<User> = serde_json::from_slice(&body).unwrap();
+        assert_eq!(other, user.data.unwrap().id);
+    }
 }
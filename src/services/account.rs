@@ -0,0 +1,316 @@
+pub(crate) use crate::{
+    dao::account_repo::{AccountRepo, AccountRepoImpl},
+    dto::account::{CreateAccountInput, MoveAccountInput, RenameAccountInput},
+    errors::{Error, Result},
+    models::account::{Account, AccountNode, CreateAccount},
+};
+use axum::async_trait;
+use chrono::Utc;
+use sqlx::postgres::PgPool;
+use std::{collections::HashMap, sync::Arc};
+use uuid::Uuid;
+
+pub type DynAccountService = Arc<dyn AccountService + Send + Sync>;
+
+#[cfg_attr(test, mockall::automock)]
+#[async_trait]
+pub trait AccountService {
+    async fn create(&self, ledger_id: Uuid, input: CreateAccountInput) -> Result<Account>;
+    async fn tree(&self, ledger_id: Uuid) -> Result<Vec<AccountNode>>;
+    async fn rename(&self, ledger_id: Uuid, id: Uuid, input: RenameAccountInput)
+        -> Result<Account>;
+    async fn move_to(&self, ledger_id: Uuid, id: Uuid, input: MoveAccountInput) -> Result<Account>;
+    async fn close(&self, ledger_id: Uuid, id: Uuid) -> Result<Account>;
+}
+
+#[derive(Clone)]
+pub struct AccountServiceImpl<T: AccountRepo>
+where
+    T: AccountRepo + Sync + Send,
+{
+    pub account_repo: T,
+}
+
+impl AccountServiceImpl<AccountRepoImpl> {
+    pub fn new(pool: Arc<PgPool>) -> Self {
+        AccountServiceImpl {
+            account_repo: AccountRepoImpl::new(pool),
+        }
+    }
+}
+
+impl<T> AccountServiceImpl<T>
+where
+    T: AccountRepo + Sync + Send,
+{
+    // 获取科目并校验其属于该账本
+    async fn get_in_ledger(&self, ledger_id: Uuid, id: Uuid) -> Result<Account> {
+        let account = self.account_repo.get(id).await?;
+        if account.ledger_id != ledger_id {
+            return Err(Error::Forbidden);
+        }
+        Ok(account)
+    }
+
+    // 校验父科目可以挂载同类别的子科目
+    async fn check_parent(&self, ledger_id: Uuid, parent_id: Uuid, child: &Account) -> Result<()> {
+        let parent = self.get_in_ledger(ledger_id, parent_id).await?;
+        if parent.is_closed() {
+            return Err(Error::InvalidAccount(format!(
+                "parent account {} is closed",
+                parent.name
+            )));
+        }
+        if parent.kind != child.kind {
+            return Err(Error::InvalidAccount(format!(
+                "parent account {} is of a different kind",
+                parent.name
+            )));
+        }
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl<T> AccountService for AccountServiceImpl<T>
+where
+    T: AccountRepo + Sync + Send,
+{
+    async fn create(&self, ledger_id: Uuid, input: CreateAccountInput) -> Result<Account> {
+        if let Some(parent_id) = input.parent_id {
+            let child = Account {
+                kind: input.kind,
+                ..Default::default()
+            };
+            self.check_parent(ledger_id, parent_id, &child).await?;
+        }
+
+        let account = CreateAccount {
+            ledger_id,
+            parent_id: input.parent_id,
+            name: input.name,
+            kind: input.kind,
+        };
+        Ok(self.account_repo.create(account).await?)
+    }
+
+    async fn tree(&self, ledger_id: Uuid) -> Result<Vec<AccountNode>> {
+        let accounts = self.account_repo.list(ledger_id).await?;
+        Ok(AccountNode::build_tree(accounts))
+    }
+
+    async fn rename(
+        &self,
+        ledger_id: Uuid,
+        id: Uuid,
+        input: RenameAccountInput,
+    ) -> Result<Account> {
+        let mut account = self.get_in_ledger(ledger_id, id).await?;
+        account.name = input.name;
+        Ok(self.account_repo.update(&account).await?)
+    }
+
+    async fn move_to(&self, ledger_id: Uuid, id: Uuid, input: MoveAccountInput) -> Result<Account> {
+        let mut account = self.get_in_ledger(ledger_id, id).await?;
+
+        if let Some(parent_id) = input.parent_id {
+            self.check_parent(ledger_id, parent_id, &account).await?;
+
+            // 新的父科目不能是自身或自身的子孙科目
+            let parents: HashMap<Uuid, Option<Uuid>> = self
+                .account_repo
+                .list(ledger_id)
+                .await?
+                .into_iter()
+                .map(|a| (a.id, a.parent_id))
+                .collect();
+            let mut cursor = Some(parent_id);
+            while let Some(ancestor) = cursor {
+                if ancestor == account.id {
+                    return Err(Error::InvalidAccount(
+                        "account cannot be moved under itself".to_string(),
+                    ));
+                }
+                cursor = parents.get(&ancestor).copied().flatten();
+            }
+        }
+
+        account.parent_id = input.parent_id;
+        Ok(self.account_repo.update(&account).await?)
+    }
+
+    async fn close(&self, ledger_id: Uuid, id: Uuid) -> Result<Account> {
+        let mut account = self.get_in_ledger(ledger_id, id).await?;
+        if account.is_closed() {
+            return Ok(account);
+        }
+
+        let has_open_children = self
+            .account_repo
+            .list(ledger_id)
+            .await?
+            .iter()
+            .any(|a| a.parent_id == Some(account.id) && !a.is_closed());
+        if has_open_children {
+            return Err(Error::InvalidAccount(format!(
+                "account {} has open sub-accounts",
+                account.name
+            )));
+        }
+
+        if let Some(money) = self.account_repo.balance(account.id).await?.pop() {
+            return Err(Error::AccountHasBalance(money.amount, money.currency));
+        }
+
+        account.closed_at = Some(Utc::now());
+        Ok(self.account_repo.update(&account).await?)
+    }
+}
+
+#[cfg(test)]
+pub mod tests {
+    use super::*;
+    use crate::{
+        dao::account_repo::MockAccountRepo,
+        models::{account::AccountKind, currency::Money},
+    };
+    use mockall::predicate::*;
+
+    #[tokio::test]
+    async fn test_account_service_create_under_parent() {
+        let ledger_id = Uuid::new_v4();
+        let parent_id = Uuid::new_v4();
+        let mut account_repo = MockAccountRepo::new();
+        account_repo
+            .expect_get()
+            .with(eq(parent_id))
+            .returning(move |id| {
+                Ok(Account {
+                    id,
+                    ledger_id,
+                    kind: AccountKind::Expense,
+                    ..Default::default()
+                })
+            });
+        account_repo.expect_create().times(1).returning(|param| {
+            Ok(Account {
+                ledger_id: param.ledger_id,
+                parent_id: param.parent_id,
+                name: param.name,
+                kind: param.kind,
+                ..Default::default()
+            })
+        });
+
+        let sut = AccountServiceImpl { account_repo };
+        let account = sut
+            .create(
+                ledger_id,
+                CreateAccountInput {
+                    name: "Groceries".to_string(),
+                    kind: AccountKind::Expense,
+                    parent_id: Some(parent_id),
+                },
+            )
+            .await
+            .unwrap();
+        assert_eq!(Some(parent_id), account.parent_id);
+
+        let result = sut
+            .create(
+                ledger_id,
+                CreateAccountInput {
+                    name: "Salary".to_string(),
+                    kind: AccountKind::Income,
+                    parent_id: Some(parent_id),
+                },
+            )
+            .await;
+        assert!(matches!(result, Err(Error::InvalidAccount(_))));
+    }
+
+    #[tokio::test]
+    async fn test_account_service_move_under_descendant() {
+        let ledger_id = Uuid::new_v4();
+        let root = Account {
+            ledger_id,
+            ..Default::default()
+        };
+        let child = Account {
+            ledger_id,
+            parent_id: Some(root.id),
+            ..Default::default()
+        };
+        let accounts = [root.clone(), child.clone()];
+
+        let mut account_repo = MockAccountRepo::new();
+        account_repo
+            .expect_get()
+            .returning(move |id| Ok(accounts.iter().find(|a| a.id == id).cloned().unwrap()));
+        let accounts = vec![root.clone(), child.clone()];
+        account_repo
+            .expect_list()
+            .returning(move |_| Ok(accounts.clone()));
+        account_repo.expect_update().never();
+
+        let sut = AccountServiceImpl { account_repo };
+        let result = sut
+            .move_to(
+                ledger_id,
+                root.id,
+                MoveAccountInput {
+                    parent_id: Some(child.id),
+                },
+            )
+            .await;
+        assert!(matches!(result, Err(Error::InvalidAccount(_))));
+    }
+
+    #[tokio::test]
+    async fn test_account_service_close_with_balance() {
+        let ledger_id = Uuid::new_v4();
+        let mut account_repo = MockAccountRepo::new();
+        account_repo.expect_get().returning(move |id| {
+            Ok(Account {
+                id,
+                ledger_id,
+                ..Default::default()
+            })
+        });
+        account_repo.expect_list().returning(|_| Ok(Vec::new()));
+        account_repo.expect_balance().returning(|_| {
+            Ok(vec![Money {
+                amount: 1500,
+                currency: "USD".to_string(),
+            }])
+        });
+        account_repo.expect_update().never();
+
+        let sut = AccountServiceImpl { account_repo };
+        let result = sut.close(ledger_id, Uuid::new_v4()).await;
+        assert!(matches!(result, Err(Error::AccountHasBalance(1500, _))));
+    }
+
+    #[tokio::test]
+    async fn test_account_service_close() {
+        let ledger_id = Uuid::new_v4();
+        let mut account_repo = MockAccountRepo::new();
+        account_repo.expect_get().returning(move |id| {
+            Ok(Account {
+                id,
+                ledger_id,
+                ..Default::default()
+            })
+        });
+        account_repo.expect_list().returning(|_| Ok(Vec::new()));
+        account_repo.expect_balance().returning(|_| Ok(Vec::new()));
+        account_repo
+            .expect_update()
+            .with(always())
+            .returning(|account| Ok(account.clone()));
+
+        let sut = AccountServiceImpl { account_repo };
+        let account = sut.close(ledger_id, Uuid::new_v4()).await.unwrap();
+        assert!(account.is_closed());
+    }
+}
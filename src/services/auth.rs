@@ -1,10 +1,17 @@
+use super::password::{hash_password, verify_password, Argon2Hasher, DynPasswordHasher};
 pub(crate) use crate::{
-    dao::user_repo::{UserRepo, UserRepoImpl},
+    config::env::VerificationConfig,
+    dao::{
+        revocation_repo::{RevocationRepo, RevocationRepoImpl},
+        token_repo::{RefreshTokenRepo, RefreshTokenRepoImpl},
+        user_repo::{UserRepo, UserRepoImpl},
+    },
     dto::auth::LoginInput,
-    errors::Result,
-    models::{auth::Credential, user::User},
+    errors::{Error, Result},
+    models::user::User,
 };
 use axum::async_trait;
+use chrono::{TimeZone, Utc};
 use sqlx::postgres::PgPool;
 use std::sync::Arc;
 use uuid::Uuid;
@@ -15,84 +22,331 @@ pub type DynAuthService = Arc<dyn AuthService + Send + Sync>;
 #[async_trait]
 pub trait AuthService {
     async fn sign_in(&self, input: LoginInput) -> Result<User>;
-    async fn get(&self, id: Uuid) -> Result<User>;
+    /// 校验 access token 未被撤销并返回其所属用户, issued_at 为签发时间戳
+    async fn authenticate(&self, user_id: Uuid, jti: Uuid, issued_at: i64) -> Result<User>;
+    /// 撤销当前 access token 及签发它的 refresh token family
+    async fn logout(
+        &self,
+        user_id: Uuid,
+        jti: Uuid,
+        expires_at: i64,
+        sid: Option<Uuid>,
+    ) -> Result<()>;
+    /// 撤销用户在所有设备上的令牌
+    async fn logout_all(&self, user_id: Uuid) -> Result<()>;
 }
 
 #[derive(Clone)]
-pub struct AuthServiceImpl<T: UserRepo>
+pub struct AuthServiceImpl<T, R, K>
 where
     T: UserRepo + Sync + Send,
+    R: RevocationRepo + Sync + Send,
+    K: RefreshTokenRepo + Sync + Send,
 {
     pub user_repo: T,
+    pub revocation_repo: R,
+    pub token_repo: K,
+    pub hasher: DynPasswordHasher,
+    /// 拒绝未验证邮箱的账号登录
+    pub require_verified_email: bool,
 }
 
-impl AuthServiceImpl<UserRepoImpl> {
+impl AuthServiceImpl<UserRepoImpl, RevocationRepoImpl, RefreshTokenRepoImpl> {
     pub fn new(pool: Arc<PgPool>) -> Self {
         AuthServiceImpl {
-            user_repo: UserRepoImpl::new(pool),
+            user_repo: UserRepoImpl::new(pool.clone()),
+            revocation_repo: RevocationRepoImpl::new(pool.clone()),
+            token_repo: RefreshTokenRepoImpl::new(pool),
+            hasher: Arc::new(Argon2Hasher::from_env()),
+            require_verified_email: VerificationConfig::from_env().require_email_verification,
         }
     }
 }
 
 #[async_trait]
-impl<T> AuthService for AuthServiceImpl<T>
+impl<T, R, K> AuthService for AuthServiceImpl<T, R, K>
 where
     T: UserRepo + Sync + Send,
+    R: RevocationRepo + Sync + Send,
+    K: RefreshTokenRepo + Sync + Send,
 {
     async fn sign_in(&self, input: LoginInput) -> Result<User> {
-        let credential = Credential {
-            email: input.email,
-            password: input.password,
+        let mut user = match self.user_repo.get_by_email(&input.email).await {
+            Ok(user) => user,
+            Err(Error::DataStore(sqlx::Error::RowNotFound)) => {
+                // 用户不存在时同样计算一次哈希, 避免按响应耗时判断邮箱是否注册
+                hash_password(&self.hasher, input.password).await?;
+                return Err(Error::WrongCredentials);
+            }
+            Err(err) => return Err(err),
         };
-        self.user_repo.authenticate(credential).await
+        if !verify_password(&self.hasher, input.password.clone(), user.password.clone()).await {
+            return Err(Error::WrongCredentials);
+        }
+        // 密码正确后才提示未验证, 避免泄露邮箱的注册状态
+        if self.require_verified_email && user.verified_at.is_none() {
+            return Err(Error::EmailNotVerified);
+        }
+
+        // 明文或哈希参数已调整的密码在登录成功后重新哈希, 失败不影响登录
+        if self.hasher.needs_rehash(&user.password) {
+            let rehashed = hash_password(&self.hasher, input.password).await;
+            match rehashed {
+                Ok(hashed) => match self
+                    .user_repo
+                    .update_password(user.id, &user.password, &hashed)
+                    .await
+                {
+                    Ok(_) => user.password = hashed,
+                    Err(err) => tracing::warn!("rehash password of user {} failed: {err}", user.id),
+                },
+                Err(err) => tracing::warn!("rehash password of user {} failed: {err}", user.id),
+            }
+        }
+        Ok(user)
+    }
+
+    async fn authenticate(&self, user_id: Uuid, jti: Uuid, issued_at: i64) -> Result<User> {
+        if self
+            .revocation_repo
+            .is_revoked(jti, user_id, Utc.timestamp(issued_at, 0))
+            .await?
+        {
+            return Err(Error::TokenRevoked);
+        }
+        Ok(self.user_repo.get(user_id).await?)
     }
 
-    async fn get(&self, id: Uuid) -> Result<User> {
-        Ok(self.user_repo.get(id).await?)
+    async fn logout(
+        &self,
+        user_id: Uuid,
+        jti: Uuid,
+        expires_at: i64,
+        sid: Option<Uuid>,
+    ) -> Result<()> {
+        self.revocation_repo
+            .revoke(jti, user_id, Utc.timestamp(expires_at, 0))
+            .await?;
+        if let Some(family_id) = sid {
+            self.token_repo.revoke_family(user_id, family_id).await?;
+        }
+        Ok(())
+    }
+
+    async fn logout_all(&self, user_id: Uuid) -> Result<()> {
+        // 先撤销 refresh token, 避免在两步之间换取到新的 access token
+        self.token_repo.revoke_user(user_id).await?;
+        self.revocation_repo.revoke_all(user_id, Utc::now()).await?;
+        Ok(())
     }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::{dao::user_repo::MockUserRepo, errors::Error, models::user::User};
+    use crate::{
+        dao::{
+            revocation_repo::MockRevocationRepo, token_repo::MockRefreshTokenRepo,
+            user_repo::MockUserRepo,
+        },
+        services::password::{tests::hasher, PasswordHasher},
+    };
     use mockall::predicate::*;
 
+    fn login_input(password: &str) -> LoginInput {
+        LoginInput {
+            email: "shenshouer51@163.com".to_string(),
+            password: password.to_string(),
+        }
+    }
+
     #[tokio::test]
     async fn test_login_success() {
+        let hashed = hasher(64).hash("123456").unwrap();
         let mut user_repo = MockUserRepo::new();
         user_repo
-            .expect_authenticate()
-            .with(always())
-            .returning(|_| Ok(User::default()));
-        let sut = AuthServiceImpl { user_repo };
-
-        let input = LoginInput {
-            email: "shenshouer51@163.com".to_string(),
-            password: "123456".to_string(),
+            .expect_get_by_email()
+            .with(eq("shenshouer51@163.com"))
+            .returning(move |email| {
+                Ok(User {
+                    email: email.to_string(),
+                    password: hashed.clone(),
+                    ..Default::default()
+                })
+            });
+        user_repo.expect_update_password().never();
+        let sut = AuthServiceImpl {
+            user_repo,
+            revocation_repo: MockRevocationRepo::new(),
+            token_repo: MockRefreshTokenRepo::new(),
+            hasher: Arc::new(hasher(64)),
+            require_verified_email: false,
         };
-        let actual = sut.sign_in(input).await;
+
+        let actual = sut.sign_in(login_input("123456")).await;
         assert!(actual.is_ok());
     }
 
     #[tokio::test]
     async fn test_login_failure() {
-        let input = LoginInput {
-            email: "u".to_string(),
-            password: "p".to_string(),
+        let hashed = hasher(64).hash("123456").unwrap();
+        let mut user_repo = MockUserRepo::new();
+        user_repo.expect_get_by_email().returning(move |_| {
+            Ok(User {
+                password: hashed.clone(),
+                ..Default::default()
+            })
+        });
+        let sut = AuthServiceImpl {
+            user_repo,
+            revocation_repo: MockRevocationRepo::new(),
+            token_repo: MockRefreshTokenRepo::new(),
+            hasher: Arc::new(hasher(64)),
+            require_verified_email: false,
+        };
+
+        let actual = sut.sign_in(login_input("654321")).await;
+        assert!(matches!(actual, Err(Error::WrongCredentials)));
+    }
+
+    #[tokio::test]
+    async fn test_login_requires_verified_email() {
+        let hashed = hasher(64).hash("123456").unwrap();
+        let mut user_repo = MockUserRepo::new();
+        user_repo.expect_get_by_email().returning(move |_| {
+            Ok(User {
+                password: hashed.clone(),
+                ..Default::default()
+            })
+        });
+        let sut = AuthServiceImpl {
+            user_repo,
+            revocation_repo: MockRevocationRepo::new(),
+            token_repo: MockRefreshTokenRepo::new(),
+            hasher: Arc::new(hasher(64)),
+            require_verified_email: true,
+        };
+
+        let actual = sut.sign_in(login_input("123456")).await;
+        assert!(matches!(actual, Err(Error::EmailNotVerified)));
+        let actual = sut.sign_in(login_input("654321")).await;
+        assert!(matches!(actual, Err(Error::WrongCredentials)));
+    }
+
+    #[tokio::test]
+    async fn test_login_unknown_email() {
+        let mut user_repo = MockUserRepo::new();
+        user_repo
+            .expect_get_by_email()
+            .returning(|_| Err(Error::DataStore(sqlx::Error::RowNotFound)));
+        let sut = AuthServiceImpl {
+            user_repo,
+            revocation_repo: MockRevocationRepo::new(),
+            token_repo: MockRefreshTokenRepo::new(),
+            hasher: Arc::new(hasher(64)),
+            require_verified_email: false,
         };
+
+        let actual = sut.sign_in(login_input("123456")).await;
+        assert!(matches!(actual, Err(Error::WrongCredentials)));
+    }
+
+    #[tokio::test]
+    async fn test_login_propagates_datastore_error() {
         let mut user_repo = MockUserRepo::new();
         user_repo
-            .expect_authenticate()
-            .with(always())
-            .returning(|_| {
-                Err(Error::new_empty_fields_error(
-                    "mock login failure in auth service".to_string(),
-                ))
-            });
-        let sut = AuthServiceImpl { user_repo };
+            .expect_get_by_email()
+            .returning(|_| Err(Error::DataStore(sqlx::Error::PoolTimedOut)));
+        let sut = AuthServiceImpl {
+            user_repo,
+            revocation_repo: MockRevocationRepo::new(),
+            token_repo: MockRefreshTokenRepo::new(),
+            hasher: Arc::new(hasher(64)),
+            require_verified_email: false,
+        };
+
+        let actual = sut.sign_in(login_input("123456")).await;
+        assert!(matches!(
+            actual,
+            Err(Error::DataStore(sqlx::Error::PoolTimedOut))
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_login_rehashes_outdated_password() {
+        let outdated = hasher(64).hash("123456").unwrap();
+        let current = outdated.clone();
+        let mut user_repo = MockUserRepo::new();
+        user_repo.expect_get_by_email().returning(move |_| {
+            Ok(User {
+                password: outdated.clone(),
+                ..Default::default()
+            })
+        });
+        user_repo
+            .expect_update_password()
+            .withf(move |_, old, hashed| old == current && hashed.contains("$m=128,"))
+            .times(1)
+            .returning(|_, _, _| Ok(true));
+        let sut = AuthServiceImpl {
+            user_repo,
+            revocation_repo: MockRevocationRepo::new(),
+            token_repo: MockRefreshTokenRepo::new(),
+            hasher: Arc::new(hasher(128)),
+            require_verified_email: false,
+        };
+
+        let user = sut.sign_in(login_input("123456")).await.unwrap();
+        assert!(user.password.contains("$m=128,"));
+    }
+
+    #[tokio::test]
+    async fn test_authenticate_revoked_token() {
+        let mut user_repo = MockUserRepo::new();
+        user_repo.expect_get().never();
+        let mut revocation_repo = MockRevocationRepo::new();
+        revocation_repo
+            .expect_is_revoked()
+            .returning(|_, _, _| Ok(true));
+        let sut = AuthServiceImpl {
+            user_repo,
+            revocation_repo,
+            token_repo: MockRefreshTokenRepo::new(),
+            hasher: Arc::new(hasher(64)),
+            require_verified_email: false,
+        };
+
+        let actual = sut
+            .authenticate(Uuid::new_v4(), Uuid::new_v4(), Utc::now().timestamp())
+            .await;
+        assert!(matches!(actual, Err(Error::TokenRevoked)));
+    }
+
+    #[tokio::test]
+    async fn test_logout_revokes_session() {
+        let (user_id, jti, family_id) = (Uuid::new_v4(), Uuid::new_v4(), Uuid::new_v4());
+        let mut revocation_repo = MockRevocationRepo::new();
+        revocation_repo
+            .expect_revoke()
+            .withf(move |id, owner, _| *id == jti && *owner == user_id)
+            .times(1)
+            .returning(|_, _, _| Ok(()));
+        let mut token_repo = MockRefreshTokenRepo::new();
+        token_repo
+            .expect_revoke_family()
+            .with(eq(user_id), eq(family_id))
+            .times(1)
+            .returning(|_, _| Ok(1));
+        let sut = AuthServiceImpl {
+            user_repo: MockUserRepo::new(),
+            revocation_repo,
+            token_repo,
+            hasher: Arc::new(hasher(64)),
+            require_verified_email: false,
+        };
 
-        let actual = sut.sign_in(input).await;
-        assert!(actual.is_err());
+        sut.logout(user_id, jti, Utc::now().timestamp(), Some(family_id))
+            .await
+            .unwrap();
     }
 }
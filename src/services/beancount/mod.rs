@@ -0,0 +1,695 @@
+/// Beancount 文本解析
+mod parser;
+
+use self::parser::{Dated, Directive, Price, RawPosting, Transaction};
+use super::currency::{CurrencyServiceImpl, DynCurrencyService};
+pub(crate) use crate::{
+    dao::beancount_repo::{BeancountRepo, BeancountRepoImpl},
+    errors::Result,
+    models::{
+        account::{Account, AccountKind},
+        beancount::{
+            BeancountPlan, BeancountReport, LineError, NewAccount, PlannedEntry, PlannedPosting,
+        },
+        currency::UpsertExchangeRate,
+        entry::Side,
+        export::account_name,
+        ledger::Ledger,
+    },
+};
+use axum::async_trait;
+use chrono::NaiveDate;
+use rust_decimal::{prelude::ToPrimitive, Decimal};
+use sqlx::postgres::PgPool;
+use std::{
+    collections::{BTreeMap, HashMap, HashSet},
+    sync::Arc,
+};
+use uuid::Uuid;
+
+/// 带价格注解的过账行自动生成的货币兑换科目
+const CONVERSIONS_ACCOUNT: &str = "Equity:Conversions";
+
+pub type DynBeancountService = Arc<dyn BeancountService + Send + Sync>;
+
+#[cfg_attr(test, mockall::automock)]
+#[async_trait]
+pub trait BeancountService {
+    /// 将 Beancount 文本导入账本, 存在任何错误时不写入数据并在结果中逐行报告;
+    /// 汇率为所有账本共用, write_rates 为 false 时只校验 price 指令而不写入
+    async fn import(
+        &self,
+        ledger: &Ledger,
+        text: String,
+        write_rates: bool,
+    ) -> Result<BeancountReport>;
+}
+
+#[derive(Clone)]
+pub struct BeancountServiceImpl<T: BeancountRepo>
+where
+    T: BeancountRepo + Sync + Send,
+{
+    pub beancount_repo: T,
+    pub currencies: DynCurrencyService,
+}
+
+impl BeancountServiceImpl<BeancountRepoImpl> {
+    pub fn new(pool: Arc<PgPool>) -> Self {
+        BeancountServiceImpl {
+            beancount_repo: BeancountRepoImpl::new(pool.clone()),
+            currencies: Arc::new(CurrencyServiceImpl::new(pool)),
+        }
+    }
+}
+
+fn kind_of(root: &str) -> Option<AccountKind> {
+    match root {
+        "Assets" => Some(AccountKind::Asset),
+        "Liabilities" => Some(AccountKind::Liability),
+        "Equity" => Some(AccountKind::Equity),
+        "Income" => Some(AccountKind::Income),
+        "Expenses" => Some(AccountKind::Expense),
+        _ => None,
+    }
+}
+
+// 账本中已有科目按 Beancount 全名索引, 同时返回已关闭的科目
+fn existing_names(accounts: Vec<Account>) -> (HashMap<String, Uuid>, HashSet<String>) {
+    let by_id: HashMap<Uuid, &Account> = accounts.iter().map(|a| (a.id, a)).collect();
+    let mut names = HashMap::new();
+    let mut closed = HashSet::new();
+    for account in &accounts {
+        let mut path = vec![account.name.clone()];
+        let mut parent = account.parent_id.and_then(|id| by_id.get(&id));
+        while let Some(node) = parent {
+            path.insert(0, node.name.clone());
+            parent = node.parent_id.and_then(|id| by_id.get(&id));
+        }
+        let name = account_name(account.kind, &path);
+        if account.is_closed() {
+            closed.insert(name.clone());
+        }
+        names.insert(name, account.id);
+    }
+    (names, closed)
+}
+
+type Check<T> = std::result::Result<T, LineError>;
+
+fn fail<T>(line: usize, message: String) -> Check<T> {
+    Err(LineError { line, message })
+}
+
+// 金额来自上传文件, 溢出时报告所在行而不是 panic 或回绕
+fn checked_add(line: usize, left: i64, right: i64) -> Check<i64> {
+    match left.checked_add(right) {
+        Some(sum) => Ok(sum),
+        None => fail(line, "amount is out of range".to_string()),
+    }
+}
+
+/// 在内存中校验导入内容并生成写入计划
+struct Planner {
+    plan: BeancountPlan,
+    report: BeancountReport,
+    minor_units: HashMap<String, u32>,
+    /// 已存在或计划创建的科目
+    known: HashSet<String>,
+    /// 可以过账的科目
+    open: HashSet<String>,
+    /// 本文件中各科目按币种的余额, 借方为正
+    balances: HashMap<(String, String), i64>,
+}
+
+impl Planner {
+    fn new(ledger_id: Uuid, accounts: Vec<Account>, minor_units: HashMap<String, u32>) -> Self {
+        let (existing, closed) = existing_names(accounts);
+        let known: HashSet<String> = existing.keys().cloned().collect();
+        let open = known.difference(&closed).cloned().collect();
+        Planner {
+            plan: BeancountPlan {
+                ledger_id,
+                existing,
+                ..Default::default()
+            },
+            report: BeancountReport::default(),
+            minor_units,
+            known,
+            open,
+            balances: HashMap::new(),
+        }
+    }
+
+    // 计划创建科目及其缺失的上级科目, 上级科目在前
+    fn create_account(&mut self, line: usize, name: &str) -> Check<()> {
+        let components: Vec<&str> = name.split(':').collect();
+        let kind = match kind_of(components[0]) {
+            Some(kind) => kind,
+            None => return fail(line, format!("invalid account root {name}")),
+        };
+        for depth in 2..=components.len() {
+            let name = components[..depth].join(":");
+            if self.known.insert(name.clone()) {
+                self.plan.accounts.push(NewAccount {
+                    parent: Some(components[..depth - 1].join(":")).filter(|_| depth > 2),
+                    component: components[depth - 1].to_string(),
+                    name,
+                    kind,
+                });
+            }
+        }
+        Ok(())
+    }
+
+    fn minor_unit(&self, line: usize, currency: &str) -> Check<u32> {
+        match self.minor_units.get(currency) {
+            Some(minor_unit) => Ok(*minor_unit),
+            None => fail(line, format!("unknown currency {currency}")),
+        }
+    }
+
+    // 金额转换为最小货币单位, 小数位数超出货币精度时报错
+    fn to_minor(&self, line: usize, amount: Decimal, currency: &str) -> Check<i64> {
+        let minor_unit = self.minor_unit(line, currency)?;
+        match amount
+            .checked_mul(Decimal::from(10u64.pow(minor_unit)))
+            .filter(|scaled| scaled.fract().is_zero())
+            .and_then(|scaled| scaled.to_i64())
+            // 保证取反与取绝对值不会溢出
+            .filter(|minor| *minor != i64::MIN)
+        {
+            Some(minor) => Ok(minor),
+            None => fail(line, format!("invalid amount {amount} {currency}")),
+        }
+    }
+
+    fn apply(&mut self, dated: Dated) {
+        let line = dated.line;
+        let result = match dated.directive {
+            Directive::Open { account } => self.open(line, account),
+            Directive::Close { account } => self.close(line, account, dated.date),
+            Directive::Transaction(txn) => self.transaction(line, dated.date, txn),
+            Directive::Price { base, rate, quote } => {
+                self.price(line, base, rate, quote, dated.date)
+            }
+            Directive::Balance {
+                account,
+                amount,
+                currency,
+            } => self.balance(line, account, amount, currency),
+        };
+        if let Err(error) = result {
+            self.report.errors.push(error);
+        }
+    }
+
+    fn open(&mut self, line: usize, account: String) -> Check<()> {
+        if self.open.contains(&account) {
+            // 账本中已有的科目视为已开立
+            if self.plan.existing.contains_key(&account) {
+                return Ok(());
+            }
+            return fail(line, format!("account {account} is already open"));
+        }
+        let planned = self.plan.accounts.iter().any(|a| a.name == account);
+        if self.known.contains(&account)
+            && (!planned || self.plan.closes.iter().any(|(name, _)| *name == account))
+        {
+            return fail(line, format!("account {account} is closed"));
+        }
+        self.create_account(line, &account)?;
+        self.open.insert(account);
+        Ok(())
+    }
+
+    fn close(&mut self, line: usize, account: String, date: NaiveDate) -> Check<()> {
+        if !self.open.remove(&account) {
+            return fail(line, format!("account {account} is not open"));
+        }
+        self.plan.closes.push((account, date));
+        Ok(())
+    }
+
+    // 带价格注解且币种不同的过账行返回成交币种与金额, 用于生成兑换过账
+    fn cost(
+        &self,
+        posting: &RawPosting,
+        units: Decimal,
+        currency: &str,
+    ) -> Check<Option<(String, i64)>> {
+        let (cost, quote) = match &posting.price {
+            Some(Price::Unit(rate, quote)) if quote != currency => match units.checked_mul(*rate) {
+                Some(cost) => (cost, quote),
+                None => {
+                    return fail(
+                        posting.line,
+                        format!("price {rate} {quote} is out of range"),
+                    )
+                }
+            },
+            Some(Price::Total(total, quote)) if quote != currency => (
+                if units.is_sign_negative() {
+                    -*total
+                } else {
+                    *total
+                },
+                quote,
+            ),
+            _ => return Ok(None),
+        };
+        let minor_unit = self.minor_unit(posting.line, quote)?;
+        let cost = self.to_minor(posting.line, cost.round_dp(minor_unit), quote)?;
+        Ok(Some((quote.clone(), cost)))
+    }
+
+    fn transaction(&mut self, line: usize, date: NaiveDate, txn: Transaction) -> Check<()> {
+        let mut postings: Vec<(String, String, i64)> = Vec::new();
+        let mut missing: Option<&RawPosting> = None;
+        let mut converted = false;
+
+        for posting in &txn.postings {
+            if !self.open.contains(&posting.account) {
+                return fail(
+                    posting.line,
+                    format!("account {} is not open", posting.account),
+                );
+            }
+            let (units, currency) = match &posting.units {
+                Some(units) => units,
+                None => {
+                    if missing.replace(posting).is_some() {
+                        return fail(
+                            posting.line,
+                            "only one posting may omit its amount".to_string(),
+                        );
+                    }
+                    continue;
+                }
+            };
+            let amount = self.to_minor(posting.line, *units, currency)?;
+            postings.push((posting.account.clone(), currency.clone(), amount));
+            // 兑换科目收回原币种金额并付出成交金额, 使各币种分别平衡
+            if let Some((quote, cost)) = self.cost(posting, *units, currency)? {
+                converted = true;
+                postings.push((CONVERSIONS_ACCOUNT.to_string(), currency.clone(), -amount));
+                postings.push((CONVERSIONS_ACCOUNT.to_string(), quote, cost));
+            }
+        }
+
+        let mut residual: BTreeMap<String, i64> = BTreeMap::new();
+        for (_, currency, amount) in &postings {
+            let sum = residual.entry(currency.clone()).or_default();
+            *sum = checked_add(line, *sum, *amount)?;
+        }
+        if let Some(posting) = missing {
+            for (currency, amount) in std::mem::take(&mut residual) {
+                let amount = match amount.checked_neg().filter(|amount| *amount != i64::MIN) {
+                    Some(amount) => amount,
+                    None => return fail(line, "amount is out of range".to_string()),
+                };
+                postings.push((posting.account.clone(), currency, amount));
+            }
+        }
+        if let Some((currency, amount)) = residual.iter().find(|(_, amount)| **amount != 0) {
+            return fail(
+                line,
+                format!("transaction does not balance, {currency} residual {amount}"),
+            );
+        }
+        postings.retain(|(_, _, amount)| *amount != 0);
+        if postings.len() < 2 {
+            return fail(
+                line,
+                "transaction needs at least two non-zero postings".to_string(),
+            );
+        }
+
+        if converted && !self.open.contains(CONVERSIONS_ACCOUNT) {
+            self.create_account(line, CONVERSIONS_ACCOUNT)?;
+            self.open.insert(CONVERSIONS_ACCOUNT.to_string());
+        }
+        // 先算出全部新余额, 溢出时不留下写了一半的余额
+        let mut balances: HashMap<(String, String), i64> = HashMap::new();
+        for (account, currency, amount) in &postings {
+            let key = (account.clone(), currency.clone());
+            let current = balances
+                .get(&key)
+                .or_else(|| self.balances.get(&key))
+                .copied()
+                .unwrap_or_default();
+            balances.insert(key, checked_add(line, current, *amount)?);
+        }
+        self.balances.extend(balances);
+        self.plan.entries.push(PlannedEntry {
+            date,
+            description: txn.description,
+            tags: txn.tags,
+            postings: postings
+                .into_iter()
+                .map(|(account, currency, amount)| PlannedPosting {
+                    account,
+                    amount: amount.abs(),
+                    currency,
+                    side: if amount > 0 {
+                        Side::Debit
+                    } else {
+                        Side::Credit
+                    },
+                })
+                .collect(),
+        });
+        Ok(())
+    }
+
+    fn price(
+        &mut self,
+        line: usize,
+        base: String,
+        rate: Decimal,
+        quote: String,
+        date: NaiveDate,
+    ) -> Check<()> {
+        self.minor_unit(line, &base)?;
+        self.minor_unit(line, &quote)?;
+        if rate <= Decimal::ZERO || base == quote {
+            return fail(line, format!("invalid price {base} {rate} {quote}"));
+        }
+        self.plan.rates.push(UpsertExchangeRate {
+            base,
+            quote,
+            rate,
+            effective_date: date,
+        });
+        self.report.prices += 1;
+        Ok(())
+    }
+
+    // 余额断言包含子科目, 只核对本文件中的过账
+    fn balance(
+        &mut self,
+        line: usize,
+        account: String,
+        amount: Decimal,
+        currency: String,
+    ) -> Check<()> {
+        if !self.known.contains(&account) {
+            return fail(line, format!("account {account} is not open"));
+        }
+        let expected = self.to_minor(line, amount, &currency)?;
+        let prefix = format!("{account}:");
+        let actual = self
+            .balances
+            .iter()
+            .filter(|((name, code), _)| {
+                *code == currency && (*name == account || name.starts_with(&prefix))
+            })
+            .try_fold(0i64, |sum, (_, amount)| checked_add(line, sum, *amount))?;
+        if actual != expected {
+            let actual = Decimal::new(actual, self.minor_units[&currency]);
+            return fail(
+                line,
+                format!("balance of {account} is {actual} {currency}, expected {amount}"),
+            );
+        }
+        self.report.balances += 1;
+        Ok(())
+    }
+
+    fn finish(mut self) -> (BeancountPlan, BeancountReport) {
+        self.report.errors.sort_by_key(|error| error.line);
+        self.report.accounts = self.plan.accounts.len();
+        self.report.entries = self.plan.entries.len();
+        (self.plan, self.report)
+    }
+}
+
+/// 解析并校验 Beancount 文本, 同一天内先开户、再核对余额、然后记账, 最后销户
+pub fn plan(
+    ledger_id: Uuid,
+    accounts: Vec<Account>,
+    minor_units: HashMap<String, u32>,
+    text: &str,
+) -> (BeancountPlan, BeancountReport) {
+    let (mut directives, errors) = parser::parse(text);
+    directives.sort_by_key(|dated| (dated.date, dated.directive.rank()));
+
+    let mut planner = Planner::new(ledger_id, accounts, minor_units);
+    planner.report.errors = errors;
+    for dated in directives {
+        planner.apply(dated);
+    }
+    planner.finish()
+}
+
+#[async_trait]
+impl<T> BeancountService for BeancountServiceImpl<T>
+where
+    T: BeancountRepo + Sync + Send,
+{
+    async fn import(
+        &self,
+        ledger: &Ledger,
+        text: String,
+        write_rates: bool,
+    ) -> Result<BeancountReport> {
+        let accounts = self.beancount_repo.accounts(ledger.id).await?;
+        let minor_units = self
+            .currencies
+            .list()
+            .await?
+            .into_iter()
+            .map(|currency| (currency.code, currency.minor_unit as u32))
+            .collect();
+
+        let (mut plan, mut report) = plan(ledger.id, accounts, minor_units, &text);
+        if !write_rates {
+            report.skipped_prices = std::mem::take(&mut plan.rates).len();
+            report.prices = 0;
+        }
+        if report.errors.is_empty() {
+            self.beancount_repo.apply(plan).await?;
+            report.committed = true;
+        }
+        Ok(report)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        dao::beancount_repo::MockBeancountRepo, models::currency::Currency,
+        services::currency::MockCurrencyService,
+    };
+
+    fn minor_units() -> HashMap<String, u32> {
+        HashMap::from([("CNY".to_string(), 2), ("USD".to_string(), 2)])
+    }
+
+    fn currencies() -> MockCurrencyService {
+        let mut currencies = MockCurrencyService::new();
+        currencies.expect_list().returning(|| {
+            Ok(minor_units()
+                .into_iter()
+                .map(|(code, minor_unit)| Currency {
+                    code,
+                    name: String::new(),
+                    minor_unit: minor_unit as i16,
+                })
+                .collect())
+        });
+        currencies
+    }
+
+    #[test]
+    fn test_plan_fixture() {
+        let text = include_str!("../../../tests/fixtures/history.beancount");
+        let bank = Account {
+            name: "Bank".to_string(),
+            kind: AccountKind::Asset,
+            ..Default::default()
+        };
+        let (plan, report) = plan(Uuid::new_v4(), vec![bank.clone()], minor_units(), text);
+
+        // 只有格式错误的那一行
+        assert_eq!(
+            vec![35],
+            report.errors.iter().map(|e| e.line).collect::<Vec<_>>()
+        );
+        assert_eq!(Some(&bank.id), plan.existing.get("Assets:Bank"));
+        assert_eq!(
+            vec![
+                "Assets:Bank:Checking",
+                "Assets:Bank:Dollar",
+                "Expenses:Food",
+                "Income:Salary",
+                "Equity:Opening-Balances",
+                "Equity:Conversions",
+            ],
+            plan.accounts
+                .iter()
+                .map(|a| a.name.as_str())
+                .collect::<Vec<_>>()
+        );
+        assert_eq!(Some("Assets:Bank".to_string()), plan.accounts[0].parent);
+        assert_eq!(4, report.entries);
+        assert_eq!(2, report.balances);
+        assert_eq!(1, report.prices);
+        assert_eq!(
+            vec![(
+                "Assets:Bank:Dollar".to_string(),
+                NaiveDate::from_ymd(2022, 3, 1)
+            )],
+            plan.closes
+        );
+
+        let lunch = &plan.entries[1];
+        assert_eq!(vec!["work"], lunch.tags);
+        assert_eq!(
+            PlannedPosting {
+                account: "Assets:Bank:Checking".to_string(),
+                amount: 4500,
+                currency: "CNY".to_string(),
+                side: Side::Credit,
+            },
+            lunch.postings[1]
+        );
+
+        // 兑换分录按币种分别平衡
+        let exchange = &plan.entries[3];
+        assert_eq!(4, exchange.postings.len());
+        assert_eq!(CONVERSIONS_ACCOUNT, exchange.postings[2].account);
+        assert_eq!(63500, exchange.postings[2].amount);
+        assert_eq!(Side::Debit, exchange.postings[2].side);
+    }
+
+    #[test]
+    fn test_plan_reports_line_errors() {
+        let text = "\
+2022-01-01 open Assets:Cash
+2022-01-02 * \"Unbalanced\"
+  Assets:Cash  10.00 CNY
+  Expenses:Food  -5.00 CNY
+2022-01-03 * \"Too precise\"
+  Assets:Cash  1.001 CNY
+  Assets:Cash
+2022-01-04 price EUR 7.1 CNY
+2022-01-05 balance Assets:Cash 3.00 CNY
+2022-01-06 close Assets:Cash
+2022-01-07 close Assets:Cash
+2022-01-08 open Assets:Cash
+2022-01-09 open Other:Thing
+";
+        let (_, report) = plan(Uuid::new_v4(), Vec::new(), minor_units(), text);
+        assert_eq!(
+            vec![4, 6, 8, 9, 11, 12, 13],
+            report.errors.iter().map(|e| e.line).collect::<Vec<_>>()
+        );
+        assert_eq!(
+            "account Expenses:Food is not open",
+            report.errors[0].message
+        );
+    }
+
+    #[test]
+    fn test_plan_reports_overflow_as_line_error() {
+        let text = "\
+2022-01-01 open Assets:Cash
+2022-01-01 open Income:Gift
+2022-01-02 * \"Huge price\"
+  Assets:Cash  2 USD @ 79228162514264337593543950335 CNY
+  Income:Gift
+2022-01-03 * \"Huge amounts\"
+  Assets:Cash  90000000000000000.00 CNY
+  Assets:Cash  90000000000000000.00 CNY
+  Income:Gift
+";
+        let (plan, report) = plan(Uuid::new_v4(), Vec::new(), minor_units(), text);
+        assert_eq!(
+            vec![4, 6],
+            report.errors.iter().map(|e| e.line).collect::<Vec<_>>()
+        );
+        assert!(report.errors[0].message.contains("out of range"));
+        assert_eq!("amount is out of range", report.errors[1].message);
+        assert!(plan.entries.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_beancount_service_import() {
+        let mut beancount_repo = MockBeancountRepo::new();
+        beancount_repo
+            .expect_accounts()
+            .returning(|_| Ok(Vec::new()));
+        beancount_repo
+            .expect_apply()
+            .times(1)
+            .withf(|plan| plan.accounts.len() == 2 && plan.entries.len() == 1)
+            .returning(|_| Ok(()));
+
+        let sut = BeancountServiceImpl {
+            beancount_repo,
+            currencies: Arc::new(currencies()),
+        };
+        let text = "\
+2022-01-01 open Assets:Cash
+2022-01-01 open Income:Gift
+2022-01-02 * \"Gift\"
+  Assets:Cash  100 CNY
+  Income:Gift
+";
+        let report = sut
+            .import(&Ledger::default(), text.to_string(), true)
+            .await
+            .unwrap();
+        assert!(report.committed);
+        assert_eq!(1, report.entries);
+    }
+
+    #[tokio::test]
+    async fn test_beancount_service_import_skips_prices_without_permission() {
+        let mut beancount_repo = MockBeancountRepo::new();
+        beancount_repo
+            .expect_accounts()
+            .returning(|_| Ok(Vec::new()));
+        beancount_repo
+            .expect_apply()
+            .times(1)
+            .withf(|plan| plan.rates.is_empty() && plan.accounts.len() == 1)
+            .returning(|_| Ok(()));
+
+        let sut = BeancountServiceImpl {
+            beancount_repo,
+            currencies: Arc::new(currencies()),
+        };
+        let text = "2022-01-01 open Assets:Cash\n2022-01-02 price USD 6.35 CNY\n";
+        let report = sut
+            .import(&Ledger::default(), text.to_string(), false)
+            .await
+            .unwrap();
+        assert!(report.committed);
+        assert_eq!(0, report.prices);
+        assert_eq!(1, report.skipped_prices);
+    }
+
+    #[tokio::test]
+    async fn test_beancount_service_import_with_errors_writes_nothing() {
+        let mut beancount_repo = MockBeancountRepo::new();
+        beancount_repo
+            .expect_accounts()
+            .returning(|_| Ok(Vec::new()));
+        beancount_repo.expect_apply().never();
+
+        let sut = BeancountServiceImpl {
+            beancount_repo,
+            currencies: Arc::new(currencies()),
+        };
+        let text = "2022-01-01 open Assets:Cash\n2022-01-02 pad Assets:Cash Equity:Opening\n";
+        let report = sut
+            .import(&Ledger::default(), text.to_string(), true)
+            .await
+            .unwrap();
+        assert!(!report.committed);
+        assert_eq!(2, report.errors[0].line);
+    }
+}
@@ -0,0 +1,400 @@
+use crate::models::beancount::LineError;
+use chrono::NaiveDate;
+use rust_decimal::Decimal;
+use std::str::FromStr;
+
+/// 过账行上的价格注解, @ 为单价, @@ 为总价
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Price {
+    Unit(Decimal, String),
+    Total(Decimal, String),
+}
+
+/// 过账行, units 为空表示金额由其余过账行推算
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RawPosting {
+    pub line: usize,
+    pub account: String,
+    pub units: Option<(Decimal, String)>,
+    pub price: Option<Price>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Transaction {
+    pub description: String,
+    pub tags: Vec<String>,
+    pub postings: Vec<RawPosting>,
+}
+
+/// 支持导入的指令, 其它指令忽略
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Directive {
+    Open {
+        account: String,
+    },
+    Close {
+        account: String,
+    },
+    Transaction(Transaction),
+    Price {
+        base: String,
+        rate: Decimal,
+        quote: String,
+    },
+    Balance {
+        account: String,
+        amount: Decimal,
+        currency: String,
+    },
+}
+
+impl Directive {
+    /// 同一天内的处理顺序: 先开户, 再按日初余额断言, 然后是交易与价格, 最后销户
+    pub fn rank(&self) -> u8 {
+        match self {
+            Directive::Open { .. } => 0,
+            Directive::Balance { .. } => 1,
+            Directive::Transaction(_) | Directive::Price { .. } => 2,
+            Directive::Close { .. } => 3,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Dated {
+    pub line: usize,
+    pub date: NaiveDate,
+    pub directive: Directive,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Token<'a> {
+    Word(&'a str),
+    Str(String),
+}
+
+// 按空白切分, 支持带转义的双引号字符串, 分号之后为注释
+fn tokenize(line: &str) -> Result<Vec<Token<'_>>, String> {
+    let mut tokens = Vec::new();
+    let mut chars = line.char_indices().peekable();
+    while let Some(&(start, c)) = chars.peek() {
+        if c.is_whitespace() {
+            chars.next();
+        } else if c == ';' {
+            break;
+        } else if c == '"' {
+            chars.next();
+            let mut value = String::new();
+            loop {
+                match chars.next() {
+                    Some((_, '\\')) => value.extend(chars.next().map(|(_, c)| c)),
+                    Some((_, '"')) => break,
+                    Some((_, c)) => value.push(c),
+                    None => return Err("unterminated string".to_string()),
+                }
+            }
+            tokens.push(Token::Str(value));
+        } else {
+            let mut end = line.len();
+            while let Some(&(index, c)) = chars.peek() {
+                if c.is_whitespace() || c == '"' {
+                    end = index;
+                    break;
+                }
+                chars.next();
+            }
+            tokens.push(Token::Word(&line[start..end]));
+        }
+    }
+    Ok(tokens)
+}
+
+fn word<'a>(token: Option<&Token<'a>>, what: &str) -> Result<&'a str, String> {
+    match token {
+        Some(Token::Word(word)) => Ok(word),
+        _ => Err(format!("expected {what}")),
+    }
+}
+
+fn account<'a>(token: Option<&Token<'a>>) -> Result<&'a str, String> {
+    let name = word(token, "account")?;
+    if !name.contains(':') || !name.starts_with(|c: char| c.is_ascii_uppercase()) {
+        return Err(format!("invalid account {name}"));
+    }
+    Ok(name)
+}
+
+fn number(token: Option<&Token<'_>>) -> Result<Decimal, String> {
+    let value = word(token, "number")?;
+    Decimal::from_str(&value.replace(',', "")).map_err(|_| format!("invalid number {value}"))
+}
+
+fn currency(token: Option<&Token<'_>>) -> Result<String, String> {
+    let code = word(token, "currency")?;
+    if code.is_empty() || !code.chars().all(|c| c.is_ascii_uppercase()) {
+        return Err(format!("invalid currency {code}"));
+    }
+    Ok(code.to_string())
+}
+
+fn is_number(token: Option<&Token<'_>>) -> bool {
+    matches!(token, Some(Token::Word(word)) if word.starts_with(|c: char| c.is_ascii_digit() || c == '-' || c == '+' || c == '.'))
+}
+
+fn parse_dated(tokens: &[Token<'_>]) -> Result<Option<Directive>, String> {
+    let directive = match word(tokens.get(1), "directive")? {
+        "open" => Directive::Open {
+            account: account(tokens.get(2))?.to_string(),
+        },
+        "close" => Directive::Close {
+            account: account(tokens.get(2))?.to_string(),
+        },
+        "price" => Directive::Price {
+            base: currency(tokens.get(2))?,
+            rate: number(tokens.get(3))?,
+            quote: currency(tokens.get(4))?,
+        },
+        "balance" => Directive::Balance {
+            account: account(tokens.get(2))?.to_string(),
+            amount: number(tokens.get(3))?,
+            currency: currency(tokens.get(4))?,
+        },
+        "*" | "!" | "txn" => {
+            let mut strings = Vec::new();
+            let mut tags = Vec::new();
+            for token in &tokens[2..] {
+                match token {
+                    Token::Str(value) => strings.push(value.clone()),
+                    Token::Word(tag) if tag.starts_with('#') => tags.push(tag[1..].to_string()),
+                    Token::Word(link) if link.starts_with('^') => {}
+                    Token::Word(other) => return Err(format!("unexpected {other}")),
+                }
+            }
+            let description = match strings.as_slice() {
+                [] => String::new(),
+                [narration] => narration.clone(),
+                [payee, narration] if payee.is_empty() => narration.clone(),
+                [payee, narration] => format!("{payee} - {narration}"),
+                _ => return Err("too many strings".to_string()),
+            };
+            Directive::Transaction(Transaction {
+                description,
+                tags,
+                postings: Vec::new(),
+            })
+        }
+        "pad" => return Err("unsupported directive pad".to_string()),
+        // commodity、note、event、document 等指令不影响账目
+        _ => return Ok(None),
+    };
+    Ok(Some(directive))
+}
+
+fn parse_posting(line: usize, tokens: &[Token<'_>]) -> Result<RawPosting, String> {
+    let mut tokens = tokens.iter().peekable();
+    if matches!(tokens.peek(), Some(Token::Word("!" | "*"))) {
+        tokens.next();
+    }
+    let account = account(tokens.next())?.to_string();
+
+    let units = if is_number(tokens.peek().copied()) {
+        Some((number(tokens.next())?, currency(tokens.next())?))
+    } else {
+        None
+    };
+
+    // 成本注解 {..} 不影响记账金额
+    if matches!(tokens.peek(), Some(Token::Word(word)) if word.starts_with('{')) {
+        for token in tokens.by_ref() {
+            if matches!(token, Token::Word(word) if word.ends_with('}')) {
+                break;
+            }
+        }
+    }
+
+    let price = match tokens.next() {
+        None => None,
+        Some(Token::Word("@")) => Some(Price::Unit(
+            number(tokens.next())?,
+            currency(tokens.next())?,
+        )),
+        Some(Token::Word("@@")) => Some(Price::Total(
+            number(tokens.next())?,
+            currency(tokens.next())?,
+        )),
+        Some(Token::Word(other)) => return Err(format!("unexpected {other}")),
+        Some(Token::Str(other)) => return Err(format!("unexpected \"{other}\"")),
+    };
+    if price.is_some() && units.is_none() {
+        return Err("price without amount".to_string());
+    }
+    if let Some(token) = tokens.next() {
+        return Err(format!("unexpected {token:?}"));
+    }
+
+    Ok(RawPosting {
+        line,
+        account,
+        units,
+        price,
+    })
+}
+
+/// 解析 Beancount 文本, 返回可导入的指令与逐行错误。
+/// 只支持 open、close、交易、price 与 balance 指令, option、include 等其它内容忽略
+pub fn parse(text: &str) -> (Vec<Dated>, Vec<LineError>) {
+    let mut directives = Vec::new();
+    let mut errors = Vec::new();
+    // 当前交易及其是否已出错, 出错的交易整体丢弃
+    let mut current: Option<(Dated, bool)> = None;
+
+    let finish = |current: &mut Option<(Dated, bool)>, directives: &mut Vec<Dated>| {
+        if let Some((dated, false)) = current.take() {
+            directives.push(dated);
+        }
+    };
+
+    for (index, raw) in text.lines().enumerate() {
+        let line = index + 1;
+        let mut error = |message: String| errors.push(LineError { line, message });
+        let tokens = match tokenize(raw) {
+            Ok(tokens) if tokens.is_empty() => continue,
+            Ok(tokens) => tokens,
+            Err(message) => {
+                error(message);
+                continue;
+            }
+        };
+
+        // 缩进行为交易的过账行或元数据
+        if raw.starts_with(char::is_whitespace) {
+            let (dated, failed) = match current.as_mut() {
+                Some((
+                    Dated {
+                        directive: Directive::Transaction(txn),
+                        ..
+                    },
+                    failed,
+                )) => (txn, failed),
+                _ => continue,
+            };
+            if matches!(tokens[0], Token::Word(word) if word.ends_with(':') && !word[..word.len() - 1].contains(':'))
+            {
+                continue;
+            }
+            match parse_posting(line, &tokens) {
+                Ok(posting) => dated.postings.push(posting),
+                Err(message) => {
+                    *failed = true;
+                    error(message);
+                }
+            }
+            continue;
+        }
+
+        finish(&mut current, &mut directives);
+        let date = match tokens[0] {
+            Token::Word(word) => {
+                NaiveDate::parse_from_str(&word.replace('/', "-"), "%Y-%m-%d").ok()
+            }
+            Token::Str(_) => None,
+        };
+        // option、plugin、include 等无日期的内容忽略
+        let date = match date {
+            Some(date) => date,
+            None => continue,
+        };
+        match parse_dated(&tokens) {
+            Ok(Some(directive @ Directive::Transaction(_))) => {
+                current = Some((
+                    Dated {
+                        line,
+                        date,
+                        directive,
+                    },
+                    false,
+                ));
+            }
+            Ok(Some(directive)) => directives.push(Dated {
+                line,
+                date,
+                directive,
+            }),
+            Ok(None) => {}
+            Err(message) => error(message),
+        }
+    }
+    finish(&mut current, &mut directives);
+
+    (directives, errors)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_tokenize() {
+        let tokens = tokenize(r#"2022-04-01 * "Cafe \"Blue\"" "latte" #coffee ; comment"#).unwrap();
+        assert_eq!(
+            vec![
+                Token::Word("2022-04-01"),
+                Token::Word("*"),
+                Token::Str("Cafe \"Blue\"".to_string()),
+                Token::Str("latte".to_string()),
+                Token::Word("#coffee"),
+            ],
+            tokens
+        );
+        assert!(tokenize(r#"2022-04-01 * "open"#).is_err());
+    }
+
+    #[test]
+    fn test_parse_fixture() {
+        let text = include_str!("../../../tests/fixtures/history.beancount");
+        let (directives, errors) = parse(text);
+        assert_eq!(
+            vec![LineError {
+                line: 35,
+                message: "invalid number 12.5.0".to_string()
+            }],
+            errors
+        );
+        assert_eq!(13, directives.len());
+
+        assert_eq!(
+            Directive::Open {
+                account: "Assets:Bank:Checking".to_string()
+            },
+            directives[0].directive
+        );
+        let lunch = match &directives[6].directive {
+            Directive::Transaction(txn) => txn,
+            other => panic!("unexpected {other:?}"),
+        };
+        assert_eq!(NaiveDate::from_ymd(2022, 1, 5), directives[6].date);
+        assert_eq!("Noodle Bar - Lunch with team", lunch.description);
+        assert_eq!(vec!["work"], lunch.tags);
+        assert_eq!(None, lunch.postings[1].units);
+
+        let exchange = match &directives[8].directive {
+            Directive::Transaction(txn) => txn,
+            other => panic!("unexpected {other:?}"),
+        };
+        assert_eq!(
+            Some(Price::Unit(
+                Decimal::from_str("6.35").unwrap(),
+                "CNY".to_string()
+            )),
+            exchange.postings[0].price
+        );
+        assert_eq!(
+            Directive::Price {
+                base: "USD".to_string(),
+                rate: Decimal::from_str("6.36").unwrap(),
+                quote: "CNY".to_string()
+            },
+            directives[9].directive
+        );
+    }
+}
@@ -0,0 +1,254 @@
+pub(crate) use crate::{
+    dao::budget_repo::{BudgetRepo, BudgetRepoImpl},
+    dto::{
+        budget::{CreateBudgetInput, UpdateBudgetInput},
+        report::AsOfInput,
+    },
+    errors::{Error, Result},
+    models::budget::{Budget, BudgetStatus, CreateBudget, PeriodStatus},
+};
+use axum::async_trait;
+use chrono::Utc;
+use sqlx::postgres::PgPool;
+use std::{collections::HashMap, sync::Arc};
+use uuid::Uuid;
+
+pub type DynBudgetService = Arc<dyn BudgetService + Send + Sync>;
+
+#[cfg_attr(test, mockall::automock)]
+#[async_trait]
+pub trait BudgetService {
+    async fn create(&self, ledger_id: Uuid, input: CreateBudgetInput) -> Result<Budget>;
+    async fn get(&self, ledger_id: Uuid, id: Uuid) -> Result<Budget>;
+    async fn delete(&self, ledger_id: Uuid, id: Uuid) -> Result<Budget>;
+    async fn update(&self, ledger_id: Uuid, id: Uuid, input: UpdateBudgetInput) -> Result<Budget>;
+    async fn list(&self, ledger_id: Uuid) -> Result<Vec<Budget>>;
+    async fn status(&self, ledger_id: Uuid, id: Uuid, input: AsOfInput) -> Result<BudgetStatus>;
+}
+
+#[derive(Clone)]
+pub struct BudgetServiceImpl<T: BudgetRepo>
+where
+    T: BudgetRepo + Sync + Send,
+{
+    pub budget_repo: T,
+}
+
+impl BudgetServiceImpl<BudgetRepoImpl> {
+    pub fn new(pool: Arc<PgPool>) -> Self {
+        BudgetServiceImpl {
+            budget_repo: BudgetRepoImpl::new(pool),
+        }
+    }
+}
+
+#[async_trait]
+impl<T> BudgetService for BudgetServiceImpl<T>
+where
+    T: BudgetRepo + Sync + Send,
+{
+    async fn create(&self, ledger_id: Uuid, input: CreateBudgetInput) -> Result<Budget> {
+        let budget = CreateBudget {
+            ledger_id,
+            name: input.name,
+            category_id: input.category_id,
+            account_id: input.account_id,
+            period: input.period,
+            amount: input.amount,
+            rollover: input.rollover,
+            start_date: input.start_date.unwrap_or_else(|| Utc::today().naive_utc()),
+        };
+        Ok(self.budget_repo.create(budget).await?)
+    }
+
+    async fn get(&self, ledger_id: Uuid, id: Uuid) -> Result<Budget> {
+        let budget = self.budget_repo.get(id).await?;
+        if budget.ledger_id != ledger_id {
+            return Err(Error::Forbidden);
+        }
+        Ok(budget)
+    }
+
+    async fn delete(&self, ledger_id: Uuid, id: Uuid) -> Result<Budget> {
+        self.get(ledger_id, id).await?;
+        Ok(self.budget_repo.delete(id).await?)
+    }
+
+    async fn update(&self, ledger_id: Uuid, id: Uuid, input: UpdateBudgetInput) -> Result<Budget> {
+        let mut budget = self.get(ledger_id, id).await?;
+
+        if let Some(name) = input.name {
+            budget.name = name;
+        }
+
+        if let Some(amount) = input.amount {
+            budget.amount = amount;
+        }
+
+        if let Some(rollover) = input.rollover {
+            budget.rollover = rollover;
+        }
+
+        Ok(self.budget_repo.update(&budget).await?)
+    }
+
+    async fn list(&self, ledger_id: Uuid) -> Result<Vec<Budget>> {
+        Ok(self.budget_repo.list(ledger_id).await?)
+    }
+
+    async fn status(&self, ledger_id: Uuid, id: Uuid, input: AsOfInput) -> Result<BudgetStatus> {
+        let budget = self.get(ledger_id, id).await?;
+        let period = budget.period;
+        let as_of = input.as_of.unwrap_or_else(|| Utc::today().naive_utc());
+        let current = period.start_of(as_of);
+        let previous = period.shift(current, -1);
+        // 开启结转时需要从预算开始的周期逐期累计未用完的额度
+        let first = period.start_of(budget.start_date).min(current);
+
+        let spent = self
+            .budget_repo
+            .spent(&budget, first, period.end_of(current))
+            .await?;
+        if spent.iter().any(|s| s.missing_rates > 0) {
+            return Err(Error::MissingExchangeRate(format!(
+                "budget {} has postings without a rate to the base currency",
+                budget.name
+            )));
+        }
+        let spent: HashMap<_, _> = spent.into_iter().map(|s| (s.start, s.spent)).collect();
+
+        let (mut carry, mut previous_status) = (0i64, None);
+        let mut start = first;
+        loop {
+            let budgeted = budget.amount + carry;
+            let status = PeriodStatus::new(
+                start,
+                period.end_of(start),
+                budgeted,
+                spent.get(&start).copied().unwrap_or(0),
+            );
+            if start == current {
+                return Ok(BudgetStatus {
+                    budget_id: budget.id,
+                    current: status,
+                    previous: previous_status,
+                });
+            }
+            if start == previous {
+                previous_status = Some(status.clone());
+            }
+            if budget.rollover {
+                carry = status.remaining.max(0);
+            }
+            start = period.shift(start, 1);
+        }
+    }
+}
+
+#[cfg(test)]
+pub mod tests {
+    use super::*;
+    use crate::{
+        dao::budget_repo::MockBudgetRepo,
+        models::budget::{BudgetPeriod, PeriodSpent},
+    };
+    use chrono::NaiveDate;
+
+    fn budget_repo(ledger_id: Uuid, rollover: bool, spent: Vec<(u32, i64)>) -> MockBudgetRepo {
+        let mut budget_repo = MockBudgetRepo::new();
+        budget_repo.expect_get().returning(move |id| {
+            Ok(Budget {
+                id,
+                ledger_id,
+                period: BudgetPeriod::Monthly,
+                amount: 1000,
+                rollover,
+                start_date: NaiveDate::from_ymd(2022, 1, 15),
+                ..Default::default()
+            })
+        });
+        budget_repo.expect_spent().returning(move |_, _, _| {
+            Ok(spent
+                .iter()
+                .map(|&(month, spent)| PeriodSpent {
+                    start: NaiveDate::from_ymd(2022, month, 1),
+                    spent,
+                    missing_rates: 0,
+                })
+                .collect())
+        });
+        budget_repo
+    }
+
+    #[tokio::test]
+    async fn test_budget_service_status() {
+        let ledger_id = Uuid::new_v4();
+        let sut = BudgetServiceImpl {
+            budget_repo: budget_repo(ledger_id, false, vec![(1, 400), (2, 1200), (3, 250)]),
+        };
+        let as_of = Some(NaiveDate::from_ymd(2022, 3, 20));
+        let status = sut
+            .status(ledger_id, Uuid::new_v4(), AsOfInput { as_of })
+            .await
+            .unwrap();
+        assert_eq!(NaiveDate::from_ymd(2022, 3, 1), status.current.start);
+        assert_eq!(1000, status.current.budgeted);
+        assert_eq!(750, status.current.remaining);
+
+        let previous = status.previous.unwrap();
+        assert_eq!(NaiveDate::from_ymd(2022, 2, 28), previous.end);
+        assert_eq!(-200, previous.remaining);
+        assert!(previous.over_spent);
+    }
+
+    #[tokio::test]
+    async fn test_budget_service_status_rollover() {
+        let ledger_id = Uuid::new_v4();
+        let sut = BudgetServiceImpl {
+            budget_repo: budget_repo(ledger_id, true, vec![(1, 400), (3, 250)]),
+        };
+        let as_of = Some(NaiveDate::from_ymd(2022, 3, 20));
+        let status = sut
+            .status(ledger_id, Uuid::new_v4(), AsOfInput { as_of })
+            .await
+            .unwrap();
+        // 1月结余 600 结转到 2月, 2月未支出共 1600 结转到 3月
+        assert_eq!(1600, status.previous.unwrap().budgeted);
+        assert_eq!(2600, status.current.budgeted);
+        assert_eq!(2350, status.current.remaining);
+    }
+
+    #[tokio::test]
+    async fn test_budget_service_status_first_period() {
+        let ledger_id = Uuid::new_v4();
+        let sut = BudgetServiceImpl {
+            budget_repo: budget_repo(ledger_id, true, Vec::new()),
+        };
+        let as_of = Some(NaiveDate::from_ymd(2022, 1, 20));
+        let status = sut
+            .status(ledger_id, Uuid::new_v4(), AsOfInput { as_of })
+            .await
+            .unwrap();
+        assert!(status.previous.is_none());
+        assert_eq!(0, status.current.spent);
+    }
+
+    #[tokio::test]
+    async fn test_budget_service_status_other_ledger() {
+        let mut budget_repo = MockBudgetRepo::new();
+        budget_repo.expect_get().returning(|id| {
+            Ok(Budget {
+                id,
+                ledger_id: Uuid::new_v4(),
+                ..Default::default()
+            })
+        });
+        budget_repo.expect_spent().never();
+
+        let sut = BudgetServiceImpl { budget_repo };
+        let result = sut
+            .status(Uuid::new_v4(), Uuid::new_v4(), AsOfInput::default())
+            .await;
+        assert!(matches!(result, Err(Error::Forbidden)));
+    }
+}
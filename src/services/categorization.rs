@@ -0,0 +1,280 @@
+pub(crate) use crate::{
+    dao::category_repo::{CategoryRepo, CategoryRepoImpl},
+    dto::category::{CreateCategoryInput, CreateRuleInput},
+    errors::{Error, Result},
+    models::{
+        category::{CategorizationRule, Category, CreateCategory, CreateRule},
+        entry::CreateEntry,
+    },
+};
+use axum::async_trait;
+use sqlx::postgres::PgPool;
+use std::sync::Arc;
+use uuid::Uuid;
+
+pub type DynCategorizationService = Arc<dyn CategorizationService + Send + Sync>;
+
+#[cfg_attr(test, mockall::automock)]
+#[async_trait]
+pub trait CategorizationService {
+    async fn create_category(
+        &self,
+        ledger_id: Uuid,
+        input: CreateCategoryInput,
+    ) -> Result<Category>;
+    async fn list_categories(&self, ledger_id: Uuid) -> Result<Vec<Category>>;
+    async fn delete_category(&self, ledger_id: Uuid, id: Uuid) -> Result<Category>;
+    async fn create_rule(
+        &self,
+        ledger_id: Uuid,
+        input: CreateRuleInput,
+    ) -> Result<CategorizationRule>;
+    async fn list_rules(&self, ledger_id: Uuid) -> Result<Vec<CategorizationRule>>;
+    async fn delete_rule(&self, ledger_id: Uuid, id: Uuid) -> Result<CategorizationRule>;
+    /// 校验分录的分类并应用账本的自动分类规则, 分录写入前调用
+    async fn categorize(&self, entry: CreateEntry) -> Result<CreateEntry>;
+}
+
+#[derive(Clone)]
+pub struct CategorizationServiceImpl<T: CategoryRepo>
+where
+    T: CategoryRepo + Sync + Send,
+{
+    pub category_repo: T,
+}
+
+impl CategorizationServiceImpl<CategoryRepoImpl> {
+    pub fn new(pool: Arc<PgPool>) -> Self {
+        CategorizationServiceImpl {
+            category_repo: CategoryRepoImpl::new(pool),
+        }
+    }
+}
+
+/// 按规则顺序应用: 分录未指定分类时取第一条匹配规则的分类, 所有匹配规则的标签都会添加
+pub fn apply_rules(rules: &[CategorizationRule], entry: &mut CreateEntry) {
+    for rule in rules.iter().filter(|rule| rule.matches(&entry.description)) {
+        if entry.category_id.is_none() {
+            entry.category_id = rule.category_id;
+        }
+        if let Some(ref tag) = rule.tag {
+            entry.tags.push(tag.clone());
+        }
+    }
+
+    // 标签去除首尾空白并去重, 保留首次出现的顺序
+    let mut tags: Vec<String> = Vec::with_capacity(entry.tags.len());
+    for tag in entry.tags.drain(..) {
+        let tag = tag.trim().to_string();
+        if !tags.contains(&tag) {
+            tags.push(tag);
+        }
+    }
+    entry.tags = tags;
+}
+
+impl<T> CategorizationServiceImpl<T>
+where
+    T: CategoryRepo + Sync + Send,
+{
+    // 获取分类并校验其属于该账本
+    async fn category_in_ledger(&self, ledger_id: Uuid, id: Uuid) -> Result<Category> {
+        let category = match self.category_repo.get_category(id).await {
+            Ok(category) => category,
+            Err(Error::DataStore(sqlx::Error::RowNotFound)) => {
+                return Err(Error::InvalidCategory(format!(
+                    "category {id} does not exist"
+                )))
+            }
+            Err(err) => return Err(err),
+        };
+        if category.ledger_id != ledger_id {
+            return Err(Error::InvalidCategory(format!(
+                "category {id} does not belong to the ledger"
+            )));
+        }
+        Ok(category)
+    }
+}
+
+#[async_trait]
+impl<T> CategorizationService for CategorizationServiceImpl<T>
+where
+    T: CategoryRepo + Sync + Send,
+{
+    async fn create_category(
+        &self,
+        ledger_id: Uuid,
+        input: CreateCategoryInput,
+    ) -> Result<Category> {
+        let category = CreateCategory {
+            ledger_id,
+            name: input.name,
+        };
+        Ok(self.category_repo.create_category(category).await?)
+    }
+
+    async fn list_categories(&self, ledger_id: Uuid) -> Result<Vec<Category>> {
+        Ok(self.category_repo.list_categories(ledger_id).await?)
+    }
+
+    async fn delete_category(&self, ledger_id: Uuid, id: Uuid) -> Result<Category> {
+        let category = self.category_repo.get_category(id).await?;
+        if category.ledger_id != ledger_id {
+            return Err(Error::Forbidden);
+        }
+        Ok(self.category_repo.delete_category(id).await?)
+    }
+
+    async fn create_rule(
+        &self,
+        ledger_id: Uuid,
+        input: CreateRuleInput,
+    ) -> Result<CategorizationRule> {
+        if let Some(category_id) = input.category_id {
+            self.category_in_ledger(ledger_id, category_id).await?;
+        }
+
+        let rule = CreateRule {
+            ledger_id,
+            pattern: input.pattern,
+            category_id: input.category_id,
+            tag: input.tag,
+            priority: input.priority,
+        };
+        Ok(self.category_repo.create_rule(rule).await?)
+    }
+
+    async fn list_rules(&self, ledger_id: Uuid) -> Result<Vec<CategorizationRule>> {
+        Ok(self.category_repo.list_rules(ledger_id).await?)
+    }
+
+    async fn delete_rule(&self, ledger_id: Uuid, id: Uuid) -> Result<CategorizationRule> {
+        let rule = self.category_repo.get_rule(id).await?;
+        if rule.ledger_id != ledger_id {
+            return Err(Error::Forbidden);
+        }
+        Ok(self.category_repo.delete_rule(id).await?)
+    }
+
+    async fn categorize(&self, mut entry: CreateEntry) -> Result<CreateEntry> {
+        if let Some(category_id) = entry.category_id {
+            self.category_in_ledger(entry.ledger_id, category_id)
+                .await?;
+        }
+
+        let rules = self.category_repo.list_rules(entry.ledger_id).await?;
+        apply_rules(&rules, &mut entry);
+        Ok(entry)
+    }
+}
+
+#[cfg(test)]
+pub mod tests {
+    use super::*;
+    use crate::dao::category_repo::MockCategoryRepo;
+    use chrono::NaiveDate;
+
+    fn entry(description: &str) -> CreateEntry {
+        CreateEntry {
+            ledger_id: Uuid::new_v4(),
+            date: NaiveDate::from_ymd(2022, 3, 1),
+            description: description.to_string(),
+            category_id: None,
+            tags: Vec::new(),
+            postings: Vec::new(),
+        }
+    }
+
+    fn rule(pattern: &str, category_id: Option<Uuid>, tag: Option<&str>) -> CategorizationRule {
+        CategorizationRule {
+            pattern: pattern.to_string(),
+            category_id,
+            tag: tag.map(str::to_string),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_apply_rules() {
+        let (groceries, dining) = (Uuid::new_v4(), Uuid::new_v4());
+        let rules = [
+            rule("SUPERMARKET", Some(groceries), Some("weekly")),
+            rule("market", Some(dining), Some("food")),
+            rule("cinema", None, Some("fun")),
+        ];
+
+        let mut matched = entry("Local supermarket");
+        matched.tags = vec![" food ".to_string()];
+        apply_rules(&rules, &mut matched);
+        assert_eq!(Some(groceries), matched.category_id);
+        assert_eq!(vec!["food", "weekly"], matched.tags);
+
+        let mut explicit = entry("market stall");
+        explicit.category_id = Some(groceries);
+        apply_rules(&rules, &mut explicit);
+        assert_eq!(Some(groceries), explicit.category_id);
+        assert_eq!(vec!["food"], explicit.tags);
+
+        let mut unmatched = entry("rent");
+        apply_rules(&rules, &mut unmatched);
+        assert_eq!(None, unmatched.category_id);
+        assert!(unmatched.tags.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_categorization_service_categorize() {
+        let category_id = Uuid::new_v4();
+        let mut category_repo = MockCategoryRepo::new();
+        category_repo
+            .expect_list_rules()
+            .returning(move |ledger_id| {
+                Ok(vec![CategorizationRule {
+                    ledger_id,
+                    ..rule("coffee", Some(category_id), Some("daily"))
+                }])
+            });
+
+        let sut = CategorizationServiceImpl { category_repo };
+        let categorized = sut.categorize(entry("Coffee shop")).await.unwrap();
+        assert_eq!(Some(category_id), categorized.category_id);
+        assert_eq!(vec!["daily"], categorized.tags);
+    }
+
+    #[tokio::test]
+    async fn test_categorization_service_categorize_foreign_category() {
+        let mut category_repo = MockCategoryRepo::new();
+        category_repo.expect_get_category().returning(|id| {
+            Ok(Category {
+                id,
+                ledger_id: Uuid::new_v4(),
+                ..Default::default()
+            })
+        });
+        category_repo.expect_list_rules().never();
+
+        let sut = CategorizationServiceImpl { category_repo };
+        let mut input = entry("rent");
+        input.category_id = Some(Uuid::new_v4());
+        let result = sut.categorize(input).await;
+        assert!(matches!(result, Err(Error::InvalidCategory(_))));
+    }
+
+    #[tokio::test]
+    async fn test_categorization_service_categorize_propagates_store_error() {
+        let mut category_repo = MockCategoryRepo::new();
+        category_repo
+            .expect_get_category()
+            .returning(|_| Err(Error::DataStore(sqlx::Error::PoolTimedOut)));
+        category_repo.expect_list_rules().never();
+
+        let sut = CategorizationServiceImpl { category_repo };
+        let mut input = entry("rent");
+        input.category_id = Some(Uuid::new_v4());
+        let result = sut.categorize(input).await;
+        assert!(matches!(
+            result,
+            Err(Error::DataStore(sqlx::Error::PoolTimedOut))
+        ));
+    }
+}
@@ -0,0 +1,171 @@
+pub(crate) use crate::{
+    dao::currency_repo::{CurrencyRepo, CurrencyRepoImpl},
+    dto::currency::{ListExchangeRateInput, UpsertExchangeRateInput},
+    errors::{Error, Result},
+    models::currency::{Currency, ExchangeRate, ExchangeRateOption, UpsertExchangeRate},
+};
+use axum::async_trait;
+use sqlx::postgres::PgPool;
+use std::sync::Arc;
+
+pub type DynCurrencyService = Arc<dyn CurrencyService + Send + Sync>;
+
+#[cfg_attr(test, mockall::automock)]
+#[async_trait]
+pub trait CurrencyService {
+    async fn list(&self) -> Result<Vec<Currency>>;
+    async fn get(&self, code: &str) -> Result<Currency>;
+    async fn upsert_rate(&self, input: UpsertExchangeRateInput) -> Result<ExchangeRate>;
+    async fn list_rates(&self, input: ListExchangeRateInput) -> Result<Vec<ExchangeRate>>;
+}
+
+#[derive(Clone)]
+pub struct CurrencyServiceImpl<T: CurrencyRepo>
+where
+    T: CurrencyRepo + Sync + Send,
+{
+    pub currency_repo: T,
+}
+
+impl CurrencyServiceImpl<CurrencyRepoImpl> {
+    pub fn new(pool: Arc<PgPool>) -> Self {
+        CurrencyServiceImpl {
+            currency_repo: CurrencyRepoImpl::new(pool),
+        }
+    }
+}
+
+#[async_trait]
+impl<T> CurrencyService for CurrencyServiceImpl<T>
+where
+    T: CurrencyRepo + Sync + Send,
+{
+    async fn list(&self) -> Result<Vec<Currency>> {
+        Ok(self.currency_repo.list().await?)
+    }
+
+    async fn get(&self, code: &str) -> Result<Currency> {
+        match self.currency_repo.get(code).await {
+            Err(Error::DataStore(sqlx::Error::RowNotFound)) => {
+                Err(Error::InvalidCurrency(format!("unknown currency {code}")))
+            }
+            result => result,
+        }
+    }
+
+    async fn upsert_rate(&self, input: UpsertExchangeRateInput) -> Result<ExchangeRate> {
+        if input.base == input.quote {
+            return Err(Error::InvalidCurrency(format!(
+                "cannot set a rate from {} to itself",
+                input.base
+            )));
+        }
+        for code in [&input.base, &input.quote] {
+            self.get(code).await?;
+        }
+
+        let rate = UpsertExchangeRate {
+            base: input.base,
+            quote: input.quote,
+            rate: input.rate,
+            effective_date: input.effective_date,
+        };
+        Ok(self.currency_repo.upsert_rate(rate).await?)
+    }
+
+    async fn list_rates(&self, input: ListExchangeRateInput) -> Result<Vec<ExchangeRate>> {
+        let opt = ExchangeRateOption {
+            base: input.base,
+            quote: input.quote,
+            offset: input.limit_offset.offset,
+            limit: input.limit_offset.limit,
+        };
+        Ok(self.currency_repo.list_rates(opt).await?)
+    }
+}
+
+#[cfg(test)]
+pub mod tests {
+    use super::*;
+    use crate::dao::currency_repo::MockCurrencyRepo;
+    use chrono::{NaiveDate, Utc};
+    use rust_decimal::Decimal;
+    use uuid::Uuid;
+
+    fn input(base: &str, quote: &str) -> UpsertExchangeRateInput {
+        UpsertExchangeRateInput {
+            base: base.to_string(),
+            quote: quote.to_string(),
+            rate: Decimal::new(63172, 4),
+            effective_date: NaiveDate::from_ymd(2022, 3, 1),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_currency_service_upsert_rate() {
+        let mut currency_repo = MockCurrencyRepo::new();
+        currency_repo.expect_get().returning(|code| {
+            Ok(Currency {
+                code: code.to_string(),
+                name: code.to_string(),
+                minor_unit: 2,
+            })
+        });
+        currency_repo
+            .expect_upsert_rate()
+            .times(1)
+            .returning(|rate| {
+                Ok(ExchangeRate {
+                    id: Uuid::new_v4(),
+                    base: rate.base,
+                    quote: rate.quote,
+                    rate: rate.rate,
+                    effective_date: rate.effective_date,
+                    created_at: Utc::now(),
+                    updated_at: Utc::now(),
+                })
+            });
+
+        let sut = CurrencyServiceImpl { currency_repo };
+        let rate = sut.upsert_rate(input("USD", "CNY")).await.unwrap();
+        assert_eq!(Decimal::new(63172, 4), rate.rate);
+    }
+
+    #[tokio::test]
+    async fn test_currency_service_upsert_rate_invalid_pair() {
+        let mut currency_repo = MockCurrencyRepo::new();
+        currency_repo
+            .expect_get()
+            .returning(|_| Err(Error::DataStore(sqlx::Error::RowNotFound)));
+        currency_repo.expect_upsert_rate().never();
+
+        let sut = CurrencyServiceImpl { currency_repo };
+        let same = sut.upsert_rate(input("USD", "USD")).await;
+        assert!(matches!(same, Err(Error::InvalidCurrency(_))));
+
+        let unknown = sut.upsert_rate(input("USD", "XXX")).await;
+        assert!(matches!(unknown, Err(Error::InvalidCurrency(_))));
+    }
+
+    #[tokio::test]
+    async fn test_currency_service_get_propagates_datastore_error() {
+        let mut currency_repo = MockCurrencyRepo::new();
+        currency_repo
+            .expect_get()
+            .returning(|_| Err(Error::DataStore(sqlx::Error::PoolTimedOut)));
+        currency_repo.expect_upsert_rate().never();
+
+        let sut = CurrencyServiceImpl { currency_repo };
+        let result = sut.get("USD").await;
+        assert!(matches!(
+            result,
+            Err(Error::DataStore(sqlx::Error::PoolTimedOut))
+        ));
+
+        let result = sut.upsert_rate(input("USD", "CNY")).await;
+        assert!(matches!(
+            result,
+            Err(Error::DataStore(sqlx::Error::PoolTimedOut))
+        ));
+    }
+}
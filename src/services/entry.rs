@@ -0,0 +1,296 @@
+use super::categorization::{CategorizationServiceImpl, DynCategorizationService};
+pub(crate) use crate::{
+    dao::entry_repo::{EntryRepo, EntryRepoImpl},
+    dto::entry::{CreateEntryInput, ListEntryInput, PostingInput},
+    errors::{Error, Result},
+    models::{
+        entry::{CreateEntry, CreatePosting, Entry, EntryDetail, EntryOption, Side},
+        ledger::Ledger,
+    },
+};
+use axum::async_trait;
+use sqlx::postgres::PgPool;
+use std::{collections::BTreeMap, sync::Arc};
+use uuid::Uuid;
+
+pub type DynEntryService = Arc<dyn EntryService + Send + Sync>;
+
+#[cfg_attr(test, mockall::automock)]
+#[async_trait]
+pub trait EntryService {
+    async fn create(&self, ledger: &Ledger, input: CreateEntryInput) -> Result<EntryDetail>;
+    async fn get(&self, ledger_id: Uuid, id: Uuid) -> Result<EntryDetail>;
+    async fn delete(&self, ledger_id: Uuid, id: Uuid) -> Result<Entry>;
+    async fn list(&self, ledger_id: Uuid, input: ListEntryInput) -> Result<Vec<EntryDetail>>;
+}
+
+#[derive(Clone)]
+pub struct EntryServiceImpl<T: EntryRepo>
+where
+    T: EntryRepo + Sync + Send,
+{
+    pub entry_repo: T,
+    pub categorization: DynCategorizationService,
+}
+
+impl EntryServiceImpl<EntryRepoImpl> {
+    pub fn new(pool: Arc<PgPool>) -> Self {
+        EntryServiceImpl {
+            entry_repo: EntryRepoImpl::new(pool.clone()),
+            categorization: Arc::new(CategorizationServiceImpl::new(pool)),
+        }
+    }
+}
+
+/// 复式记账校验: 每个币种的借方合计必须等于贷方合计, 币种为空的过账行计入本位币
+pub fn check_balanced(postings: &[PostingInput], base_currency: &str) -> Result<()> {
+    let mut totals: BTreeMap<&str, (i64, i64)> = BTreeMap::new();
+    for posting in postings {
+        let currency = posting.currency.as_deref().unwrap_or(base_currency);
+        let (debit, credit) = totals.entry(currency).or_default();
+        let total = match posting.side {
+            Side::Debit => debit,
+            Side::Credit => credit,
+        };
+        *total = total
+            .checked_add(posting.amount)
+            .ok_or_else(|| Error::UnbalancedEntry("amount overflow".to_string()))?;
+    }
+
+    for (currency, (debit, credit)) in totals {
+        if debit != credit {
+            return Err(Error::UnbalancedEntry(format!(
+                "{currency} debits {debit} != credits {credit}"
+            )));
+        }
+    }
+    Ok(())
+}
+
+#[async_trait]
+impl<T> EntryService for EntryServiceImpl<T>
+where
+    T: EntryRepo + Sync + Send,
+{
+    async fn create(&self, ledger: &Ledger, input: CreateEntryInput) -> Result<EntryDetail> {
+        check_balanced(&input.postings, &ledger.base_currency)?;
+
+        let entry = CreateEntry {
+            ledger_id: ledger.id,
+            date: input.date,
+            description: input.description,
+            category_id: input.category_id,
+            tags: input.tags,
+            postings: input
+                .postings
+                .into_iter()
+                .map(|p| CreatePosting {
+                    account_id: p.account_id,
+                    amount: p.amount,
+                    currency: p.currency.unwrap_or_else(|| ledger.base_currency.clone()),
+                    side: p.side,
+                })
+                .collect(),
+        };
+        let entry = self.categorization.categorize(entry).await?;
+        Ok(self.entry_repo.create(entry).await?)
+    }
+
+    async fn get(&self, ledger_id: Uuid, id: Uuid) -> Result<EntryDetail> {
+        let detail = self.entry_repo.get(id).await?;
+        if detail.entry.ledger_id != ledger_id {
+            return Err(Error::Forbidden);
+        }
+        Ok(detail)
+    }
+
+    async fn delete(&self, ledger_id: Uuid, id: Uuid) -> Result<Entry> {
+        self.get(ledger_id, id).await?;
+        Ok(self.entry_repo.delete(id).await?)
+    }
+
+    async fn list(&self, ledger_id: Uuid, input: ListEntryInput) -> Result<Vec<EntryDetail>> {
+        let opt = EntryOption {
+            ledger_id: Some(ledger_id),
+            from: input.from,
+            to: input.to,
+            category_id: input.category_id,
+            offset: input.limit_offset.offset,
+            limit: input.limit_offset.limit,
+        };
+        Ok(self.entry_repo.list(opt).await?)
+    }
+}
+
+#[cfg(test)]
+pub mod tests {
+    use super::*;
+    use crate::{
+        dao::entry_repo::MockEntryRepo, services::categorization::MockCategorizationService,
+    };
+    use chrono::NaiveDate;
+
+    // 不做任何修改的自动分类
+    fn passthrough() -> DynCategorizationService {
+        let mut categorization = MockCategorizationService::new();
+        categorization.expect_categorize().returning(Ok);
+        Arc::new(categorization)
+    }
+
+    fn posting(amount: i64, side: Side) -> PostingInput {
+        PostingInput {
+            account_id: Uuid::new_v4(),
+            amount,
+            currency: None,
+            side,
+        }
+    }
+
+    fn foreign_posting(amount: i64, currency: &str, side: Side) -> PostingInput {
+        PostingInput {
+            currency: Some(currency.to_string()),
+            ..posting(amount, side)
+        }
+    }
+
+    #[test]
+    fn test_check_balanced() {
+        let balanced = vec![
+            posting(1000, Side::Debit),
+            posting(250, Side::Debit),
+            posting(1250, Side::Credit),
+        ];
+        assert!(check_balanced(&balanced, "CNY").is_ok());
+
+        let unbalanced = vec![posting(1000, Side::Debit), posting(999, Side::Credit)];
+        assert!(matches!(
+            check_balanced(&unbalanced, "CNY"),
+            Err(Error::UnbalancedEntry(_))
+        ));
+
+        let overflow = vec![posting(i64::MAX, Side::Debit), posting(1, Side::Debit)];
+        assert!(check_balanced(&overflow, "CNY").is_err());
+    }
+
+    #[test]
+    fn test_check_balanced_per_currency() {
+        let explicit_base = vec![
+            posting(1000, Side::Debit),
+            foreign_posting(1000, "CNY", Side::Credit),
+        ];
+        assert!(check_balanced(&explicit_base, "CNY").is_ok());
+
+        let mixed = vec![
+            foreign_posting(100, "USD", Side::Debit),
+            foreign_posting(100, "USD", Side::Credit),
+            posting(632, Side::Debit),
+            posting(632, Side::Credit),
+        ];
+        assert!(check_balanced(&mixed, "CNY").is_ok());
+
+        let cross_currency = vec![
+            foreign_posting(100, "USD", Side::Debit),
+            posting(100, Side::Credit),
+        ];
+        assert!(matches!(
+            check_balanced(&cross_currency, "CNY"),
+            Err(Error::UnbalancedEntry(_))
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_entry_service_create() {
+        let mut entry_repo = MockEntryRepo::new();
+        entry_repo
+            .expect_create()
+            .withf(|param| param.postings.iter().all(|p| p.currency == "EUR"))
+            .times(1)
+            .returning(|param| {
+                Ok(EntryDetail {
+                    entry: Entry {
+                        ledger_id: param.ledger_id,
+                        ..Default::default()
+                    },
+                    ..Default::default()
+                })
+            });
+
+        let sut = EntryServiceImpl {
+            entry_repo,
+            categorization: passthrough(),
+        };
+        let ledger = Ledger {
+            base_currency: "EUR".to_string(),
+            ..Default::default()
+        };
+        let input = CreateEntryInput {
+            date: NaiveDate::from_ymd(2022, 3, 1),
+            description: "rent".to_string(),
+            category_id: None,
+            tags: Vec::new(),
+            postings: vec![posting(5000, Side::Debit), posting(5000, Side::Credit)],
+        };
+        let detail = sut.create(&ledger, input).await.unwrap();
+        assert_eq!(ledger.id, detail.entry.ledger_id);
+    }
+
+    #[tokio::test]
+    async fn test_entry_service_create_unbalanced() {
+        let mut entry_repo = MockEntryRepo::new();
+        entry_repo.expect_create().never();
+
+        let sut = EntryServiceImpl {
+            entry_repo,
+            categorization: passthrough(),
+        };
+        let input = CreateEntryInput {
+            date: NaiveDate::from_ymd(2022, 3, 1),
+            description: "rent".to_string(),
+            category_id: None,
+            tags: Vec::new(),
+            postings: vec![posting(5000, Side::Debit), posting(4000, Side::Credit)],
+        };
+        let result = sut.create(&Ledger::default(), input).await;
+        assert!(matches!(result, Err(Error::UnbalancedEntry(_))));
+    }
+
+    #[tokio::test]
+    async fn test_entry_service_get_other_ledger() {
+        let mut entry_repo = MockEntryRepo::new();
+        entry_repo.expect_get().returning(|id| {
+            Ok(EntryDetail {
+                entry: Entry {
+                    id,
+                    ledger_id: Uuid::new_v4(),
+                    ..Default::default()
+                },
+                ..Default::default()
+            })
+        });
+        entry_repo.expect_delete().never();
+
+        let sut = EntryServiceImpl {
+            entry_repo,
+            categorization: passthrough(),
+        };
+        let result = sut.delete(Uuid::new_v4(), Uuid::new_v4()).await;
+        assert!(matches!(result, Err(Error::Forbidden)));
+    }
+
+    #[tokio::test]
+    async fn test_entry_service_list() {
+        let mut entry_repo = MockEntryRepo::new();
+        let ledger_id = Uuid::new_v4();
+        entry_repo
+            .expect_list()
+            .withf(move |opt| opt.ledger_id == Some(ledger_id))
+            .returning(|_| Ok(Vec::new()));
+
+        let sut = EntryServiceImpl {
+            entry_repo,
+            categorization: passthrough(),
+        };
+        let result = sut.list(ledger_id, ListEntryInput::default()).await;
+        assert!(result.is_ok());
+    }
+}
@@ -0,0 +1,69 @@
+use super::{single_line, tag_name, ExportAccount, ExportEntry, ExportWriter, Ledger};
+use std::fmt::Write;
+
+/// Beancount 纯文本格式, 科目以 open/close 指令声明
+pub struct BeancountWriter;
+
+fn quote(text: &str) -> String {
+    format!(
+        "\"{}\"",
+        single_line(text).replace('\\', "\\\\").replace('"', "\\\"")
+    )
+}
+
+impl ExportWriter for BeancountWriter {
+    fn header(&self, ledger: &Ledger) -> String {
+        format!(
+            "option \"title\" {}\noption \"operating_currency\" \"{}\"\n\n",
+            quote(&ledger.name),
+            ledger.base_currency
+        )
+    }
+
+    fn account(&self, account: &ExportAccount) -> String {
+        let mut text = format!("{} open {}\n", account.opened_on, account.name());
+        if let Some(closed_on) = account.closed_on {
+            let _ = writeln!(text, "{} close {}", closed_on, account.name());
+        }
+        text
+    }
+
+    fn entry(&self, entry: &ExportEntry) -> String {
+        let mut text = format!("\n{} * {}", entry.date, quote(&entry.description));
+        for tag in &entry.tags {
+            let _ = write!(text, " #{}", tag_name(tag));
+        }
+        text.push('\n');
+        if let Some(ref category) = entry.category {
+            let _ = writeln!(text, "  category: {}", quote(category));
+        }
+        for posting in &entry.postings {
+            let _ = writeln!(
+                text,
+                "  {}  {} {}",
+                posting.account_name(),
+                posting.signed_amount(),
+                posting.currency
+            );
+        }
+        text
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::services::export::tests::{account, entry};
+
+    #[test]
+    fn test_beancount_writer() {
+        assert_eq!(
+            "2022-01-01 open Assets:Bank:Checking\n2022-06-30 close Assets:Bank:Checking\n",
+            BeancountWriter.account(&account())
+        );
+        assert_eq!(
+            "\n2022-04-01 * \"Lunch \\\"noodles\\\"\" #work-trip\n  category: \"Food\"\n  Expenses:Food  45.00 CNY\n  Assets:Bank:Checking  -45.00 CNY\n",
+            BeancountWriter.entry(&entry())
+        );
+    }
+}
@@ -0,0 +1,64 @@
+use super::{ExportAccount, ExportEntry, ExportWriter, Ledger};
+
+/// 每个过账行一行, 只包含分录数据
+pub struct CsvWriter;
+
+// 含分隔符、引号或换行的字段加引号
+fn field(value: &str) -> String {
+    if value.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value.to_string()
+    }
+}
+
+impl ExportWriter for CsvWriter {
+    fn header(&self, _ledger: &Ledger) -> String {
+        "date,entry_id,description,category,tags,account,amount,currency\n".to_string()
+    }
+
+    fn account(&self, _account: &ExportAccount) -> String {
+        String::new()
+    }
+
+    fn entry(&self, entry: &ExportEntry) -> String {
+        let mut rows = String::new();
+        for posting in &entry.postings {
+            let row = [
+                entry.date.to_string(),
+                entry.id.to_string(),
+                field(&entry.description),
+                field(entry.category.as_deref().unwrap_or_default()),
+                field(&entry.tags.join(";")),
+                field(&posting.account_name()),
+                posting.signed_amount().to_string(),
+                posting.currency.clone(),
+            ];
+            rows.push_str(&row.join(","));
+            rows.push('\n');
+        }
+        rows
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::services::export::tests::entry;
+
+    #[test]
+    fn test_csv_writer_entry() {
+        let entry = entry();
+        let rows = CsvWriter.entry(&entry);
+        let lines: Vec<&str> = rows.lines().collect();
+        assert_eq!(2, lines.len());
+        assert_eq!(
+            format!(
+                "2022-04-01,{},\"Lunch \"\"noodles\"\"\",Food,work trip,Expenses:Food,45.00,CNY",
+                entry.id
+            ),
+            lines[0]
+        );
+        assert!(lines[1].ends_with("Assets:Bank:Checking,-45.00,CNY"));
+    }
+}
@@ -0,0 +1,74 @@
+use super::{ExportAccount, ExportEntry, ExportWriter, Ledger};
+use serde_json::json;
+
+/// 每行一个 JSON 对象, type 字段区分账本、科目与分录
+pub struct JsonlWriter;
+
+impl ExportWriter for JsonlWriter {
+    fn header(&self, ledger: &Ledger) -> String {
+        let line = json!({
+            "type": "ledger",
+            "id": ledger.id,
+            "name": ledger.name,
+            "base_currency": ledger.base_currency,
+        });
+        format!("{line}\n")
+    }
+
+    fn account(&self, account: &ExportAccount) -> String {
+        let line = json!({
+            "type": "account",
+            "id": account.id,
+            "name": account.name(),
+            "kind": account.kind,
+            "path": account.path,
+            "opened_on": account.opened_on,
+            "closed_on": account.closed_on,
+        });
+        format!("{line}\n")
+    }
+
+    fn entry(&self, entry: &ExportEntry) -> String {
+        let postings: Vec<_> = entry
+            .postings
+            .iter()
+            .map(|posting| {
+                json!({
+                    "account": posting.account_name(),
+                    "amount": posting.signed_amount().to_string(),
+                    "currency": posting.currency,
+                })
+            })
+            .collect();
+        let line = json!({
+            "type": "entry",
+            "id": entry.id,
+            "date": entry.date,
+            "description": entry.description,
+            "category": entry.category,
+            "tags": entry.tags,
+            "postings": postings,
+        });
+        format!("{line}\n")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::services::export::tests::{account, entry};
+
+    #[test]
+    fn test_jsonl_writer() {
+        let line = JsonlWriter.account(&account());
+        let value: serde_json::Value = serde_json::from_str(&line).unwrap();
+        assert_eq!("Assets:Bank:Checking", value["name"]);
+        assert_eq!("2022-06-30", value["closed_on"]);
+
+        let line = JsonlWriter.entry(&entry());
+        assert_eq!(1, line.lines().count());
+        let value: serde_json::Value = serde_json::from_str(&line).unwrap();
+        assert_eq!("entry", value["type"]);
+        assert_eq!("-45.00", value["postings"][1]["amount"]);
+    }
+}
@@ -0,0 +1,58 @@
+use super::{single_line, tag_name, ExportAccount, ExportEntry, ExportWriter, Ledger};
+use std::fmt::Write;
+
+/// Ledger-cli 纯文本格式, 标签与分类写在分录注释中
+pub struct LedgerWriter;
+
+impl ExportWriter for LedgerWriter {
+    fn header(&self, ledger: &Ledger) -> String {
+        format!(
+            "; {}, base currency {}\n\n",
+            single_line(&ledger.name),
+            ledger.base_currency
+        )
+    }
+
+    fn account(&self, account: &ExportAccount) -> String {
+        format!("account {}\n", account.name())
+    }
+
+    fn entry(&self, entry: &ExportEntry) -> String {
+        let mut text = format!(
+            "\n{} {}\n",
+            entry.date.format("%Y/%m/%d"),
+            single_line(&entry.description)
+        );
+        if !entry.tags.is_empty() {
+            let tags: Vec<String> = entry.tags.iter().map(|tag| tag_name(tag)).collect();
+            let _ = writeln!(text, "    ; :{}:", tags.join(":"));
+        }
+        if let Some(ref category) = entry.category {
+            let _ = writeln!(text, "    ; category: {}", single_line(category));
+        }
+        for posting in &entry.postings {
+            let _ = writeln!(
+                text,
+                "    {}  {} {}",
+                posting.account_name(),
+                posting.signed_amount(),
+                posting.currency
+            );
+        }
+        text
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::services::export::tests::entry;
+
+    #[test]
+    fn test_ledger_writer_entry() {
+        assert_eq!(
+            "\n2022/04/01 Lunch \"noodles\"\n    ; :work-trip:\n    ; category: Food\n    Expenses:Food  45.00 CNY\n    Assets:Bank:Checking  -45.00 CNY\n",
+            LedgerWriter.entry(&entry())
+        );
+    }
+}
@@ -0,0 +1,228 @@
+/// Beancount 格式输出
+mod beancount;
+/// CSV 格式输出
+mod csv;
+/// JSON Lines 格式输出
+mod jsonl;
+/// Ledger-cli 格式输出
+mod ledger;
+
+use self::{beancount::BeancountWriter, csv::CsvWriter, jsonl::JsonlWriter, ledger::LedgerWriter};
+pub(crate) use crate::{
+    dao::export_repo::{ExportRepo, ExportRepoImpl},
+    dto::export::ExportInput,
+    errors::Result,
+    models::{
+        export::{ExportAccount, ExportEntry, ExportFormat},
+        ledger::Ledger,
+    },
+};
+use futures::{stream::BoxStream, StreamExt, TryStreamExt};
+use sqlx::postgres::PgPool;
+use std::sync::Arc;
+
+pub type DynExportService = Arc<dyn ExportService + Send + Sync>;
+
+/// 按导出格式逐段输出的文本流
+pub type ExportStream = BoxStream<'static, Result<String>>;
+
+#[cfg_attr(test, mockall::automock)]
+pub trait ExportService {
+    /// 依次输出文件头、科目与分录, 数据库中的行边读边写
+    fn export(&self, ledger: &Ledger, format: ExportFormat) -> ExportStream;
+}
+
+#[derive(Clone)]
+pub struct ExportServiceImpl<T: ExportRepo>
+where
+    T: ExportRepo + Sync + Send,
+{
+    pub export_repo: T,
+}
+
+impl ExportServiceImpl<ExportRepoImpl> {
+    pub fn new(pool: Arc<PgPool>) -> Self {
+        ExportServiceImpl {
+            export_repo: ExportRepoImpl::new(pool),
+        }
+    }
+}
+
+/// 导出格式输出接口, 新增格式时实现该接口并在 writer_for 中注册
+pub trait ExportWriter {
+    fn header(&self, _ledger: &Ledger) -> String {
+        String::new()
+    }
+    fn account(&self, account: &ExportAccount) -> String;
+    fn entry(&self, entry: &ExportEntry) -> String;
+}
+
+fn writer_for(format: ExportFormat) -> Box<dyn ExportWriter + Send + Sync> {
+    match format {
+        ExportFormat::Csv => Box::new(CsvWriter),
+        ExportFormat::Jsonl => Box::new(JsonlWriter),
+        ExportFormat::Beancount => Box::new(BeancountWriter),
+        ExportFormat::Ledger => Box::new(LedgerWriter),
+    }
+}
+
+// 纯文本格式中摘要只能占一行
+fn single_line(text: &str) -> String {
+    text.split_whitespace().collect::<Vec<_>>().join(" ")
+}
+
+// 标签中不能出现空白与分隔符
+fn tag_name(tag: &str) -> String {
+    tag.chars()
+        .map(|c| match c {
+            c if c.is_alphanumeric() || matches!(c, '-' | '_' | '/' | '.') => c,
+            _ => '-',
+        })
+        .collect()
+}
+
+impl<T> ExportService for ExportServiceImpl<T>
+where
+    T: ExportRepo + Sync + Send,
+{
+    fn export(&self, ledger: &Ledger, format: ExportFormat) -> ExportStream {
+        let writer = writer_for(format);
+        let header = writer.header(ledger);
+        let mut accounts = self.export_repo.accounts(ledger.id);
+        let mut postings = self.export_repo.postings(ledger.id);
+
+        async_stream::try_stream! {
+            yield header;
+            while let Some(account) = accounts.try_next().await? {
+                yield writer.account(&account);
+            }
+
+            // 同一分录的过账行连续出现, 遇到下一个分录时输出上一个
+            let mut current: Option<ExportEntry> = None;
+            while let Some(posting) = postings.try_next().await? {
+                match current.as_mut() {
+                    Some(entry) if entry.id == posting.entry_id => entry.postings.push(posting),
+                    _ => {
+                        if let Some(entry) = current.replace(posting.into()) {
+                            yield writer.entry(&entry);
+                        }
+                    }
+                }
+            }
+            if let Some(entry) = current {
+                yield writer.entry(&entry);
+            }
+        }
+        .try_filter(|chunk| futures::future::ready(!chunk.is_empty()))
+        .boxed()
+    }
+}
+
+#[cfg(test)]
+pub mod tests {
+    use super::*;
+    use crate::{
+        dao::export_repo::MockExportRepo,
+        errors::Error,
+        models::{account::AccountKind, entry::Side, export::ExportPosting},
+    };
+    use chrono::NaiveDate;
+    use futures::stream;
+    use uuid::Uuid;
+
+    pub fn posting(
+        entry_id: Uuid,
+        account: &[&str],
+        kind: AccountKind,
+        side: Side,
+    ) -> ExportPosting {
+        ExportPosting {
+            entry_id,
+            date: NaiveDate::from_ymd(2022, 4, 1),
+            description: "Lunch \"noodles\"".to_string(),
+            category: Some("Food".to_string()),
+            tags: vec!["work trip".to_string()],
+            kind,
+            account: account.iter().map(|name| name.to_string()).collect(),
+            amount: 4500,
+            currency: "CNY".to_string(),
+            minor_unit: 2,
+            side,
+        }
+    }
+
+    pub fn entry() -> ExportEntry {
+        let id = Uuid::new_v4();
+        let mut entry =
+            ExportEntry::from(posting(id, &["Food"], AccountKind::Expense, Side::Debit));
+        entry.postings.push(posting(
+            id,
+            &["Bank", "Checking"],
+            AccountKind::Asset,
+            Side::Credit,
+        ));
+        entry
+    }
+
+    pub fn account() -> ExportAccount {
+        ExportAccount {
+            id: Uuid::new_v4(),
+            kind: AccountKind::Asset,
+            path: vec!["Bank".to_string(), "Checking".to_string()],
+            opened_on: NaiveDate::from_ymd(2022, 1, 1),
+            closed_on: Some(NaiveDate::from_ymd(2022, 6, 30)),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_export_service_groups_postings_into_entries() {
+        let (first, second) = (Uuid::new_v4(), Uuid::new_v4());
+        let mut export_repo = MockExportRepo::new();
+        export_repo
+            .expect_accounts()
+            .returning(|_| stream::iter(vec![Ok(account())]).boxed());
+        export_repo.expect_postings().returning(move |_| {
+            stream::iter(vec![
+                Ok(posting(first, &["Food"], AccountKind::Expense, Side::Debit)),
+                Ok(posting(first, &["Bank"], AccountKind::Asset, Side::Credit)),
+                Ok(posting(
+                    second,
+                    &["Food"],
+                    AccountKind::Expense,
+                    Side::Debit,
+                )),
+                Ok(posting(second, &["Bank"], AccountKind::Asset, Side::Credit)),
+            ])
+            .boxed()
+        });
+
+        let sut = ExportServiceImpl { export_repo };
+        let chunks: Vec<String> = sut
+            .export(&Ledger::default(), ExportFormat::Jsonl)
+            .try_collect()
+            .await
+            .unwrap();
+        // 文件头 + 1 个科目 + 2 个分录
+        assert_eq!(4, chunks.len());
+        assert!(chunks[2].contains(&first.to_string()));
+        assert!(chunks[3].contains(&second.to_string()));
+    }
+
+    #[tokio::test]
+    async fn test_export_service_stops_on_error() {
+        let mut export_repo = MockExportRepo::new();
+        export_repo
+            .expect_accounts()
+            .returning(|_| stream::iter(vec![Err(Error::Forbidden)]).boxed());
+        export_repo
+            .expect_postings()
+            .returning(|_| stream::empty().boxed());
+
+        let sut = ExportServiceImpl { export_repo };
+        let result: Result<Vec<String>> = sut
+            .export(&Ledger::default(), ExportFormat::Csv)
+            .try_collect()
+            .await;
+        assert!(result.is_err());
+    }
+}
@@ -0,0 +1,121 @@
+use super::{parse_amount, Error, ImportFormat, Result, StatementRecord};
+use chrono::NaiveDate;
+use roxmltree::{Document, Node};
+
+/// ISO 20022 CAMT.053 银行对账单, 只导入已记账(BOOK)的条目
+pub struct Camt053Format;
+
+// 按本地名查找子节点, 忽略命名空间以兼容 camt.053 的各个版本
+fn child<'a, 'input>(node: Node<'a, 'input>, name: &str) -> Option<Node<'a, 'input>> {
+    node.children().find(|n| n.tag_name().name() == name)
+}
+
+fn path<'a, 'input>(node: Node<'a, 'input>, names: &[&str]) -> Option<Node<'a, 'input>> {
+    names.iter().try_fold(node, |node, name| child(node, name))
+}
+
+fn text<'a>(node: Option<Node<'a, '_>>) -> Option<&'a str> {
+    node.and_then(|n| n.text())
+        .map(str::trim)
+        .filter(|value| !value.is_empty())
+}
+
+fn descendant_text<'a>(node: Node<'a, '_>, name: &str) -> Option<&'a str> {
+    text(node.descendants().find(|n| n.tag_name().name() == name))
+}
+
+impl ImportFormat for Camt053Format {
+    fn parse(&self, data: &[u8], minor_unit: u32) -> Result<Vec<StatementRecord>> {
+        let xml =
+            std::str::from_utf8(data).map_err(|err| Error::InvalidStatement(err.to_string()))?;
+        let document =
+            Document::parse(xml).map_err(|err| Error::InvalidStatement(err.to_string()))?;
+
+        let mut records = Vec::new();
+        for entry in document
+            .descendants()
+            .filter(|n| n.tag_name().name() == "Ntry")
+        {
+            let line = document.text_pos_at(entry.range().start).row as usize;
+            let invalid = |name: &str, value: Option<&str>| {
+                Error::InvalidStatement(format!("line {line}: invalid {name} {value:?}"))
+            };
+
+            // 早期版本 Sts 直接为文本, 新版本为 Sts/Cd
+            let status = text(child(entry, "Sts")).or_else(|| text(path(entry, &["Sts", "Cd"])));
+            if status.is_some_and(|status| status != "BOOK") {
+                continue;
+            }
+
+            let currency = child(entry, "Amt")
+                .and_then(|amount| amount.attribute("Ccy"))
+                .map(str::to_string);
+            let amount = text(child(entry, "Amt"));
+            let amount = amount
+                .and_then(|value| parse_amount(value, minor_unit))
+                .ok_or_else(|| invalid("Amt", amount))?;
+            let amount = match text(child(entry, "CdtDbtInd")) {
+                Some("CRDT") => amount,
+                Some("DBIT") => -amount,
+                indicator => return Err(invalid("CdtDbtInd", indicator)),
+            };
+
+            let booked = text(path(entry, &["BookgDt", "Dt"]))
+                .or_else(|| text(path(entry, &["BookgDt", "DtTm"])))
+                .or_else(|| text(path(entry, &["ValDt", "Dt"])));
+            let date = booked
+                .and_then(|value| value.get(..10))
+                .and_then(|value| NaiveDate::parse_from_str(value, "%Y-%m-%d").ok())
+                .ok_or_else(|| invalid("BookgDt", booked))?;
+
+            // 优先使用非结构化附言, 其次是条目附加信息与交易对手名称
+            let description = descendant_text(entry, "Ustrd")
+                .or_else(|| text(child(entry, "AddtlNtryInf")))
+                .or_else(|| descendant_text(entry, "Nm"))
+                .unwrap_or_default()
+                .to_string();
+
+            records.push(StatementRecord {
+                line,
+                date,
+                description,
+                amount,
+                currency,
+                transaction_id: text(child(entry, "AcctSvcrRef")).map(str::to_string),
+            });
+        }
+        Ok(records)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_camt053_format_parse() {
+        let data = include_bytes!("../../../tests/fixtures/statement.camt053.xml");
+        let records = Camt053Format.parse(data, 2).unwrap();
+        assert_eq!(3, records.len());
+        assert_eq!(NaiveDate::from_ymd(2022, 4, 1), records[0].date);
+        assert_eq!(-150000, records[0].amount);
+        assert_eq!(Some("EUR"), records[0].currency.as_deref());
+        assert_eq!(Some("20220401-0001"), records[0].transaction_id.as_deref());
+        assert_eq!(None, records[1].transaction_id);
+        assert_eq!("Rent April 2022", records[0].description);
+        assert_eq!(320000, records[1].amount);
+        assert_eq!("ACME GmbH", records[1].description);
+        assert_eq!(NaiveDate::from_ymd(2022, 4, 6), records[2].date);
+        assert_eq!("Card payment", records[2].description);
+    }
+
+    #[test]
+    fn test_camt053_format_parse_invalid() {
+        let result = Camt053Format.parse(b"<Document><Ntry></Document>", 2);
+        assert!(matches!(result, Err(Error::InvalidStatement(_))));
+
+        let data = br#"<Document><Ntry><Amt Ccy="EUR">1.00</Amt><BookgDt><Dt>2022-04-01</Dt></BookgDt></Ntry></Document>"#;
+        let result = Camt053Format.parse(data, 2);
+        assert!(matches!(result, Err(Error::InvalidStatement(msg)) if msg.contains("CdtDbtInd")));
+    }
+}
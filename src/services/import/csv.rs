@@ -0,0 +1,117 @@
+use super::{parse_amount, ColumnMapping, Error, ImportFormat, Result, StatementRecord};
+use chrono::NaiveDate;
+
+/// 按列映射解析的 CSV 对账单
+pub struct CsvFormat {
+    columns: ColumnMapping,
+}
+
+impl CsvFormat {
+    pub fn new(columns: ColumnMapping) -> Self {
+        CsvFormat { columns }
+    }
+}
+
+impl ImportFormat for CsvFormat {
+    fn parse(&self, data: &[u8], minor_unit: u32) -> Result<Vec<StatementRecord>> {
+        let columns = &self.columns;
+        let mut reader = ::csv::ReaderBuilder::new()
+            .delimiter(columns.delimiter as u8)
+            .trim(::csv::Trim::All)
+            .flexible(true)
+            .from_reader(data);
+        let headers = reader
+            .headers()
+            .map_err(|err| Error::InvalidStatement(err.to_string()))?
+            .clone();
+        let column = |name: &String| {
+            headers
+                .iter()
+                .position(|header| header == name)
+                .ok_or_else(|| Error::InvalidStatement(format!("missing column {name}")))
+        };
+        let required = |name: &Option<String>| {
+            name.as_ref()
+                .ok_or_else(|| Error::new_empty_fields_error("csv column mapping".to_string()))
+                .and_then(column)
+        };
+        let date_column = required(&columns.date)?;
+        let description_column = required(&columns.description)?;
+        let amount_column = columns.amount.as_ref().map(column).transpose()?;
+        let debit_column = columns.debit.as_ref().map(column).transpose()?;
+        let credit_column = columns.credit.as_ref().map(column).transpose()?;
+
+        let mut records = Vec::new();
+        for record in reader.records() {
+            let record = record.map_err(|err| Error::InvalidStatement(err.to_string()))?;
+            let line = record.position().map_or(0, |pos| pos.line() as usize);
+            let field = |column: usize| record.get(column).unwrap_or_default();
+            let invalid = |name: &str, value: &str| {
+                Error::InvalidStatement(format!("line {line}: invalid {name} {value:?}"))
+            };
+
+            let date = NaiveDate::parse_from_str(field(date_column), &columns.date_format)
+                .map_err(|_| invalid("date", field(date_column)))?;
+            let amount = match amount_column {
+                Some(column) => parse_amount(field(column), minor_unit)
+                    .ok_or_else(|| invalid("amount", field(column)))?,
+                None => {
+                    // debit 列为转出, credit 列为转入, 空值视为零
+                    let split = |column: Option<usize>, name: &str| match column.map(field) {
+                        None | Some("") => Ok(0),
+                        Some(value) => parse_amount(value, minor_unit)
+                            .map(i64::abs)
+                            .ok_or_else(|| invalid(name, value)),
+                    };
+                    split(credit_column, "credit")? - split(debit_column, "debit")?
+                }
+            };
+
+            records.push(StatementRecord {
+                line,
+                date,
+                description: field(description_column).to_string(),
+                amount,
+                currency: None,
+                transaction_id: None,
+            });
+        }
+        Ok(records)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::services::import::tests::columns;
+
+    #[test]
+    fn test_csv_format_parse() {
+        let data = include_bytes!("../../../tests/fixtures/statement.csv");
+        let sut = CsvFormat::new(columns(Some("Amount"), None, None));
+        let records = sut.parse(data, 2).unwrap();
+        assert_eq!(4, records.len());
+        assert_eq!(NaiveDate::from_ymd(2022, 4, 1), records[0].date);
+        assert_eq!(-1250, records[0].amount);
+        assert_eq!(500000, records[3].amount);
+
+        let data = "Date;Memo;Out;In\n01/04/2022;Rent;3000;\n02/04/2022;Refund;;45.10\n";
+        let mut split = columns(None, Some("Out"), Some("In"));
+        split.delimiter = ';';
+        let records = CsvFormat::new(split).parse(data.as_bytes(), 2).unwrap();
+        assert_eq!(
+            vec![-300000, 4510],
+            records.iter().map(|r| r.amount).collect::<Vec<_>>()
+        );
+    }
+
+    #[test]
+    fn test_csv_format_parse_invalid() {
+        let data = "Date,Memo,Amount\n2022-04-01,Coffee,-12.50\n";
+        let result = CsvFormat::new(columns(Some("Amount"), None, None)).parse(data.as_bytes(), 2);
+        assert!(matches!(result, Err(Error::InvalidStatement(msg)) if msg.starts_with("line 2")));
+
+        let result = CsvFormat::new(columns(Some("Value"), None, None)).parse(data.as_bytes(), 2);
+        assert!(matches!(result, Err(Error::InvalidStatement(_))));
+    }
+}
@@ -0,0 +1,412 @@
+/// CAMT.053 对账单解析
+mod camt;
+/// CSV 对账单解析
+mod csv;
+/// OFX/QFX 对账单解析
+mod ofx;
+
+use self::{camt::Camt053Format, csv::CsvFormat, ofx::OfxFormat};
+use super::{
+    categorization::{CategorizationServiceImpl, DynCategorizationService},
+    currency::{CurrencyServiceImpl, DynCurrencyService},
+};
+pub(crate) use crate::{
+    dao::import_repo::{ImportRepo, ImportRepoImpl},
+    dto::import::{ColumnMapping, ImportOptions},
+    errors::{Error, Result},
+    models::{
+        import::{
+            fingerprint, transaction_fingerprint, ImportSummary, PreviewLine, StatementFormat,
+            StatementLine, StatementRecord,
+        },
+        ledger::Ledger,
+    },
+};
+use axum::async_trait;
+use rust_decimal::{prelude::ToPrimitive, Decimal};
+use sqlx::postgres::PgPool;
+use std::{collections::HashMap, str::FromStr, sync::Arc};
+use uuid::Uuid;
+
+pub type DynImportService = Arc<dyn ImportService + Send + Sync>;
+
+#[cfg_attr(test, mockall::automock)]
+#[async_trait]
+pub trait ImportService {
+    /// 解析对账单并标记已导入过的行, 不写入数据
+    async fn preview(
+        &self,
+        ledger: &Ledger,
+        options: ImportOptions,
+        data: Vec<u8>,
+    ) -> Result<Vec<PreviewLine>>;
+    /// 解析对账单并将未导入过的行写入为分录
+    async fn import(
+        &self,
+        ledger: &Ledger,
+        options: ImportOptions,
+        data: Vec<u8>,
+    ) -> Result<ImportSummary>;
+}
+
+#[derive(Clone)]
+pub struct ImportServiceImpl<T: ImportRepo>
+where
+    T: ImportRepo + Sync + Send,
+{
+    pub import_repo: T,
+    pub currencies: DynCurrencyService,
+    pub categorization: DynCategorizationService,
+}
+
+impl ImportServiceImpl<ImportRepoImpl> {
+    pub fn new(pool: Arc<PgPool>) -> Self {
+        ImportServiceImpl {
+            import_repo: ImportRepoImpl::new(pool.clone()),
+            currencies: Arc::new(CurrencyServiceImpl::new(pool.clone())),
+            categorization: Arc::new(CategorizationServiceImpl::new(pool)),
+        }
+    }
+}
+
+/// 对账单格式解析接口, 新增格式时实现该接口并在 parser_for 中注册
+pub trait ImportFormat {
+    /// 解析对账单中的交易, minor_unit 为对账单币种的小数位数
+    fn parse(&self, data: &[u8], minor_unit: u32) -> Result<Vec<StatementRecord>>;
+}
+
+fn parser_for(options: &ImportOptions) -> Box<dyn ImportFormat + Send + Sync> {
+    match options.format {
+        StatementFormat::Csv => Box::new(CsvFormat::new(options.columns.clone())),
+        StatementFormat::Ofx => Box::new(OfxFormat),
+        StatementFormat::Camt053 => Box::new(Camt053Format),
+    }
+}
+
+/// 将金额文本转换为最小货币单位, 支持千分位分隔符与括号表示的负数,
+/// 小数位数超出货币精度时视为无效
+pub(crate) fn parse_amount(raw: &str, minor_unit: u32) -> Option<i64> {
+    let raw = raw.trim();
+    let (negative, raw) = match raw.strip_prefix('(').and_then(|r| r.strip_suffix(')')) {
+        Some(inner) => (true, inner),
+        None => (false, raw),
+    };
+    let cleaned: String = raw
+        .chars()
+        .filter(|c| !matches!(c, ',' | ' ' | '\u{a0}'))
+        .collect();
+    let scaled = Decimal::from_str(&cleaned)
+        .ok()?
+        .checked_mul(Decimal::from(10u64.checked_pow(minor_unit)?))?;
+    if !scaled.fract().is_zero() {
+        return None;
+    }
+    let amount = scaled.to_i64()?;
+    Some(if negative { -amount } else { amount })
+}
+
+/// 为解析出的交易计算指纹, 金额为零的交易(如余额行)跳过;
+/// 带银行交易标识的交易以标识计算指纹, 其余按日期、金额与摘要计算
+pub fn fingerprint_records(account_id: Uuid, records: Vec<StatementRecord>) -> Vec<StatementLine> {
+    let mut occurrences: HashMap<String, usize> = HashMap::new();
+    records
+        .into_iter()
+        .filter(|record| record.amount != 0)
+        .map(|record| {
+            let fingerprint = match &record.transaction_id {
+                Some(transaction_id) => transaction_fingerprint(account_id, transaction_id),
+                None => {
+                    // 同一文件内完全相同的交易按出现顺序编号, 保证各自的指纹不同
+                    let occurrence = occurrences
+                        .entry(fingerprint(
+                            account_id,
+                            record.date,
+                            record.amount,
+                            &record.description,
+                            0,
+                        ))
+                        .or_insert(0);
+                    let fingerprint = fingerprint(
+                        account_id,
+                        record.date,
+                        record.amount,
+                        &record.description,
+                        *occurrence,
+                    );
+                    *occurrence += 1;
+                    fingerprint
+                }
+            };
+            StatementLine {
+                line: record.line,
+                date: record.date,
+                fingerprint,
+                description: record.description,
+                amount: record.amount,
+            }
+        })
+        .collect()
+}
+
+impl<T> ImportServiceImpl<T>
+where
+    T: ImportRepo + Sync + Send,
+{
+    // 按对账单格式与币种精度解析
+    async fn parse(
+        &self,
+        ledger: &Ledger,
+        options: &ImportOptions,
+        data: &[u8],
+    ) -> Result<(String, Vec<StatementLine>)> {
+        let currency = options
+            .currency
+            .clone()
+            .unwrap_or_else(|| ledger.base_currency.clone());
+        let minor_unit = self.currencies.get(&currency).await?.minor_unit as u32;
+        let records = parser_for(options).parse(data, minor_unit)?;
+        // 对账单声明的币种须与导入币种一致, 否则金额会按错误的币种入账
+        let mismatched = records.iter().find(|record| {
+            record
+                .currency
+                .as_deref()
+                .is_some_and(|code| !code.eq_ignore_ascii_case(&currency))
+        });
+        if let Some(record) = mismatched {
+            return Err(Error::InvalidStatement(format!(
+                "line {}: statement currency {:?} does not match {currency}",
+                record.line, record.currency
+            )));
+        }
+        Ok((currency, fingerprint_records(options.account_id, records)))
+    }
+}
+
+#[async_trait]
+impl<T> ImportService for ImportServiceImpl<T>
+where
+    T: ImportRepo + Sync + Send,
+{
+    async fn preview(
+        &self,
+        ledger: &Ledger,
+        options: ImportOptions,
+        data: Vec<u8>,
+    ) -> Result<Vec<PreviewLine>> {
+        let (_, lines) = self.parse(ledger, &options, &data).await?;
+        let fingerprints = lines.iter().map(|l| l.fingerprint.clone()).collect();
+        let imported = self.import_repo.imported(ledger.id, fingerprints).await?;
+        Ok(lines
+            .into_iter()
+            .map(|line| PreviewLine {
+                duplicate: imported.contains(&line.fingerprint),
+                line,
+            })
+            .collect())
+    }
+
+    async fn import(
+        &self,
+        ledger: &Ledger,
+        options: ImportOptions,
+        data: Vec<u8>,
+    ) -> Result<ImportSummary> {
+        let (currency, lines) = self.parse(ledger, &options, &data).await?;
+        let total = lines.len();
+        let mut entries = Vec::with_capacity(total);
+        for line in lines {
+            let entry = line.to_entry(
+                ledger.id,
+                options.account_id,
+                options.contra_account_id,
+                &currency,
+            );
+            entries.push((
+                line.fingerprint,
+                self.categorization.categorize(entry).await?,
+            ));
+        }
+
+        let created = self.import_repo.import(ledger.id, entries).await?;
+        Ok(ImportSummary {
+            imported: created.len(),
+            duplicates: total - created.len(),
+            entries: created,
+        })
+    }
+}
+
+#[cfg(test)]
+pub mod tests {
+    use super::*;
+    use crate::{
+        dao::import_repo::MockImportRepo,
+        models::{currency::Currency, entry::EntryDetail},
+        services::{categorization::MockCategorizationService, currency::MockCurrencyService},
+    };
+    use chrono::NaiveDate;
+
+    pub fn columns(
+        amount: Option<&str>,
+        debit: Option<&str>,
+        credit: Option<&str>,
+    ) -> ColumnMapping {
+        ColumnMapping {
+            date: Some("Date".to_string()),
+            description: Some("Memo".to_string()),
+            amount: amount.map(str::to_string),
+            debit: debit.map(str::to_string),
+            credit: credit.map(str::to_string),
+            date_format: "%d/%m/%Y".to_string(),
+            delimiter: ',',
+        }
+    }
+
+    #[test]
+    fn test_parse_amount() {
+        assert_eq!(Some(123456), parse_amount("1,234.56", 2));
+        assert_eq!(Some(-1250), parse_amount("(12.50)", 2));
+        assert_eq!(Some(-1250), parse_amount(" -12.5 ", 2));
+        assert_eq!(Some(1200), parse_amount("1200", 0));
+        assert_eq!(None, parse_amount("12.345", 2));
+        assert_eq!(None, parse_amount("abc", 2));
+    }
+
+    #[test]
+    fn test_fingerprint_records() {
+        let record = |line, description: &str, amount| StatementRecord {
+            line,
+            date: NaiveDate::from_ymd(2022, 4, 1),
+            description: description.to_string(),
+            amount,
+            currency: None,
+            transaction_id: None,
+        };
+        let records = vec![
+            record(2, "Coffee", -1250),
+            record(3, "Coffee", -1250),
+            record(4, "Balance", 0),
+            record(5, "Salary", 500000),
+        ];
+        let lines = fingerprint_records(Uuid::new_v4(), records);
+        assert_eq!(
+            vec![2, 3, 5],
+            lines.iter().map(|l| l.line).collect::<Vec<_>>()
+        );
+        assert_ne!(lines[0].fingerprint, lines[1].fingerprint);
+
+        // 带交易标识时摘要变化不影响指纹, 相同摘要与金额的不同交易也不会混淆
+        let account_id = Uuid::new_v4();
+        let with_id = |line, description: &str, id: &str| StatementRecord {
+            transaction_id: Some(id.to_string()),
+            ..record(line, description, -1250)
+        };
+        let first = fingerprint_records(account_id, vec![with_id(2, "Coffee", "T1")]);
+        let lines = fingerprint_records(
+            account_id,
+            vec![
+                with_id(2, "COFFEE SHOP", "T1"),
+                with_id(3, "COFFEE SHOP", "T2"),
+            ],
+        );
+        assert_eq!(first[0].fingerprint, lines[0].fingerprint);
+        assert_ne!(lines[0].fingerprint, lines[1].fingerprint);
+    }
+
+    #[tokio::test]
+    async fn test_import_service_import_skips_duplicates() {
+        let mut currencies = MockCurrencyService::new();
+        currencies.expect_get().returning(|code| {
+            Ok(Currency {
+                code: code.to_string(),
+                name: "Chinese Yuan".to_string(),
+                minor_unit: 2,
+            })
+        });
+        let mut categorization = MockCategorizationService::new();
+        categorization
+            .expect_categorize()
+            .times(2)
+            .returning(|mut entry| {
+                entry.tags.push("imported".to_string());
+                Ok(entry)
+            });
+        let mut import_repo = MockImportRepo::new();
+        import_repo
+            .expect_import()
+            .withf(|_, lines| {
+                lines.len() == 2
+                    && lines[0].1.postings[0].currency == "CNY"
+                    && lines[0].1.tags == vec!["imported"]
+            })
+            .returning(|_, _| Ok(vec![EntryDetail::default()]));
+
+        let sut = ImportServiceImpl {
+            import_repo,
+            currencies: Arc::new(currencies),
+            categorization: Arc::new(categorization),
+        };
+        let ledger = Ledger {
+            base_currency: "CNY".to_string(),
+            ..Default::default()
+        };
+        let options = ImportOptions {
+            account_id: Uuid::new_v4(),
+            contra_account_id: Uuid::new_v4(),
+            currency: None,
+            format: StatementFormat::Csv,
+            columns: columns(Some("Amount"), None, None),
+        };
+        let data = "Date,Memo,Amount\n01/04/2022,Coffee,-12.50\n02/04/2022,Lunch,-30\n";
+        let summary = sut
+            .import(&ledger, options, data.as_bytes().to_vec())
+            .await
+            .unwrap();
+        assert_eq!(1, summary.imported);
+        assert_eq!(1, summary.duplicates);
+    }
+
+    #[tokio::test]
+    async fn test_import_service_rejects_currency_mismatch() {
+        let mut currencies = MockCurrencyService::new();
+        currencies.expect_get().returning(|code| {
+            Ok(Currency {
+                code: code.to_string(),
+                name: "Chinese Yuan".to_string(),
+                minor_unit: 2,
+            })
+        });
+        let mut import_repo = MockImportRepo::new();
+        import_repo
+            .expect_imported()
+            .times(1)
+            .returning(|_, _| Ok(Vec::new()));
+
+        let sut = ImportServiceImpl {
+            import_repo,
+            currencies: Arc::new(currencies),
+            categorization: Arc::new(MockCategorizationService::new()),
+        };
+        let ledger = Ledger {
+            base_currency: "CNY".to_string(),
+            ..Default::default()
+        };
+        let options = ImportOptions {
+            account_id: Uuid::new_v4(),
+            contra_account_id: Uuid::new_v4(),
+            currency: None,
+            format: StatementFormat::Ofx,
+            columns: columns(None, None, None),
+        };
+        let data = include_bytes!("../../../tests/fixtures/statement.ofx").to_vec();
+        let result = sut.preview(&ledger, options.clone(), data.clone()).await;
+        assert!(matches!(result, Err(Error::InvalidStatement(msg)) if msg.contains("USD")));
+
+        let options = ImportOptions {
+            currency: Some("USD".to_string()),
+            ..options
+        };
+        let lines = sut.preview(&ledger, options, data).await.unwrap();
+        assert_eq!(3, lines.len());
+    }
+}
@@ -0,0 +1,131 @@
+use super::{parse_amount, Error, ImportFormat, Result, StatementRecord};
+use chrono::NaiveDate;
+
+/// OFX/QFX 对账单, 兼容 OFX 1.x 的 SGML 写法(标签可不闭合)与 OFX 2.x 的 XML 写法
+pub struct OfxFormat;
+
+// 取块内第一个 tag 的值, 值截止到下一个标签
+fn tag_value<'a>(block: &'a str, tag: &str) -> Option<&'a str> {
+    let open = format!("<{tag}>");
+    let value = &block[block.find(&open)? + open.len()..];
+    let value = value[..value.find('<').unwrap_or(value.len())].trim();
+    Some(value).filter(|value| !value.is_empty())
+}
+
+fn unescape(value: &str) -> String {
+    value
+        .replace("&lt;", "<")
+        .replace("&gt;", ">")
+        .replace("&quot;", "\"")
+        .replace("&apos;", "'")
+        .replace("&amp;", "&")
+}
+
+impl ImportFormat for OfxFormat {
+    fn parse(&self, data: &[u8], minor_unit: u32) -> Result<Vec<StatementRecord>> {
+        const OPEN: &str = "<STMTTRN>";
+        const CLOSE: &str = "</STMTTRN>";
+
+        let text = String::from_utf8_lossy(data);
+        let mut records = Vec::new();
+        let mut offset = 0;
+        let mut line = 1;
+        let mut counted = 0;
+        let mut currency = None;
+        while let Some(start) = text[offset..].find(OPEN) {
+            let start = offset + start + OPEN.len();
+            // 行号与币种只扫描上次位置之后的内容, 避免每笔交易都从文件开头重新计算
+            line += text[counted..start].matches('\n').count();
+            counted = start;
+            if let Some(value) = tag_value(&text[offset..start], "CURDEF") {
+                currency = Some(value.to_string());
+            }
+            let invalid = |name: &str, value: Option<&str>| {
+                Error::InvalidStatement(format!("line {line}: invalid {name} {value:?}"))
+            };
+            let end = text[start..]
+                .find(CLOSE)
+                .map(|end| start + end)
+                .ok_or_else(|| invalid("transaction", None))?;
+            let block = &text[start..end];
+            offset = end + CLOSE.len();
+
+            // DTPOSTED 形如 20220401120000.000[-5:EST], 只取日期部分
+            let posted = tag_value(block, "DTPOSTED");
+            let date = posted
+                .and_then(|value| value.get(..8))
+                .and_then(|value| NaiveDate::parse_from_str(value, "%Y%m%d").ok())
+                .ok_or_else(|| invalid("DTPOSTED", posted))?;
+
+            // 部分地区的 OFX 以逗号作小数点
+            let amount = tag_value(block, "TRNAMT");
+            let amount = amount
+                .map(|value| match value.contains('.') {
+                    true => value.to_string(),
+                    false => value.replace(',', "."),
+                })
+                .and_then(|value| parse_amount(&value, minor_unit))
+                .ok_or_else(|| invalid("TRNAMT", amount))?;
+
+            let description = [tag_value(block, "NAME"), tag_value(block, "MEMO")]
+                .iter()
+                .flatten()
+                .map(|value| unescape(value))
+                .collect::<Vec<_>>()
+                .join(" ");
+
+            records.push(StatementRecord {
+                line,
+                date,
+                description,
+                amount,
+                currency: currency.clone(),
+                transaction_id: tag_value(block, "FITID").map(unescape),
+            });
+        }
+        Ok(records)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_ofx_format_parse_sgml() {
+        let data = include_bytes!("../../../tests/fixtures/statement.ofx");
+        let records = OfxFormat.parse(data, 2).unwrap();
+        assert_eq!(3, records.len());
+        assert_eq!(NaiveDate::from_ymd(2022, 4, 1), records[0].date);
+        assert_eq!(-1250, records[0].amount);
+        assert_eq!("COFFEE SHOP Card 1234", records[0].description);
+        assert_eq!(500000, records[1].amount);
+        assert_eq!("AT&T", records[2].description);
+        assert_eq!(Some("USD"), records[0].currency.as_deref());
+        assert_eq!(Some("202204010001"), records[0].transaction_id.as_deref());
+        assert_eq!(
+            vec![39, 47, 54],
+            records.iter().map(|r| r.line).collect::<Vec<_>>()
+        );
+    }
+
+    #[test]
+    fn test_ofx_format_parse_xml_qfx() {
+        let data = include_bytes!("../../../tests/fixtures/statement.qfx");
+        let records = OfxFormat.parse(data, 2).unwrap();
+        assert_eq!(2, records.len());
+        assert_eq!(NaiveDate::from_ymd(2022, 4, 5), records[0].date);
+        assert_eq!(-8800, records[0].amount);
+        assert_eq!("GROCERY MART", records[0].description);
+        assert_eq!(-4550, records[1].amount);
+        assert_eq!(Some("USD"), records[1].currency.as_deref());
+        assert_eq!(Some("CC20220407001"), records[1].transaction_id.as_deref());
+    }
+
+    #[test]
+    fn test_ofx_format_parse_invalid() {
+        let data = b"<OFX><STMTTRN><TRNTYPE>DEBIT<DTPOSTED>2022-04-01<TRNAMT>-1</STMTTRN></OFX>";
+        let result = OfxFormat.parse(data, 2);
+        assert!(matches!(result, Err(Error::InvalidStatement(msg)) if msg.contains("DTPOSTED")));
+    }
+}
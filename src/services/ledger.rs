@@ -0,0 +1,276 @@
+pub(crate) use crate::{
+    dao::{
+        ledger_repo::{LedgerRepo, LedgerRepoImpl},
+        member_repo::{MemberRepo, MemberRepoImpl},
+    },
+    dto::ledger::{CreateLedgerInput, ListLedgerInput, UpdateLedgerInput},
+    errors::{Error, Result},
+    models::{
+        ledger::{CreateLedger, Ledger, LedgerOption},
+        member::LedgerRole,
+    },
+};
+use axum::async_trait;
+use sqlx::postgres::PgPool;
+use std::sync::Arc;
+use uuid::Uuid;
+
+pub type DynLedgerService = Arc<dyn LedgerService + Send + Sync>;
+
+#[cfg_attr(test, mockall::automock)]
+#[async_trait]
+pub trait LedgerService {
+    async fn create(&self, owner_id: Uuid, input: CreateLedgerInput) -> Result<Ledger>;
+    /// 账本及用户在其中的角色, 用户不是账本成员时返回 Forbidden
+    async fn access(&self, user_id: Uuid, id: Uuid) -> Result<(Ledger, LedgerRole)>;
+    async fn delete(&self, user_id: Uuid, id: Uuid) -> Result<Ledger>;
+    async fn update(&self, user_id: Uuid, id: Uuid, input: UpdateLedgerInput) -> Result<Ledger>;
+    /// 用户作为成员可访问的账本
+    async fn list(&self, user_id: Uuid, input: ListLedgerInput) -> Result<Vec<Ledger>>;
+}
+
+#[derive(Clone)]
+pub struct LedgerServiceImpl<T, M>
+where
+    T: LedgerRepo + Sync + Send,
+    M: MemberRepo + Sync + Send,
+{
+    pub ledger_repo: T,
+    pub member_repo: M,
+}
+
+impl LedgerServiceImpl<LedgerRepoImpl, MemberRepoImpl> {
+    pub fn new(pool: Arc<PgPool>) -> Self {
+        LedgerServiceImpl {
+            ledger_repo: LedgerRepoImpl::new(pool.clone()),
+            member_repo: MemberRepoImpl::new(pool),
+        }
+    }
+}
+
+impl<T, M> LedgerServiceImpl<T, M>
+where
+    T: LedgerRepo + Sync + Send,
+    M: MemberRepo + Sync + Send,
+{
+    // 获取账本并校验用户的角色不低于 required
+    async fn get_with_role(&self, user_id: Uuid, id: Uuid, required: LedgerRole) -> Result<Ledger> {
+        let (ledger, role) = self.access(user_id, id).await?;
+        if role < required {
+            return Err(Error::Forbidden);
+        }
+        Ok(ledger)
+    }
+}
+
+#[async_trait]
+impl<T, M> LedgerService for LedgerServiceImpl<T, M>
+where
+    T: LedgerRepo + Sync + Send,
+    M: MemberRepo + Sync + Send,
+{
+    async fn create(&self, owner_id: Uuid, input: CreateLedgerInput) -> Result<Ledger> {
+        let ledger = CreateLedger {
+            name: input.name,
+            owner_id,
+            base_currency: input.base_currency,
+        };
+        Ok(self.ledger_repo.create(ledger).await?)
+    }
+
+    async fn access(&self, user_id: Uuid, id: Uuid) -> Result<(Ledger, LedgerRole)> {
+        let role = self
+            .member_repo
+            .role(id, user_id)
+            .await?
+            .ok_or(Error::Forbidden)?;
+        Ok((self.ledger_repo.get(id).await?, role))
+    }
+
+    async fn delete(&self, user_id: Uuid, id: Uuid) -> Result<Ledger> {
+        self.get_with_role(user_id, id, LedgerRole::Owner).await?;
+        Ok(self.ledger_repo.delete(id).await?)
+    }
+
+    async fn update(&self, user_id: Uuid, id: Uuid, input: UpdateLedgerInput) -> Result<Ledger> {
+        let mut origin_ledger = self.get_with_role(user_id, id, LedgerRole::Owner).await?;
+
+        if let Some(name) = input.name {
+            origin_ledger.name = name;
+        }
+
+        if let Some(base_currency) = input.base_currency {
+            origin_ledger.base_currency = base_currency;
+        }
+
+        if let Some(archived) = input.archived {
+            origin_ledger.archived = archived;
+        }
+        Ok(self.ledger_repo.update(&origin_ledger).await?)
+    }
+
+    async fn list(&self, user_id: Uuid, input: ListLedgerInput) -> Result<Vec<Ledger>> {
+        let opt = LedgerOption {
+            member_id: Some(user_id),
+            archived: input.archived,
+            offset: input.limit_offset.offset,
+            limit: input.limit_offset.limit,
+        };
+        Ok(self.ledger_repo.list(opt).await?)
+    }
+}
+
+#[cfg(test)]
+pub mod tests {
+    use super::*;
+    use crate::dao::{ledger_repo::MockLedgerRepo, member_repo::MockMemberRepo};
+    use mockall::predicate::*;
+
+    fn member_repo(role: Option<LedgerRole>) -> MockMemberRepo {
+        let mut member_repo = MockMemberRepo::new();
+        member_repo.expect_role().returning(move |_, _| Ok(role));
+        member_repo
+    }
+
+    fn ledger_repo() -> MockLedgerRepo {
+        let mut ledger_repo = MockLedgerRepo::new();
+        ledger_repo.expect_get().returning(|id| {
+            Ok(Ledger {
+                id,
+                ..Default::default()
+            })
+        });
+        ledger_repo
+    }
+
+    #[tokio::test]
+    async fn test_ledger_service_create() {
+        let mut ledger_repo = MockLedgerRepo::new();
+        ledger_repo
+            .expect_create()
+            .with(always())
+            .times(1)
+            .returning(|param| {
+                Ok(Ledger {
+                    name: param.name,
+                    owner_id: Some(param.owner_id),
+                    base_currency: param.base_currency,
+                    ..Default::default()
+                })
+            });
+
+        let sut = LedgerServiceImpl {
+            ledger_repo,
+            member_repo: MockMemberRepo::new(),
+        };
+        let owner_id = Uuid::new_v4();
+        let ledger = sut
+            .create(
+                owner_id,
+                CreateLedgerInput {
+                    name: "household".to_string(),
+                    base_currency: "USD".to_string(),
+                },
+            )
+            .await
+            .unwrap();
+        assert_eq!(Some(owner_id), ledger.owner_id);
+        assert_eq!("USD", ledger.base_currency);
+    }
+
+    #[tokio::test]
+    async fn test_ledger_service_access() {
+        let sut = LedgerServiceImpl {
+            ledger_repo: ledger_repo(),
+            member_repo: member_repo(Some(LedgerRole::Viewer)),
+        };
+        let ledger_id = Uuid::new_v4();
+        let (ledger, role) = sut.access(Uuid::new_v4(), ledger_id).await.unwrap();
+        assert_eq!(ledger_id, ledger.id);
+        assert_eq!(LedgerRole::Viewer, role);
+    }
+
+    #[tokio::test]
+    async fn test_ledger_service_access_forbidden() {
+        let mut ledger_repo = MockLedgerRepo::new();
+        ledger_repo.expect_get().never();
+
+        let sut = LedgerServiceImpl {
+            ledger_repo,
+            member_repo: member_repo(None),
+        };
+        let result = sut.access(Uuid::new_v4(), Uuid::new_v4()).await;
+        assert!(matches!(result, Err(Error::Forbidden)));
+    }
+
+    #[tokio::test]
+    async fn test_ledger_service_update() {
+        let mut ledger_repo = ledger_repo();
+        ledger_repo
+            .expect_update()
+            .with(always())
+            .returning(|ledger| Ok(ledger.clone()));
+
+        let sut = LedgerServiceImpl {
+            ledger_repo,
+            member_repo: member_repo(Some(LedgerRole::Owner)),
+        };
+        let opt = UpdateLedgerInput {
+            name: None,
+            base_currency: None,
+            archived: Some(true),
+        };
+        let ledger = sut
+            .update(Uuid::new_v4(), Uuid::new_v4(), opt)
+            .await
+            .unwrap();
+        assert!(ledger.archived);
+    }
+
+    #[tokio::test]
+    async fn test_ledger_service_delete() {
+        let mut ledger_repo = ledger_repo();
+        ledger_repo.expect_delete().times(1).returning(|id| {
+            Ok(Ledger {
+                id,
+                ..Default::default()
+            })
+        });
+
+        let sut = LedgerServiceImpl {
+            ledger_repo,
+            member_repo: member_repo(Some(LedgerRole::Owner)),
+        };
+        assert!(sut.delete(Uuid::new_v4(), Uuid::new_v4()).await.is_ok());
+    }
+
+    #[tokio::test]
+    async fn test_ledger_service_delete_requires_owner() {
+        let mut ledger_repo = ledger_repo();
+        ledger_repo.expect_delete().never();
+
+        let sut = LedgerServiceImpl {
+            ledger_repo,
+            member_repo: member_repo(Some(LedgerRole::Editor)),
+        };
+        let result = sut.delete(Uuid::new_v4(), Uuid::new_v4()).await;
+        assert!(matches!(result, Err(Error::Forbidden)));
+    }
+
+    #[tokio::test]
+    async fn test_ledger_service_list() {
+        let mut ledger_repo = MockLedgerRepo::new();
+        let user_id = Uuid::new_v4();
+        ledger_repo
+            .expect_list()
+            .withf(move |opt| opt.member_id == Some(user_id))
+            .returning(|_| Ok(Vec::new()));
+
+        let sut = LedgerServiceImpl {
+            ledger_repo,
+            member_repo: MockMemberRepo::new(),
+        };
+        let result = sut.list(user_id, ListLedgerInput::default()).await;
+        assert!(result.is_ok());
+    }
+}
@@ -0,0 +1,422 @@
+use crate::{
+    config::env::MailConfig,
+    errors::{Error, Result},
+};
+use axum::async_trait;
+use chrono::Utc;
+use std::{fmt::Display, future::Future, path::PathBuf, sync::Arc, time::Duration};
+use tokio::{
+    io::{AsyncBufReadExt, AsyncRead, AsyncWrite, AsyncWriteExt, BufReader},
+    net::TcpStream,
+};
+use uuid::Uuid;
+
+pub type DynMailer = Arc<dyn Mailer + Send + Sync>;
+
+/// 纯文本邮件
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Email {
+    pub to: String,
+    pub subject: String,
+    pub body: String,
+}
+
+impl Email {
+    /// 生成 RFC 5322 邮件, 主题与正文以 base64 编码, 无需处理行首的点与 8bit 字符
+    pub fn to_mime(&self, from: &str) -> Result<String> {
+        for address in [from, &self.to] {
+            if address.contains(['\r', '\n', '<', '>']) {
+                return Err(Error::Mail(format!("invalid address: {:?}", address)));
+            }
+        }
+        let body = base64::encode(&self.body);
+        let body = body
+            .as_bytes()
+            .chunks(76)
+            .map(|line| std::str::from_utf8(line).unwrap_or_default())
+            .collect::<Vec<_>>()
+            .join("\r\n");
+        Ok(format!(
+            "From: <{from}>\r\n\
+             To: <{to}>\r\n\
+             Subject: =?UTF-8?B?{subject}?=\r\n\
+             Date: {date}\r\n\
+             Message-ID: <{id}@cashbook>\r\n\
+             MIME-Version: 1.0\r\n\
+             Content-Type: text/plain; charset=utf-8\r\n\
+             Content-Transfer-Encoding: base64\r\n\
+             \r\n\
+             {body}\r\n",
+            to = self.to,
+            subject = base64::encode(&self.subject),
+            date = Utc::now().to_rfc2822(),
+            id = Uuid::new_v4(),
+        ))
+    }
+}
+
+/// 邮件发送接口
+#[async_trait]
+pub trait Mailer {
+    async fn send(&self, email: Email) -> Result<()>;
+}
+
+/// 在后台发送邮件, 失败只记录日志; 响应时间不受 SMTP 往返影响, 无法据此判断邮箱是否注册
+pub fn spawn_send(mailer: &DynMailer, email: Email, user_id: Uuid, kind: &'static str) {
+    let mailer = mailer.clone();
+    tokio::spawn(async move {
+        if let Err(err) = mailer.send(email).await {
+            tracing::error!("send {kind} email to user {user_id} failed: {err}");
+        }
+    });
+}
+
+/// 按配置选择 SMTP 或本地目录发送
+pub fn from_env() -> DynMailer {
+    let config = MailConfig::from_env();
+    if config.smtp_host.is_some() {
+        Arc::new(SmtpMailer::new(config))
+    } else {
+        Arc::new(FileMailer::new(config))
+    }
+}
+
+/// 将邮件保存为 .eml 文件, 用于开发环境
+pub struct FileMailer {
+    dir: PathBuf,
+    from: String,
+}
+
+impl FileMailer {
+    pub fn new(config: MailConfig) -> Self {
+        FileMailer {
+            dir: config.mail_dir.into(),
+            from: config.mail_from,
+        }
+    }
+}
+
+#[async_trait]
+impl Mailer for FileMailer {
+    async fn send(&self, email: Email) -> Result<()> {
+        let message = email.to_mime(&self.from)?;
+        let path = self.dir.join(format!(
+            "{}-{}.eml",
+            Utc::now().format("%Y%m%d%H%M%S"),
+            Uuid::new_v4()
+        ));
+        tokio::fs::create_dir_all(&self.dir)
+            .await
+            .map_err(mail_error)?;
+        tokio::fs::write(&path, message).await.map_err(mail_error)?;
+        tracing::info!("mail to {} saved as {}", email.to, path.display());
+        Ok(())
+    }
+}
+
+/// SMTP 发送, 每封邮件建立一次连接, 连接、TLS 握手与每次读写都有超时
+pub struct SmtpMailer {
+    config: MailConfig,
+}
+
+impl SmtpMailer {
+    pub fn new(config: MailConfig) -> Self {
+        SmtpMailer { config }
+    }
+}
+
+#[async_trait]
+impl Mailer for SmtpMailer {
+    async fn send(&self, email: Email) -> Result<()> {
+        let config = &self.config;
+        let host = config.smtp_host.as_deref().unwrap_or("localhost");
+        let message = email.to_mime(&config.mail_from)?;
+        let timeout = Duration::from_secs(config.smtp_timeout_secs);
+
+        let tcp = timed(timeout, TcpStream::connect((host, config.smtp_port))).await?;
+        let mut conn = SmtpConnection::new(tcp, timeout);
+        conn.reply(220).await?;
+        conn.command(&format!("EHLO {}", config.smtp_helo), 250)
+            .await?;
+        if !config.smtp_starttls {
+            return conn.deliver(config, &email.to, &message).await;
+        }
+
+        conn.command("STARTTLS", 220).await?;
+        let connector = tokio_native_tls::native_tls::TlsConnector::new().map_err(mail_error)?;
+        let tls = timed(
+            timeout,
+            tokio_native_tls::TlsConnector::from(connector).connect(host, conn.into_inner()),
+        )
+        .await?;
+        let mut conn = SmtpConnection::new(tls, timeout);
+        conn.command(&format!("EHLO {}", config.smtp_helo), 250)
+            .await?;
+        conn.deliver(config, &email.to, &message).await
+    }
+}
+
+struct SmtpConnection<S> {
+    stream: BufReader<S>,
+    timeout: Duration,
+}
+
+impl<S> SmtpConnection<S>
+where
+    S: AsyncRead + AsyncWrite + Unpin,
+{
+    fn new(stream: S, timeout: Duration) -> Self {
+        SmtpConnection {
+            stream: BufReader::new(stream),
+            timeout,
+        }
+    }
+
+    fn into_inner(self) -> S {
+        self.stream.into_inner()
+    }
+
+    // 读取一个(可能多行的)应答, 状态码不符时返回错误
+    async fn reply(&mut self, expected: u16) -> Result<String> {
+        let mut reply = String::new();
+        loop {
+            let mut line = String::new();
+            if timed(self.timeout, self.stream.read_line(&mut line)).await? == 0 {
+                return Err(Error::Mail("connection closed".to_string()));
+            }
+            reply.push_str(&line);
+            // "250-..." 表示后面还有行, "250 ..." 为最后一行
+            if line.as_bytes().get(3) != Some(&b'-') {
+                break;
+            }
+        }
+        if reply.get(..3) != Some(expected.to_string().as_str()) {
+            return Err(Error::Mail(format!(
+                "unexpected reply: {}",
+                reply.trim_end()
+            )));
+        }
+        Ok(reply)
+    }
+
+    async fn command(&mut self, line: &str, expected: u16) -> Result<String> {
+        let line = format!("{line}\r\n");
+        timed(self.timeout, self.stream.write_all(line.as_bytes())).await?;
+        self.reply(expected).await
+    }
+
+    async fn deliver(mut self, config: &MailConfig, to: &str, message: &str) -> Result<()> {
+        if let (Some(username), Some(password)) = (&config.smtp_username, &config.smtp_password) {
+            let credential = base64::encode(format!("\0{username}\0{password}"));
+            self.command(&format!("AUTH PLAIN {credential}"), 235)
+                .await?;
+        }
+        self.command(&format!("MAIL FROM:<{}>", config.mail_from), 250)
+            .await?;
+        self.command(&format!("RCPT TO:<{to}>"), 250).await?;
+        self.command("DATA", 354).await?;
+        self.command(&format!("{message}."), 250).await?;
+        // 邮件已被接收, QUIT 失败不影响结果
+        let _ = self.command("QUIT", 221).await;
+        Ok(())
+    }
+}
+
+fn mail_error(err: impl Display) -> Error {
+    Error::Mail(err.to_string())
+}
+
+// 服务器无响应时放弃发送, 避免后台任务一直挂起
+async fn timed<T, E: Display>(
+    timeout: Duration,
+    future: impl Future<Output = std::result::Result<T, E>>,
+) -> Result<T> {
+    tokio::time::timeout(timeout, future)
+        .await
+        .map_err(|_| Error::Mail(format!("no response within {}s", timeout.as_secs())))?
+        .map_err(mail_error)
+}
+
+#[cfg(test)]
+pub mod tests {
+    use super::*;
+    use std::sync::Mutex;
+    use tokio::net::TcpListener;
+
+    /// 保存在内存中的邮件, 供测试检查
+    #[derive(Default)]
+    pub struct MemoryMailer {
+        pub sent: Mutex<Vec<Email>>,
+    }
+
+    impl MemoryMailer {
+        /// 等待后台任务发出 count 封邮件, 返回已发送的全部邮件
+        pub async fn wait_sent(&self, count: usize) -> Vec<Email> {
+            for _ in 0..100 {
+                if self.sent.lock().unwrap().len() >= count {
+                    break;
+                }
+                tokio::task::yield_now().await;
+            }
+            self.sent.lock().unwrap().clone()
+        }
+    }
+
+    #[async_trait]
+    impl Mailer for MemoryMailer {
+        async fn send(&self, email: Email) -> Result<()> {
+            self.sent.lock().unwrap().push(email);
+            Ok(())
+        }
+    }
+
+    fn email() -> Email {
+        Email {
+            to: "alice@example.com".to_string(),
+            subject: "验证邮箱".to_string(),
+            body: ".leading dot\n验证链接".to_string(),
+        }
+    }
+
+    fn config(port: u16) -> MailConfig {
+        MailConfig {
+            smtp_host: Some("127.0.0.1".to_string()),
+            smtp_port: port,
+            smtp_username: Some("cashbook".to_string()),
+            smtp_password: Some("secret".to_string()),
+            smtp_starttls: false,
+            smtp_helo: "localhost".to_string(),
+            smtp_timeout_secs: 1,
+            mail_from: "cashbook@example.com".to_string(),
+            mail_dir: String::new(),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_file_mailer_writes_eml() {
+        let dir = std::env::temp_dir().join(format!("cashbook-mail-{}", Uuid::new_v4()));
+        let mut config = config(0);
+        config.mail_dir = dir.to_string_lossy().into_owned();
+        FileMailer::new(config).send(email()).await.unwrap();
+
+        let mut files = std::fs::read_dir(&dir).unwrap();
+        let message = std::fs::read_to_string(files.next().unwrap().unwrap().path()).unwrap();
+        assert!(message.starts_with("From: <cashbook@example.com>\r\nTo: <alice@example.com>\r\n"));
+        assert!(files.next().is_none());
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn test_email_rejects_header_injection() {
+        let mut email = email();
+        email.to = "alice@example.com>\r\nBcc: <mallory@example.com".to_string();
+        assert!(email.to_mime("cashbook@example.com").is_err());
+    }
+
+    #[tokio::test]
+    async fn test_smtp_mailer_delivers_message() {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let port = listener.local_addr().unwrap().port();
+
+        // 按顺序应答的 SMTP 服务端, 返回收到的命令与邮件内容
+        let server = tokio::spawn(async move {
+            let (socket, _) = listener.accept().await.unwrap();
+            let mut socket = BufReader::new(socket);
+            socket.write_all(b"220 ready\r\n").await.unwrap();
+            let mut commands = Vec::new();
+            let mut data = String::new();
+            loop {
+                let mut line = String::new();
+                socket.read_line(&mut line).await.unwrap();
+                let line = line.trim_end().to_string();
+                let reply: &[u8] = match line.split(' ').next().unwrap() {
+                    "EHLO" => b"250-localhost\r\n250 AUTH PLAIN\r\n",
+                    "AUTH" => b"235 ok\r\n",
+                    "DATA" => {
+                        socket.write_all(b"354 go ahead\r\n").await.unwrap();
+                        loop {
+                            let mut line = String::new();
+                            socket.read_line(&mut line).await.unwrap();
+                            if line == ".\r\n" {
+                                break;
+                            }
+                            data.push_str(&line);
+                        }
+                        b"250 queued\r\n"
+                    }
+                    "QUIT" => {
+                        socket.write_all(b"221 bye\r\n").await.unwrap();
+                        commands.push(line);
+                        break;
+                    }
+                    _ => b"250 ok\r\n",
+                };
+                commands.push(line);
+                socket.write_all(reply).await.unwrap();
+            }
+            (commands, data)
+        });
+
+        SmtpMailer::new(config(port)).send(email()).await.unwrap();
+
+        let (commands, data) = server.await.unwrap();
+        assert_eq!(
+            vec![
+                "EHLO localhost".to_string(),
+                format!("AUTH PLAIN {}", base64::encode("\0cashbook\0secret")),
+                "MAIL FROM:<cashbook@example.com>".to_string(),
+                "RCPT TO:<alice@example.com>".to_string(),
+                "DATA".to_string(),
+                "QUIT".to_string(),
+            ],
+            commands
+        );
+        assert!(data.contains("To: <alice@example.com>\r\n"));
+        let body = data.split("\r\n\r\n").nth(1).unwrap().replace("\r\n", "");
+        assert_eq!(email().body.as_bytes(), base64::decode(body).unwrap());
+    }
+
+    #[tokio::test]
+    async fn test_smtp_mailer_rejected_recipient() {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let port = listener.local_addr().unwrap().port();
+        tokio::spawn(async move {
+            let (socket, _) = listener.accept().await.unwrap();
+            let mut socket = BufReader::new(socket);
+            socket.write_all(b"220 ready\r\n").await.unwrap();
+            loop {
+                let mut line = String::new();
+                if socket.read_line(&mut line).await.unwrap() == 0 {
+                    break;
+                }
+                let reply: &[u8] = if line.starts_with("RCPT") {
+                    b"550 no such user\r\n"
+                } else {
+                    b"250 ok\r\n"
+                };
+                socket.write_all(reply).await.unwrap();
+            }
+        });
+
+        let mut config = config(port);
+        config.smtp_username = None;
+        let actual = SmtpMailer::new(config).send(email()).await;
+        assert!(matches!(actual, Err(Error::Mail(reply)) if reply.contains("550")));
+    }
+
+    #[tokio::test]
+    async fn test_smtp_mailer_times_out_on_silent_server() {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let port = listener.local_addr().unwrap().port();
+        // 接受连接后不发送问候语
+        let server = tokio::spawn(async move {
+            let (socket, _) = listener.accept().await.unwrap();
+            tokio::time::sleep(Duration::from_secs(5)).await;
+            drop(socket);
+        });
+
+        let actual = SmtpMailer::new(config(port)).send(email()).await;
+        assert!(matches!(actual, Err(Error::Mail(msg)) if msg.contains("no response")));
+        server.abort();
+    }
+}
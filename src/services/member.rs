@@ -0,0 +1,197 @@
+pub(crate) use crate::{
+    dao::{
+        member_repo::{MemberRepo, MemberRepoImpl},
+        user_repo::{UserRepo, UserRepoImpl},
+    },
+    dto::member::{AddMemberInput, UpdateMemberInput},
+    errors::{Error, Result},
+    models::member::{CreateLedgerMember, LedgerMember, LedgerRole},
+};
+use axum::async_trait;
+use sqlx::postgres::PgPool;
+use std::sync::Arc;
+use uuid::Uuid;
+
+pub type DynMemberService = Arc<dyn MemberService + Send + Sync>;
+
+#[cfg_attr(test, mockall::automock)]
+#[async_trait]
+pub trait MemberService {
+    async fn list(&self, ledger_id: Uuid) -> Result<Vec<LedgerMember>>;
+    /// 按邮箱邀请已注册用户加入账本
+    async fn add(&self, ledger_id: Uuid, input: AddMemberInput) -> Result<LedgerMember>;
+    async fn update(
+        &self,
+        ledger_id: Uuid,
+        user_id: Uuid,
+        input: UpdateMemberInput,
+    ) -> Result<LedgerMember>;
+    async fn remove(&self, ledger_id: Uuid, user_id: Uuid) -> Result<LedgerMember>;
+}
+
+#[derive(Clone)]
+pub struct MemberServiceImpl<T, U>
+where
+    T: MemberRepo + Sync + Send,
+    U: UserRepo + Sync + Send,
+{
+    pub member_repo: T,
+    pub user_repo: U,
+}
+
+impl MemberServiceImpl<MemberRepoImpl, UserRepoImpl> {
+    pub fn new(pool: Arc<PgPool>) -> Self {
+        MemberServiceImpl {
+            member_repo: MemberRepoImpl::new(pool.clone()),
+            user_repo: UserRepoImpl::new(pool),
+        }
+    }
+}
+
+#[async_trait]
+impl<T, U> MemberService for MemberServiceImpl<T, U>
+where
+    T: MemberRepo + Sync + Send,
+    U: UserRepo + Sync + Send,
+{
+    async fn list(&self, ledger_id: Uuid) -> Result<Vec<LedgerMember>> {
+        Ok(self.member_repo.list(ledger_id).await?)
+    }
+
+    async fn add(&self, ledger_id: Uuid, input: AddMemberInput) -> Result<LedgerMember> {
+        let user = match self.user_repo.get_by_email(&input.email).await {
+            Ok(user) => user,
+            Err(Error::DataStore(sqlx::Error::RowNotFound)) => {
+                return Err(Error::InvalidMember(format!(
+                    "no user registered as {}",
+                    input.email
+                )))
+            }
+            Err(err) => return Err(err),
+        };
+        let member = CreateLedgerMember {
+            ledger_id,
+            user_id: user.id,
+            role: input.role,
+        };
+        Ok(self.member_repo.create(member).await?)
+    }
+
+    async fn update(
+        &self,
+        ledger_id: Uuid,
+        user_id: Uuid,
+        input: UpdateMemberInput,
+    ) -> Result<LedgerMember> {
+        Ok(self
+            .member_repo
+            .update_role(ledger_id, user_id, input.role)
+            .await?)
+    }
+
+    async fn remove(&self, ledger_id: Uuid, user_id: Uuid) -> Result<LedgerMember> {
+        Ok(self.member_repo.delete(ledger_id, user_id).await?)
+    }
+}
+
+#[cfg(test)]
+pub mod tests {
+    use super::*;
+    use crate::{
+        dao::{member_repo::MockMemberRepo, user_repo::MockUserRepo},
+        models::user::User,
+    };
+
+    #[tokio::test]
+    async fn test_member_service_add() {
+        let mut user_repo = MockUserRepo::new();
+        user_repo.expect_get_by_email().returning(|email| {
+            Ok(User {
+                email: email.to_string(),
+                ..Default::default()
+            })
+        });
+        let mut member_repo = MockMemberRepo::new();
+        member_repo
+            .expect_create()
+            .withf(|member| member.role == LedgerRole::Editor)
+            .returning(|member| {
+                Ok(LedgerMember {
+                    ledger_id: member.ledger_id,
+                    user_id: member.user_id,
+                    role: member.role,
+                    ..Default::default()
+                })
+            });
+
+        let sut = MemberServiceImpl {
+            member_repo,
+            user_repo,
+        };
+        let ledger_id = Uuid::new_v4();
+        let member = sut
+            .add(
+                ledger_id,
+                AddMemberInput {
+                    email: "editor@example.com".to_string(),
+                    role: LedgerRole::Editor,
+                },
+            )
+            .await
+            .unwrap();
+        assert_eq!(ledger_id, member.ledger_id);
+    }
+
+    #[tokio::test]
+    async fn test_member_service_add_unknown_user() {
+        let mut user_repo = MockUserRepo::new();
+        user_repo
+            .expect_get_by_email()
+            .returning(|_| Err(Error::DataStore(sqlx::Error::RowNotFound)));
+        let mut member_repo = MockMemberRepo::new();
+        member_repo.expect_create().never();
+
+        let sut = MemberServiceImpl {
+            member_repo,
+            user_repo,
+        };
+        let result = sut
+            .add(
+                Uuid::new_v4(),
+                AddMemberInput {
+                    email: "nobody@example.com".to_string(),
+                    role: LedgerRole::Viewer,
+                },
+            )
+            .await;
+        assert!(matches!(result, Err(Error::InvalidMember(_))));
+    }
+
+    #[tokio::test]
+    async fn test_member_service_add_propagates_store_error() {
+        let mut user_repo = MockUserRepo::new();
+        user_repo
+            .expect_get_by_email()
+            .returning(|_| Err(Error::DataStore(sqlx::Error::PoolTimedOut)));
+        let mut member_repo = MockMemberRepo::new();
+        member_repo.expect_create().never();
+
+        let sut = MemberServiceImpl {
+            member_repo,
+            user_repo,
+        };
+        let result = sut
+            .add(
+                Uuid::new_v4(),
+                AddMemberInput {
+                    email: "alice@example.com".to_string(),
+                    role: LedgerRole::Viewer,
+                },
+            )
+            .await;
+        assert!(matches!(
+            result,
+            Err(Error::DataStore(sqlx::Error::PoolTimedOut))
+        ));
+    }
+}
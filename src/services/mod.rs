@@ -1,4 +1,50 @@
+/// 会计科目业务层实现
+pub(crate) mod account;
 /// 认证业务层实现
 pub(crate) mod auth;
+/// Beancount 导入业务层实现
+pub(crate) mod beancount;
+/// 预算业务层实现
+pub(crate) mod budget;
+/// 分类与自动分类业务层实现
+pub(crate) mod categorization;
+/// 货币与汇率业务层实现
+pub(crate) mod currency;
+/// 记账分录业务层实现
+pub(crate) mod entry;
+/// 账本导出业务层实现
+pub(crate) mod export;
+/// 对账单导入业务层实现
+pub(crate) mod import;
+/// 账本业务层实现
+pub(crate) mod ledger;
+/// 邮件发送实现
+pub(crate) mod mailer;
+/// 账本成员业务层实现
+pub(crate) mod member;
+/// OIDC 单点登录业务层实现
+pub(crate) mod oidc;
+/// OIDC 提供方 HTTP 客户端
+pub(crate) mod oidc_client;
+/// passkey 注册与登录业务层实现
+pub(crate) mod passkey;
+/// 密码哈希实现
+pub(crate) mod password;
+/// 密码重置业务层实现
+pub(crate) mod password_reset;
+/// 系统角色与权限业务层实现
+pub(crate) mod rbac;
+/// 周期分录业务层实现
+pub(crate) mod recurring;
+/// 报表业务层实现
+pub(crate) mod report;
+/// 令牌签发与轮换业务层实现
+pub(crate) mod token;
+/// TOTP 两步验证业务层实现
+pub(crate) mod totp;
 /// user 业务层实现
 pub(crate) mod user;
+/// 邮箱验证业务层实现
+pub(crate) mod verification;
+/// WebAuthn 协议数据解析与验签
+pub(crate) mod webauthn;
@@ -0,0 +1,606 @@
+use super::{
+    oidc_client::{
+        CodeExchange, DynOidcClient, HttpOidcClient, JsonWebKey, JsonWebKeySet, ProviderMetadata,
+    },
+    password::{hash_password, Argon2Hasher, DynPasswordHasher},
+};
+pub(crate) use crate::{
+    config::env::{OidcConfig, OidcProviderConfig},
+    dao::{
+        oidc_repo::{OidcRepo, OidcRepoImpl},
+        user_repo::{UserRepo, UserRepoImpl},
+    },
+    errors::{Error, Result},
+    models::{
+        oidc::{CreateUserIdentity, OidcLoginState},
+        user::{CreateUser, User},
+    },
+};
+use axum::async_trait;
+use chrono::{Duration, Utc};
+use jsonwebtoken::{Algorithm, DecodingKey, Validation};
+use rand::{rngs::OsRng, RngCore};
+use serde::{Deserialize, Deserializer};
+use sha2::{Digest, Sha256};
+use sqlx::postgres::PgPool;
+use std::sync::Arc;
+
+/// 调用提供方接口的超时时间
+const HTTP_TIMEOUT_SECS: u64 = 10;
+/// 校验 id_token 的 exp/iat 时容忍的时钟偏差(秒)
+const LEEWAY_SECS: u64 = 60;
+
+pub type DynOidcService = Arc<dyn OidcService + Send + Sync>;
+
+#[cfg_attr(test, mockall::automock)]
+#[async_trait]
+pub trait OidcService {
+    /// 生成跳转到提供方的授权地址, state、nonce 与 PKCE 校验值保存在服务端
+    async fn start(&self, provider: &str) -> Result<String>;
+    /// 校验回调的 state, 用授权码换取 id_token 并验签, 返回关联或新建的用户
+    async fn callback(&self, provider: &str, code: &str, state: &str) -> Result<User>;
+}
+
+#[derive(Clone)]
+pub struct OidcServiceImpl<T, O>
+where
+    T: UserRepo + Sync + Send,
+    O: OidcRepo + Sync + Send,
+{
+    pub user_repo: T,
+    pub oidc_repo: O,
+    pub client: DynOidcClient,
+    pub hasher: DynPasswordHasher,
+    pub config: OidcConfig,
+    pub providers: Vec<OidcProviderConfig>,
+}
+
+impl OidcServiceImpl<UserRepoImpl, OidcRepoImpl> {
+    pub fn new(pool: Arc<PgPool>) -> Self {
+        let config = OidcConfig::from_env();
+        let providers = config
+            .providers()
+            .expect("invalid OIDC provider configuration");
+        OidcServiceImpl {
+            user_repo: UserRepoImpl::new(pool.clone()),
+            oidc_repo: OidcRepoImpl::new(pool),
+            client: Arc::new(HttpOidcClient::new(std::time::Duration::from_secs(
+                HTTP_TIMEOUT_SECS,
+            ))),
+            hasher: Arc::new(Argon2Hasher::from_env()),
+            config,
+            providers,
+        }
+    }
+}
+
+/// id_token 中用到的声明, iss/aud/exp 由 jsonwebtoken 校验
+#[derive(Debug, Deserialize)]
+struct IdTokenClaims {
+    sub: String,
+    nonce: Option<String>,
+    email: Option<String>,
+    /// 部分提供方以字符串 "true" 返回
+    #[serde(default, deserialize_with = "bool_or_string")]
+    email_verified: bool,
+    name: Option<String>,
+    preferred_username: Option<String>,
+}
+
+fn bool_or_string<'de, D: Deserializer<'de>>(
+    deserializer: D,
+) -> std::result::Result<bool, D::Error> {
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum Flag {
+        Bool(bool),
+        Text(String),
+    }
+    Ok(match Flag::deserialize(deserializer)? {
+        Flag::Bool(value) => value,
+        Flag::Text(value) => value.eq_ignore_ascii_case("true"),
+    })
+}
+
+fn login_failed(reason: &str) -> Error {
+    Error::OidcLogin(reason.to_string())
+}
+
+fn b64url(bytes: &[u8]) -> String {
+    base64::encode_config(bytes, base64::URL_SAFE_NO_PAD)
+}
+
+fn random_token() -> String {
+    let mut bytes = [0u8; 32];
+    OsRng.fill_bytes(&mut bytes);
+    b64url(&bytes)
+}
+
+/// 按 JWK 构造验签密钥, 只接受与令牌头部算法一致的非对称签名密钥
+fn decoding_key(jwk: &JsonWebKey, alg: Algorithm) -> Option<DecodingKey> {
+    if jwk.key_use.as_deref().unwrap_or("sig") != "sig" {
+        return None;
+    }
+    if let Some(expected) = &jwk.alg {
+        if serde_json::to_value(alg).ok()?.as_str() != Some(expected) {
+            return None;
+        }
+    }
+    let decode = |value: &Option<String>| {
+        base64::decode_config(
+            value.as_deref()?.trim_end_matches('='),
+            base64::URL_SAFE_NO_PAD,
+        )
+        .ok()
+    };
+    match (jwk.kty.as_str(), jwk.crv.as_deref(), alg) {
+        ("RSA", _, Algorithm::RS256 | Algorithm::RS384 | Algorithm::RS512) => {
+            DecodingKey::from_rsa_components(jwk.n.as_deref()?, jwk.e.as_deref()?).ok()
+        }
+        ("EC", Some("P-256"), Algorithm::ES256) => {
+            let point = [vec![0x04], decode(&jwk.x)?, decode(&jwk.y)?].concat();
+            (point.len() == 65).then(|| DecodingKey::from_ec_der(&point))
+        }
+        ("OKP", Some("Ed25519"), Algorithm::EdDSA) => {
+            Some(DecodingKey::from_ed_der(&decode(&jwk.x)?))
+        }
+        _ => None,
+    }
+}
+
+/// 校验 id_token 的签名、签发方、受众、有效期与 nonce
+fn verify_id_token(
+    id_token: &str,
+    jwks: &JsonWebKeySet,
+    issuer: &str,
+    client_id: &str,
+    nonce: &str,
+) -> Result<IdTokenClaims> {
+    let header =
+        jsonwebtoken::decode_header(id_token).map_err(|_| login_failed("malformed id_token"))?;
+    let key = jwks
+        .keys
+        .iter()
+        .filter(|jwk| header.kid.is_none() || jwk.kid == header.kid)
+        .find_map(|jwk| decoding_key(jwk, header.alg))
+        .ok_or_else(|| login_failed("no matching signing key for id_token"))?;
+
+    let mut validation = Validation::new(header.alg);
+    validation.set_issuer(&[issuer]);
+    validation.set_audience(&[client_id]);
+    validation.set_required_spec_claims(&["exp", "iss", "aud", "sub"]);
+    validation.leeway = LEEWAY_SECS;
+    let claims: IdTokenClaims = jsonwebtoken::decode(id_token, &key, &validation)
+        .map_err(|err| Error::OidcLogin(format!("invalid id_token: {}", err)))?
+        .claims;
+
+    if claims.nonce.as_deref() != Some(nonce) {
+        return Err(login_failed("nonce mismatch"));
+    }
+    Ok(claims)
+}
+
+impl<T, O> OidcServiceImpl<T, O>
+where
+    T: UserRepo + Sync + Send,
+    O: OidcRepo + Sync + Send,
+{
+    fn provider(&self, name: &str) -> Result<&OidcProviderConfig> {
+        self.providers
+            .iter()
+            .find(|provider| provider.name == name)
+            .ok_or_else(|| Error::UnknownOidcProvider(name.to_string()))
+    }
+
+    fn redirect_uri(&self, provider: &OidcProviderConfig) -> String {
+        format!(
+            "{}/{}/callback",
+            self.config.oidc_redirect_base_url.trim_end_matches('/'),
+            provider.name
+        )
+    }
+
+    async fn metadata(&self, provider: &OidcProviderConfig) -> Result<ProviderMetadata> {
+        let metadata = self.client.discover(&provider.issuer).await?;
+        if metadata.issuer.trim_end_matches('/') != provider.issuer {
+            return Err(Error::OidcProvider(format!(
+                "discovery document issuer {} does not match",
+                metadata.issuer
+            )));
+        }
+        Ok(metadata)
+    }
+
+    // 先按外部身份查找; 未关联时按双方都已验证的邮箱关联已有账号, 否则创建新用户
+    async fn resolve_user(&self, provider: &str, claims: IdTokenClaims) -> Result<User> {
+        if let Some(identity) = self.oidc_repo.record_login(provider, &claims.sub).await? {
+            return self.user_repo.get(identity.user_id).await;
+        }
+
+        let email = claims
+            .email
+            .clone()
+            .filter(|email| !email.is_empty() && claims.email_verified)
+            .ok_or_else(|| login_failed("identity provider did not return a verified email"))?;
+        let user = match self.user_repo.get_by_email(&email).await {
+            // 本地邮箱未验证时可能是他人抢注, 不能自动关联
+            Ok(user) if user.verified_at.is_none() => return Err(Error::OidcAccountConflict),
+            Ok(user) => user,
+            Err(Error::DataStore(sqlx::Error::RowNotFound)) => {
+                self.provision(&email, &claims).await?
+            }
+            Err(err) => return Err(err),
+        };
+
+        self.oidc_repo
+            .link(CreateUserIdentity {
+                user_id: user.id,
+                provider: provider.to_string(),
+                subject: claims.sub,
+                email: Some(email),
+            })
+            .await?;
+        Ok(user)
+    }
+
+    // 首次登录时创建用户, 随机密码不告知任何人, 需要时可通过找回密码设置
+    async fn provision(&self, email: &str, claims: &IdTokenClaims) -> Result<User> {
+        let name = claims
+            .name
+            .clone()
+            .or_else(|| claims.preferred_username.clone())
+            .filter(|name| !name.is_empty())
+            .unwrap_or_else(|| email.split('@').next().unwrap_or(email).to_string());
+        let password = hash_password(&self.hasher, random_token()).await?;
+        let mut user = self
+            .user_repo
+            .create(CreateUser {
+                name,
+                email: email.to_string(),
+                password,
+            })
+            .await?;
+        self.user_repo.mark_verified(user.id, email).await?;
+        user.verified_at = Some(Utc::now());
+        Ok(user)
+    }
+}
+
+#[async_trait]
+impl<T, O> OidcService for OidcServiceImpl<T, O>
+where
+    T: UserRepo + Sync + Send,
+    O: OidcRepo + Sync + Send,
+{
+    async fn start(&self, name: &str) -> Result<String> {
+        let provider = self.provider(name)?;
+        let metadata = self.metadata(provider).await?;
+
+        let now = Utc::now();
+        let state = OidcLoginState {
+            state: random_token(),
+            provider: provider.name.clone(),
+            nonce: random_token(),
+            code_verifier: random_token(),
+            expires_at: now + Duration::seconds(self.config.oidc_state_ttl_secs),
+            created_at: now,
+        };
+        let code_challenge = b64url(&Sha256::digest(state.code_verifier.as_bytes()));
+        let url = reqwest::Url::parse_with_params(
+            &metadata.authorization_endpoint,
+            &[
+                ("response_type", "code"),
+                ("client_id", &provider.client_id),
+                ("redirect_uri", &self.redirect_uri(provider)),
+                ("scope", &provider.scopes),
+                ("state", &state.state),
+                ("nonce", &state.nonce),
+                ("code_challenge", &code_challenge),
+                ("code_challenge_method", "S256"),
+            ],
+        )
+        .map_err(|err| Error::OidcProvider(err.to_string()))?;
+
+        self.oidc_repo.create_state(state).await?;
+        Ok(url.into())
+    }
+
+    async fn callback(&self, name: &str, code: &str, state: &str) -> Result<User> {
+        let provider = self.provider(name)?;
+        let login_state = self
+            .oidc_repo
+            .take_state(state, name)
+            .await?
+            .ok_or_else(|| login_failed("unknown or expired state"))?;
+        let metadata = self.metadata(provider).await?;
+
+        let tokens = self
+            .client
+            .exchange_code(
+                &metadata.token_endpoint,
+                CodeExchange {
+                    code: code.to_string(),
+                    redirect_uri: self.redirect_uri(provider),
+                    client_id: provider.client_id.clone(),
+                    client_secret: provider.client_secret.clone(),
+                    code_verifier: login_state.code_verifier,
+                },
+            )
+            .await?;
+        let jwks = self.client.jwks(&metadata.jwks_uri).await?;
+        let claims = verify_id_token(
+            &tokens.id_token,
+            &jwks,
+            &metadata.issuer,
+            &provider.client_id,
+            &login_state.nonce,
+        )?;
+        self.resolve_user(name, claims).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        dao::{oidc_repo::MockOidcRepo, user_repo::MockUserRepo},
+        models::oidc::UserIdentity,
+        services::{
+            oidc_client::tests::{MockIdp, CLIENT_ID, CLIENT_SECRET},
+            password::tests::hasher,
+        },
+    };
+    use mockall::predicate::*;
+    use serde_json::json;
+    use std::sync::Mutex;
+    use uuid::Uuid;
+
+    fn sut(
+        idp: &MockIdp,
+        user_repo: MockUserRepo,
+        mut oidc_repo: MockOidcRepo,
+    ) -> OidcServiceImpl<MockUserRepo, MockOidcRepo> {
+        // 模拟数据库中的 state 表
+        let states = Arc::new(Mutex::new(Vec::<OidcLoginState>::new()));
+        let saved = states.clone();
+        oidc_repo.expect_create_state().returning(move |state| {
+            saved.lock().unwrap().push(state);
+            Ok(())
+        });
+        oidc_repo
+            .expect_take_state()
+            .returning(move |value, provider| {
+                let mut states = states.lock().unwrap();
+                let index = states
+                    .iter()
+                    .position(|state| state.state == value && state.provider == provider);
+                Ok(index.map(|index| states.remove(index)))
+            });
+        OidcServiceImpl {
+            user_repo,
+            oidc_repo,
+            client: Arc::new(HttpOidcClient::new(std::time::Duration::from_secs(5))),
+            hasher: Arc::new(hasher(64)),
+            config: OidcConfig {
+                oidc_providers: Some("corp".to_string()),
+                oidc_redirect_base_url: "http://localhost:8080/auth/oidc/".to_string(),
+                oidc_state_ttl_secs: 600,
+            },
+            providers: vec![OidcProviderConfig {
+                name: "corp".to_string(),
+                issuer: idp.issuer.clone(),
+                client_id: CLIENT_ID.to_string(),
+                client_secret: Some(CLIENT_SECRET.to_string()),
+                scopes: "openid email profile".to_string(),
+            }],
+        }
+    }
+
+    fn identity(user_id: Uuid) -> UserIdentity {
+        UserIdentity {
+            id: Uuid::new_v4(),
+            user_id,
+            provider: "corp".to_string(),
+            subject: "alice".to_string(),
+            email: Some("alice@example.com".to_string()),
+            created_at: Utc::now(),
+            last_login_at: None,
+        }
+    }
+
+    // 完整走一遍授权码流程
+    async fn sign_in(
+        idp: &MockIdp,
+        sut: &OidcServiceImpl<MockUserRepo, MockOidcRepo>,
+    ) -> Result<User> {
+        let url = sut.start("corp").await?;
+        let (code, state) = idp.authorize(&url).await;
+        sut.callback("corp", &code, &state).await
+    }
+
+    #[tokio::test]
+    async fn test_start_builds_pkce_authorization_url() {
+        let idp = MockIdp::spawn(json!({"sub": "alice"})).await;
+        let sut = sut(&idp, MockUserRepo::new(), MockOidcRepo::new());
+
+        let url = reqwest::Url::parse(&sut.start("corp").await.unwrap()).unwrap();
+        let params: std::collections::HashMap<_, _> = url.query_pairs().into_owned().collect();
+        assert_eq!(
+            format!("{}/authorize", idp.issuer),
+            url.as_str().split('?').next().unwrap()
+        );
+        assert_eq!("code", params["response_type"]);
+        assert_eq!(
+            "http://localhost:8080/auth/oidc/corp/callback",
+            params["redirect_uri"]
+        );
+        assert_eq!("S256", params["code_challenge_method"]);
+        assert_eq!(43, params["code_challenge"].len());
+        assert_ne!(params["state"], params["nonce"]);
+
+        let result = sut.start("google").await;
+        assert!(matches!(result, Err(Error::UnknownOidcProvider(_))));
+    }
+
+    #[tokio::test]
+    async fn test_callback_signs_in_linked_identity() {
+        let idp = MockIdp::spawn(json!({"sub": "alice"})).await;
+        let user = User::default();
+        let user_id = user.id;
+        let mut user_repo = MockUserRepo::new();
+        user_repo
+            .expect_get()
+            .with(eq(user_id))
+            .returning(move |_| Ok(user.clone()));
+        let mut oidc_repo = MockOidcRepo::new();
+        oidc_repo
+            .expect_record_login()
+            .withf(|provider, subject| provider == "corp" && subject == "alice")
+            .returning(move |_, _| Ok(Some(identity(user_id))));
+        let sut = sut(&idp, user_repo, oidc_repo);
+
+        assert_eq!(user_id, sign_in(&idp, &sut).await.unwrap().id);
+
+        // state 只能使用一次
+        let url = sut.start("corp").await.unwrap();
+        let (code, state) = idp.authorize(&url).await;
+        sut.callback("corp", &code, &state).await.unwrap();
+        let result = sut.callback("corp", &code, &state).await;
+        assert!(matches!(result, Err(Error::OidcLogin(_))));
+    }
+
+    #[tokio::test]
+    async fn test_callback_provisions_new_user() {
+        let idp = MockIdp::spawn(json!({
+            "sub": "alice",
+            "email": "alice@example.com",
+            "email_verified": "true",
+            "name": "Alice",
+        }))
+        .await;
+        let mut user_repo = MockUserRepo::new();
+        user_repo
+            .expect_get_by_email()
+            .returning(|_| Err(Error::DataStore(sqlx::Error::RowNotFound)));
+        user_repo
+            .expect_create()
+            .withf(|input| {
+                input.name == "Alice"
+                    && input.email == "alice@example.com"
+                    && input.password.starts_with("$argon2id$")
+            })
+            .times(1)
+            .returning(|input| {
+                Ok(User {
+                    name: input.name,
+                    email: input.email,
+                    ..Default::default()
+                })
+            });
+        user_repo
+            .expect_mark_verified()
+            .withf(|_, email| email == "alice@example.com")
+            .times(1)
+            .returning(|_, _| Ok(true));
+        let mut oidc_repo = MockOidcRepo::new();
+        oidc_repo.expect_record_login().returning(|_, _| Ok(None));
+        oidc_repo
+            .expect_link()
+            .withf(|input| input.provider == "corp" && input.subject == "alice")
+            .times(1)
+            .returning(|input| Ok(identity(input.user_id)));
+        let sut = sut(&idp, user_repo, oidc_repo);
+
+        let user = sign_in(&idp, &sut).await.unwrap();
+        assert_eq!("alice@example.com", user.email);
+        assert!(user.verified_at.is_some());
+    }
+
+    #[tokio::test]
+    async fn test_callback_links_account_only_by_verified_email() {
+        let idp = MockIdp::spawn(json!({
+            "sub": "alice",
+            "email": "alice@example.com",
+            "email_verified": true,
+        }))
+        .await;
+        let verified = User {
+            email: "alice@example.com".to_string(),
+            verified_at: Some(Utc::now()),
+            ..Default::default()
+        };
+        let user_id = verified.id;
+        let unverified = Arc::new(Mutex::new(false));
+        let local_unverified = unverified.clone();
+        let mut user_repo = MockUserRepo::new();
+        user_repo.expect_get_by_email().returning(move |_| {
+            let mut user = verified.clone();
+            if *local_unverified.lock().unwrap() {
+                user.verified_at = None;
+            }
+            Ok(user)
+        });
+        let mut oidc_repo = MockOidcRepo::new();
+        oidc_repo.expect_record_login().returning(|_, _| Ok(None));
+        oidc_repo
+            .expect_link()
+            .withf(move |input| input.user_id == user_id)
+            .times(1)
+            .returning(|input| Ok(identity(input.user_id)));
+        let sut = sut(&idp, user_repo, oidc_repo);
+
+        assert_eq!(user_id, sign_in(&idp, &sut).await.unwrap().id);
+
+        *unverified.lock().unwrap() = true;
+        let result = sign_in(&idp, &sut).await;
+        assert!(matches!(result, Err(Error::OidcAccountConflict)));
+
+        idp.set_claims(json!({
+            "sub": "alice",
+            "email": "alice@example.com",
+            "email_verified": false,
+        }));
+        let result = sign_in(&idp, &sut).await;
+        assert!(matches!(result, Err(Error::OidcLogin(_))));
+    }
+
+    #[tokio::test]
+    async fn test_callback_rejects_invalid_id_token() {
+        let idp = MockIdp::spawn(json!({"sub": "alice", "nonce": "replayed"})).await;
+        let sut = sut(&idp, MockUserRepo::new(), MockOidcRepo::new());
+        let result = sign_in(&idp, &sut).await;
+        assert!(matches!(result, Err(Error::OidcLogin(reason)) if reason == "nonce mismatch"));
+
+        idp.set_claims(json!({"sub": "alice", "aud": "another-client"}));
+        let result = sign_in(&idp, &sut).await;
+        assert!(
+            matches!(result, Err(Error::OidcLogin(reason)) if reason.contains("invalid id_token"))
+        );
+
+        idp.set_claims(json!({"sub": "alice", "exp": Utc::now().timestamp() - 3600}));
+        let result = sign_in(&idp, &sut).await;
+        assert!(
+            matches!(result, Err(Error::OidcLogin(reason)) if reason.contains("invalid id_token"))
+        );
+    }
+
+    #[test]
+    fn test_decoding_key_rejects_mismatched_keys() {
+        let jwk = |kty: &str, alg: Option<&str>| JsonWebKey {
+            kty: kty.to_string(),
+            kid: None,
+            alg: alg.map(str::to_string),
+            key_use: None,
+            n: Some("sXch".to_string()),
+            e: Some("AQAB".to_string()),
+            crv: None,
+            x: None,
+            y: None,
+        };
+        assert!(decoding_key(&jwk("RSA", None), Algorithm::RS256).is_some());
+        assert!(decoding_key(&jwk("RSA", Some("RS256")), Algorithm::RS512).is_none());
+        // 不能用公钥当作 HMAC 密钥
+        assert!(decoding_key(&jwk("RSA", None), Algorithm::HS256).is_none());
+        assert!(decoding_key(&jwk("EC", None), Algorithm::ES256).is_none());
+    }
+}
@@ -0,0 +1,398 @@
+use crate::errors::{Error, Result};
+use axum::async_trait;
+use serde::{Deserialize, Serialize};
+use std::{sync::Arc, time::Duration};
+
+pub type DynOidcClient = Arc<dyn OidcClient + Send + Sync>;
+
+/// OpenID Connect Discovery 返回的提供方元数据
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ProviderMetadata {
+    pub issuer: String,
+    pub authorization_endpoint: String,
+    pub token_endpoint: String,
+    pub jwks_uri: String,
+}
+
+/// 授权码换取令牌的参数
+#[derive(Debug, Clone)]
+pub struct CodeExchange {
+    pub code: String,
+    pub redirect_uri: String,
+    pub client_id: String,
+    /// 设置时以 client_secret_post 方式认证
+    pub client_secret: Option<String>,
+    pub code_verifier: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TokenResponse {
+    pub id_token: String,
+}
+
+/// RFC 7517 JSON Web Key, 只保留验签需要的参数
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct JsonWebKey {
+    pub kty: String,
+    pub kid: Option<String>,
+    pub alg: Option<String>,
+    #[serde(rename = "use")]
+    pub key_use: Option<String>,
+    pub n: Option<String>,
+    pub e: Option<String>,
+    pub crv: Option<String>,
+    pub x: Option<String>,
+    pub y: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct JsonWebKeySet {
+    pub keys: Vec<JsonWebKey>,
+}
+
+/// 与 OIDC 提供方通信的接口
+#[async_trait]
+pub trait OidcClient {
+    async fn discover(&self, issuer: &str) -> Result<ProviderMetadata>;
+    /// 授权码无效或 PKCE 校验失败时返回 OidcLogin
+    async fn exchange_code(
+        &self,
+        token_endpoint: &str,
+        request: CodeExchange,
+    ) -> Result<TokenResponse>;
+    async fn jwks(&self, jwks_uri: &str) -> Result<JsonWebKeySet>;
+}
+
+#[derive(Debug, Deserialize)]
+struct TokenError {
+    error: String,
+}
+
+/// 基于 reqwest 的实现
+#[derive(Clone)]
+pub struct HttpOidcClient {
+    client: reqwest::Client,
+}
+
+impl HttpOidcClient {
+    pub fn new(timeout: Duration) -> Self {
+        let client = reqwest::Client::builder()
+            .timeout(timeout)
+            .build()
+            .expect("failed to build http client");
+        HttpOidcClient { client }
+    }
+
+    async fn get_json<T: serde::de::DeserializeOwned>(&self, url: &str) -> Result<T> {
+        self.client
+            .get(url)
+            .send()
+            .await
+            .and_then(|response| response.error_for_status())
+            .map_err(unavailable)?
+            .json()
+            .await
+            .map_err(unavailable)
+    }
+}
+
+fn unavailable(err: reqwest::Error) -> Error {
+    Error::OidcProvider(err.to_string())
+}
+
+#[async_trait]
+impl OidcClient for HttpOidcClient {
+    async fn discover(&self, issuer: &str) -> Result<ProviderMetadata> {
+        self.get_json(&format!("{}/.well-known/openid-configuration", issuer))
+            .await
+    }
+
+    async fn exchange_code(
+        &self,
+        token_endpoint: &str,
+        request: CodeExchange,
+    ) -> Result<TokenResponse> {
+        let mut form = vec![
+            ("grant_type", "authorization_code".to_string()),
+            ("code", request.code),
+            ("redirect_uri", request.redirect_uri),
+            ("client_id", request.client_id),
+            ("code_verifier", request.code_verifier),
+        ];
+        if let Some(secret) = request.client_secret {
+            form.push(("client_secret", secret));
+        }
+        let response = self
+            .client
+            .post(token_endpoint)
+            .form(&form)
+            .send()
+            .await
+            .map_err(unavailable)?;
+
+        let status = response.status();
+        if status.is_client_error() {
+            // invalid_grant 等错误说明授权码或校验值不对, 而不是提供方不可用
+            let reason = match response.json::<TokenError>().await {
+                Ok(body) => body.error,
+                Err(_) => status.to_string(),
+            };
+            return Err(Error::OidcLogin(format!(
+                "token exchange failed: {}",
+                reason
+            )));
+        }
+        response
+            .error_for_status()
+            .map_err(unavailable)?
+            .json()
+            .await
+            .map_err(unavailable)
+    }
+
+    async fn jwks(&self, jwks_uri: &str) -> Result<JsonWebKeySet> {
+        self.get_json(jwks_uri).await
+    }
+}
+
+#[cfg(test)]
+pub mod tests {
+    use super::*;
+    use crate::config::keys::{tests::fixture, KeyRing, SigningKey};
+    use axum::{
+        extract::{Extension, Form, Query},
+        http::StatusCode,
+        response::{IntoResponse, Redirect, Response},
+        routing::{get, post},
+        AddExtensionLayer, Json, Router,
+    };
+    use chrono::Utc;
+    use serde_json::{json, Value};
+    use sha2::{Digest, Sha256};
+    use std::{collections::HashMap, net::TcpListener, sync::Mutex};
+    use uuid::Uuid;
+
+    pub const CLIENT_ID: &str = "cashbook";
+    pub const CLIENT_SECRET: &str = "client-secret";
+
+    struct Grant {
+        redirect_uri: String,
+        nonce: String,
+        code_challenge: String,
+    }
+
+    struct IdpState {
+        issuer: String,
+        keys: KeyRing,
+        claims: Mutex<Value>,
+        grants: Mutex<HashMap<String, Grant>>,
+    }
+
+    /// 本地模拟的 OIDC 提供方, 授权端点不需要登录, 直接为当前配置的用户签发授权码
+    pub struct MockIdp {
+        pub issuer: String,
+        state: Arc<IdpState>,
+    }
+
+    impl MockIdp {
+        /// claims 至少包含 sub, iss/aud/nonce/exp 由提供方补齐
+        pub async fn spawn(claims: Value) -> Self {
+            let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+            let issuer = format!("http://{}", listener.local_addr().unwrap());
+            let pem = std::fs::read(fixture("jwt_rs256_2022a.pem")).unwrap();
+            let state = Arc::new(IdpState {
+                issuer: issuer.clone(),
+                keys: KeyRing::new(vec![SigningKey::from_pem("idp", &pem).unwrap()], 0),
+                claims: Mutex::new(claims),
+                grants: Mutex::new(HashMap::new()),
+            });
+            let app = Router::new()
+                .route("/.well-known/openid-configuration", get(discovery))
+                .route("/authorize", get(authorize))
+                .route("/token", post(token))
+                .route("/jwks", get(jwks))
+                .layer(AddExtensionLayer::new(state.clone()));
+            let server = axum::Server::from_tcp(listener)
+                .unwrap()
+                .serve(app.into_make_service());
+            tokio::spawn(server);
+            MockIdp { issuer, state }
+        }
+
+        pub fn set_claims(&self, claims: Value) {
+            *self.state.claims.lock().unwrap() = claims;
+        }
+
+        /// 模拟浏览器打开授权地址并跟随跳转, 返回回调收到的 code 与 state
+        pub async fn authorize(&self, url: &str) -> (String, String) {
+            let client = reqwest::Client::builder()
+                .redirect(reqwest::redirect::Policy::none())
+                .build()
+                .unwrap();
+            let response = client.get(url).send().await.unwrap();
+            let location = response.headers()[reqwest::header::LOCATION]
+                .to_str()
+                .unwrap()
+                .to_string();
+            let callback = reqwest::Url::parse(&location).unwrap();
+            let param = |name: &str| {
+                callback
+                    .query_pairs()
+                    .find(|(key, _)| key == name)
+                    .map(|(_, value)| value.into_owned())
+                    .unwrap()
+            };
+            (param("code"), param("state"))
+        }
+    }
+
+    async fn discovery(Extension(state): Extension<Arc<IdpState>>) -> Json<ProviderMetadata> {
+        Json(ProviderMetadata {
+            issuer: state.issuer.clone(),
+            authorization_endpoint: format!("{}/authorize", state.issuer),
+            token_endpoint: format!("{}/token", state.issuer),
+            jwks_uri: format!("{}/jwks", state.issuer),
+        })
+    }
+
+    async fn authorize(
+        Extension(state): Extension<Arc<IdpState>>,
+        Query(params): Query<HashMap<String, String>>,
+    ) -> Response {
+        let param = |name: &str| params.get(name).cloned().unwrap_or_default();
+        if param("client_id") != CLIENT_ID
+            || param("response_type") != "code"
+            || param("code_challenge_method") != "S256"
+        {
+            return StatusCode::BAD_REQUEST.into_response();
+        }
+        let code = Uuid::new_v4().to_string();
+        let mut callback = reqwest::Url::parse(&param("redirect_uri")).unwrap();
+        callback
+            .query_pairs_mut()
+            .append_pair("code", &code)
+            .append_pair("state", &param("state"));
+        state.grants.lock().unwrap().insert(
+            code,
+            Grant {
+                redirect_uri: param("redirect_uri"),
+                nonce: param("nonce"),
+                code_challenge: param("code_challenge"),
+            },
+        );
+        Redirect::to(callback.as_str().parse().unwrap()).into_response()
+    }
+
+    async fn token(
+        Extension(state): Extension<Arc<IdpState>>,
+        Form(form): Form<HashMap<String, String>>,
+    ) -> Response {
+        let param = |name: &str| form.get(name).cloned().unwrap_or_default();
+        let invalid_grant = || {
+            (
+                StatusCode::BAD_REQUEST,
+                Json(json!({"error": "invalid_grant"})),
+            )
+                .into_response()
+        };
+        if param("client_id") != CLIENT_ID || param("client_secret") != CLIENT_SECRET {
+            return (
+                StatusCode::UNAUTHORIZED,
+                Json(json!({"error": "invalid_client"})),
+            )
+                .into_response();
+        }
+        let grant = match state.grants.lock().unwrap().remove(&param("code")) {
+            Some(grant) => grant,
+            None => return invalid_grant(),
+        };
+        let challenge = base64::encode_config(
+            Sha256::digest(param("code_verifier").as_bytes()),
+            base64::URL_SAFE_NO_PAD,
+        );
+        if param("grant_type") != "authorization_code"
+            || param("redirect_uri") != grant.redirect_uri
+            || challenge != grant.code_challenge
+        {
+            return invalid_grant();
+        }
+
+        let mut claims = state.claims.lock().unwrap().clone();
+        let now = Utc::now().timestamp();
+        for (key, value) in [
+            ("iss", json!(state.issuer)),
+            ("aud", json!(CLIENT_ID)),
+            ("nonce", json!(grant.nonce)),
+            ("iat", json!(now)),
+            ("exp", json!(now + 300)),
+        ] {
+            if claims.get(key).is_none() {
+                claims[key] = value;
+            }
+        }
+        Json(TokenResponse {
+            id_token: state.keys.encode(&claims).unwrap(),
+        })
+        .into_response()
+    }
+
+    async fn jwks(Extension(state): Extension<Arc<IdpState>>) -> impl IntoResponse {
+        Json(state.keys.jwks())
+    }
+
+    #[tokio::test]
+    async fn test_http_client_against_mock_idp() {
+        let idp = MockIdp::spawn(json!({"sub": "alice"})).await;
+        let sut = HttpOidcClient::new(Duration::from_secs(5));
+
+        let metadata = sut.discover(&idp.issuer).await.unwrap();
+        assert_eq!(idp.issuer, metadata.issuer);
+        let jwks = sut.jwks(&metadata.jwks_uri).await.unwrap();
+        assert_eq!(Some("idp".to_string()), jwks.keys[0].kid);
+        assert_eq!("RSA", jwks.keys[0].kty);
+
+        let verifier = "verifier".to_string();
+        let challenge =
+            base64::encode_config(Sha256::digest(verifier.as_bytes()), base64::URL_SAFE_NO_PAD);
+        let mut url = reqwest::Url::parse(&metadata.authorization_endpoint).unwrap();
+        url.query_pairs_mut()
+            .append_pair("response_type", "code")
+            .append_pair("client_id", CLIENT_ID)
+            .append_pair("redirect_uri", "http://localhost/callback")
+            .append_pair("state", "state")
+            .append_pair("nonce", "nonce")
+            .append_pair("code_challenge", &challenge)
+            .append_pair("code_challenge_method", "S256");
+        let (code, state) = idp.authorize(url.as_str()).await;
+        assert_eq!("state", state);
+
+        let request = |code: &str, verifier: &str| CodeExchange {
+            code: code.to_string(),
+            redirect_uri: "http://localhost/callback".to_string(),
+            client_id: CLIENT_ID.to_string(),
+            client_secret: Some(CLIENT_SECRET.to_string()),
+            code_verifier: verifier.to_string(),
+        };
+        let result = sut
+            .exchange_code(&metadata.token_endpoint, request(&code, "other"))
+            .await;
+        assert!(
+            matches!(result, Err(Error::OidcLogin(reason)) if reason.contains("invalid_grant"))
+        );
+
+        let (code, _) = idp.authorize(url.as_str()).await;
+        let response = sut
+            .exchange_code(&metadata.token_endpoint, request(&code, &verifier))
+            .await
+            .unwrap();
+        assert_eq!(3, response.id_token.split('.').count());
+        // 授权码只能使用一次
+        let result = sut
+            .exchange_code(&metadata.token_endpoint, request(&code, &verifier))
+            .await;
+        assert!(matches!(result, Err(Error::OidcLogin(_))));
+
+        let result = sut.discover("http://127.0.0.1:1").await;
+        assert!(matches!(result, Err(Error::OidcProvider(_))));
+    }
+}
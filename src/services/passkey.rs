@@ -0,0 +1,548 @@
+use super::webauthn::{
+    attestation_auth_data, b64url_decode, b64url_encode, signed_message, verify_client_data,
+    AuthenticatorData, CoseKey, ALGORITHMS,
+};
+pub(crate) use crate::{
+    config::env::WebAuthnConfig,
+    dao::{
+        passkey_repo::{PasskeyRepo, PasskeyRepoImpl},
+        user_repo::{UserRepo, UserRepoImpl},
+    },
+    dto::passkey::{
+        AuthenticatorSelection, CredentialDescriptor, CredentialParameter, PasskeyCreationOptions,
+        PasskeyLoginInput, PasskeyRegistrationInput, PasskeyRequestOptions,
+        PublicKeyCredentialCreationOptions, PublicKeyCredentialRequestOptions, RelyingParty,
+        UserEntity,
+    },
+    errors::{Error, Result},
+    models::{
+        passkey::{CreatePasskeyCredential, PasskeyChallenge, PasskeyCredential},
+        user::User,
+    },
+};
+use axum::async_trait;
+use chrono::{Duration, Utc};
+use rand::{rngs::OsRng, RngCore};
+use sqlx::postgres::PgPool;
+use std::sync::Arc;
+use uuid::Uuid;
+
+const PUBLIC_KEY: &str = "public-key";
+/// 无密码登录只依赖 passkey, 要求认证器验证用户身份
+const USER_VERIFICATION: &str = "required";
+
+pub type DynPasskeyService = Arc<dyn PasskeyService + Send + Sync>;
+
+#[cfg_attr(test, mockall::automock)]
+#[async_trait]
+pub trait PasskeyService {
+    /// 为当前用户生成注册选项
+    async fn start_registration(&self, user: &User) -> Result<PasskeyCreationOptions>;
+    /// 校验认证器返回的凭据并保存公钥
+    async fn finish_registration(
+        &self,
+        user_id: Uuid,
+        input: PasskeyRegistrationInput,
+    ) -> Result<PasskeyCredential>;
+    /// 生成登录选项, 由认证器选择可用的 passkey
+    async fn start_authentication(&self) -> Result<PasskeyRequestOptions>;
+    /// 校验断言签名与计数器, 返回凭据所属用户
+    async fn finish_authentication(&self, input: PasskeyLoginInput) -> Result<User>;
+    async fn list(&self, user_id: Uuid) -> Result<Vec<PasskeyCredential>>;
+    async fn delete(&self, user_id: Uuid, id: Uuid) -> Result<bool>;
+}
+
+#[derive(Clone)]
+pub struct PasskeyServiceImpl<T, P>
+where
+    T: UserRepo + Sync + Send,
+    P: PasskeyRepo + Sync + Send,
+{
+    pub user_repo: T,
+    pub passkey_repo: P,
+    pub config: WebAuthnConfig,
+}
+
+impl PasskeyServiceImpl<UserRepoImpl, PasskeyRepoImpl> {
+    pub fn new(pool: Arc<PgPool>) -> Self {
+        PasskeyServiceImpl {
+            user_repo: UserRepoImpl::new(pool.clone()),
+            passkey_repo: PasskeyRepoImpl::new(pool),
+            config: WebAuthnConfig::from_env(),
+        }
+    }
+}
+
+impl<T, P> PasskeyServiceImpl<T, P>
+where
+    T: UserRepo + Sync + Send,
+    P: PasskeyRepo + Sync + Send,
+{
+    async fn create_challenge(&self, user_id: Option<Uuid>) -> Result<PasskeyChallenge> {
+        let mut bytes = [0u8; 32];
+        OsRng.fill_bytes(&mut bytes);
+        let expires_at = Utc::now() + Duration::seconds(self.config.webauthn_timeout_secs);
+        self.passkey_repo
+            .create_challenge(user_id, b64url_encode(&bytes), expires_at)
+            .await
+    }
+
+    // 挑战值只能使用一次, 且必须属于同一用户(登录时为空)
+    async fn take_challenge(&self, id: Uuid, user_id: Option<Uuid>) -> Result<PasskeyChallenge> {
+        self.passkey_repo
+            .take_challenge(id)
+            .await?
+            .filter(|challenge| challenge.user_id == user_id)
+            .ok_or_else(|| Error::InvalidPasskey("unknown or expired challenge".to_string()))
+    }
+
+    fn timeout_millis(&self) -> i64 {
+        self.config.webauthn_timeout_secs * 1000
+    }
+}
+
+#[async_trait]
+impl<T, P> PasskeyService for PasskeyServiceImpl<T, P>
+where
+    T: UserRepo + Sync + Send,
+    P: PasskeyRepo + Sync + Send,
+{
+    async fn start_registration(&self, user: &User) -> Result<PasskeyCreationOptions> {
+        let exclude_credentials = self
+            .passkey_repo
+            .list(user.id)
+            .await?
+            .into_iter()
+            .map(|credential| CredentialDescriptor {
+                kind: PUBLIC_KEY.to_string(),
+                id: credential.credential_id,
+            })
+            .collect();
+        let challenge = self.create_challenge(Some(user.id)).await?;
+
+        Ok(PasskeyCreationOptions {
+            challenge_id: challenge.id,
+            public_key: PublicKeyCredentialCreationOptions {
+                challenge: challenge.challenge,
+                rp: RelyingParty {
+                    id: self.config.webauthn_rp_id.clone(),
+                    name: self.config.webauthn_rp_name.clone(),
+                },
+                user: UserEntity {
+                    id: b64url_encode(user.id.as_bytes()),
+                    name: user.email.clone(),
+                    display_name: user.name.clone(),
+                },
+                pub_key_cred_params: ALGORITHMS
+                    .iter()
+                    .map(|&alg| CredentialParameter {
+                        kind: PUBLIC_KEY.to_string(),
+                        alg,
+                    })
+                    .collect(),
+                timeout: self.timeout_millis(),
+                attestation: "none".to_string(),
+                exclude_credentials,
+                authenticator_selection: AuthenticatorSelection {
+                    resident_key: "required".to_string(),
+                    user_verification: USER_VERIFICATION.to_string(),
+                },
+            },
+        })
+    }
+
+    async fn finish_registration(
+        &self,
+        user_id: Uuid,
+        input: PasskeyRegistrationInput,
+    ) -> Result<PasskeyCredential> {
+        let challenge = self
+            .take_challenge(input.challenge_id, Some(user_id))
+            .await?;
+        let response = &input.credential.response;
+        verify_client_data(
+            &b64url_decode(&response.client_data_json)?,
+            "webauthn.create",
+            &challenge.challenge,
+            &self.config.webauthn_origin,
+        )?;
+
+        let auth_data = attestation_auth_data(&b64url_decode(&response.attestation_object)?)?;
+        let auth_data = AuthenticatorData::parse(&auth_data, &self.config.webauthn_rp_id, true)?;
+        let attested = auth_data
+            .attested
+            .ok_or_else(|| Error::InvalidPasskey("missing credential data".to_string()))?;
+        if b64url_decode(&input.credential.id)? != attested.credential_id {
+            return Err(Error::InvalidPasskey("credential id mismatch".to_string()));
+        }
+        // 保存前确认公钥算法受支持, 避免登录时才失败
+        CoseKey::parse(&attested.public_key)?;
+
+        self.passkey_repo
+            .create(CreatePasskeyCredential {
+                user_id,
+                credential_id: b64url_encode(&attested.credential_id),
+                public_key: attested.public_key,
+                sign_count: auth_data.sign_count as i64,
+                name: input.name,
+            })
+            .await
+    }
+
+    async fn start_authentication(&self) -> Result<PasskeyRequestOptions> {
+        let challenge = self.create_challenge(None).await?;
+        Ok(PasskeyRequestOptions {
+            challenge_id: challenge.id,
+            public_key: PublicKeyCredentialRequestOptions {
+                challenge: challenge.challenge,
+                rp_id: self.config.webauthn_rp_id.clone(),
+                timeout: self.timeout_millis(),
+                user_verification: USER_VERIFICATION.to_string(),
+            },
+        })
+    }
+
+    async fn finish_authentication(&self, input: PasskeyLoginInput) -> Result<User> {
+        let challenge = self.take_challenge(input.challenge_id, None).await?;
+        let response = &input.credential.response;
+        let credential_id = b64url_encode(&b64url_decode(&input.credential.id)?);
+        let credential = self
+            .passkey_repo
+            .get_by_credential_id(&credential_id)
+            .await?
+            .ok_or_else(|| Error::InvalidPasskey("unknown credential".to_string()))?;
+
+        let client_data_json = b64url_decode(&response.client_data_json)?;
+        verify_client_data(
+            &client_data_json,
+            "webauthn.get",
+            &challenge.challenge,
+            &self.config.webauthn_origin,
+        )?;
+        if let Some(handle) = &response.user_handle {
+            if b64url_decode(handle)? != credential.user_id.as_bytes() {
+                return Err(Error::InvalidPasskey("user handle mismatch".to_string()));
+            }
+        }
+
+        let raw_auth_data = b64url_decode(&response.authenticator_data)?;
+        let auth_data =
+            AuthenticatorData::parse(&raw_auth_data, &self.config.webauthn_rp_id, true)?;
+        CoseKey::parse(&credential.public_key)?.verify(
+            &signed_message(&raw_auth_data, &client_data_json),
+            &b64url_decode(&response.signature)?,
+        )?;
+
+        // 计数器不支持时始终为 0; 否则必须递增, 回退说明认证器可能被克隆
+        let sign_count = auth_data.sign_count as i64;
+        if (sign_count != 0 || credential.sign_count != 0) && sign_count <= credential.sign_count {
+            tracing::warn!(
+                "passkey {} sign count went from {} to {}",
+                credential.id,
+                credential.sign_count,
+                sign_count
+            );
+            return Err(Error::InvalidPasskey("sign count mismatch".to_string()));
+        }
+        if !self
+            .passkey_repo
+            .update_sign_count(credential.id, credential.sign_count, sign_count)
+            .await?
+        {
+            return Err(Error::InvalidPasskey("sign count mismatch".to_string()));
+        }
+
+        self.user_repo.get(credential.user_id).await
+    }
+
+    async fn list(&self, user_id: Uuid) -> Result<Vec<PasskeyCredential>> {
+        self.passkey_repo.list(user_id).await
+    }
+
+    async fn delete(&self, user_id: Uuid, id: Uuid) -> Result<bool> {
+        self.passkey_repo.delete(user_id, id).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        dao::{passkey_repo::MockPasskeyRepo, user_repo::MockUserRepo},
+        dto::passkey::{
+            AssertionCredential, AssertionResponse, AttestationResponse, RegistrationCredential,
+        },
+        services::webauthn::tests::Authenticator,
+    };
+    use mockall::predicate::*;
+
+    const RP_ID: &str = "cashbook.test";
+    const ORIGIN: &str = "https://cashbook.test";
+
+    fn config() -> WebAuthnConfig {
+        WebAuthnConfig {
+            webauthn_rp_id: RP_ID.to_string(),
+            webauthn_rp_name: "cashbook".to_string(),
+            webauthn_origin: ORIGIN.to_string(),
+            webauthn_timeout_secs: 300,
+        }
+    }
+
+    fn challenge(user_id: Option<Uuid>) -> PasskeyChallenge {
+        PasskeyChallenge {
+            id: Uuid::new_v4(),
+            user_id,
+            challenge: b64url_encode(b"server-challenge"),
+            expires_at: Utc::now() + Duration::minutes(5),
+            created_at: Utc::now(),
+        }
+    }
+
+    fn stored(user_id: Uuid, authenticator: &Authenticator, sign_count: i64) -> PasskeyCredential {
+        PasskeyCredential {
+            id: Uuid::new_v4(),
+            user_id,
+            credential_id: b64url_encode(&authenticator.credential_id),
+            public_key: authenticator.cose_key(),
+            sign_count,
+            name: "laptop".to_string(),
+            created_at: Utc::now(),
+            last_used_at: None,
+        }
+    }
+
+    fn login_input(
+        authenticator: &mut Authenticator,
+        challenge: &PasskeyChallenge,
+        origin: &str,
+    ) -> PasskeyLoginInput {
+        let client_data = Authenticator::client_data("webauthn.get", &challenge.challenge, origin);
+        let (auth_data, signature) = authenticator.assert(RP_ID, &client_data);
+        PasskeyLoginInput {
+            challenge_id: challenge.id,
+            credential: AssertionCredential {
+                id: b64url_encode(&authenticator.credential_id),
+                response: AssertionResponse {
+                    client_data_json: b64url_encode(&client_data),
+                    authenticator_data: b64url_encode(&auth_data),
+                    signature: b64url_encode(&signature),
+                    user_handle: None,
+                },
+            },
+        }
+    }
+
+    #[tokio::test]
+    async fn test_start_registration_options() {
+        let user = User {
+            email: "alice@example.com".to_string(),
+            ..Default::default()
+        };
+        let mut passkey_repo = MockPasskeyRepo::new();
+        passkey_repo
+            .expect_list()
+            .returning(|user_id| Ok(vec![stored(user_id, &Authenticator::default(), 0)]));
+        passkey_repo
+            .expect_create_challenge()
+            .withf(move |id, _, _| *id == Some(user.id))
+            .returning(|user_id, value, expires_at| {
+                Ok(PasskeyChallenge {
+                    challenge: value,
+                    expires_at,
+                    ..challenge(user_id)
+                })
+            });
+        let sut = PasskeyServiceImpl {
+            user_repo: MockUserRepo::new(),
+            passkey_repo,
+            config: config(),
+        };
+
+        let options = sut.start_registration(&user).await.unwrap().public_key;
+        assert_eq!(RP_ID, options.rp.id);
+        assert_eq!(
+            user.id.as_bytes().to_vec(),
+            b64url_decode(&options.user.id).unwrap()
+        );
+        assert_eq!(32, b64url_decode(&options.challenge).unwrap().len());
+        assert_eq!(1, options.exclude_credentials.len());
+        assert_eq!(300_000, options.timeout);
+    }
+
+    #[tokio::test]
+    async fn test_finish_registration_stores_public_key() {
+        let user_id = Uuid::new_v4();
+        let authenticator = Authenticator::default();
+        let challenge = challenge(Some(user_id));
+        let client_data =
+            Authenticator::client_data("webauthn.create", &challenge.challenge, ORIGIN);
+        let (attestation_object, _) = authenticator.attestation_object(RP_ID);
+
+        let mut passkey_repo = MockPasskeyRepo::new();
+        let taken = challenge.clone();
+        passkey_repo
+            .expect_take_challenge()
+            .with(eq(challenge.id))
+            .times(1)
+            .returning(move |_| Ok(Some(taken.clone())));
+        let expected_key = authenticator.cose_key();
+        let expected_id = b64url_encode(&authenticator.credential_id);
+        passkey_repo
+            .expect_create()
+            .withf(move |credential| {
+                credential.user_id == user_id
+                    && credential.public_key == expected_key
+                    && credential.credential_id == expected_id
+                    && credential.name == "laptop"
+            })
+            .times(1)
+            .returning(|credential| {
+                Ok(PasskeyCredential {
+                    id: Uuid::new_v4(),
+                    user_id: credential.user_id,
+                    credential_id: credential.credential_id,
+                    public_key: credential.public_key,
+                    sign_count: credential.sign_count,
+                    name: credential.name,
+                    created_at: Utc::now(),
+                    last_used_at: None,
+                })
+            });
+        let sut = PasskeyServiceImpl {
+            user_repo: MockUserRepo::new(),
+            passkey_repo,
+            config: config(),
+        };
+
+        sut.finish_registration(
+            user_id,
+            PasskeyRegistrationInput {
+                challenge_id: challenge.id,
+                name: "laptop".to_string(),
+                credential: RegistrationCredential {
+                    id: b64url_encode(&authenticator.credential_id),
+                    response: AttestationResponse {
+                        client_data_json: b64url_encode(&client_data),
+                        attestation_object: b64url_encode(&attestation_object),
+                    },
+                },
+            },
+        )
+        .await
+        .unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_finish_registration_rejects_foreign_challenge() {
+        let challenge = challenge(Some(Uuid::new_v4()));
+        let authenticator = Authenticator::default();
+        let (attestation_object, _) = authenticator.attestation_object(RP_ID);
+        let mut passkey_repo = MockPasskeyRepo::new();
+        let taken = challenge.clone();
+        passkey_repo
+            .expect_take_challenge()
+            .returning(move |_| Ok(Some(taken.clone())));
+        passkey_repo.expect_create().never();
+        let sut = PasskeyServiceImpl {
+            user_repo: MockUserRepo::new(),
+            passkey_repo,
+            config: config(),
+        };
+
+        let actual = sut
+            .finish_registration(
+                Uuid::new_v4(),
+                PasskeyRegistrationInput {
+                    challenge_id: challenge.id,
+                    name: "laptop".to_string(),
+                    credential: RegistrationCredential {
+                        id: b64url_encode(&authenticator.credential_id),
+                        response: AttestationResponse {
+                            client_data_json: b64url_encode(&Authenticator::client_data(
+                                "webauthn.create",
+                                &challenge.challenge,
+                                ORIGIN,
+                            )),
+                            attestation_object: b64url_encode(&attestation_object),
+                        },
+                    },
+                },
+            )
+            .await;
+        assert!(matches!(actual, Err(Error::InvalidPasskey(_))));
+    }
+
+    #[tokio::test]
+    async fn test_finish_authentication() {
+        let user_id = Uuid::new_v4();
+        let mut authenticator = Authenticator::default();
+        let credential = stored(user_id, &authenticator, 0);
+        let credential_pk = credential.id;
+        let challenge = challenge(None);
+        let input = login_input(&mut authenticator, &challenge, ORIGIN);
+
+        let mut passkey_repo = MockPasskeyRepo::new();
+        let taken = challenge.clone();
+        passkey_repo
+            .expect_take_challenge()
+            .returning(move |_| Ok(Some(taken.clone())));
+        let credential_id = b64url_encode(&authenticator.credential_id);
+        passkey_repo
+            .expect_get_by_credential_id()
+            .withf(move |id| id == credential_id)
+            .returning(move |_| Ok(Some(credential.clone())));
+        passkey_repo
+            .expect_update_sign_count()
+            .with(eq(credential_pk), eq(0), eq(1))
+            .times(1)
+            .returning(|_, _, _| Ok(true));
+        let mut user_repo = MockUserRepo::new();
+        user_repo.expect_get().with(eq(user_id)).returning(|id| {
+            Ok(User {
+                id,
+                ..Default::default()
+            })
+        });
+        let sut = PasskeyServiceImpl {
+            user_repo,
+            passkey_repo,
+            config: config(),
+        };
+
+        assert_eq!(user_id, sut.finish_authentication(input).await.unwrap().id);
+    }
+
+    #[tokio::test]
+    async fn test_finish_authentication_rejects_replayed_counter_and_origin() {
+        let mut authenticator = Authenticator::default();
+        let credential = stored(Uuid::new_v4(), &authenticator, 5);
+        let challenge = challenge(None);
+
+        let mut passkey_repo = MockPasskeyRepo::new();
+        let taken = challenge.clone();
+        passkey_repo
+            .expect_take_challenge()
+            .returning(move |_| Ok(Some(taken.clone())));
+        passkey_repo
+            .expect_get_by_credential_id()
+            .returning(move |_| Ok(Some(credential.clone())));
+        passkey_repo.expect_update_sign_count().never();
+        let sut = PasskeyServiceImpl {
+            user_repo: MockUserRepo::new(),
+            passkey_repo,
+            config: config(),
+        };
+
+        // 认证器计数为 1, 低于已记录的 5
+        let input = login_input(&mut authenticator, &challenge, ORIGIN);
+        assert!(matches!(
+            sut.finish_authentication(input).await,
+            Err(Error::InvalidPasskey(reason)) if reason == "sign count mismatch"
+        ));
+        let input = login_input(&mut authenticator, &challenge, "https://evil.test");
+        assert!(matches!(
+            sut.finish_authentication(input).await,
+            Err(Error::InvalidPasskey(reason)) if reason == "origin mismatch"
+        ));
+    }
+}
@@ -0,0 +1,155 @@
+use crate::{
+    config::env::PasswordConfig,
+    errors::{Error, Result},
+};
+use argon2::{
+    password_hash::{rand_core::OsRng, PasswordHash, PasswordHasher as _, SaltString},
+    Algorithm, Argon2, Params, PasswordVerifier, Version,
+};
+use std::sync::Arc;
+
+pub type DynPasswordHasher = Arc<dyn PasswordHasher + Send + Sync>;
+
+/// 密码哈希接口, 哈希结果为 PHC 字符串
+pub trait PasswordHasher {
+    fn hash(&self, password: &str) -> Result<String>;
+    /// 校验密码, 兼容迁移前以明文保存的密码
+    fn verify(&self, password: &str, hashed: &str) -> bool;
+    /// 保存的密码为明文或哈希参数与当前配置不一致时需要重新哈希
+    fn needs_rehash(&self, hashed: &str) -> bool;
+}
+
+/// 在阻塞线程池中计算哈希, 避免占用异步运行时
+pub async fn hash_password(hasher: &DynPasswordHasher, password: String) -> Result<String> {
+    let hasher = hasher.clone();
+    tokio::task::spawn_blocking(move || hasher.hash(&password))
+        .await
+        .map_err(|err| Error::PasswordHash(err.to_string()))?
+}
+
+/// 在阻塞线程池中校验密码
+pub async fn verify_password(hasher: &DynPasswordHasher, password: String, hashed: String) -> bool {
+    let hasher = hasher.clone();
+    tokio::task::spawn_blocking(move || hasher.verify(&password, &hashed))
+        .await
+        .unwrap_or(false)
+}
+
+/// Argon2id 实现
+#[derive(Clone)]
+pub struct Argon2Hasher {
+    params: Params,
+}
+
+impl Argon2Hasher {
+    pub fn new(config: &PasswordConfig) -> Result<Self> {
+        let params = Params::new(
+            config.argon2_memory_kib,
+            config.argon2_iterations,
+            config.argon2_parallelism,
+            None,
+        )
+        .map_err(|err| Error::PasswordHash(err.to_string()))?;
+        Ok(Argon2Hasher { params })
+    }
+
+    /// 使用环境变量中的参数, 参数非法时启动即失败
+    pub fn from_env() -> Self {
+        Argon2Hasher::new(&PasswordConfig::from_env()).expect("invalid argon2 parameters")
+    }
+
+    fn argon2(&self) -> Argon2<'static> {
+        Argon2::new(Algorithm::Argon2id, Version::V0x13, self.params.clone())
+    }
+}
+
+/// 迁移前的密码以明文保存, 不是 PHC 字符串
+fn is_legacy(hashed: &str) -> bool {
+    !hashed.starts_with('$')
+}
+
+// 定长比较, 避免按比较耗时推测明文密码
+fn constant_time_eq(a: &[u8], b: &[u8]) -> bool {
+    a.len() == b.len() && a.iter().zip(b).fold(0, |acc, (x, y)| acc | (x ^ y)) == 0
+}
+
+impl PasswordHasher for Argon2Hasher {
+    fn hash(&self, password: &str) -> Result<String> {
+        let salt = SaltString::generate(&mut OsRng);
+        Ok(self
+            .argon2()
+            .hash_password(password.as_bytes(), &salt)
+            .map_err(|err| Error::PasswordHash(err.to_string()))?
+            .to_string())
+    }
+
+    fn verify(&self, password: &str, hashed: &str) -> bool {
+        if is_legacy(hashed) {
+            return constant_time_eq(password.as_bytes(), hashed.as_bytes());
+        }
+        // 按哈希中记录的算法与参数校验, 参数调整前的密码仍可登录
+        PasswordHash::new(hashed)
+            .map(|parsed| {
+                Argon2::default()
+                    .verify_password(password.as_bytes(), &parsed)
+                    .is_ok()
+            })
+            .unwrap_or(false)
+    }
+
+    fn needs_rehash(&self, hashed: &str) -> bool {
+        let parsed = match PasswordHash::new(hashed) {
+            Ok(parsed) => parsed,
+            Err(_) => return true,
+        };
+        if parsed.algorithm != Algorithm::Argon2id.ident()
+            || parsed.version != Some(Version::V0x13.into())
+        {
+            return true;
+        }
+        Params::try_from(&parsed).map_or(true, |params| {
+            params.m_cost() != self.params.m_cost()
+                || params.t_cost() != self.params.t_cost()
+                || params.p_cost() != self.params.p_cost()
+        })
+    }
+}
+
+#[cfg(test)]
+pub mod tests {
+    use super::*;
+
+    /// 测试用的低开销参数
+    pub fn hasher(memory_kib: u32) -> Argon2Hasher {
+        Argon2Hasher::new(&PasswordConfig {
+            argon2_memory_kib: memory_kib,
+            argon2_iterations: 1,
+            argon2_parallelism: 1,
+        })
+        .unwrap()
+    }
+
+    #[test]
+    fn test_argon2_hasher_round_trip() {
+        let sut = hasher(64);
+        let hashed = sut.hash("s3cret-pass").unwrap();
+        assert!(hashed.starts_with("$argon2id$v=19$m=64,t=1,p=1$"));
+        assert!(sut.verify("s3cret-pass", &hashed));
+        assert!(!sut.verify("wrong-pass", &hashed));
+        assert!(!sut.needs_rehash(&hashed));
+        assert_ne!(hashed, sut.hash("s3cret-pass").unwrap());
+    }
+
+    #[test]
+    fn test_argon2_hasher_needs_rehash() {
+        let old = hasher(64).hash("s3cret-pass").unwrap();
+        let sut = hasher(128);
+        assert!(sut.verify("s3cret-pass", &old));
+        assert!(sut.needs_rehash(&old));
+
+        assert!(sut.verify("plaintext", "plaintext"));
+        assert!(!sut.verify("plaintext", "plain"));
+        assert!(sut.needs_rehash("plaintext"));
+        assert!(!sut.verify("", "$not-a-phc-string"));
+    }
+}
@@ -0,0 +1,297 @@
+use super::{
+    mailer::{self, DynMailer, Email},
+    password::{hash_password, Argon2Hasher, DynPasswordHasher},
+    token::generate_token,
+};
+pub(crate) use crate::{
+    config::env::PasswordResetConfig,
+    dao::{
+        password_reset_repo::{PasswordResetRepo, PasswordResetRepoImpl},
+        revocation_repo::{RevocationRepo, RevocationRepoImpl},
+        token_repo::{RefreshTokenRepo, RefreshTokenRepoImpl},
+        user_repo::{UserRepo, UserRepoImpl},
+    },
+    errors::{Error, Result},
+    models::token::token_hash,
+};
+use axum::async_trait;
+use chrono::{Duration, Utc};
+use sqlx::postgres::PgPool;
+use std::sync::Arc;
+
+pub type DynPasswordResetService = Arc<dyn PasswordResetService + Send + Sync>;
+
+#[cfg_attr(test, mockall::automock)]
+#[async_trait]
+pub trait PasswordResetService {
+    /// 向已注册邮箱发送重置链接, 邮箱未注册时同样返回成功
+    async fn forgot(&self, email: &str) -> Result<()>;
+    /// 使用令牌设置新密码, 并注销该用户的全部会话
+    async fn reset(&self, token: &str, password: String) -> Result<()>;
+}
+
+#[derive(Clone)]
+pub struct PasswordResetServiceImpl<T, P, K, R>
+where
+    T: UserRepo + Sync + Send,
+    P: PasswordResetRepo + Sync + Send,
+    K: RefreshTokenRepo + Sync + Send,
+    R: RevocationRepo + Sync + Send,
+{
+    pub user_repo: T,
+    pub reset_repo: P,
+    pub token_repo: K,
+    pub revocation_repo: R,
+    pub hasher: DynPasswordHasher,
+    pub mailer: DynMailer,
+    pub config: PasswordResetConfig,
+}
+
+impl
+    PasswordResetServiceImpl<
+        UserRepoImpl,
+        PasswordResetRepoImpl,
+        RefreshTokenRepoImpl,
+        RevocationRepoImpl,
+    >
+{
+    pub fn new(pool: Arc<PgPool>) -> Self {
+        PasswordResetServiceImpl {
+            user_repo: UserRepoImpl::new(pool.clone()),
+            reset_repo: PasswordResetRepoImpl::new(pool.clone()),
+            token_repo: RefreshTokenRepoImpl::new(pool.clone()),
+            revocation_repo: RevocationRepoImpl::new(pool),
+            hasher: Arc::new(Argon2Hasher::from_env()),
+            mailer: mailer::from_env(),
+            config: PasswordResetConfig::from_env(),
+        }
+    }
+}
+
+#[async_trait]
+impl<T, P, K, R> PasswordResetService for PasswordResetServiceImpl<T, P, K, R>
+where
+    T: UserRepo + Sync + Send,
+    P: PasswordResetRepo + Sync + Send,
+    K: RefreshTokenRepo + Sync + Send,
+    R: RevocationRepo + Sync + Send,
+{
+    async fn forgot(&self, email: &str) -> Result<()> {
+        let user = match self.user_repo.get_by_email(email).await {
+            Ok(user) => user,
+            Err(Error::DataStore(sqlx::Error::RowNotFound)) => return Ok(()),
+            Err(err) => return Err(err),
+        };
+
+        let token = generate_token();
+        let ttl = self.config.password_reset_ttl_minutes;
+        self.reset_repo
+            .create(
+                user.id,
+                token_hash(&token),
+                Utc::now() + Duration::minutes(ttl),
+            )
+            .await?;
+
+        let url = &self.config.password_reset_url;
+        let separator = if url.contains('?') { '&' } else { '?' };
+        let body = format!(
+            "Hi {},\n\nSomeone asked to reset the password of your cashbook account. \
+             Open the link below within {} minutes to choose a new one:\n\n\
+             {url}{separator}token={token}\n\n\
+             If you did not ask for this, you can ignore this email.\n",
+            user.name, ttl
+        );
+        // 在后台发送, 响应与邮箱未注册时保持一致
+        mailer::spawn_send(
+            &self.mailer,
+            Email {
+                to: user.email,
+                subject: "Reset your password".to_string(),
+                body,
+            },
+            user.id,
+            "password reset",
+        );
+        Ok(())
+    }
+
+    async fn reset(&self, token: &str, password: String) -> Result<()> {
+        let hashed = hash_password(&self.hasher, password).await?;
+        let user_id = self.reset_repo.reset(&token_hash(token), &hashed).await?;
+
+        // 与 logout_all 相同, 先撤销 refresh token 再作废已签发的 access token
+        self.token_repo.revoke_user(user_id).await?;
+        self.revocation_repo.revoke_all(user_id, Utc::now()).await?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        dao::{
+            password_reset_repo::MockPasswordResetRepo, revocation_repo::MockRevocationRepo,
+            token_repo::MockRefreshTokenRepo, user_repo::MockUserRepo,
+        },
+        models::{token::PasswordReset, user::User},
+        services::{mailer::tests::MemoryMailer, password::tests::hasher},
+    };
+    use mockall::predicate::*;
+    use std::sync::Mutex;
+    use uuid::Uuid;
+
+    fn sut(
+        user_repo: MockUserRepo,
+        reset_repo: MockPasswordResetRepo,
+        token_repo: MockRefreshTokenRepo,
+        revocation_repo: MockRevocationRepo,
+        mailer: Arc<MemoryMailer>,
+    ) -> PasswordResetServiceImpl<
+        MockUserRepo,
+        MockPasswordResetRepo,
+        MockRefreshTokenRepo,
+        MockRevocationRepo,
+    > {
+        PasswordResetServiceImpl {
+            user_repo,
+            reset_repo,
+            token_repo,
+            revocation_repo,
+            hasher: Arc::new(hasher(64)),
+            mailer,
+            config: PasswordResetConfig {
+                password_reset_ttl_minutes: 60,
+                password_reset_url: "https://cashbook.test/reset".to_string(),
+            },
+        }
+    }
+
+    #[tokio::test]
+    async fn test_forgot_mails_token_and_stores_hash() {
+        let user = User {
+            email: "alice@example.com".to_string(),
+            ..Default::default()
+        };
+        let user_id = user.id;
+        let mut user_repo = MockUserRepo::new();
+        user_repo
+            .expect_get_by_email()
+            .with(eq("alice@example.com"))
+            .returning(move |_| Ok(user.clone()));
+        let stored = Arc::new(Mutex::new(String::new()));
+        let captured = stored.clone();
+        let mut reset_repo = MockPasswordResetRepo::new();
+        reset_repo
+            .expect_create()
+            .withf(move |id, _, _| *id == user_id)
+            .times(1)
+            .returning(move |user_id, token_hash, expires_at| {
+                *captured.lock().unwrap() = token_hash.clone();
+                Ok(PasswordReset {
+                    id: Uuid::new_v4(),
+                    user_id,
+                    token_hash,
+                    expires_at,
+                    used_at: None,
+                    created_at: Utc::now(),
+                })
+            });
+        let mailer = Arc::new(MemoryMailer::default());
+        let sut = sut(
+            user_repo,
+            reset_repo,
+            MockRefreshTokenRepo::new(),
+            MockRevocationRepo::new(),
+            mailer.clone(),
+        );
+
+        sut.forgot("alice@example.com").await.unwrap();
+
+        let sent = mailer.wait_sent(1).await;
+        assert_eq!("alice@example.com", sent[0].to);
+        let token = sent[0]
+            .body
+            .split("token=")
+            .nth(1)
+            .and_then(|rest| rest.split_whitespace().next())
+            .unwrap();
+        assert_eq!(*stored.lock().unwrap(), token_hash(token));
+    }
+
+    #[tokio::test]
+    async fn test_forgot_unknown_email() {
+        let mut user_repo = MockUserRepo::new();
+        user_repo
+            .expect_get_by_email()
+            .returning(|_| Err(Error::DataStore(sqlx::Error::RowNotFound)));
+        let mut reset_repo = MockPasswordResetRepo::new();
+        reset_repo.expect_create().never();
+        let mailer = Arc::new(MemoryMailer::default());
+        let sut = sut(
+            user_repo,
+            reset_repo,
+            MockRefreshTokenRepo::new(),
+            MockRevocationRepo::new(),
+            mailer.clone(),
+        );
+
+        sut.forgot("unknown@example.com").await.unwrap();
+        assert!(mailer.wait_sent(1).await.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_reset_revokes_sessions() {
+        let user_id = Uuid::new_v4();
+        let mut reset_repo = MockPasswordResetRepo::new();
+        reset_repo
+            .expect_reset()
+            .withf(|hash, hashed| hash == token_hash("token") && hashed.starts_with("$argon2id$"))
+            .times(1)
+            .returning(move |_, _| Ok(user_id));
+        let mut token_repo = MockRefreshTokenRepo::new();
+        token_repo
+            .expect_revoke_user()
+            .with(eq(user_id))
+            .times(1)
+            .returning(|_| Ok(2));
+        let mut revocation_repo = MockRevocationRepo::new();
+        revocation_repo
+            .expect_revoke_all()
+            .withf(move |id, _| *id == user_id)
+            .times(1)
+            .returning(|_, _| Ok(()));
+        let sut = sut(
+            MockUserRepo::new(),
+            reset_repo,
+            token_repo,
+            revocation_repo,
+            Arc::new(MemoryMailer::default()),
+        );
+
+        sut.reset("token", "new-password".to_string())
+            .await
+            .unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_reset_invalid_token_keeps_sessions() {
+        let mut reset_repo = MockPasswordResetRepo::new();
+        reset_repo
+            .expect_reset()
+            .returning(|_, _| Err(Error::InvalidResetToken));
+        let mut token_repo = MockRefreshTokenRepo::new();
+        token_repo.expect_revoke_user().never();
+        let sut = sut(
+            MockUserRepo::new(),
+            reset_repo,
+            token_repo,
+            MockRevocationRepo::new(),
+            Arc::new(MemoryMailer::default()),
+        );
+
+        let actual = sut.reset("token", "new-password".to_string()).await;
+        assert!(matches!(actual, Err(Error::InvalidResetToken)));
+    }
+}
@@ -0,0 +1,89 @@
+pub(crate) use crate::{
+    dao::rbac_repo::{RbacRepo, RbacRepoImpl},
+    errors::Result,
+    models::rbac::{Permission, Role},
+};
+use axum::async_trait;
+use sqlx::postgres::PgPool;
+use std::{collections::HashSet, sync::Arc};
+use uuid::Uuid;
+
+pub type DynRbacService = Arc<dyn RbacService + Send + Sync>;
+
+#[cfg_attr(test, mockall::automock)]
+#[async_trait]
+pub trait RbacService {
+    /// 用户持有的系统权限
+    async fn permissions(&self, user_id: Uuid) -> Result<HashSet<Permission>>;
+    async fn roles(&self, user_id: Uuid) -> Result<Vec<Role>>;
+    async fn grant(&self, user_id: Uuid, role: String) -> Result<Role>;
+    async fn revoke(&self, user_id: Uuid, role: String) -> Result<Role>;
+    /// 管理员从未存在过时将指定邮箱的用户设为管理员
+    async fn bootstrap_admin(&self, email: &str) -> Result<bool>;
+}
+
+#[derive(Clone)]
+pub struct RbacServiceImpl<T: RbacRepo>
+where
+    T: RbacRepo + Sync + Send,
+{
+    pub rbac_repo: T,
+}
+
+impl RbacServiceImpl<RbacRepoImpl> {
+    pub fn new(pool: Arc<PgPool>) -> Self {
+        RbacServiceImpl {
+            rbac_repo: RbacRepoImpl::new(pool),
+        }
+    }
+}
+
+#[async_trait]
+impl<T> RbacService for RbacServiceImpl<T>
+where
+    T: RbacRepo + Sync + Send,
+{
+    async fn permissions(&self, user_id: Uuid) -> Result<HashSet<Permission>> {
+        Ok(self
+            .rbac_repo
+            .permissions(user_id)
+            .await?
+            .iter()
+            .filter_map(|name| Permission::from_name(name))
+            .collect())
+    }
+
+    async fn roles(&self, user_id: Uuid) -> Result<Vec<Role>> {
+        Ok(self.rbac_repo.roles(user_id).await?)
+    }
+
+    async fn grant(&self, user_id: Uuid, role: String) -> Result<Role> {
+        Ok(self.rbac_repo.grant(user_id, &role).await?)
+    }
+
+    async fn revoke(&self, user_id: Uuid, role: String) -> Result<Role> {
+        Ok(self.rbac_repo.revoke(user_id, &role).await?)
+    }
+
+    async fn bootstrap_admin(&self, email: &str) -> Result<bool> {
+        self.rbac_repo.bootstrap_admin(email).await
+    }
+}
+
+#[cfg(test)]
+pub mod tests {
+    use super::*;
+    use crate::dao::rbac_repo::MockRbacRepo;
+
+    #[tokio::test]
+    async fn test_rbac_service_permissions_ignores_unknown() {
+        let mut rbac_repo = MockRbacRepo::new();
+        rbac_repo
+            .expect_permissions()
+            .returning(|_| Ok(vec!["users:read".to_string(), "reports:export".to_string()]));
+
+        let sut = RbacServiceImpl { rbac_repo };
+        let permissions = sut.permissions(Uuid::new_v4()).await.unwrap();
+        assert_eq!(HashSet::from([Permission::UsersRead]), permissions);
+    }
+}
@@ -0,0 +1,305 @@
+use super::{
+    categorization::{CategorizationServiceImpl, DynCategorizationService},
+    entry::check_balanced,
+};
+pub(crate) use crate::{
+    dao::recurring_repo::{RecurringRepo, RecurringRepoImpl},
+    dto::recurring::CreateRecurringInput,
+    errors::{Error, Result},
+    models::{
+        entry::{CreateEntry, CreatePosting},
+        ledger::Ledger,
+        recurring::{CreateRecurringEntry, RecurringEntry},
+    },
+};
+use axum::async_trait;
+use chrono::NaiveDate;
+use sqlx::postgres::PgPool;
+use std::sync::Arc;
+use uuid::Uuid;
+
+pub type DynRecurringService = Arc<dyn RecurringService + Send + Sync>;
+
+#[cfg_attr(test, mockall::automock)]
+#[async_trait]
+pub trait RecurringService {
+    async fn create(&self, ledger: &Ledger, input: CreateRecurringInput) -> Result<RecurringEntry>;
+    async fn get(&self, ledger_id: Uuid, id: Uuid) -> Result<RecurringEntry>;
+    async fn delete(&self, ledger_id: Uuid, id: Uuid) -> Result<RecurringEntry>;
+    async fn list(&self, ledger_id: Uuid) -> Result<Vec<RecurringEntry>>;
+    /// 恢复因连续失败而暂停的模板
+    async fn resume(&self, ledger_id: Uuid, id: Uuid) -> Result<RecurringEntry>;
+    /// 生成所有截至 today 到期的分录, 返回新生成的分录数
+    async fn run_due(&self, today: NaiveDate) -> Result<usize>;
+}
+
+#[derive(Clone)]
+pub struct RecurringServiceImpl<T: RecurringRepo>
+where
+    T: RecurringRepo + Sync + Send,
+{
+    pub recurring_repo: T,
+    pub categorization: DynCategorizationService,
+}
+
+impl RecurringServiceImpl<RecurringRepoImpl> {
+    pub fn new(pool: Arc<PgPool>) -> Self {
+        RecurringServiceImpl {
+            recurring_repo: RecurringRepoImpl::new(pool.clone()),
+            categorization: Arc::new(CategorizationServiceImpl::new(pool)),
+        }
+    }
+}
+
+#[async_trait]
+impl<T> RecurringService for RecurringServiceImpl<T>
+where
+    T: RecurringRepo + Sync + Send,
+{
+    async fn create(&self, ledger: &Ledger, input: CreateRecurringInput) -> Result<RecurringEntry> {
+        if input
+            .end_date
+            .is_some_and(|end_date| end_date < input.start_date)
+        {
+            return Err(Error::InvalidSchedule(
+                "end_date is earlier than start_date".to_string(),
+            ));
+        }
+        check_balanced(&input.postings, &ledger.base_currency)?;
+
+        // 模板创建时即应用自动分类规则, 之后每次生成的分录沿用模板的分类与标签
+        let template = CreateEntry {
+            ledger_id: ledger.id,
+            date: input.start_date,
+            description: input.description,
+            category_id: input.category_id,
+            tags: input.tags,
+            postings: input
+                .postings
+                .into_iter()
+                .map(|p| CreatePosting {
+                    account_id: p.account_id,
+                    amount: p.amount,
+                    currency: p.currency.unwrap_or_else(|| ledger.base_currency.clone()),
+                    side: p.side,
+                })
+                .collect(),
+        };
+        let template = self.categorization.categorize(template).await?;
+
+        let recurring = CreateRecurringEntry {
+            ledger_id: ledger.id,
+            description: template.description,
+            category_id: template.category_id,
+            tags: template.tags,
+            postings: template.postings,
+            frequency: input.frequency,
+            every: input.every,
+            start_date: input.start_date,
+            end_date: input.end_date,
+        };
+        Ok(self.recurring_repo.create(recurring).await?)
+    }
+
+    async fn get(&self, ledger_id: Uuid, id: Uuid) -> Result<RecurringEntry> {
+        let recurring = self.recurring_repo.get(id).await?;
+        if recurring.ledger_id != ledger_id {
+            return Err(Error::Forbidden);
+        }
+        Ok(recurring)
+    }
+
+    async fn delete(&self, ledger_id: Uuid, id: Uuid) -> Result<RecurringEntry> {
+        self.get(ledger_id, id).await?;
+        Ok(self.recurring_repo.delete(id).await?)
+    }
+
+    async fn list(&self, ledger_id: Uuid) -> Result<Vec<RecurringEntry>> {
+        Ok(self.recurring_repo.list(ledger_id).await?)
+    }
+
+    async fn resume(&self, ledger_id: Uuid, id: Uuid) -> Result<RecurringEntry> {
+        self.get(ledger_id, id).await?;
+        Ok(self.recurring_repo.resume(id).await?)
+    }
+
+    async fn run_due(&self, today: NaiveDate) -> Result<usize> {
+        let mut created = 0;
+        for id in self.recurring_repo.due(today).await? {
+            // 单个模板失败(如科目已关闭)不影响其它模板, 记录失败后下次调度时重试,
+            // 连续失败达到上限后暂停
+            let err = match self.recurring_repo.materialize(id, today).await {
+                Ok(entries) => {
+                    created += entries.len();
+                    continue;
+                }
+                Err(err) => err,
+            };
+            tracing::warn!("materialize recurring entry {id} failed: {err}");
+            match self
+                .recurring_repo
+                .record_failure(id, err.to_string())
+                .await
+            {
+                Ok(recurring) if recurring.failures >= RecurringEntry::MAX_FAILURES => {
+                    tracing::warn!(
+                        "recurring entry {id} paused after {} failures",
+                        recurring.failures
+                    )
+                }
+                Ok(_) => {}
+                Err(err) => tracing::error!("record failure of recurring entry {id} failed: {err}"),
+            }
+        }
+        Ok(created)
+    }
+}
+
+#[cfg(test)]
+pub mod tests {
+    use super::*;
+    use crate::{
+        dao::recurring_repo::MockRecurringRepo,
+        dto::entry::PostingInput,
+        models::{
+            entry::{EntryDetail, Side},
+            recurring::Frequency,
+        },
+        services::categorization::MockCategorizationService,
+    };
+
+    fn input(end_date: Option<NaiveDate>, credit: i64) -> CreateRecurringInput {
+        let posting = |amount, side| PostingInput {
+            account_id: Uuid::new_v4(),
+            amount,
+            currency: None,
+            side,
+        };
+        CreateRecurringInput {
+            description: "netflix".to_string(),
+            category_id: None,
+            tags: Vec::new(),
+            postings: vec![posting(1500, Side::Debit), posting(credit, Side::Credit)],
+            frequency: Frequency::Monthly,
+            every: 1,
+            start_date: NaiveDate::from_ymd(2022, 3, 5),
+            end_date,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_recurring_service_create() {
+        let mut recurring_repo = MockRecurringRepo::new();
+        recurring_repo
+            .expect_create()
+            .withf(|param| {
+                param.tags == vec!["subscription"] && param.postings[0].currency == "USD"
+            })
+            .returning(|param| {
+                Ok(RecurringEntry {
+                    ledger_id: param.ledger_id,
+                    next_run: Some(param.start_date),
+                    ..Default::default()
+                })
+            });
+        let mut categorization = MockCategorizationService::new();
+        categorization.expect_categorize().returning(|mut entry| {
+            entry.tags.push("subscription".to_string());
+            Ok(entry)
+        });
+
+        let sut = RecurringServiceImpl {
+            recurring_repo,
+            categorization: Arc::new(categorization),
+        };
+        let ledger = Ledger {
+            base_currency: "USD".to_string(),
+            ..Default::default()
+        };
+        let recurring = sut.create(&ledger, input(None, 1500)).await.unwrap();
+        assert_eq!(Some(NaiveDate::from_ymd(2022, 3, 5)), recurring.next_run);
+    }
+
+    #[tokio::test]
+    async fn test_recurring_service_create_invalid() {
+        let mut recurring_repo = MockRecurringRepo::new();
+        recurring_repo.expect_create().never();
+        let sut = RecurringServiceImpl {
+            recurring_repo,
+            categorization: Arc::new(MockCategorizationService::new()),
+        };
+
+        let ended = input(Some(NaiveDate::from_ymd(2022, 3, 1)), 1500);
+        let result = sut.create(&Ledger::default(), ended).await;
+        assert!(matches!(result, Err(Error::InvalidSchedule(_))));
+
+        let unbalanced = input(None, 1000);
+        let result = sut.create(&Ledger::default(), unbalanced).await;
+        assert!(matches!(result, Err(Error::UnbalancedEntry(_))));
+    }
+
+    #[tokio::test]
+    async fn test_recurring_service_run_due_continues_after_failure() {
+        let (broken, healthy) = (Uuid::new_v4(), Uuid::new_v4());
+        let mut recurring_repo = MockRecurringRepo::new();
+        recurring_repo
+            .expect_due()
+            .returning(move |_| Ok(vec![broken, healthy]));
+        recurring_repo
+            .expect_materialize()
+            .withf(move |id, _| *id == broken)
+            .returning(|_, _| Err(Error::InvalidAccount("closed".to_string())));
+        recurring_repo
+            .expect_materialize()
+            .withf(move |id, _| *id == healthy)
+            .returning(|_, _| Ok(vec![EntryDetail::default(), EntryDetail::default()]));
+        recurring_repo
+            .expect_record_failure()
+            .withf(move |id, error| *id == broken && error.contains("closed"))
+            .times(1)
+            .returning(|id, error| {
+                Ok(RecurringEntry {
+                    id,
+                    failures: 1,
+                    last_error: Some(error),
+                    ..Default::default()
+                })
+            });
+
+        let sut = RecurringServiceImpl {
+            recurring_repo,
+            categorization: Arc::new(MockCategorizationService::new()),
+        };
+        let created = sut.run_due(NaiveDate::from_ymd(2022, 4, 1)).await.unwrap();
+        assert_eq!(2, created);
+    }
+
+    #[tokio::test]
+    async fn test_recurring_service_resume_checks_ledger() {
+        let ledger_id = Uuid::new_v4();
+        let mut recurring_repo = MockRecurringRepo::new();
+        recurring_repo.expect_get().returning(move |id| {
+            Ok(RecurringEntry {
+                id,
+                ledger_id,
+                failures: RecurringEntry::MAX_FAILURES,
+                ..Default::default()
+            })
+        });
+        recurring_repo.expect_resume().times(1).returning(|id| {
+            Ok(RecurringEntry {
+                id,
+                ..Default::default()
+            })
+        });
+
+        let sut = RecurringServiceImpl {
+            recurring_repo,
+            categorization: Arc::new(MockCategorizationService::new()),
+        };
+        let other = sut.resume(Uuid::new_v4(), Uuid::new_v4()).await;
+        assert!(matches!(other, Err(Error::Forbidden)));
+        let resumed = sut.resume(ledger_id, Uuid::new_v4()).await.unwrap();
+        assert_eq!(0, resumed.failures);
+    }
+}
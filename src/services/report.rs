@@ -0,0 +1,321 @@
+pub(crate) use crate::{
+    dao::report_repo::{ReportRepo, ReportRepoImpl},
+    dto::report::{AsOfInput, PeriodInput},
+    errors::{Error, Result},
+    models::report::{AccountBalance, CashFlow, IncomeStatement, TrialBalance},
+};
+use axum::async_trait;
+use chrono::{Datelike, NaiveDate, Utc};
+use sqlx::postgres::PgPool;
+use std::sync::Arc;
+use uuid::Uuid;
+
+pub type DynReportService = Arc<dyn ReportService + Send + Sync>;
+
+#[cfg_attr(test, mockall::automock)]
+#[async_trait]
+pub trait ReportService {
+    async fn balances(&self, ledger_id: Uuid, input: AsOfInput) -> Result<Vec<AccountBalance>>;
+    async fn trial_balance(&self, ledger_id: Uuid, input: AsOfInput) -> Result<TrialBalance>;
+    async fn income_statement(
+        &self,
+        ledger_id: Uuid,
+        input: PeriodInput,
+    ) -> Result<IncomeStatement>;
+    async fn cash_flow(&self, ledger_id: Uuid, input: PeriodInput) -> Result<CashFlow>;
+}
+
+#[derive(Clone)]
+pub struct ReportServiceImpl<T: ReportRepo>
+where
+    T: ReportRepo + Sync + Send,
+{
+    pub report_repo: T,
+}
+
+impl ReportServiceImpl<ReportRepoImpl> {
+    pub fn new(pool: Arc<PgPool>) -> Self {
+        ReportServiceImpl {
+            report_repo: ReportRepoImpl::new(pool),
+        }
+    }
+}
+
+impl<T> ReportServiceImpl<T>
+where
+    T: ReportRepo + Sync + Send,
+{
+    /// 报表换算为本位币前, 确认所有过账币种截至 as_of 均有可用汇率
+    async fn check_rates(&self, ledger_id: Uuid, as_of: NaiveDate) -> Result<()> {
+        let missing = self.report_repo.missing_rates(ledger_id, as_of).await?;
+        if !missing.is_empty() {
+            return Err(Error::MissingExchangeRate(format!(
+                "{} as of {as_of}",
+                missing.join(", ")
+            )));
+        }
+        Ok(())
+    }
+
+    /// 期间报表按分录日期的汇率换算, 确认期间内所有过账币种均有可用汇率
+    async fn check_period_rates(
+        &self,
+        ledger_id: Uuid,
+        from: NaiveDate,
+        to: NaiveDate,
+    ) -> Result<()> {
+        let missing = self
+            .report_repo
+            .missing_period_rates(ledger_id, from, to)
+            .await?;
+        if !missing.is_empty() {
+            return Err(Error::MissingExchangeRate(format!(
+                "{} between {from} and {to}",
+                missing.join(", ")
+            )));
+        }
+        Ok(())
+    }
+}
+
+// 查询区间缺省为 to 所在年度初至 to, to 缺省为当天
+fn period_range(input: &PeriodInput) -> (NaiveDate, NaiveDate) {
+    let to = input.to.unwrap_or_else(|| Utc::today().naive_utc());
+    let from = input
+        .from
+        .unwrap_or_else(|| NaiveDate::from_ymd(to.year(), 1, 1));
+    (from, to)
+}
+
+#[async_trait]
+impl<T> ReportService for ReportServiceImpl<T>
+where
+    T: ReportRepo + Sync + Send,
+{
+    async fn balances(&self, ledger_id: Uuid, input: AsOfInput) -> Result<Vec<AccountBalance>> {
+        let as_of = input.as_of.unwrap_or_else(|| Utc::today().naive_utc());
+        self.check_rates(ledger_id, as_of).await?;
+        Ok(self.report_repo.balances(ledger_id, as_of).await?)
+    }
+
+    async fn trial_balance(&self, ledger_id: Uuid, input: AsOfInput) -> Result<TrialBalance> {
+        let as_of = input.as_of.unwrap_or_else(|| Utc::today().naive_utc());
+        self.check_rates(ledger_id, as_of).await?;
+        let lines = self.report_repo.trial_balance(ledger_id, as_of).await?;
+        Ok(TrialBalance::new(as_of, lines))
+    }
+
+    async fn income_statement(
+        &self,
+        ledger_id: Uuid,
+        input: PeriodInput,
+    ) -> Result<IncomeStatement> {
+        let (from, to) = period_range(&input);
+        self.check_period_rates(ledger_id, from, to).await?;
+        let unit = input.group_by.period().trunc_unit();
+        let rows = self
+            .report_repo
+            .income_statement(ledger_id, from, to, unit)
+            .await?;
+        Ok(IncomeStatement::new(from, to, input.group_by, rows))
+    }
+
+    async fn cash_flow(&self, ledger_id: Uuid, input: PeriodInput) -> Result<CashFlow> {
+        let (from, to) = period_range(&input);
+        self.check_period_rates(ledger_id, from, to).await?;
+        let unit = input.group_by.period().trunc_unit();
+        let rows = self
+            .report_repo
+            .cash_flow(ledger_id, from, to, unit)
+            .await?;
+        Ok(CashFlow::new(from, to, input.group_by, rows))
+    }
+}
+
+#[cfg(test)]
+pub mod tests {
+    use super::*;
+    use crate::{
+        dao::report_repo::MockReportRepo,
+        models::{
+            account::AccountKind,
+            report::{CashFlowRow, IncomeStatementRow, ReportGrouping, TrialBalanceLine},
+        },
+    };
+    use chrono::NaiveDate;
+    use mockall::predicate::*;
+
+    #[tokio::test]
+    async fn test_report_service_trial_balance() {
+        let ledger_id = Uuid::new_v4();
+        let as_of = NaiveDate::from_ymd(2022, 3, 31);
+        let mut report_repo = MockReportRepo::new();
+        report_repo
+            .expect_missing_rates()
+            .returning(|_, _| Ok(Vec::new()));
+        report_repo
+            .expect_trial_balance()
+            .with(eq(ledger_id), eq(as_of))
+            .returning(|_, _| {
+                Ok(vec![
+                    TrialBalanceLine {
+                        account_id: Uuid::new_v4(),
+                        name: "Bank".to_string(),
+                        kind: AccountKind::Asset,
+                        debit: 700,
+                        credit: 0,
+                    },
+                    TrialBalanceLine {
+                        account_id: Uuid::new_v4(),
+                        name: "Salary".to_string(),
+                        kind: AccountKind::Income,
+                        debit: 0,
+                        credit: 700,
+                    },
+                ])
+            });
+
+        let sut = ReportServiceImpl { report_repo };
+        let trial_balance = sut
+            .trial_balance(ledger_id, AsOfInput { as_of: Some(as_of) })
+            .await
+            .unwrap();
+        assert_eq!(700, trial_balance.total_debit);
+        assert_eq!(700, trial_balance.total_credit);
+        assert!(trial_balance.balanced);
+    }
+
+    #[tokio::test]
+    async fn test_report_service_balances_default_today() {
+        let mut report_repo = MockReportRepo::new();
+        let today = Utc::today().naive_utc();
+        report_repo
+            .expect_missing_rates()
+            .returning(|_, _| Ok(Vec::new()));
+        report_repo
+            .expect_balances()
+            .with(always(), eq(today))
+            .returning(|_, _| Ok(Vec::new()));
+
+        let sut = ReportServiceImpl { report_repo };
+        let result = sut.balances(Uuid::new_v4(), AsOfInput::default()).await;
+        assert!(result.is_ok());
+    }
+
+    #[tokio::test]
+    async fn test_report_service_missing_rate() {
+        let mut report_repo = MockReportRepo::new();
+        report_repo
+            .expect_missing_rates()
+            .returning(|_, _| Ok(vec!["USD".to_string()]));
+        report_repo.expect_balances().never();
+
+        let sut = ReportServiceImpl { report_repo };
+        let result = sut.balances(Uuid::new_v4(), AsOfInput::default()).await;
+        assert!(matches!(result, Err(Error::MissingExchangeRate(_))));
+    }
+
+    #[tokio::test]
+    async fn test_report_service_income_statement() {
+        let category_id = Uuid::new_v4();
+        let row = move |month, kind, category: Option<&str>, amount| IncomeStatementRow {
+            period_start: NaiveDate::from_ymd(2022, month, 1),
+            category_id: category.map(|_| category_id),
+            category: category.map(str::to_string),
+            kind,
+            amount,
+        };
+        let mut report_repo = MockReportRepo::new();
+        report_repo
+            .expect_missing_period_rates()
+            .returning(|_, _, _| Ok(Vec::new()));
+        report_repo
+            .expect_income_statement()
+            .with(always(), always(), always(), eq("month"))
+            .returning(move |_, _, _, _| {
+                Ok(vec![
+                    row(1, AccountKind::Income, Some("Salary"), -500000),
+                    row(1, AccountKind::Expense, Some("Food"), 42000),
+                    row(1, AccountKind::Expense, None, 8000),
+                    row(3, AccountKind::Expense, Some("Food"), 39000),
+                ])
+            });
+
+        let sut = ReportServiceImpl { report_repo };
+        let statement = sut
+            .income_statement(
+                Uuid::new_v4(),
+                PeriodInput {
+                    from: Some(NaiveDate::from_ymd(2022, 1, 15)),
+                    to: Some(NaiveDate::from_ymd(2022, 3, 31)),
+                    group_by: ReportGrouping::Month,
+                },
+            )
+            .await
+            .unwrap();
+
+        assert_eq!(3, statement.periods.len());
+        let january = &statement.periods[0];
+        assert_eq!(NaiveDate::from_ymd(2022, 1, 15), january.start);
+        assert_eq!(NaiveDate::from_ymd(2022, 1, 31), january.end);
+        assert_eq!(500000, january.total_income);
+        assert_eq!(50000, january.total_expenses);
+        assert_eq!(450000, january.net_income);
+        assert_eq!(2, january.expenses.len());
+        assert!(statement.periods[1].expenses.is_empty());
+        assert_eq!(-39000, statement.periods[2].net_income);
+    }
+
+    #[tokio::test]
+    async fn test_report_service_cash_flow_by_year() {
+        let mut report_repo = MockReportRepo::new();
+        report_repo
+            .expect_missing_period_rates()
+            .returning(|_, _, _| Ok(Vec::new()));
+        report_repo
+            .expect_cash_flow()
+            .with(always(), always(), always(), eq("year"))
+            .returning(|_, _, _, _| {
+                Ok(vec![CashFlowRow {
+                    period_start: NaiveDate::from_ymd(2022, 1, 1),
+                    category_id: None,
+                    category: None,
+                    inflow: 1000,
+                    outflow: 300,
+                }])
+            });
+
+        let sut = ReportServiceImpl { report_repo };
+        let cash_flow = sut
+            .cash_flow(
+                Uuid::new_v4(),
+                PeriodInput {
+                    from: Some(NaiveDate::from_ymd(2021, 7, 1)),
+                    to: Some(NaiveDate::from_ymd(2022, 6, 30)),
+                    group_by: ReportGrouping::Year,
+                },
+            )
+            .await
+            .unwrap();
+
+        assert_eq!(2, cash_flow.periods.len());
+        assert_eq!(0, cash_flow.periods[0].net);
+        assert_eq!(700, cash_flow.periods[1].net);
+        assert_eq!(NaiveDate::from_ymd(2022, 6, 30), cash_flow.periods[1].end);
+    }
+
+    #[tokio::test]
+    async fn test_report_service_income_statement_missing_rate() {
+        let mut report_repo = MockReportRepo::new();
+        report_repo
+            .expect_missing_period_rates()
+            .returning(|_, _, _| Ok(vec!["USD".to_string()]));
+        report_repo.expect_income_statement().never();
+
+        let sut = ReportServiceImpl { report_repo };
+        let result = sut
+            .income_statement(Uuid::new_v4(), PeriodInput::default())
+            .await;
+        assert!(matches!(result, Err(Error::MissingExchangeRate(_))));
+    }
+}
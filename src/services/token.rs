@@ -0,0 +1,123 @@
+pub(crate) use crate::{
+    config::constants::TOKEN_TTL,
+    dao::token_repo::{RefreshTokenRepo, RefreshTokenRepoImpl},
+    errors::Result,
+    models::token::{token_hash, CreateRefreshToken, RefreshGrant},
+};
+use axum::async_trait;
+use chrono::{DateTime, Duration, Utc};
+use rand::{rngs::OsRng, RngCore};
+use sqlx::postgres::PgPool;
+use std::sync::Arc;
+use uuid::Uuid;
+
+pub type DynTokenService = Arc<dyn TokenService + Send + Sync>;
+
+#[cfg_attr(test, mockall::automock)]
+#[async_trait]
+pub trait TokenService {
+    /// 登录成功后签发新 family 的 refresh token
+    async fn issue(&self, user_id: Uuid) -> Result<RefreshGrant>;
+    /// 轮换 refresh token, 返回同一 family 中的新令牌
+    async fn refresh(&self, refresh_token: String) -> Result<RefreshGrant>;
+}
+
+#[derive(Clone)]
+pub struct TokenServiceImpl<T: RefreshTokenRepo>
+where
+    T: RefreshTokenRepo + Sync + Send,
+{
+    pub token_repo: T,
+}
+
+impl TokenServiceImpl<RefreshTokenRepoImpl> {
+    pub fn new(pool: Arc<PgPool>) -> Self {
+        TokenServiceImpl {
+            token_repo: RefreshTokenRepoImpl::new(pool),
+        }
+    }
+}
+
+// 256 位随机数的十六进制表示, 不包含任何用户信息
+pub(crate) fn generate_token() -> String {
+    let mut bytes = [0u8; 32];
+    OsRng.fill_bytes(&mut bytes);
+    hex::encode(bytes)
+}
+
+fn expires_at() -> DateTime<Utc> {
+    Utc::now() + Duration::days(TOKEN_TTL.refresh_token_ttl_days)
+}
+
+#[async_trait]
+impl<T> TokenService for TokenServiceImpl<T>
+where
+    T: RefreshTokenRepo + Sync + Send,
+{
+    async fn issue(&self, user_id: Uuid) -> Result<RefreshGrant> {
+        let token = generate_token();
+        let created = self
+            .token_repo
+            .create(CreateRefreshToken {
+                user_id,
+                family_id: Uuid::new_v4(),
+                token_hash: token_hash(&token),
+                expires_at: expires_at(),
+            })
+            .await?;
+        Ok(RefreshGrant {
+            user_id,
+            family_id: created.family_id,
+            token,
+        })
+    }
+
+    async fn refresh(&self, refresh_token: String) -> Result<RefreshGrant> {
+        let token = generate_token();
+        let rotated = self
+            .token_repo
+            .rotate(
+                &token_hash(&refresh_token),
+                token_hash(&token),
+                expires_at(),
+            )
+            .await?;
+        Ok(RefreshGrant {
+            user_id: rotated.user_id,
+            family_id: rotated.family_id,
+            token,
+        })
+    }
+}
+
+#[cfg(test)]
+pub mod tests {
+    use super::*;
+    use crate::{dao::token_repo::MockRefreshTokenRepo, models::token::RefreshToken};
+
+    #[tokio::test]
+    async fn test_token_service_refresh_rotates() {
+        let user_id = Uuid::new_v4();
+        let mut token_repo = MockRefreshTokenRepo::new();
+        token_repo
+            .expect_rotate()
+            .withf(|current, next, _| current == token_hash("old-token") && next.len() == 64)
+            .returning(move |_, next, expires_at| {
+                Ok(RefreshToken {
+                    user_id,
+                    token_hash: next,
+                    expires_at,
+                    ..Default::default()
+                })
+            });
+
+        let sut = TokenServiceImpl { token_repo };
+        let grant = sut.refresh("old-token".to_string()).await.unwrap();
+        assert_eq!(user_id, grant.user_id);
+        assert_eq!(64, grant.token.len());
+        assert_ne!(
+            grant.token,
+            sut.refresh("old-token".to_string()).await.unwrap().token
+        );
+    }
+}
@@ -0,0 +1,535 @@
+pub(crate) use crate::{
+    config::{
+        constants::{JWT_CLAIMS, JWT_KEYS},
+        env::TotpConfig,
+    },
+    dao::totp_repo::{TotpRepo, TotpRepoImpl},
+    dto::auth::MfaChallenge,
+    errors::{Error, Result},
+    models::{token::token_hash, totp::TotpEnrollment, user::User},
+};
+use axum::async_trait;
+use chrono::{Duration, Utc};
+use jsonwebtoken::Validation;
+use rand::{rngs::OsRng, RngCore};
+use ring::{constant_time, hmac};
+use serde::{Deserialize, Serialize};
+use sqlx::postgres::PgPool;
+use std::sync::Arc;
+use uuid::Uuid;
+
+/// 登录第二步令牌的 aud, 不能当作 access token 使用
+const AUDIENCE: &str = "mfa";
+/// RFC 6238 默认参数: 30 秒一个时间步, 6 位数字, HMAC-SHA1
+const STEP_SECS: i64 = 30;
+const DIGITS: usize = 6;
+/// 160 位密钥, 与 RFC 4226 推荐长度一致
+const SECRET_BYTES: usize = 20;
+const BASE32: &[u8; 32] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZ234567";
+
+pub type DynTotpService = Arc<dyn TotpService + Send + Sync>;
+
+#[cfg_attr(test, mockall::automock)]
+#[async_trait]
+pub trait TotpService {
+    /// 生成新密钥, 需 confirm 后才生效
+    async fn enroll(&self, user: &User) -> Result<TotpEnrollment>;
+    /// 校验首个验证码并启用两步验证, 返回恢复码原文
+    async fn confirm(&self, user_id: Uuid, code: &str) -> Result<Vec<String>>;
+    /// 用户已启用两步验证时签发登录第二步令牌
+    async fn challenge(&self, user_id: Uuid) -> Result<Option<MfaChallenge>>;
+    /// 校验第二步令牌与验证码(或恢复码), 返回用户 id;
+    /// 每个令牌只能通过一次, 尝试次数用完后作废
+    async fn verify(&self, mfa_token: &str, code: &str) -> Result<Uuid>;
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct MfaClaims {
+    iss: String,
+    aud: String,
+    sub: Uuid,
+    jti: Uuid,
+    iat: i64,
+    exp: i64,
+}
+
+#[derive(Clone)]
+pub struct TotpServiceImpl<T>
+where
+    T: TotpRepo + Sync + Send,
+{
+    pub totp_repo: T,
+    pub config: TotpConfig,
+}
+
+impl TotpServiceImpl<TotpRepoImpl> {
+    pub fn new(pool: Arc<PgPool>) -> Self {
+        TotpServiceImpl {
+            totp_repo: TotpRepoImpl::new(pool),
+            config: TotpConfig::from_env(),
+        }
+    }
+}
+
+impl<T> TotpServiceImpl<T>
+where
+    T: TotpRepo + Sync + Send,
+{
+    // 返回与 code 匹配的时间步, 允许前后 totp_skew_steps 个时间步的偏差
+    fn matching_step(&self, secret: &str, code: &str, now: i64) -> Option<i64> {
+        let secret = base32_decode(secret)?;
+        let current = now / STEP_SECS;
+        let skew = self.config.totp_skew_steps;
+        (current - skew..=current + skew).find(|&step| {
+            constant_time::verify_slices_are_equal(totp(&secret, step).as_bytes(), code.as_bytes())
+                .is_ok()
+        })
+    }
+
+    fn otpauth_uri(&self, secret: &str, email: &str) -> String {
+        let issuer = percent_encode(&self.config.totp_issuer);
+        format!(
+            "otpauth://totp/{issuer}:{account}?secret={secret}&issuer={issuer}\
+             &algorithm=SHA1&digits={DIGITS}&period={STEP_SECS}",
+            account = percent_encode(email),
+        )
+    }
+}
+
+#[async_trait]
+impl<T> TotpService for TotpServiceImpl<T>
+where
+    T: TotpRepo + Sync + Send,
+{
+    async fn enroll(&self, user: &User) -> Result<TotpEnrollment> {
+        let mut bytes = [0u8; SECRET_BYTES];
+        OsRng.fill_bytes(&mut bytes);
+        let secret = base32_encode(&bytes);
+        self.totp_repo.enroll(user.id, secret.clone()).await?;
+        Ok(TotpEnrollment {
+            otpauth_uri: self.otpauth_uri(&secret, &user.email),
+            secret,
+        })
+    }
+
+    async fn confirm(&self, user_id: Uuid, code: &str) -> Result<Vec<String>> {
+        let totp = match self.totp_repo.get(user_id).await? {
+            Some(totp) if totp.confirmed_at.is_none() => totp,
+            Some(_) => return Err(Error::MfaAlreadyEnabled),
+            None => return Err(Error::InvalidMfaCode),
+        };
+        let step = self
+            .matching_step(&totp.secret, code.trim(), Utc::now().timestamp())
+            .ok_or(Error::InvalidMfaCode)?;
+
+        let codes: Vec<String> = (0..self.config.totp_recovery_codes)
+            .map(|_| recovery_code())
+            .collect();
+        let hashes = codes.iter().map(|code| token_hash(code)).collect();
+        if !self.totp_repo.confirm(user_id, step, hashes).await? {
+            return Err(Error::MfaAlreadyEnabled);
+        }
+        Ok(codes)
+    }
+
+    async fn challenge(&self, user_id: Uuid) -> Result<Option<MfaChallenge>> {
+        match self.totp_repo.get(user_id).await? {
+            Some(totp) if totp.confirmed_at.is_some() => {}
+            _ => return Ok(None),
+        }
+        let jti = Uuid::new_v4();
+        self.totp_repo.start_challenge(user_id, jti).await?;
+        let iat = Utc::now();
+        let exp = iat + Duration::seconds(self.config.mfa_token_ttl_secs);
+        let mfa_token = JWT_KEYS.encode(&MfaClaims {
+            iss: JWT_CLAIMS.jwt_issuer.clone(),
+            aud: AUDIENCE.to_string(),
+            sub: user_id,
+            jti,
+            iat: iat.timestamp(),
+            exp: exp.timestamp(),
+        })?;
+        Ok(Some(MfaChallenge {
+            mfa_required: true,
+            mfa_token,
+            expires_in: self.config.mfa_token_ttl_secs,
+        }))
+    }
+
+    async fn verify(&self, mfa_token: &str, code: &str) -> Result<Uuid> {
+        let mut validation = Validation::default();
+        validation.leeway = JWT_CLAIMS.jwt_leeway_secs;
+        validation.set_issuer(&[&JWT_CLAIMS.jwt_issuer]);
+        validation.set_audience(&[AUDIENCE]);
+        let claims: MfaClaims = JWT_KEYS
+            .decode(mfa_token, validation)
+            .map_err(|_| Error::InvalidMfaToken)?;
+        let user_id = claims.sub;
+
+        let totp = self
+            .totp_repo
+            .get(user_id)
+            .await?
+            .filter(|totp| totp.confirmed_at.is_some())
+            .ok_or(Error::InvalidMfaToken)?;
+        if !self
+            .totp_repo
+            .attempt_challenge(user_id, claims.jti, self.config.mfa_max_attempts)
+            .await?
+        {
+            return Err(Error::InvalidMfaToken);
+        }
+
+        let code = code.trim();
+        let accepted = if code.len() == DIGITS && code.bytes().all(|b| b.is_ascii_digit()) {
+            // 时间步只能前进, 同一验证码在有效期内不能重复使用
+            match self.matching_step(&totp.secret, code, Utc::now().timestamp()) {
+                Some(step) => self.totp_repo.use_step(user_id, step).await?,
+                None => false,
+            }
+        } else {
+            let normalized = normalize_recovery_code(code);
+            self.totp_repo
+                .use_recovery_code(user_id, &token_hash(&normalized))
+                .await?
+        };
+        if accepted {
+            self.totp_repo.finish_challenge(user_id, claims.jti).await?;
+            Ok(user_id)
+        } else {
+            Err(Error::InvalidMfaCode)
+        }
+    }
+}
+
+/// RFC 4226 HOTP, 以时间步为计数器
+fn totp(secret: &[u8], step: i64) -> String {
+    let key = hmac::Key::new(hmac::HMAC_SHA1_FOR_LEGACY_USE_ONLY, secret);
+    let digest = hmac::sign(&key, &(step as u64).to_be_bytes());
+    let digest = digest.as_ref();
+    let offset = (digest[digest.len() - 1] & 0x0f) as usize;
+    let value = u32::from_be_bytes([
+        digest[offset] & 0x7f,
+        digest[offset + 1],
+        digest[offset + 2],
+        digest[offset + 3],
+    ]);
+    format!(
+        "{:0width$}",
+        value % 10u32.pow(DIGITS as u32),
+        width = DIGITS
+    )
+}
+
+/// 恢复码形如 abcd-efgh, 校验时忽略大小写与分隔符
+fn recovery_code() -> String {
+    let mut bytes = [0u8; 5];
+    OsRng.fill_bytes(&mut bytes);
+    let code = base32_encode(&bytes).to_lowercase();
+    format!("{}-{}", &code[..4], &code[4..])
+}
+
+fn normalize_recovery_code(code: &str) -> String {
+    let code: String = code
+        .chars()
+        .filter(|c| c.is_ascii_alphanumeric())
+        .collect::<String>()
+        .to_lowercase();
+    match code.len() {
+        8 => format!("{}-{}", &code[..4], &code[4..]),
+        _ => code,
+    }
+}
+
+// RFC 4648 base32, 不带填充
+fn base32_encode(bytes: &[u8]) -> String {
+    let mut out = String::with_capacity((bytes.len() * 8).div_ceil(5));
+    let (mut buffer, mut bits) = (0u32, 0);
+    for &byte in bytes {
+        buffer = (buffer << 8) | byte as u32;
+        bits += 8;
+        while bits >= 5 {
+            bits -= 5;
+            out.push(BASE32[((buffer >> bits) & 0x1f) as usize] as char);
+        }
+    }
+    if bits > 0 {
+        out.push(BASE32[((buffer << (5 - bits)) & 0x1f) as usize] as char);
+    }
+    out
+}
+
+fn base32_decode(text: &str) -> Option<Vec<u8>> {
+    let mut out = Vec::with_capacity(text.len() * 5 / 8);
+    let (mut buffer, mut bits) = (0u32, 0);
+    for c in text.trim_end_matches('=').bytes() {
+        let value = BASE32.iter().position(|&b| b == c.to_ascii_uppercase())? as u32;
+        buffer = (buffer << 5) | value;
+        bits += 5;
+        if bits >= 8 {
+            bits -= 8;
+            out.push((buffer >> bits) as u8);
+        }
+    }
+    Some(out)
+}
+
+// otpauth URI 的 label 与参数只保留 RFC 3986 非保留字符
+fn percent_encode(text: &str) -> String {
+    text.bytes()
+        .map(|b| match b {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'.' | b'_' | b'~' => {
+                (b as char).to_string()
+            }
+            _ => format!("%{:02X}", b),
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{dao::totp_repo::MockTotpRepo, models::totp::UserTotp};
+    use mockall::predicate::*;
+
+    fn config() -> TotpConfig {
+        TotpConfig {
+            totp_issuer: "Cash Book".to_string(),
+            totp_skew_steps: 1,
+            totp_recovery_codes: 3,
+            mfa_token_ttl_secs: 300,
+            mfa_max_attempts: 5,
+        }
+    }
+
+    fn user_totp(user_id: Uuid, secret: &str, confirmed: bool) -> UserTotp {
+        UserTotp {
+            user_id,
+            secret: secret.to_string(),
+            confirmed_at: confirmed.then(Utc::now),
+            last_step: None,
+            challenge_attempts: 0,
+            created_at: Utc::now(),
+        }
+    }
+
+    fn current_code(secret: &str) -> String {
+        totp(
+            &base32_decode(secret).unwrap(),
+            Utc::now().timestamp() / STEP_SECS,
+        )
+    }
+
+    #[test]
+    fn test_totp_rfc6238_vectors() {
+        // RFC 6238 附录 B 的 SHA1 测试向量取后 6 位
+        let secret = b"12345678901234567890";
+        for (time, expected) in [
+            (59, "287082"),
+            (1111111109, "081804"),
+            (1234567890, "005924"),
+            (20000000000, "353130"),
+        ] {
+            assert_eq!(expected, totp(secret, time / STEP_SECS));
+        }
+    }
+
+    #[test]
+    fn test_base32_round_trip() {
+        assert_eq!("MZXW6YTBOI", base32_encode(b"foobar"));
+        assert_eq!(
+            b"foobar".to_vec(),
+            base32_decode("mzxw6ytboi======").unwrap()
+        );
+        assert!(base32_decode("not base32!").is_none());
+    }
+
+    #[tokio::test]
+    async fn test_enroll_returns_otpauth_uri() {
+        let user = User {
+            email: "alice+2fa@example.com".to_string(),
+            ..Default::default()
+        };
+        let mut totp_repo = MockTotpRepo::new();
+        totp_repo
+            .expect_enroll()
+            .withf(|_, secret| secret.len() == 32)
+            .returning(|user_id, secret| Ok(user_totp(user_id, &secret, false)));
+        let sut = TotpServiceImpl {
+            totp_repo,
+            config: config(),
+        };
+
+        let enrollment = sut.enroll(&user).await.unwrap();
+        assert_eq!(
+            format!(
+                "otpauth://totp/Cash%20Book:alice%2B2fa%40example.com?secret={}\
+                 &issuer=Cash%20Book&algorithm=SHA1&digits=6&period=30",
+                enrollment.secret
+            ),
+            enrollment.otpauth_uri
+        );
+    }
+
+    #[tokio::test]
+    async fn test_confirm_issues_recovery_codes() {
+        let user_id = Uuid::new_v4();
+        let secret = base32_encode(b"12345678901234567890");
+        let code = current_code(&secret);
+        let mut totp_repo = MockTotpRepo::new();
+        totp_repo
+            .expect_get()
+            .returning(move |user_id| Ok(Some(user_totp(user_id, &secret, false))));
+        totp_repo
+            .expect_confirm()
+            .withf(move |id, _, hashes| *id == user_id && hashes.len() == 3)
+            .times(1)
+            .returning(|_, _, _| Ok(true));
+        let sut = TotpServiceImpl {
+            totp_repo,
+            config: config(),
+        };
+
+        assert!(matches!(
+            sut.confirm(user_id, "000000x").await,
+            Err(Error::InvalidMfaCode)
+        ));
+        let codes = sut.confirm(user_id, &code).await.unwrap();
+        assert_eq!(3, codes.len());
+        assert!(codes.iter().all(|code| code.len() == 9));
+    }
+
+    #[tokio::test]
+    async fn test_challenge_only_when_enabled() {
+        let (enabled, pending) = (Uuid::new_v4(), Uuid::new_v4());
+        let mut totp_repo = MockTotpRepo::new();
+        totp_repo
+            .expect_get()
+            .returning(move |user_id| Ok(Some(user_totp(user_id, "SECRET", user_id == enabled))));
+        totp_repo
+            .expect_start_challenge()
+            .with(eq(enabled), always())
+            .times(1)
+            .returning(|_, _| Ok(()));
+        let sut = TotpServiceImpl {
+            totp_repo,
+            config: config(),
+        };
+
+        assert!(sut.challenge(pending).await.unwrap().is_none());
+        let challenge = sut.challenge(enabled).await.unwrap().unwrap();
+        assert!(challenge.mfa_required);
+        assert_eq!(300, challenge.expires_in);
+    }
+
+    #[tokio::test]
+    async fn test_verify_totp_and_recovery_code() {
+        let user_id = Uuid::new_v4();
+        let secret = base32_encode(b"12345678901234567890");
+        let code = current_code(&secret);
+        let mut totp_repo = MockTotpRepo::new();
+        totp_repo
+            .expect_get()
+            .returning(move |user_id| Ok(Some(user_totp(user_id, &secret, true))));
+        totp_repo
+            .expect_start_challenge()
+            .times(2)
+            .returning(|_, _| Ok(()));
+        totp_repo
+            .expect_attempt_challenge()
+            .with(eq(user_id), always(), eq(5))
+            .times(2)
+            .returning(|_, _, _| Ok(true));
+        totp_repo
+            .expect_finish_challenge()
+            .times(2)
+            .returning(|_, _| Ok(()));
+        totp_repo
+            .expect_use_step()
+            .with(eq(user_id), always())
+            .times(1)
+            .returning(|_, _| Ok(true));
+        totp_repo
+            .expect_use_recovery_code()
+            .withf(move |id, hash| *id == user_id && hash == token_hash("abcd-efgh"))
+            .times(1)
+            .returning(|_, _| Ok(true));
+        let sut = TotpServiceImpl {
+            totp_repo,
+            config: config(),
+        };
+
+        let token = sut.challenge(user_id).await.unwrap().unwrap().mfa_token;
+        assert_eq!(user_id, sut.verify(&token, &code).await.unwrap());
+        let token = sut.challenge(user_id).await.unwrap().unwrap().mfa_token;
+        assert_eq!(user_id, sut.verify(&token, "ABCD EFGH").await.unwrap());
+    }
+
+    #[tokio::test]
+    async fn test_verify_locks_challenge_after_max_attempts() {
+        let user_id = Uuid::new_v4();
+        let secret = base32_encode(b"12345678901234567890");
+        let code = current_code(&secret);
+        let attempts = Arc::new(std::sync::atomic::AtomicI32::new(0));
+        let mut totp_repo = MockTotpRepo::new();
+        totp_repo
+            .expect_get()
+            .returning(move |user_id| Ok(Some(user_totp(user_id, &secret, true))));
+        totp_repo.expect_start_challenge().returning(|_, _| Ok(()));
+        let counter = attempts.clone();
+        totp_repo
+            .expect_attempt_challenge()
+            .returning(move |_, _, max_attempts| {
+                let used = counter.fetch_add(1, std::sync::atomic::Ordering::SeqCst);
+                Ok(used < max_attempts)
+            });
+        totp_repo.expect_use_step().never();
+        totp_repo
+            .expect_use_recovery_code()
+            .times(5)
+            .returning(|_, _| Ok(false));
+        let sut = TotpServiceImpl {
+            totp_repo,
+            config: config(),
+        };
+        let token = sut.challenge(user_id).await.unwrap().unwrap().mfa_token;
+
+        for _ in 0..5 {
+            assert!(matches!(
+                sut.verify(&token, "wrong-code").await,
+                Err(Error::InvalidMfaCode)
+            ));
+        }
+        // 次数用完后即使验证码正确也不能通过
+        assert!(matches!(
+            sut.verify(&token, &code).await,
+            Err(Error::InvalidMfaToken)
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_verify_rejects_access_token() {
+        let mut totp_repo = MockTotpRepo::new();
+        totp_repo.expect_get().never();
+        let sut = TotpServiceImpl {
+            totp_repo,
+            config: config(),
+        };
+
+        // aud 不同的令牌不能用于登录第二步
+        let token = JWT_KEYS
+            .encode(&MfaClaims {
+                iss: JWT_CLAIMS.jwt_issuer.clone(),
+                aud: JWT_CLAIMS.jwt_audience.clone(),
+                sub: Uuid::new_v4(),
+                jti: Uuid::new_v4(),
+                iat: Utc::now().timestamp(),
+                exp: Utc::now().timestamp() + 60,
+            })
+            .unwrap();
+        assert!(matches!(
+            sut.verify(&token, "123456").await,
+            Err(Error::InvalidMfaToken)
+        ));
+    }
+}
@@ -1,3 +1,4 @@
+use super::password::{hash_password, Argon2Hasher, DynPasswordHasher};
 pub(crate) use crate::{
     dao::user_repo::{UserRepo, UserRepoImpl},
     dto::user::{ListUserInput, RegisterInput, UpdateUserInput},
@@ -19,6 +20,8 @@ pub trait UserService {
     async fn delete(&self, id: Uuid) -> Result<User>;
     async fn update(&self, id: Uuid, opt: UpdateUserInput) -> Result<User>;
     async fn list(&self, input: ListUserInput) -> Result<Vec<User>>;
+    /// 将仍以明文保存的密码替换为哈希, 返回处理的用户数
+    async fn rehash_legacy_passwords(&self) -> Result<usize>;
 }
 
 #[derive(Clone)]
@@ -27,12 +30,14 @@ where
     T: UserRepo + Sync + Send,
 {
     pub user_repo: T,
+    pub hasher: DynPasswordHasher,
 }
 
 impl UserServiceImpl<UserRepoImpl> {
     pub fn new(pool: Arc<PgPool>) -> Self {
         UserServiceImpl {
             user_repo: UserRepoImpl::new(pool),
+            hasher: Arc::new(Argon2Hasher::from_env()),
         }
     }
 }
@@ -43,17 +48,16 @@ where
     T: UserRepo + Sync + Send,
 {
     async fn create(&self, input: RegisterInput) -> Result<User> {
+        if self.user_repo.get_by_email(&input.email).await.is_ok() {
+            return Err(Error::DuplicateUserEmail(input.email));
+        }
+
         let user = CreateUser {
             name: input.name,
             email: input.email,
-            password: input.password,
+            password: hash_password(&self.hasher, input.password).await?,
         };
 
-        let email = user.email.clone();
-        if self.user_repo.get_by_email(&email).await.is_ok() {
-            return Err(Error::DuplicateUserEmail(email));
-        }
-
         Ok(self.user_repo.create(user).await?)
     }
 
@@ -77,7 +81,7 @@ where
         }
 
         if let Some(password) = input.password {
-            origin_user.password = password;
+            origin_user.password = hash_password(&self.hasher, password).await?;
         }
         Ok(self.user_repo.update(&origin_user).await?)
     }
@@ -91,12 +95,30 @@ where
         };
         Ok(self.user_repo.list(opt).await?)
     }
+
+    async fn rehash_legacy_passwords(&self) -> Result<usize> {
+        let mut rehashed = 0;
+        for user in self.user_repo.legacy_passwords().await? {
+            let hashed = hash_password(&self.hasher, user.password.clone()).await?;
+            // 期间用户已修改密码时跳过
+            if self
+                .user_repo
+                .update_password(user.id, &user.password, &hashed)
+                .await?
+            {
+                rehashed += 1;
+            }
+        }
+        Ok(rehashed)
+    }
 }
 
 #[cfg(test)]
 pub mod tests {
     use super::*;
-    use crate::{dao::user_repo::MockUserRepo, models::user::User};
+    use crate::{
+        dao::user_repo::MockUserRepo, models::user::User, services::password::tests::hasher,
+    };
     use chrono::Utc;
     use mockall::predicate::*;
     use uuid::Uuid;
@@ -107,7 +129,7 @@ pub mod tests {
 
         user_repo
             .expect_create()
-            .with(always())
+            .withf(|param| param.password.starts_with("$argon2id$"))
             .times(1)
             .returning(|param| {
                 Ok(User {
@@ -115,6 +137,7 @@ pub mod tests {
                     name: param.name,
                     password: param.password,
                     email: param.email,
+                    verified_at: None,
                     created_at: Utc::now(),
                     updated_at: Utc::now(),
                 })
@@ -124,7 +147,10 @@ pub mod tests {
             .expect_get_by_email()
             .returning(|_| Err(Error::new_empty_fields_error("mock test".to_string())));
 
-        let sut = UserServiceImpl { user_repo };
+        let sut = UserServiceImpl {
+            user_repo,
+            hasher: Arc::new(hasher(64)),
+        };
         let mock_create_result = sut
             .create(RegisterInput {
                 name: "f1".to_string(),
@@ -147,7 +173,10 @@ pub mod tests {
             })
         });
 
-        let sut = UserServiceImpl { user_repo };
+        let sut = UserServiceImpl {
+            user_repo,
+            hasher: Arc::new(hasher(64)),
+        };
 
         let mock_get_result = sut.get(get_param).await;
         assert!(mock_get_result.is_ok());
@@ -166,7 +195,10 @@ pub mod tests {
                     ..Default::default()
                 })
             });
-        let sut = UserServiceImpl { user_repo };
+        let sut = UserServiceImpl {
+            user_repo,
+            hasher: Arc::new(hasher(64)),
+        };
         let mock_delete_result = sut.delete(delete_param).await;
         assert!(mock_delete_result.is_ok());
     }
@@ -178,7 +210,10 @@ pub mod tests {
             .expect_list()
             .with(always())
             .returning(|_x| Ok(Vec::new()));
-        let sut = UserServiceImpl { user_repo };
+        let sut = UserServiceImpl {
+            user_repo,
+            hasher: Arc::new(hasher(64)),
+        };
         let mock_list_result = sut.list(ListUserInput::default()).await;
         assert!(mock_list_result.is_ok());
     }
@@ -199,7 +234,10 @@ pub mod tests {
             .with(always())
             .returning(|user| Ok(user.clone()));
 
-        let sut = UserServiceImpl { user_repo };
+        let sut = UserServiceImpl {
+            user_repo,
+            hasher: Arc::new(hasher(64)),
+        };
 
         let opt = UpdateUserInput {
             name: Some("fk".to_string()),
@@ -222,4 +260,28 @@ pub mod tests {
         let result = user_repo.update(&User::default()).await;
         assert!(result.is_ok());
     }
+
+    #[tokio::test]
+    async fn test_user_service_rehash_legacy_passwords() {
+        let mut user_repo = MockUserRepo::new();
+        user_repo.expect_legacy_passwords().returning(|| {
+            Ok(vec![User {
+                password: "plain-secret".to_string(),
+                ..Default::default()
+            }])
+        });
+        user_repo
+            .expect_update_password()
+            .withf(|_, current, hashed| {
+                current == "plain-secret" && hashed.starts_with("$argon2id$")
+            })
+            .times(1)
+            .returning(|_, _, _| Ok(true));
+
+        let sut = UserServiceImpl {
+            user_repo,
+            hasher: Arc::new(hasher(64)),
+        };
+        assert_eq!(1, sut.rehash_legacy_passwords().await.unwrap());
+    }
 }
@@ -0,0 +1,280 @@
+use super::mailer::{self, DynMailer, Email};
+pub(crate) use crate::{
+    config::{
+        constants::{JWT_CLAIMS, JWT_KEYS},
+        env::VerificationConfig,
+    },
+    dao::user_repo::{UserRepo, UserRepoImpl},
+    errors::{Error, Result},
+    models::user::User,
+};
+use axum::async_trait;
+use chrono::{Duration, Utc};
+use jsonwebtoken::Validation;
+use serde::{Deserialize, Serialize};
+use sqlx::postgres::PgPool;
+use std::sync::Arc;
+use uuid::Uuid;
+
+/// 验证令牌的 aud, 与 access token 区分, 两者不能互换使用
+const AUDIENCE: &str = "verify-email";
+
+pub type DynVerificationService = Arc<dyn VerificationService + Send + Sync>;
+
+#[cfg_attr(test, mockall::automock)]
+#[async_trait]
+pub trait VerificationService {
+    /// 在后台向尚未验证的用户发送验证邮件, 发送失败只记录日志
+    async fn send(&self, user: &User) -> Result<()>;
+    /// 按邮箱在后台重新发送, 邮箱未注册或已验证时静默忽略, 发送失败只记录日志
+    async fn resend(&self, email: &str) -> Result<()>;
+    /// 校验令牌并将邮箱标记为已验证
+    async fn verify(&self, token: &str) -> Result<()>;
+}
+
+/// 令牌绑定注册邮箱, 验证成功或修改邮箱后即失效
+#[derive(Debug, Serialize, Deserialize)]
+struct VerificationClaims {
+    iss: String,
+    aud: String,
+    sub: Uuid,
+    email: String,
+    iat: i64,
+    exp: i64,
+}
+
+#[derive(Clone)]
+pub struct VerificationServiceImpl<T>
+where
+    T: UserRepo + Sync + Send,
+{
+    pub user_repo: T,
+    pub mailer: DynMailer,
+    pub config: VerificationConfig,
+}
+
+impl VerificationServiceImpl<UserRepoImpl> {
+    pub fn new(pool: Arc<PgPool>) -> Self {
+        VerificationServiceImpl {
+            user_repo: UserRepoImpl::new(pool),
+            mailer: mailer::from_env(),
+            config: VerificationConfig::from_env(),
+        }
+    }
+}
+
+impl<T> VerificationServiceImpl<T>
+where
+    T: UserRepo + Sync + Send,
+{
+    fn sign(&self, user: &User) -> Result<String> {
+        let iat = Utc::now();
+        let exp = iat + Duration::hours(self.config.email_verification_ttl_hours);
+        Ok(JWT_KEYS.encode(&VerificationClaims {
+            iss: JWT_CLAIMS.jwt_issuer.clone(),
+            aud: AUDIENCE.to_string(),
+            sub: user.id,
+            email: user.email.clone(),
+            iat: iat.timestamp(),
+            exp: exp.timestamp(),
+        })?)
+    }
+
+    fn link(&self, token: &str) -> String {
+        let url = &self.config.email_verification_url;
+        let separator = if url.contains('?') { '&' } else { '?' };
+        format!("{url}{separator}token={token}")
+    }
+
+    fn email(&self, user: &User) -> Result<Email> {
+        let link = self.link(&self.sign(user)?);
+        let body = format!(
+            "Hi {},\n\nPlease confirm your email address for cashbook by opening the link below \
+             within {} hours:\n\n{}\n\nIf you did not sign up, you can ignore this email.\n",
+            user.name, self.config.email_verification_ttl_hours, link
+        );
+        Ok(Email {
+            to: user.email.clone(),
+            subject: "Confirm your email address".to_string(),
+            body,
+        })
+    }
+}
+
+#[async_trait]
+impl<T> VerificationService for VerificationServiceImpl<T>
+where
+    T: UserRepo + Sync + Send,
+{
+    async fn send(&self, user: &User) -> Result<()> {
+        if user.verified_at.is_some() {
+            return Ok(());
+        }
+        // 不等待 SMTP 往返, 邮件服务器缓慢时不拖慢注册与修改邮箱
+        mailer::spawn_send(&self.mailer, self.email(user)?, user.id, "verification");
+        Ok(())
+    }
+
+    async fn resend(&self, email: &str) -> Result<()> {
+        match self.user_repo.get_by_email(email).await {
+            // 与 forgot 相同在后台发送, 响应时间与邮箱未注册时一致
+            Ok(user) if user.verified_at.is_none() => {
+                mailer::spawn_send(&self.mailer, self.email(&user)?, user.id, "verification");
+                Ok(())
+            }
+            Ok(_) | Err(Error::DataStore(sqlx::Error::RowNotFound)) => Ok(()),
+            Err(err) => Err(err),
+        }
+    }
+
+    async fn verify(&self, token: &str) -> Result<()> {
+        let mut validation = Validation::default();
+        validation.leeway = JWT_CLAIMS.jwt_leeway_secs;
+        validation.set_issuer(&[&JWT_CLAIMS.jwt_issuer]);
+        validation.set_audience(&[AUDIENCE]);
+        let claims: VerificationClaims = JWT_KEYS
+            .decode(token, validation)
+            .map_err(|_| Error::InvalidVerificationToken)?;
+
+        if self
+            .user_repo
+            .mark_verified(claims.sub, &claims.email)
+            .await?
+        {
+            Ok(())
+        } else {
+            Err(Error::InvalidVerificationToken)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{dao::user_repo::MockUserRepo, services::mailer::tests::MemoryMailer};
+    use mockall::predicate::*;
+
+    fn sut(
+        user_repo: MockUserRepo,
+        mailer: Arc<MemoryMailer>,
+    ) -> VerificationServiceImpl<MockUserRepo> {
+        VerificationServiceImpl {
+            user_repo,
+            mailer,
+            config: VerificationConfig {
+                require_email_verification: true,
+                email_verification_ttl_hours: 24,
+                email_verification_url: "https://cashbook.test/verify".to_string(),
+            },
+        }
+    }
+
+    fn sent_token(mailer: &MemoryMailer) -> String {
+        let sent = mailer.sent.lock().unwrap();
+        let body = &sent.last().unwrap().body;
+        let start = body.find("token=").unwrap() + "token=".len();
+        body[start..].split_whitespace().next().unwrap().to_string()
+    }
+
+    #[tokio::test]
+    async fn test_verify_email_with_sent_token() {
+        std::env::set_var("JWT_SECRET", "example_secret_key");
+        let user = User {
+            email: "alice@example.com".to_string(),
+            ..Default::default()
+        };
+        let user_id = user.id;
+        let mut user_repo = MockUserRepo::new();
+        user_repo
+            .expect_mark_verified()
+            .with(eq(user_id), eq("alice@example.com"))
+            .times(1)
+            .returning(|_, _| Ok(true));
+        let mailer = Arc::new(MemoryMailer::default());
+        let sut = sut(user_repo, mailer.clone());
+
+        sut.send(&user).await.unwrap();
+        assert_eq!("alice@example.com", mailer.wait_sent(1).await[0].to);
+        sut.verify(&sent_token(&mailer)).await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_verify_email_token_is_single_use() {
+        std::env::set_var("JWT_SECRET", "example_secret_key");
+        let user = User::default();
+        let mut user_repo = MockUserRepo::new();
+        user_repo.expect_mark_verified().returning(|_, _| Ok(false));
+        let mailer = Arc::new(MemoryMailer::default());
+        let sut = sut(user_repo, mailer.clone());
+
+        sut.send(&user).await.unwrap();
+        mailer.wait_sent(1).await;
+        let actual = sut.verify(&sent_token(&mailer)).await;
+        assert!(matches!(actual, Err(Error::InvalidVerificationToken)));
+    }
+
+    #[tokio::test]
+    async fn test_verify_email_rejects_foreign_audience() {
+        std::env::set_var("JWT_SECRET", "example_secret_key");
+        let mut user_repo = MockUserRepo::new();
+        user_repo.expect_mark_verified().never();
+        let sut = sut(user_repo, Arc::new(MemoryMailer::default()));
+
+        // 与验证令牌内容相同, 但 aud 为 access token 的受众
+        let token = JWT_KEYS
+            .encode(&VerificationClaims {
+                iss: JWT_CLAIMS.jwt_issuer.clone(),
+                aud: JWT_CLAIMS.jwt_audience.clone(),
+                sub: Uuid::new_v4(),
+                email: "alice@example.com".to_string(),
+                iat: Utc::now().timestamp(),
+                exp: (Utc::now() + Duration::hours(1)).timestamp(),
+            })
+            .unwrap();
+        let actual = sut.verify(&token).await;
+        assert!(matches!(actual, Err(Error::InvalidVerificationToken)));
+    }
+
+    #[tokio::test]
+    async fn test_resend_skips_verified_and_unknown_users() {
+        let mut user_repo = MockUserRepo::new();
+        user_repo
+            .expect_get_by_email()
+            .with(eq("verified@example.com"))
+            .returning(|email| {
+                Ok(User {
+                    email: email.to_string(),
+                    verified_at: Some(Utc::now()),
+                    ..Default::default()
+                })
+            });
+        user_repo
+            .expect_get_by_email()
+            .with(eq("unknown@example.com"))
+            .returning(|_| Err(Error::DataStore(sqlx::Error::RowNotFound)));
+        let mailer = Arc::new(MemoryMailer::default());
+        let sut = sut(user_repo, mailer.clone());
+
+        sut.resend("verified@example.com").await.unwrap();
+        sut.resend("unknown@example.com").await.unwrap();
+        assert!(mailer.wait_sent(1).await.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_resend_sends_in_background() {
+        std::env::set_var("JWT_SECRET", "example_secret_key");
+        let mut user_repo = MockUserRepo::new();
+        user_repo.expect_get_by_email().returning(|email| {
+            Ok(User {
+                email: email.to_string(),
+                ..Default::default()
+            })
+        });
+        let mailer = Arc::new(MemoryMailer::default());
+        let sut = sut(user_repo, mailer.clone());
+
+        sut.resend("alice@example.com").await.unwrap();
+        let sent = mailer.wait_sent(1).await;
+        assert_eq!("alice@example.com", sent[0].to);
+    }
+}
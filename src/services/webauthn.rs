@@ -0,0 +1,365 @@
+use crate::errors::{Error, Result};
+use ciborium::value::Value;
+use ring::signature::{self, RsaPublicKeyComponents, UnparsedPublicKey};
+use serde::Deserialize;
+use sha2::{Digest, Sha256};
+
+/// 用户在场
+pub const FLAG_UP: u8 = 0x01;
+/// 用户已通过验证(PIN、生物识别等)
+pub const FLAG_UV: u8 = 0x04;
+/// 包含 attested credential data
+const FLAG_AT: u8 = 0x40;
+
+/// 支持的 COSE 算法: ES256, EdDSA, RS256
+pub const ALGORITHMS: [i64; 3] = [-7, -8, -257];
+
+pub fn b64url_encode(bytes: &[u8]) -> String {
+    base64::encode_config(bytes, base64::URL_SAFE_NO_PAD)
+}
+
+/// 兼容带填充的 base64url
+pub fn b64url_decode(text: &str) -> Result<Vec<u8>> {
+    base64::decode_config(text.trim_end_matches('='), base64::URL_SAFE_NO_PAD)
+        .map_err(|_| invalid("malformed base64url"))
+}
+
+fn invalid(reason: &str) -> Error {
+    Error::InvalidPasskey(reason.to_string())
+}
+
+#[derive(Debug, Deserialize)]
+struct ClientData {
+    #[serde(rename = "type")]
+    kind: String,
+    challenge: String,
+    origin: String,
+}
+
+/// 校验 clientDataJSON 的类型、挑战值与来源
+pub fn verify_client_data(raw: &[u8], kind: &str, challenge: &str, origin: &str) -> Result<()> {
+    let data: ClientData =
+        serde_json::from_slice(raw).map_err(|_| invalid("malformed clientDataJSON"))?;
+    if data.kind != kind {
+        return Err(invalid("unexpected ceremony type"));
+    }
+    if data.challenge.trim_end_matches('=') != challenge {
+        return Err(invalid("challenge mismatch"));
+    }
+    if data.origin != origin {
+        return Err(invalid("origin mismatch"));
+    }
+    Ok(())
+}
+
+/// 注册时认证器返回的凭据
+#[derive(Debug)]
+pub struct AttestedCredential {
+    pub credential_id: Vec<u8>,
+    /// COSE_Key 原文
+    pub public_key: Vec<u8>,
+}
+
+#[derive(Debug)]
+pub struct AuthenticatorData {
+    pub sign_count: u32,
+    pub attested: Option<AttestedCredential>,
+}
+
+impl AuthenticatorData {
+    /// 解析并校验 rpIdHash 与用户在场标志, require_uv 时还要求用户已验证
+    pub fn parse(bytes: &[u8], rp_id: &str, require_uv: bool) -> Result<Self> {
+        if bytes.len() < 37 {
+            return Err(invalid("authenticator data too short"));
+        }
+        if bytes[..32] != Sha256::digest(rp_id.as_bytes())[..] {
+            return Err(invalid("rp id mismatch"));
+        }
+        let flags = bytes[32];
+        if flags & FLAG_UP == 0 {
+            return Err(invalid("user not present"));
+        }
+        if require_uv && flags & FLAG_UV == 0 {
+            return Err(invalid("user not verified"));
+        }
+        let sign_count = u32::from_be_bytes([bytes[33], bytes[34], bytes[35], bytes[36]]);
+
+        let attested = if flags & FLAG_AT != 0 {
+            // aaguid(16) + credentialIdLength(2) + credentialId + credentialPublicKey
+            let rest = bytes.get(37 + 16..).ok_or_else(|| invalid("truncated"))?;
+            let len = rest
+                .get(..2)
+                .map(|len| u16::from_be_bytes([len[0], len[1]]) as usize)
+                .ok_or_else(|| invalid("truncated"))?;
+            let credential_id = rest.get(2..2 + len).ok_or_else(|| invalid("truncated"))?;
+            let key = &rest[2 + len..];
+            // 公钥之后可能还有扩展数据, 按 CBOR 读取一个完整的值来确定长度
+            let mut reader = key;
+            ciborium::de::from_reader::<Value, _>(&mut reader)
+                .map_err(|_| invalid("malformed credential public key"))?;
+            let public_key = key[..key.len() - reader.len()].to_vec();
+            Some(AttestedCredential {
+                credential_id: credential_id.to_vec(),
+                public_key,
+            })
+        } else {
+            None
+        };
+
+        Ok(AuthenticatorData {
+            sign_count,
+            attested,
+        })
+    }
+}
+
+/// 取出 attestationObject 中的 authData; 注册时请求 "none", 不校验 attStmt
+pub fn attestation_auth_data(attestation_object: &[u8]) -> Result<Vec<u8>> {
+    let value: Value = ciborium::de::from_reader(attestation_object)
+        .map_err(|_| invalid("malformed attestation object"))?;
+    let entries = match value {
+        Value::Map(entries) => entries,
+        _ => return Err(invalid("malformed attestation object")),
+    };
+    entries
+        .into_iter()
+        .find_map(|(key, value)| match (key, value) {
+            (Value::Text(key), Value::Bytes(bytes)) if key == "authData" => Some(bytes),
+            _ => None,
+        })
+        .ok_or_else(|| invalid("missing authData"))
+}
+
+/// COSE_Key 编码的凭据公钥
+#[derive(Debug, PartialEq, Eq)]
+pub enum CoseKey {
+    Es256 { x: Vec<u8>, y: Vec<u8> },
+    Ed25519 { x: Vec<u8> },
+    Rs256 { n: Vec<u8>, e: Vec<u8> },
+}
+
+impl CoseKey {
+    pub fn parse(bytes: &[u8]) -> Result<Self> {
+        let value: Value =
+            ciborium::de::from_reader(bytes).map_err(|_| invalid("malformed COSE key"))?;
+        let entries = match value {
+            Value::Map(entries) => entries,
+            _ => return Err(invalid("malformed COSE key")),
+        };
+        let int = |label: i128| {
+            entries.iter().find_map(|(key, value)| match (key, value) {
+                (Value::Integer(key), Value::Integer(value)) if i128::from(*key) == label => {
+                    Some(i128::from(*value))
+                }
+                _ => None,
+            })
+        };
+        let bytes = |label: i128| {
+            entries
+                .iter()
+                .find_map(|(key, value)| match (key, value) {
+                    (Value::Integer(key), Value::Bytes(value)) if i128::from(*key) == label => {
+                        Some(value.clone())
+                    }
+                    _ => None,
+                })
+                .ok_or_else(|| invalid("incomplete COSE key"))
+        };
+
+        // 1: kty, 3: alg, -1: crv / n, -2: x / e, -3: y
+        match (int(1), int(3)) {
+            (Some(2), Some(-7)) if int(-1) == Some(1) => {
+                let (x, y) = (bytes(-2)?, bytes(-3)?);
+                if x.len() != 32 || y.len() != 32 {
+                    return Err(invalid("incomplete COSE key"));
+                }
+                Ok(CoseKey::Es256 { x, y })
+            }
+            (Some(1), Some(-8)) if int(-1) == Some(6) => Ok(CoseKey::Ed25519 { x: bytes(-2)? }),
+            (Some(3), Some(-257)) => Ok(CoseKey::Rs256 {
+                n: bytes(-1)?,
+                e: bytes(-2)?,
+            }),
+            _ => Err(invalid("unsupported public key algorithm")),
+        }
+    }
+
+    pub fn verify(&self, message: &[u8], signature: &[u8]) -> Result<()> {
+        let verified = match self {
+            CoseKey::Es256 { x, y } => {
+                let point = [&[0x04], x.as_slice(), y.as_slice()].concat();
+                UnparsedPublicKey::new(&signature::ECDSA_P256_SHA256_ASN1, point)
+                    .verify(message, signature)
+            }
+            CoseKey::Ed25519 { x } => {
+                UnparsedPublicKey::new(&signature::ED25519, x).verify(message, signature)
+            }
+            CoseKey::Rs256 { n, e } => RsaPublicKeyComponents { n, e }.verify(
+                &signature::RSA_PKCS1_2048_8192_SHA256,
+                message,
+                signature,
+            ),
+        };
+        verified.map_err(|_| invalid("signature mismatch"))
+    }
+}
+
+/// 断言签名覆盖 authenticatorData || SHA-256(clientDataJSON)
+pub fn signed_message(authenticator_data: &[u8], client_data_json: &[u8]) -> Vec<u8> {
+    [
+        authenticator_data,
+        Sha256::digest(client_data_json).as_slice(),
+    ]
+    .concat()
+}
+
+#[cfg(test)]
+pub mod tests {
+    use super::*;
+    use ring::{
+        rand::SystemRandom,
+        signature::{EcdsaKeyPair, KeyPair, ECDSA_P256_SHA256_ASN1_SIGNING},
+    };
+
+    /// 软件实现的 ES256 认证器, 用于生成注册与登录响应
+    pub struct Authenticator {
+        pub credential_id: Vec<u8>,
+        key: EcdsaKeyPair,
+        pub sign_count: u32,
+        pub flags: u8,
+    }
+
+    impl Default for Authenticator {
+        fn default() -> Self {
+            let rng = SystemRandom::new();
+            let pkcs8 =
+                EcdsaKeyPair::generate_pkcs8(&ECDSA_P256_SHA256_ASN1_SIGNING, &rng).unwrap();
+            Authenticator {
+                credential_id: uuid::Uuid::new_v4().as_bytes().to_vec(),
+                key: EcdsaKeyPair::from_pkcs8(&ECDSA_P256_SHA256_ASN1_SIGNING, pkcs8.as_ref())
+                    .unwrap(),
+                sign_count: 0,
+                flags: FLAG_UP | FLAG_UV,
+            }
+        }
+    }
+
+    impl Authenticator {
+        pub fn cose_key(&self) -> Vec<u8> {
+            let point = self.key.public_key().as_ref();
+            let key = Value::Map(vec![
+                (Value::from(1), Value::from(2)),
+                (Value::from(3), Value::from(-7)),
+                (Value::from(-1), Value::from(1)),
+                (Value::from(-2), Value::Bytes(point[1..33].to_vec())),
+                (Value::from(-3), Value::Bytes(point[33..].to_vec())),
+            ]);
+            let mut bytes = Vec::new();
+            ciborium::ser::into_writer(&key, &mut bytes).unwrap();
+            bytes
+        }
+
+        pub fn client_data(kind: &str, challenge: &str, origin: &str) -> Vec<u8> {
+            serde_json::to_vec(&serde_json::json!({
+                "type": kind,
+                "challenge": challenge,
+                "origin": origin,
+                "crossOrigin": false,
+            }))
+            .unwrap()
+        }
+
+        fn auth_data(&self, rp_id: &str, attested: bool) -> Vec<u8> {
+            let mut data = Sha256::digest(rp_id.as_bytes()).to_vec();
+            data.push(self.flags | if attested { FLAG_AT } else { 0 });
+            data.extend_from_slice(&self.sign_count.to_be_bytes());
+            if attested {
+                data.extend_from_slice(&[0u8; 16]);
+                data.extend_from_slice(&(self.credential_id.len() as u16).to_be_bytes());
+                data.extend_from_slice(&self.credential_id);
+                data.extend_from_slice(&self.cose_key());
+            }
+            data
+        }
+
+        /// 返回 (attestationObject, authData)
+        pub fn attestation_object(&self, rp_id: &str) -> (Vec<u8>, Vec<u8>) {
+            let auth_data = self.auth_data(rp_id, true);
+            let object = Value::Map(vec![
+                (Value::from("fmt"), Value::from("none")),
+                (Value::from("attStmt"), Value::Map(vec![])),
+                (Value::from("authData"), Value::Bytes(auth_data.clone())),
+            ]);
+            let mut bytes = Vec::new();
+            ciborium::ser::into_writer(&object, &mut bytes).unwrap();
+            (bytes, auth_data)
+        }
+
+        /// 返回 (authenticatorData, signature)
+        pub fn assert(&mut self, rp_id: &str, client_data_json: &[u8]) -> (Vec<u8>, Vec<u8>) {
+            self.sign_count += 1;
+            let auth_data = self.auth_data(rp_id, false);
+            let signature = self
+                .key
+                .sign(
+                    &SystemRandom::new(),
+                    &signed_message(&auth_data, client_data_json),
+                )
+                .unwrap();
+            (auth_data, signature.as_ref().to_vec())
+        }
+    }
+
+    #[test]
+    fn test_parse_attested_credential() {
+        let authenticator = Authenticator::default();
+        let (object, _) = authenticator.attestation_object("example.com");
+        let auth_data = attestation_auth_data(&object).unwrap();
+
+        let parsed = AuthenticatorData::parse(&auth_data, "example.com", true).unwrap();
+        let attested = parsed.attested.unwrap();
+        assert_eq!(authenticator.credential_id, attested.credential_id);
+        assert_eq!(authenticator.cose_key(), attested.public_key);
+        assert!(matches!(
+            CoseKey::parse(&attested.public_key).unwrap(),
+            CoseKey::Es256 { .. }
+        ));
+
+        assert!(AuthenticatorData::parse(&auth_data, "evil.example.com", true).is_err());
+    }
+
+    #[test]
+    fn test_verify_assertion_signature() {
+        let mut authenticator = Authenticator::default();
+        let client_data = Authenticator::client_data("webauthn.get", "abc", "https://example.com");
+        let (auth_data, signature) = authenticator.assert("example.com", &client_data);
+        let key = CoseKey::parse(&authenticator.cose_key()).unwrap();
+
+        key.verify(&signed_message(&auth_data, &client_data), &signature)
+            .unwrap();
+        let tampered = Authenticator::client_data("webauthn.get", "xyz", "https://example.com");
+        assert!(key
+            .verify(&signed_message(&auth_data, &tampered), &signature)
+            .is_err());
+    }
+
+    #[test]
+    fn test_verify_client_data() {
+        let raw = Authenticator::client_data("webauthn.create", "abc", "https://example.com");
+        verify_client_data(&raw, "webauthn.create", "abc", "https://example.com").unwrap();
+        assert!(verify_client_data(&raw, "webauthn.get", "abc", "https://example.com").is_err());
+        assert!(verify_client_data(&raw, "webauthn.create", "abd", "https://example.com").is_err());
+        assert!(verify_client_data(&raw, "webauthn.create", "abc", "https://evil.com").is_err());
+    }
+
+    #[test]
+    fn test_user_verification_required() {
+        let mut authenticator = Authenticator {
+            flags: FLAG_UP,
+            ..Default::default()
+        };
+        let (auth_data, _) = authenticator.assert("example.com", b"{}");
+        assert!(AuthenticatorData::parse(&auth_data, "example.com", false).is_ok());
+        assert!(AuthenticatorData::parse(&auth_data, "example.com", true).is_err());
+    }
+}